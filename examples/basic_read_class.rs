@@ -1,4 +1,4 @@
-//! Reads and prints the content of a class file to STDOUT.
+//! Reads a class file and prints it to STDOUT, similar to `javap -c -v -p`.
 extern crate jbcrs;
 
 use std::fs::File;
@@ -18,16 +18,5 @@ fn main() {
     let (pool, class) = jbcrs::basic::parse(buf.as_ref()).expect("could not parse class file");
 
     // now print it to stdout
-    // the constant pool will be printed differently
-    // to help the user get a constant pool item by index
-
-    println!("Constant Pool:");
-    for (index, item) in pool.iter() {
-        println!("    #{}: {:?}", index, item);
-    }
-
-    // Class derives Debug,
-    // the output might not be the best (compared to javap or krakatau-disassemble)
-    // but it should be enough.
-    println!("{:#?}", class);
+    print!("{}", jbcrs::basic::disassemble(&pool, &class));
 }
@@ -0,0 +1,18 @@
+/**
+ * Contains stack map frames in their extended forms,
+ * although their offset deltas would fit in the compact forms.
+ * javac always chooses the compact forms,
+ * so the frames of Frames.class have been rewritten after compiling.
+ */
+public class Frames {
+    static int count(int n) {
+        int count = 0;
+        for (int i = 0; i < n; i++) {
+            if (i % 3 == 0) {
+                count++;
+            }
+            count += i % 2 == 0 ? 1 : 2;
+        }
+        return count;
+    }
+}
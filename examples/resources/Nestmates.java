@@ -0,0 +1,25 @@
+/**
+ * Nested classes compiled for Java 11,
+ * so they are nestmates and access each other's private members directly.
+ */
+public class Nestmates {
+    private int counter;
+
+    private void increment() {
+        counter++;
+    }
+
+    public int count(int times) {
+        Inner inner = new Inner();
+        for (int i = 0; i < times; i++) {
+            inner.increment();
+        }
+        return counter;
+    }
+
+    class Inner {
+        private void increment() {
+            Nestmates.this.increment();
+        }
+    }
+}
@@ -0,0 +1,27 @@
+import java.lang.annotation.Retention;
+import java.lang.annotation.RetentionPolicy;
+import java.util.List;
+
+/**
+ * A generic record compiled for Java 16,
+ * its components have signatures and annotations.
+ */
+public record Records<T>(String name, @Component List<T> values, int count) {
+    public Records {
+        if (count < 0) {
+            throw new IllegalArgumentException("negative count");
+        }
+    }
+
+    public Records(String name) {
+        this(name, List.of(), 0);
+    }
+
+    public boolean isEmpty() {
+        return count == 0;
+    }
+
+    @Retention(RetentionPolicy.RUNTIME)
+    @interface Component {
+    }
+}
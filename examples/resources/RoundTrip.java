@@ -0,0 +1,99 @@
+/**
+ * Exercises many different encodings,
+ * to test if a class can be written back byte by byte.
+ */
+public class RoundTrip {
+    // more than 256 constants, so ldc_w is required
+    static final String[] WORDS = {
+        "w0", "w1", "w2", "w3", "w4", "w5", "w6", "w7", "w8", "w9",
+        "w10", "w11", "w12", "w13", "w14", "w15", "w16", "w17", "w18", "w19",
+        "w20", "w21", "w22", "w23", "w24", "w25", "w26", "w27", "w28", "w29",
+        "w30", "w31", "w32", "w33", "w34", "w35", "w36", "w37", "w38", "w39",
+        "w40", "w41", "w42", "w43", "w44", "w45", "w46", "w47", "w48", "w49",
+        "w50", "w51", "w52", "w53", "w54", "w55", "w56", "w57", "w58", "w59",
+        "w60", "w61", "w62", "w63", "w64", "w65", "w66", "w67", "w68", "w69",
+        "w70", "w71", "w72", "w73", "w74", "w75", "w76", "w77", "w78", "w79",
+        "w80", "w81", "w82", "w83", "w84", "w85", "w86", "w87", "w88", "w89",
+        "w90", "w91", "w92", "w93", "w94", "w95", "w96", "w97", "w98", "w99",
+        "w100", "w101", "w102", "w103", "w104", "w105", "w106", "w107", "w108", "w109",
+        "w110", "w111", "w112", "w113", "w114", "w115", "w116", "w117", "w118", "w119",
+        "w120", "w121", "w122", "w123", "w124", "w125", "w126", "w127", "w128", "w129",
+        "w130", "w131", "w132", "w133", "w134", "w135", "w136", "w137", "w138", "w139",
+        "w140", "w141", "w142", "w143", "w144", "w145", "w146", "w147", "w148", "w149",
+        "w150", "w151", "w152", "w153", "w154", "w155", "w156", "w157", "w158", "w159",
+        "w160", "w161", "w162", "w163", "w164", "w165", "w166", "w167", "w168", "w169",
+        "w170", "w171", "w172", "w173", "w174", "w175", "w176", "w177", "w178", "w179",
+        "w180", "w181", "w182", "w183", "w184", "w185", "w186", "w187", "w188", "w189",
+        "w190", "w191", "w192", "w193", "w194", "w195", "w196", "w197", "w198", "w199",
+        "w200", "w201", "w202", "w203", "w204", "w205", "w206", "w207", "w208", "w209",
+        "w210", "w211", "w212", "w213", "w214", "w215", "w216", "w217", "w218", "w219",
+        "w220", "w221", "w222", "w223", "w224", "w225", "w226", "w227", "w228", "w229",
+        "w230", "w231", "w232", "w233", "w234", "w235", "w236", "w237", "w238", "w239",
+        "w240", "w241", "w242", "w243", "w244", "w245", "w246", "w247", "w248", "w249",
+        "w250", "w251", "w252", "w253", "w254", "w255", "w256", "w257", "w258", "w259",
+        "w260", "w261", "w262", "w263", "w264", "w265", "w266", "w267", "w268", "w269",
+        "w270", "w271", "w272", "w273", "w274", "w275", "w276", "w277", "w278", "w279",
+    };
+
+    static final long BIG = 1234567890123L;
+    static final double PI = 3.141592653589793;
+
+    public static void main(String[] args) throws Exception {
+        System.out.println(tableSwitch(args.length) + lookupSwitch(args.length));
+        System.out.println(last() + wide(args.length));
+        System.out.println(tryCatch(args) + BIG * PI);
+        System.out.println(new int[2][3].length);
+    }
+
+    static String last() {
+        return "the last constant";
+    }
+
+    static int tableSwitch(int i) {
+        switch (i) {
+            case 0: return 10;
+            case 1: return 20;
+            case 2: return 30;
+            case 3: return 40;
+            default: return -1;
+        }
+    }
+
+    static int lookupSwitch(int i) {
+        switch (i) {
+            case -100: return 1;
+            case 7: return 2;
+            case 1000: return 3;
+            default: return 0;
+        }
+    }
+
+    // more than 256 local variable slots, so the wide prefix is required
+    static double wide(int i) {
+        double d0 = 0, d1 = 1, d2 = 2, d3 = 3, d4 = 4, d5 = 5, d6 = 6, d7 = 7, d8 = 8, d9 = 9;
+        double d10 = 10, d11 = 11, d12 = 12, d13 = 13, d14 = 14, d15 = 15, d16 = 16, d17 = 17, d18 = 18, d19 = 19;
+        double d20 = 20, d21 = 21, d22 = 22, d23 = 23, d24 = 24, d25 = 25, d26 = 26, d27 = 27, d28 = 28, d29 = 29;
+        double d30 = 30, d31 = 31, d32 = 32, d33 = 33, d34 = 34, d35 = 35, d36 = 36, d37 = 37, d38 = 38, d39 = 39;
+        double d40 = 40, d41 = 41, d42 = 42, d43 = 43, d44 = 44, d45 = 45, d46 = 46, d47 = 47, d48 = 48, d49 = 49;
+        double d50 = 50, d51 = 51, d52 = 52, d53 = 53, d54 = 54, d55 = 55, d56 = 56, d57 = 57, d58 = 58, d59 = 59;
+        double d60 = 60, d61 = 61, d62 = 62, d63 = 63, d64 = 64, d65 = 65, d66 = 66, d67 = 67, d68 = 68, d69 = 69;
+        double d70 = 70, d71 = 71, d72 = 72, d73 = 73, d74 = 74, d75 = 75, d76 = 76, d77 = 77, d78 = 78, d79 = 79;
+        double d80 = 80, d81 = 81, d82 = 82, d83 = 83, d84 = 84, d85 = 85, d86 = 86, d87 = 87, d88 = 88, d89 = 89;
+        double d90 = 90, d91 = 91, d92 = 92, d93 = 93, d94 = 94, d95 = 95, d96 = 96, d97 = 97, d98 = 98, d99 = 99;
+        double d100 = 100, d101 = 101, d102 = 102, d103 = 103, d104 = 104, d105 = 105, d106 = 106, d107 = 107, d108 = 108, d109 = 109;
+        double d110 = 110, d111 = 111, d112 = 112, d113 = 113, d114 = 114, d115 = 115, d116 = 116, d117 = 117, d118 = 118, d119 = 119;
+        double d120 = 120, d121 = 121, d122 = 122, d123 = 123, d124 = 124, d125 = 125, d126 = 126, d127 = 127, d128 = 128, d129 = 129;
+        i += 1000;
+        return d0 + d64 + d129 + i;
+    }
+
+    static synchronized int tryCatch(String[] args) {
+        try {
+            return Integer.parseInt(args[0]);
+        } catch (NumberFormatException | ArrayIndexOutOfBoundsException e) {
+            return 0;
+        } finally {
+            System.out.println("finally");
+        }
+    }
+}
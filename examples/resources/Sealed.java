@@ -0,0 +1,22 @@
+/**
+ * A sealed class compiled for Java 17,
+ * which permits exactly its two nested subclasses.
+ */
+public abstract sealed class Sealed permits Sealed.Leaf, Sealed.Node {
+    abstract int size();
+
+    static final class Leaf extends Sealed {
+        int size() {
+            return 1;
+        }
+    }
+
+    static non-sealed class Node extends Sealed {
+        Sealed left;
+        Sealed right;
+
+        int size() {
+            return left.size() + right.size();
+        }
+    }
+}
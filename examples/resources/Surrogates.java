@@ -0,0 +1,14 @@
+/**
+ * Contains strings with unpaired surrogates,
+ * which are valid java strings, but cannot be represented as a rust `String`.
+ */
+public class Surrogates {
+    static final String HIGH = "\uD83D";
+    static final String LOW = "a\uDE00b";
+    static final String REVERSED = "\uDE00\uD83D";
+    static final String PAIR = "\uD83D\uDE00";
+
+    static String join() {
+        return HIGH + LOW + REVERSED + PAIR;
+    }
+}
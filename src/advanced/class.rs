@@ -0,0 +1,223 @@
+//! The class module provides the structure of a class file,
+//! where every name, descriptor and constant is resolved.
+
+use basic::{ClassAccessFlags, ExportsFlags, FieldAccessFlags, InnerClassAccessFlags,
+            MethodAccessFlags, ModuleFlags, ParameterFlags, RequiresFlags};
+use types::{MethodDescriptor, TypeDescriptor};
+use super::code::{Code, TypeAnnotation};
+use super::constant::Constant;
+
+/// A java class file, which does not depend on a constant pool.
+///
+/// # Examples
+///
+/// ```
+/// use jbcrs::advanced::{self, Class};
+/// use jbcrs::basic::ClassAccessFlags;
+///
+/// let class = Class {
+///     minor_version: 0,
+///     major_version: 52,
+///     access_flags: ClassAccessFlags::PUBLIC | ClassAccessFlags::SUPER,
+///     name: "Empty".to_owned(),
+///     super_name: Some("java/lang/Object".to_owned()),
+///     interfaces: Vec::new(),
+///     fields: Vec::new(),
+///     methods: Vec::new(),
+///     attributes: Vec::new(),
+/// };
+///
+/// let bytes = advanced::write(class).unwrap();
+/// let class = advanced::parse(&bytes).unwrap();
+/// assert_eq!(class.name, "Empty");
+/// ```
+#[derive(Debug)]
+pub struct Class {
+    pub minor_version: u16,
+    pub major_version: u16,
+
+    pub access_flags: ClassAccessFlags,
+    /// The internal name of this class.
+    pub name: String,
+    /// The internal name of the super class, only `java/lang/Object` has none.
+    pub super_name: Option<String>,
+    pub interfaces: Vec<String>,
+
+    pub fields: Vec<Field>,
+    pub methods: Vec<Method>,
+
+    /// All attributes, except for `BootstrapMethods`,
+    /// which is rebuilt from the `invokedynamic` instructions.
+    pub attributes: Vec<Attribute>,
+}
+
+/// A field.
+#[derive(Debug)]
+pub struct Field {
+    pub access_flags: FieldAccessFlags,
+    pub name: String,
+    pub desc: TypeDescriptor,
+    pub attributes: Vec<Attribute>,
+}
+
+/// A method.
+#[derive(Debug)]
+pub struct Method {
+    pub access_flags: MethodAccessFlags,
+    pub name: String,
+    pub desc: MethodDescriptor,
+    /// The code, absent for abstract and native methods.
+    pub code: Option<Code>,
+    /// All attributes, except for `Code`.
+    pub attributes: Vec<Attribute>,
+}
+
+/// An attribute of a class, a field, a method or a code attribute.
+/// Attributes referencing positions in the code are part of `Code` instead.
+#[derive(Debug)]
+pub enum Attribute {
+    AnnotationDefault(ElementValue),
+    ConstantValue(Constant),
+    Deprecated,
+    EnclosingMethod {
+        /// The internal name of the enclosing class.
+        class: String,
+        /// The name and the descriptor of the enclosing method,
+        /// if the class is enclosed by a method or a constructor.
+        method: Option<(String, MethodDescriptor)>,
+    },
+    /// The internal names of all checked exceptions thrown.
+    Exceptions(Vec<String>),
+    InnerClasses(Vec<InnerClass>),
+    MethodParameters(Vec<MethodParameter>),
+    Module(Module),
+    ModuleMainClass(String),
+    ModulePackages(Vec<String>),
+    /// The internal name of the host of the nest this class is a member of.
+    NestHost(String),
+    /// The internal names of all members of the nest this class is the host of.
+    NestMembers(Vec<String>),
+    /// The internal names of all classes allowed to extend or implement this sealed class.
+    PermittedSubclasses(Vec<String>),
+    Record(Vec<RecordComponent>),
+    RuntimeVisibleAnnotations(Vec<Annotation>),
+    RuntimeInvisibleAnnotations(Vec<Annotation>),
+    RuntimeVisibleParameterAnnotations(Vec<Vec<Annotation>>),
+    RuntimeInvisibleParameterAnnotations(Vec<Vec<Annotation>>),
+    /// Type annotations outside of the code,
+    /// their target type is always `TargetType::Other(_)`.
+    RuntimeVisibleTypeAnnotations(Vec<TypeAnnotation>),
+    RuntimeInvisibleTypeAnnotations(Vec<TypeAnnotation>),
+    Signature(String),
+    Synthetic,
+    SourceFile(String),
+    SourceDebugExtension(String),
+    /// An unknown attribute given by its name and its content.
+    /// The content is written as is, so it must not reference the constant pool.
+    Unknown(String, Vec<u8>),
+}
+
+/// A component of a record.
+#[derive(Debug)]
+pub struct RecordComponent {
+    pub name: String,
+    pub desc: TypeDescriptor,
+    pub attributes: Vec<Attribute>,
+}
+
+#[derive(Debug)]
+pub struct InnerClass {
+    /// The internal name of the inner class.
+    pub inner_class: String,
+    /// The internal name of the class the inner class is a member of,
+    /// none for local and anonymous classes.
+    pub outer_class: Option<String>,
+    /// The simple name of the inner class, none for anonymous classes.
+    pub inner_name: Option<String>,
+    pub inner_class_access_flags: InnerClassAccessFlags,
+}
+
+#[derive(Debug)]
+pub struct MethodParameter {
+    /// The name of the parameter, none if it is unnamed.
+    pub name: Option<String>,
+    pub access_flags: ParameterFlags,
+}
+
+/// The content of the `Module` attribute.
+#[derive(Debug)]
+pub struct Module {
+    pub name: String,
+    pub flags: ModuleFlags,
+    pub version: Option<String>,
+
+    pub requires: Vec<Requirement>,
+    pub exports: Vec<Export>,
+    pub opens: Vec<Opening>,
+    /// The internal names of all services used.
+    pub uses: Vec<String>,
+    pub provides: Vec<Provider>,
+}
+
+#[derive(Debug)]
+pub struct Requirement {
+    /// The name of the module required.
+    pub module: String,
+    pub flags: RequiresFlags,
+    pub version: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Export {
+    /// The name of the package exported, in internal form.
+    pub package: String,
+    pub flags: ExportsFlags,
+    /// The names of the modules the package is exported to,
+    /// empty if it is exported to every module.
+    pub to: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Opening {
+    /// The name of the package opened, in internal form.
+    pub package: String,
+    pub flags: ExportsFlags,
+    /// The names of the modules the package is opened to,
+    /// empty if it is opened to every module.
+    pub to: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Provider {
+    /// The internal name of the service.
+    pub service: String,
+    /// The internal names of the implementations.
+    pub with: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub type_desc: TypeDescriptor,
+    pub element_value_pairs: Vec<(String, ElementValue)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ElementValue {
+    Byte(i8),
+    Short(i16),
+    Char(u16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    Boolean(bool),
+    String(String),
+    Enum {
+        type_desc: TypeDescriptor,
+        const_name: String,
+    },
+    /// A class literal, `None` represents `void.class`.
+    Class(Option<TypeDescriptor>),
+    Annotation(Box<Annotation>),
+    Array(Vec<ElementValue>),
+}
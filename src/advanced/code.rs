@@ -0,0 +1,397 @@
+//! The code module provides a representation of the `Code` attribute,
+//! where every position in the code is referenced by a `Label`.
+
+use std::collections::BTreeMap;
+
+use basic;
+use types::TypeDescriptor;
+use super::class::{Annotation, Attribute};
+use super::constant::{Constant, FieldRef, InvokeDynamic, MethodRef};
+
+/// A label marks a position in the code.
+/// It is placed using `Instruction::Label(_)`
+/// and may be placed after the last instruction as well.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Label(pub(super) u32);
+
+/// The `Code` attribute of a method,
+/// where branches, exception handlers, debug information and frames reference labels
+/// instead of offsets.
+///
+/// Instructions can be inserted or removed without breaking any of these,
+/// offsets are computed when converting back to an `Attribute::Code`.
+///
+/// # Examples
+///
+/// ```
+/// use jbcrs::basic::{self, Pool};
+/// use jbcrs::advanced::{Code, Instruction, Jump};
+///
+/// let mut code = Code::new(1, 1);
+/// let end = code.new_label();
+/// code.instructions.push(Instruction::Simple(basic::Instruction::ILoad0));
+/// code.instructions.push(Instruction::Jump(Jump::IfEq, end));
+/// code.instructions.push(Instruction::Simple(basic::Instruction::NOP));
+/// code.instructions.push(Instruction::Label(end));
+/// code.instructions.push(Instruction::Simple(basic::Instruction::Return));
+///
+/// let mut pool = Pool::new();
+/// let mut bootstrap_methods = Vec::new();
+/// match code.into_attribute(&mut pool, &mut bootstrap_methods).unwrap() {
+///     basic::Attribute::Code { instructions, .. } => match instructions.get(1) {
+///         Some(&basic::Instruction::IfEq(offset)) => assert_eq!(offset, 4),
+///         insn => panic!("unexpected instruction: {:?}", insn),
+///     },
+///     attribute => panic!("unexpected attribute: {:?}", attribute),
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Code {
+    pub max_stack: u16,
+    pub max_locals: u16,
+
+    /// The instructions and the labels in between them.
+    pub instructions: Vec<Instruction>,
+    pub exceptions: Vec<Exception>,
+
+    /// The content of all `LineNumberTable` attributes.
+    pub line_numbers: Vec<LineNumber>,
+    /// The content of all `LocalVariableTable` attributes.
+    pub local_variables: Vec<LocalVariable>,
+    /// The content of all `LocalVariableTypeTable` attributes.
+    pub local_variable_types: Vec<LocalVariableType>,
+    /// The content of the `StackMapTable` attribute, ordered by position.
+    pub frames: Vec<StackMapFrame>,
+    /// The content of all `RuntimeVisibleTypeAnnotations` attributes.
+    pub visible_type_annotations: Vec<TypeAnnotation>,
+    /// The content of all `RuntimeInvisibleTypeAnnotations` attributes.
+    pub invisible_type_annotations: Vec<TypeAnnotation>,
+
+    /// All other attributes, which do not reference any position in the code.
+    pub attributes: Vec<Attribute>,
+
+    pub(super) next_label: u32,
+}
+
+impl Code {
+    /// Creates a new code attribute without any instructions.
+    pub fn new(max_stack: u16, max_locals: u16) -> Code {
+        Code {
+            max_stack,
+            max_locals,
+            instructions: Vec::new(),
+            exceptions: Vec::new(),
+            line_numbers: Vec::new(),
+            local_variables: Vec::new(),
+            local_variable_types: Vec::new(),
+            frames: Vec::new(),
+            visible_type_annotations: Vec::new(),
+            invisible_type_annotations: Vec::new(),
+            attributes: Vec::new(),
+            next_label: 0,
+        }
+    }
+
+    /// Creates a new label, which is unique in this code attribute.
+    /// The label must be placed exactly once, before the code is converted back.
+    pub fn new_label(&mut self) -> Label {
+        let label = Label(self.next_label);
+        self.next_label += 1;
+        label
+    }
+}
+
+#[derive(Debug)]
+pub enum Instruction {
+    /// Marks the position of the following instruction,
+    /// or the end of the code, if there is no following instruction.
+    Label(Label),
+    /// An instruction, which does neither reference any position in the code
+    /// nor the constant pool.
+    /// Branch instructions, switches and instructions with a constant pool index
+    /// must not be used here, converting the code back returns an error for them.
+    Simple(basic::Instruction),
+    /// A conditional or unconditional jump to a label.
+    /// If the label is too far away, `goto_w` or `jsr_w` is used.
+    /// A conditional jump will be replaced by the negated jump over a `goto_w`.
+    Jump(Jump, Label),
+    TableSwitch {
+        default: Label,
+        low: i32,
+        high: i32,
+        targets: Vec<Label>,
+    },
+    LookupSwitch {
+        default: Label,
+        targets: BTreeMap<i32, Label>,
+    },
+    /// Pushes a constant using `ldc`, `ldc_w` or `ldc2_w`,
+    /// depending on its type and its index in the constant pool.
+    Constant(Constant),
+    Field(FieldInsn, FieldRef),
+    /// Invokes a method, the count of `invokeinterface` is computed from the descriptor.
+    Method(MethodInsn, MethodRef),
+    InvokeDynamic(InvokeDynamic),
+    /// An instruction referencing a class by its internal name,
+    /// or an array type by its descriptor.
+    Type(TypeInsn, String),
+    /// Creates a multidimensional array of an array type
+    /// given by its descriptor and the number of dimensions to create.
+    MultiANewArray(String, u8),
+}
+
+/// All instructions accessing a field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldInsn {
+    GetStatic,
+    PutStatic,
+    GetField,
+    PutField,
+}
+
+/// All instructions invoking a method, except for `invokedynamic`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MethodInsn {
+    InvokeVirtual,
+    InvokeSpecial,
+    InvokeStatic,
+    InvokeInterface,
+}
+
+/// All instructions referencing a single class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeInsn {
+    New,
+    ANewArray,
+    CheckCast,
+    InstanceOf,
+}
+
+/// All kinds of jumps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Jump {
+    IfEq,
+    IfNE,
+    IfLT,
+    IfGE,
+    IfGT,
+    IfLE,
+
+    IfICmpEq,
+    IfICmpNE,
+    IfICmpLT,
+    IfICmpGE,
+    IfICmpGT,
+    IfICmpLE,
+
+    IfACmpEq,
+    IfACmpNE,
+
+    IfNull,
+    IfNonNull,
+
+    GoTo,
+    JSR,
+}
+
+impl Jump {
+    /// Returns the jump, which is taken if and only if this one is not.
+    /// `GoTo` and `JSR` cannot be negated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jbcrs::advanced::Jump;
+    ///
+    /// assert_eq!(Jump::IfICmpLT.negate(), Some(Jump::IfICmpGE));
+    /// assert_eq!(Jump::IfNonNull.negate(), Some(Jump::IfNull));
+    /// assert_eq!(Jump::GoTo.negate(), None);
+    /// ```
+    pub fn negate(self) -> Option<Jump> {
+        use self::Jump::*;
+
+        Some(match self {
+            IfEq => IfNE,
+            IfNE => IfEq,
+            IfLT => IfGE,
+            IfGE => IfLT,
+            IfGT => IfLE,
+            IfLE => IfGT,
+            IfICmpEq => IfICmpNE,
+            IfICmpNE => IfICmpEq,
+            IfICmpLT => IfICmpGE,
+            IfICmpGE => IfICmpLT,
+            IfICmpGT => IfICmpLE,
+            IfICmpLE => IfICmpGT,
+            IfACmpEq => IfACmpNE,
+            IfACmpNE => IfACmpEq,
+            IfNull => IfNonNull,
+            IfNonNull => IfNull,
+            GoTo | JSR => return None,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct Exception {
+    pub start: Label,
+    pub end: Label,
+    pub handler: Label,
+    /// The internal name of the exception class caught,
+    /// none if every exception is caught.
+    pub catch_type: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct LineNumber {
+    pub start: Label,
+    pub line_number: u16,
+}
+
+/// An entry of the `LocalVariableTable`
+#[derive(Debug)]
+pub struct LocalVariable {
+    pub start: Label,
+    /// The end of the range, exclusive.
+    pub end: Label,
+    pub name: String,
+    pub descriptor: TypeDescriptor,
+    /// The index in the local variable array of the current frame.
+    /// double and long do occupy two spaces.
+    pub index: u16,
+}
+
+/// An entry of the `LocalVariableTypeTable`
+#[derive(Debug)]
+pub struct LocalVariableType {
+    pub start: Label,
+    /// The end of the range, exclusive.
+    pub end: Label,
+    pub name: String,
+    /// The generic signature of the variable.
+    pub signature: String,
+    /// The index in the local variable array of the current frame.
+    /// double and long do occupy two spaces.
+    pub index: u16,
+}
+
+/// A frame of the `StackMapTable`.
+/// Just like in the class file, every frame (except for `Full`)
+/// is relative to the previous one.
+#[derive(Debug)]
+pub enum StackMapFrame {
+    Same {
+        label: Label,
+    },
+    Same1 {
+        label: Label,
+        stack: VerificationType,
+    },
+    Chop {
+        label: Label,
+        count: u8,
+    },
+    Append {
+        label: Label,
+        locals: Vec<VerificationType>,
+    },
+    Full {
+        label: Label,
+        locals: Vec<VerificationType>,
+        stack: Vec<VerificationType>,
+    },
+}
+
+impl StackMapFrame {
+    /// Returns the label marking the position of this frame.
+    pub fn label(&self) -> Label {
+        match *self {
+            StackMapFrame::Same { label }
+            | StackMapFrame::Same1 { label, .. }
+            | StackMapFrame::Chop { label, .. }
+            | StackMapFrame::Append { label, .. }
+            | StackMapFrame::Full { label, .. } => label,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerificationType {
+    Top,
+    Integer,
+    Float,
+    Double,
+    Long,
+    Null,
+    UninitializedThis,
+    /// The internal name of a class, or the descriptor of an array type.
+    Object(String),
+    /// An object, which has been created by the `new` instruction
+    /// following the label, but not yet initialized.
+    Uninitialized(Label),
+}
+
+/// A type annotation.
+#[derive(Debug)]
+pub struct TypeAnnotation {
+    pub target_type: TargetType,
+    pub target_path: Vec<basic::TypePathElement>,
+    pub annotation: Annotation,
+}
+
+/// The target of a type annotation.
+/// Only type annotations located in the code may reference labels.
+#[derive(Debug)]
+pub enum TargetType {
+    /// Indicates that an annotation is present
+    /// on the type in a local variable declaration.
+    LocalVariable(Vec<LocalVariableTarget>),
+    /// Indicates that an annotation is present
+    /// on the type in a local variable declaration.
+    ResourceVariable(Vec<LocalVariableTarget>),
+    /// Indicates that an annotation is present
+    /// on the type in an exception parameter declaration.
+    /// The index into the exception table.
+    Catch(u16),
+    /// Indicates that an annotation is present
+    /// on the type in an instanceof expression.
+    OffsetInstanceOf(Label),
+    /// Indicates that an annotation is present
+    /// on the type in a new expression.
+    OffsetNew(Label),
+    /// Indicates that an annotation is present
+    /// on the type before the ::new
+    /// of a method reference expression.
+    OffsetNewRef(Label),
+    /// Indicates that an annotation is present
+    /// on the type before the ::name
+    /// of a method reference expression.
+    OffsetRef(Label),
+    /// Indicates that an annotation is present
+    /// on the type of a cast expression.
+    TypeArgumentCast { label: Label, type_argument: u8 },
+    /// Indicates that an annotation is present
+    /// on the type of a method call expression.
+    TypeArgumentMethod { label: Label, type_argument: u8 },
+    /// Indicates that an annotation is present
+    /// on the type of a new expression.
+    TypeArgumentConstructor { label: Label, type_argument: u8 },
+    /// Indicates that an annotation is present
+    /// on the type of a ::new expression.
+    TypeArgumentNewRef { label: Label, type_argument: u8 },
+    /// Indicates that an annotation is present
+    /// on the type of a ::name expression.
+    TypeArgumentRef { label: Label, type_argument: u8 },
+    /// Any other target type, which does not reference a position in the code.
+    Other(basic::TargetType),
+}
+
+#[derive(Debug)]
+pub struct LocalVariableTarget {
+    pub start: Label,
+    /// The end of the range, exclusive.
+    pub end: Label,
+    /// The index in the local variable array of the current frame.
+    /// double and long do occupy two spaces.
+    pub index: u16,
+}
@@ -0,0 +1,97 @@
+//! The constant module provides resolved versions of the constant pool items,
+//! which are referenced by instructions and attributes.
+
+use basic::ReferenceKind;
+use types::{MethodDescriptor, TypeDescriptor};
+
+/// A reference to a field.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldRef {
+    /// The internal name of the class declaring the field.
+    pub owner: String,
+    pub name: String,
+    pub desc: TypeDescriptor,
+}
+
+/// A reference to a method.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MethodRef {
+    /// The internal name of the class or interface declaring the method.
+    pub owner: String,
+    pub name: String,
+    pub desc: MethodDescriptor,
+    /// Whether the owner is an interface,
+    /// this decides between `Item::MethodRef` and `Item::InterfaceMethodRef`.
+    pub interface: bool,
+}
+
+/// The field or method a method handle refers to.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reference {
+    Field(FieldRef),
+    Method(MethodRef),
+}
+
+/// A method handle.
+/// Field references are used by the kinds `GetField`, `GetStatic`, `PutField` and `PutStatic`,
+/// method references by all other kinds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MethodHandle {
+    pub kind: ReferenceKind,
+    pub reference: Reference,
+}
+
+/// A loadable constant, used by `ldc` instructions,
+/// `ConstantValue` attributes and the arguments of bootstrap methods.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constant {
+    Integer(i32),
+    Float(f32),
+    Long(i64),
+    Double(f64),
+    /// A `java.lang.String` object.
+    String(String),
+    /// A `java.lang.String` object containing unpaired surrogates,
+    /// which cannot be represented as a `String`, given by its UTF-16 code units.
+    UTF16(Vec<u16>),
+    /// The internal name of a class, or the descriptor of an array type.
+    Class(String),
+    MethodType(MethodDescriptor),
+    MethodHandle(MethodHandle),
+    Dynamic(Dynamic),
+}
+
+impl Constant {
+    /// Returns true if this constant is a `long` or a `double`
+    /// and has to be loaded using `ldc2_w`.
+    pub fn is_double(&self) -> bool {
+        match *self {
+            Constant::Long(_) | Constant::Double(_) => true,
+            Constant::Dynamic(ref dynamic) => dynamic.desc.size() == 2,
+            _ => false,
+        }
+    }
+}
+
+/// A dynamically computed constant, whose value is produced by a bootstrap method.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dynamic {
+    pub name: String,
+    /// The type of the constant.
+    pub desc: TypeDescriptor,
+    /// The bootstrap method computing the constant.
+    pub bootstrap_method: MethodHandle,
+    /// The static arguments passed to the bootstrap method.
+    pub arguments: Vec<Constant>,
+}
+
+/// The call site of an `invokedynamic` instruction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvokeDynamic {
+    pub name: String,
+    pub desc: MethodDescriptor,
+    /// The bootstrap method linking the call site.
+    pub bootstrap_method: MethodHandle,
+    /// The static arguments passed to the bootstrap method.
+    pub arguments: Vec<Constant>,
+}
@@ -0,0 +1,449 @@
+//! Converts a basic `Attribute::Code` to the advanced `Code`.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use basic::{self, Pool};
+use result::*;
+use super::Lifter;
+use super::super::code::*;
+
+impl Code {
+    /// Converts an `Attribute::Code` to a `Code`,
+    /// where every offset is replaced by a label
+    /// and every index into the constant pool is resolved.
+    /// The bootstrap methods are taken from the `BootstrapMethods` attribute of the class,
+    /// they are required to resolve `invokedynamic` instructions.
+    ///
+    /// An error is returned, if the attribute is not an `Attribute::Code`,
+    /// if an offset does not point to the start of an instruction or to the end of the code
+    /// or if an index does not point to the expected item.
+    pub fn from_attribute(
+        attribute: basic::Attribute,
+        constant_pool: &Pool,
+        bootstrap_methods: &[basic::BootstrapMethod],
+    ) -> Result<Code> {
+        Lifter {
+            constant_pool,
+            bootstrap_methods,
+        }.code(attribute)
+    }
+}
+
+impl<'a> Lifter<'a> {
+    pub(super) fn code(&self, attribute: basic::Attribute) -> Result<Code> {
+        match attribute {
+            basic::Attribute::Code {
+                max_stack,
+                max_locals,
+                instructions,
+                exceptions,
+                attributes,
+            } => lift_code(
+                self,
+                max_stack,
+                max_locals,
+                instructions,
+                exceptions,
+                attributes,
+            ),
+            _ => Err(Error::UnexpectedAttribute),
+        }
+    }
+}
+
+/// Assigns a label to every offset referenced.
+struct Labels {
+    /// The offsets of all instructions
+    starts: BTreeSet<u32>,
+    /// The length of the code
+    end: u32,
+    by_offset: BTreeMap<u32, Label>,
+    next: u32,
+}
+
+impl Labels {
+    /// Returns the label of an offset,
+    /// which must be the start of an instruction or the end of the code.
+    fn get(&mut self, offset: u32) -> Result<Label> {
+        if offset != self.end && !self.starts.contains(&offset) {
+            return Err(Error::InvalidOffset(offset));
+        }
+
+        let next = &mut self.next;
+        Ok(*self.by_offset.entry(offset).or_insert_with(|| {
+            let label = Label(*next);
+            *next += 1;
+            label
+        }))
+    }
+
+    /// Returns the label of an offset relative to an instruction located at `at`.
+    fn relative(&mut self, at: u32, offset: i32) -> Result<Label> {
+        let target = i64::from(at) + i64::from(offset);
+        if target < 0 || target > i64::from(self.end) {
+            return Err(Error::InvalidOffset(at));
+        }
+
+        self.get(target as u32)
+    }
+
+    /// Returns the labels of a range given by its start and length.
+    fn range(&mut self, start: u16, length: u16) -> Result<(Label, Label)> {
+        let start = u32::from(start);
+        Ok((self.get(start)?, self.get(start + u32::from(length))?))
+    }
+}
+
+fn lift_code(
+    lifter: &Lifter,
+    max_stack: u16,
+    max_locals: u16,
+    instructions: basic::Instructions,
+    exceptions: Vec<basic::Exception>,
+    attributes: Vec<basic::Attribute>,
+) -> Result<Code> {
+    let end = match instructions.last() {
+        Some((at, insn)) => at + basic::instruction_len(at, insn)?,
+        None => 0,
+    };
+    let mut labels = Labels {
+        starts: instructions.iter().map(|(at, _)| at).collect(),
+        end,
+        by_offset: BTreeMap::new(),
+        next: 0,
+    };
+
+    let mut code = Code::new(max_stack, max_locals);
+
+    let mut lifted = Vec::with_capacity(instructions.len());
+    for (at, insn) in instructions {
+        lifted.push((at, lift_instruction(lifter, &mut labels, at, insn)?));
+    }
+
+    for exception in exceptions {
+        code.exceptions.push(Exception {
+            start: labels.get(u32::from(exception.start))?,
+            end: labels.get(u32::from(exception.end))?,
+            handler: labels.get(u32::from(exception.handler))?,
+            catch_type: lifter.class_name_opt(exception.catch_type)?,
+        });
+    }
+
+    for attribute in attributes {
+        match attribute {
+            basic::Attribute::LineNumberTable(table) => for line_number in table {
+                code.line_numbers.push(LineNumber {
+                    start: labels.get(u32::from(line_number.start))?,
+                    line_number: line_number.line_number,
+                });
+            },
+            basic::Attribute::LocalVariableTable(table) => for variable in table {
+                let (start, end) = labels.range(variable.start, variable.length)?;
+                code.local_variables.push(LocalVariable {
+                    start,
+                    end,
+                    name: lifter.utf8(variable.name)?,
+                    descriptor: lifter.utf8(variable.descriptor)?.parse()?,
+                    index: variable.index,
+                });
+            },
+            basic::Attribute::LocalVariableTypeTable(table) => for variable in table {
+                let (start, end) = labels.range(variable.start, variable.length)?;
+                code.local_variable_types.push(LocalVariableType {
+                    start,
+                    end,
+                    name: lifter.utf8(variable.name)?,
+                    signature: lifter.utf8(variable.signature)?,
+                    index: variable.index,
+                });
+            },
+            basic::Attribute::StackMapTable(table) => {
+                let mut previous = None;
+                for frame in table {
+                    let frame = lift_frame(lifter, &mut labels, &mut previous, frame)?;
+                    code.frames.push(frame);
+                }
+            }
+            basic::Attribute::RuntimeVisibleTypeAnnotations(annotations) => {
+                for annotation in annotations {
+                    let annotation = lift_type_annotation(lifter, &mut labels, annotation)?;
+                    code.visible_type_annotations.push(annotation);
+                }
+            }
+            basic::Attribute::RuntimeInvisibleTypeAnnotations(annotations) => {
+                for annotation in annotations {
+                    let annotation = lift_type_annotation(lifter, &mut labels, annotation)?;
+                    code.invisible_type_annotations.push(annotation);
+                }
+            }
+            attribute => code.attributes.push(lifter.attribute(attribute)?),
+        }
+    }
+
+    // place the labels in front of the instructions they are pointing to
+    code.instructions = Vec::with_capacity(lifted.len() + labels.by_offset.len());
+    for (at, insn) in lifted {
+        if let Some(label) = labels.by_offset.get(&at) {
+            code.instructions.push(Instruction::Label(*label));
+        }
+        code.instructions.push(insn);
+    }
+    if let Some(label) = labels.by_offset.get(&end) {
+        code.instructions.push(Instruction::Label(*label));
+    }
+    code.next_label = labels.next;
+
+    Ok(code)
+}
+
+/// Converts a single instruction located at `at`
+fn lift_instruction(
+    lifter: &Lifter,
+    labels: &mut Labels,
+    at: u32,
+    insn: basic::Instruction,
+) -> Result<Instruction> {
+    use basic::Instruction::*;
+
+    let (jump, offset) = match insn {
+        IfEq(offset) => (Jump::IfEq, offset.into()),
+        IfNE(offset) => (Jump::IfNE, offset.into()),
+        IfLT(offset) => (Jump::IfLT, offset.into()),
+        IfGE(offset) => (Jump::IfGE, offset.into()),
+        IfGT(offset) => (Jump::IfGT, offset.into()),
+        IfLE(offset) => (Jump::IfLE, offset.into()),
+        IfICmpEq(offset) => (Jump::IfICmpEq, offset.into()),
+        IfICmpNE(offset) => (Jump::IfICmpNE, offset.into()),
+        IfICmpLT(offset) => (Jump::IfICmpLT, offset.into()),
+        IfICmpGE(offset) => (Jump::IfICmpGE, offset.into()),
+        IfICmpGT(offset) => (Jump::IfICmpGT, offset.into()),
+        IfICmpLE(offset) => (Jump::IfICmpLE, offset.into()),
+        IfACmpEq(offset) => (Jump::IfACmpEq, offset.into()),
+        IfACmpNE(offset) => (Jump::IfACmpNE, offset.into()),
+        IfNull(offset) => (Jump::IfNull, offset.into()),
+        IfNonNull(offset) => (Jump::IfNonNull, offset.into()),
+        GoTo(offset) | GoToW(offset) => (Jump::GoTo, offset),
+        JSR(offset) | JSRW(offset) => (Jump::JSR, offset),
+        TableSwitch {
+            default,
+            low,
+            high,
+            offsets,
+            ..
+        } => {
+            let mut targets = Vec::with_capacity(offsets.len());
+            for offset in offsets {
+                targets.push(labels.relative(at, offset)?);
+            }
+
+            return Ok(Instruction::TableSwitch {
+                default: labels.relative(at, default)?,
+                low,
+                high,
+                targets,
+            });
+        }
+        LookupSwitch { default, offsets, .. } => {
+            let mut targets = BTreeMap::new();
+            for (key, offset) in offsets {
+                targets.insert(key, labels.relative(at, offset)?);
+            }
+
+            return Ok(Instruction::LookupSwitch {
+                default: labels.relative(at, default)?,
+                targets,
+            });
+        }
+        LDC(index) | LDCW(index) | LDC2W(index) => {
+            return Ok(Instruction::Constant(lifter.constant(index)?))
+        }
+        GetStatic(index) => return lift_field(lifter, FieldInsn::GetStatic, index),
+        PutStatic(index) => return lift_field(lifter, FieldInsn::PutStatic, index),
+        GetField(index) => return lift_field(lifter, FieldInsn::GetField, index),
+        PutField(index) => return lift_field(lifter, FieldInsn::PutField, index),
+        InvokeVirtual(index) => return lift_method(lifter, MethodInsn::InvokeVirtual, index),
+        InvokeSpecial(index) => return lift_method(lifter, MethodInsn::InvokeSpecial, index),
+        InvokeStatic(index) => return lift_method(lifter, MethodInsn::InvokeStatic, index),
+        InvokeInterface(index, ..) => {
+            return lift_method(lifter, MethodInsn::InvokeInterface, index)
+        }
+        InvokeDynamic(index, _) => {
+            return Ok(Instruction::InvokeDynamic(lifter.invoke_dynamic(index)?))
+        }
+        New(index) => return lift_type(lifter, TypeInsn::New, index),
+        ANewArray(index) => return lift_type(lifter, TypeInsn::ANewArray, index),
+        CheckCast(index) => return lift_type(lifter, TypeInsn::CheckCast, index),
+        InstanceOf(index) => return lift_type(lifter, TypeInsn::InstanceOf, index),
+        MultiANewArray(index, dimensions) => {
+            return Ok(Instruction::MultiANewArray(lifter.class_name(index)?, dimensions))
+        }
+        insn => return Ok(Instruction::Simple(insn)),
+    };
+
+    Ok(Instruction::Jump(jump, labels.relative(at, offset)?))
+}
+
+fn lift_field(lifter: &Lifter, insn: FieldInsn, index: u16) -> Result<Instruction> {
+    Ok(Instruction::Field(insn, lifter.field_ref(index)?))
+}
+
+fn lift_method(lifter: &Lifter, insn: MethodInsn, index: u16) -> Result<Instruction> {
+    Ok(Instruction::Method(insn, lifter.method_ref(index)?))
+}
+
+fn lift_type(lifter: &Lifter, insn: TypeInsn, index: u16) -> Result<Instruction> {
+    Ok(Instruction::Type(insn, lifter.class_name(index)?))
+}
+
+/// Converts a frame, `previous` is the offset of the previous frame.
+fn lift_frame(
+    lifter: &Lifter,
+    labels: &mut Labels,
+    previous: &mut Option<u32>,
+    frame: basic::StackMapFrame,
+) -> Result<StackMapFrame> {
+    use basic::StackMapFrame::*;
+
+    let offset_delta = match frame {
+        Same { offset_delta, .. }
+        | Same1 { offset_delta, .. }
+        | Chop { offset_delta, .. }
+        | Append { offset_delta, .. }
+        | Full { offset_delta, .. } => u32::from(offset_delta),
+    };
+    // every frame except for the first one is located at least one byte after the previous one
+    let offset = match *previous {
+        Some(previous) => previous + offset_delta + 1,
+        None => offset_delta,
+    };
+    *previous = Some(offset);
+    let label = labels.get(offset)?;
+
+    Ok(match frame {
+        Same { .. } => StackMapFrame::Same { label },
+        Same1 { stack, .. } => StackMapFrame::Same1 {
+            label,
+            stack: lift_verification_type(lifter, labels, stack)?,
+        },
+        Chop { count, .. } => StackMapFrame::Chop { label, count },
+        Append { locals, .. } => StackMapFrame::Append {
+            label,
+            locals: lift_verification_types(lifter, labels, locals)?,
+        },
+        Full { locals, stack, .. } => StackMapFrame::Full {
+            label,
+            locals: lift_verification_types(lifter, labels, locals)?,
+            stack: lift_verification_types(lifter, labels, stack)?,
+        },
+    })
+}
+
+fn lift_verification_types(
+    lifter: &Lifter,
+    labels: &mut Labels,
+    types: Vec<basic::VerificationType>,
+) -> Result<Vec<VerificationType>> {
+    let mut lifted = Vec::with_capacity(types.len());
+    for verification_type in types {
+        lifted.push(lift_verification_type(lifter, labels, verification_type)?);
+    }
+    Ok(lifted)
+}
+
+fn lift_verification_type(
+    lifter: &Lifter,
+    labels: &mut Labels,
+    verification_type: basic::VerificationType,
+) -> Result<VerificationType> {
+    use basic::VerificationType::*;
+
+    Ok(match verification_type {
+        Top => VerificationType::Top,
+        Integer => VerificationType::Integer,
+        Float => VerificationType::Float,
+        Double => VerificationType::Double,
+        Long => VerificationType::Long,
+        Null => VerificationType::Null,
+        UninitializedThis => VerificationType::UninitializedThis,
+        Object(index) => VerificationType::Object(lifter.class_name(index)?),
+        Uninitialized(offset) => VerificationType::Uninitialized(labels.get(u32::from(offset))?),
+    })
+}
+
+fn lift_type_annotation(
+    lifter: &Lifter,
+    labels: &mut Labels,
+    annotation: basic::TypeAnnotation,
+) -> Result<TypeAnnotation> {
+    use basic::TargetType::*;
+
+    let target_type = match annotation.target_type {
+        LocalVariable(table) => TargetType::LocalVariable(lift_local_variable_targets(labels, table)?),
+        ResourceVariable(table) => {
+            TargetType::ResourceVariable(lift_local_variable_targets(labels, table)?)
+        }
+        Catch(index) => TargetType::Catch(index),
+        OffsetInstanceOf(offset) => TargetType::OffsetInstanceOf(labels.get(offset.into())?),
+        OffsetNew(offset) => TargetType::OffsetNew(labels.get(offset.into())?),
+        OffsetNewRef(offset) => TargetType::OffsetNewRef(labels.get(offset.into())?),
+        OffsetRef(offset) => TargetType::OffsetRef(labels.get(offset.into())?),
+        TypeArgumentCast {
+            offset,
+            type_argument,
+        } => TargetType::TypeArgumentCast {
+            label: labels.get(offset.into())?,
+            type_argument,
+        },
+        TypeArgumentMethod {
+            offset,
+            type_argument,
+        } => TargetType::TypeArgumentMethod {
+            label: labels.get(offset.into())?,
+            type_argument,
+        },
+        TypeArgumentConstructor {
+            offset,
+            type_argument,
+        } => TargetType::TypeArgumentConstructor {
+            label: labels.get(offset.into())?,
+            type_argument,
+        },
+        TypeArgumentNewRef {
+            offset,
+            type_argument,
+        } => TargetType::TypeArgumentNewRef {
+            label: labels.get(offset.into())?,
+            type_argument,
+        },
+        TypeArgumentRef {
+            offset,
+            type_argument,
+        } => TargetType::TypeArgumentRef {
+            label: labels.get(offset.into())?,
+            type_argument,
+        },
+        target_type => TargetType::Other(target_type),
+    };
+
+    Ok(TypeAnnotation {
+        target_type,
+        target_path: annotation.target_path,
+        annotation: lifter.annotation(annotation.annotation)?,
+    })
+}
+
+fn lift_local_variable_targets(
+    labels: &mut Labels,
+    table: Vec<basic::LocalVariableTarget>,
+) -> Result<Vec<LocalVariableTarget>> {
+    let mut targets = Vec::with_capacity(table.len());
+    for target in table {
+        let (start, end) = labels.range(target.start, target.length)?;
+        targets.push(LocalVariableTarget {
+            start,
+            end,
+            index: target.index,
+        });
+    }
+    Ok(targets)
+}
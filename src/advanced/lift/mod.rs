@@ -0,0 +1,568 @@
+//! Converts a basic `Class` (and its constant pool) to the advanced `Class`.
+
+mod code;
+
+use basic::{self, Item, Pool, ReferenceKind};
+use result::*;
+use super::class::*;
+use super::code::TargetType;
+use super::constant::*;
+
+impl Class {
+    /// Converts a basic class to a class, where every index into the constant pool is resolved.
+    ///
+    /// An error is returned, if an index does not point to the expected item,
+    /// if a descriptor is invalid
+    /// or if an attribute is found at a place it is not allowed at.
+    pub fn from_basic(constant_pool: &Pool, class: basic::Class) -> Result<Class> {
+        let mut bootstrap_methods = Vec::new();
+        let mut attributes = Vec::with_capacity(class.attributes.len());
+        for attribute in class.attributes {
+            match attribute {
+                basic::Attribute::BootstrapMethods(methods) => bootstrap_methods = methods,
+                attribute => attributes.push(attribute),
+            }
+        }
+
+        let lifter = Lifter {
+            constant_pool,
+            bootstrap_methods: &bootstrap_methods,
+        };
+
+        let super_name = if class.super_name == 0 {
+            None
+        } else {
+            Some(lifter.class_name(class.super_name)?)
+        };
+
+        let mut interfaces = Vec::with_capacity(class.interfaces.len());
+        for interface in class.interfaces {
+            interfaces.push(lifter.class_name(interface)?);
+        }
+
+        let mut fields = Vec::with_capacity(class.fields.len());
+        for field in class.fields {
+            fields.push(Field {
+                access_flags: field.access_flags,
+                name: lifter.utf8(field.name)?,
+                desc: lifter.utf8(field.desc)?.parse()?,
+                attributes: lifter.attributes(field.attributes)?,
+            });
+        }
+
+        let mut methods = Vec::with_capacity(class.methods.len());
+        for method in class.methods {
+            let mut code = None;
+            let mut attributes = Vec::with_capacity(method.attributes.len());
+            for attribute in method.attributes {
+                match attribute {
+                    attribute @ basic::Attribute::Code { .. } if code.is_none() => {
+                        code = Some(lifter.code(attribute)?);
+                    }
+                    attribute => attributes.push(attribute),
+                }
+            }
+
+            methods.push(Method {
+                access_flags: method.access_flags,
+                name: lifter.utf8(method.name)?,
+                desc: lifter.utf8(method.desc)?.parse()?,
+                code,
+                attributes: lifter.attributes(attributes)?,
+            });
+        }
+
+        Ok(Class {
+            minor_version: class.minor_version,
+            major_version: class.major_version,
+            access_flags: class.access_flags,
+            name: lifter.class_name(class.name)?,
+            super_name,
+            interfaces,
+            fields,
+            methods,
+            attributes: lifter.attributes(attributes)?,
+        })
+    }
+}
+
+/// Resolves indices into the constant pool
+/// and into the bootstrap methods of a class.
+struct Lifter<'a> {
+    constant_pool: &'a Pool,
+    bootstrap_methods: &'a [basic::BootstrapMethod],
+}
+
+impl<'a> Lifter<'a> {
+    fn utf8(&self, index: u16) -> Result<String> {
+        self.constant_pool.get_utf8(index)
+    }
+
+    /// Returns `None` if the index is 0, the utf8 item otherwise.
+    fn utf8_opt(&self, index: u16) -> Result<Option<String>> {
+        if index == 0 {
+            Ok(None)
+        } else {
+            self.utf8(index).map(Some)
+        }
+    }
+
+    fn class_name(&self, index: u16) -> Result<String> {
+        self.constant_pool.get_class_name(index)
+    }
+
+    /// Returns `None` if the index is 0, the class name otherwise.
+    fn class_name_opt(&self, index: u16) -> Result<Option<String>> {
+        if index == 0 {
+            Ok(None)
+        } else {
+            self.class_name(index).map(Some)
+        }
+    }
+
+    fn module_name(&self, index: u16) -> Result<String> {
+        match *self.constant_pool.get(index)? {
+            Item::Module(name) => self.utf8(name),
+            _ => Err(Error::InvalidCPItem(index)),
+        }
+    }
+
+    fn package_name(&self, index: u16) -> Result<String> {
+        match *self.constant_pool.get(index)? {
+            Item::Package(name) => self.utf8(name),
+            _ => Err(Error::InvalidCPItem(index)),
+        }
+    }
+
+    fn name_and_type(&self, index: u16) -> Result<(String, String)> {
+        match *self.constant_pool.get(index)? {
+            Item::NameAndType { name, desc } => Ok((self.utf8(name)?, self.utf8(desc)?)),
+            _ => Err(Error::InvalidCPItem(index)),
+        }
+    }
+
+    fn field_ref(&self, index: u16) -> Result<FieldRef> {
+        match *self.constant_pool.get(index)? {
+            Item::FieldRef {
+                class,
+                name_and_type,
+            } => {
+                let (name, desc) = self.name_and_type(name_and_type)?;
+                Ok(FieldRef {
+                    owner: self.class_name(class)?,
+                    name,
+                    desc: desc.parse()?,
+                })
+            }
+            _ => Err(Error::InvalidCPItem(index)),
+        }
+    }
+
+    fn method_ref(&self, index: u16) -> Result<MethodRef> {
+        let (class, name_and_type, interface) = match *self.constant_pool.get(index)? {
+            Item::MethodRef {
+                class,
+                name_and_type,
+            } => (class, name_and_type, false),
+            Item::InterfaceMethodRef {
+                class,
+                name_and_type,
+            } => (class, name_and_type, true),
+            _ => return Err(Error::InvalidCPItem(index)),
+        };
+
+        let (name, desc) = self.name_and_type(name_and_type)?;
+        Ok(MethodRef {
+            owner: self.class_name(class)?,
+            name,
+            desc: desc.parse()?,
+            interface,
+        })
+    }
+
+    fn method_handle(&self, index: u16) -> Result<MethodHandle> {
+        match *self.constant_pool.get(index)? {
+            Item::MethodHandle {
+                ref kind,
+                index: reference,
+            } => {
+                let reference = match *kind {
+                    ReferenceKind::GetField
+                    | ReferenceKind::GetStatic
+                    | ReferenceKind::PutField
+                    | ReferenceKind::PutStatic => Reference::Field(self.field_ref(reference)?),
+                    _ => Reference::Method(self.method_ref(reference)?),
+                };
+
+                Ok(MethodHandle {
+                    kind: kind.clone(),
+                    reference,
+                })
+            }
+            _ => Err(Error::InvalidCPItem(index)),
+        }
+    }
+
+    fn constant(&self, index: u16) -> Result<Constant> {
+        Ok(match *self.constant_pool.get(index)? {
+            Item::Integer(value) => Constant::Integer(value),
+            Item::Float(value) => Constant::Float(value),
+            Item::Long(value) => Constant::Long(value),
+            Item::Double(value) => Constant::Double(value),
+            Item::String(string) => match *self.constant_pool.get(string)? {
+                Item::UTF8(ref string) => Constant::String(string.clone()),
+                Item::UTF16(ref units) => Constant::UTF16(units.clone()),
+                _ => return Err(Error::InvalidCPItem(string)),
+            },
+            Item::Class(name) => Constant::Class(self.utf8(name)?),
+            Item::MethodType(desc) => Constant::MethodType(self.utf8(desc)?.parse()?),
+            Item::MethodHandle { .. } => Constant::MethodHandle(self.method_handle(index)?),
+            Item::Dynamic { .. } => {
+                let (bootstrap_method, arguments, (name, desc)) = self.bootstrap_method(index)?;
+                Constant::Dynamic(Dynamic {
+                    name,
+                    desc: desc.parse()?,
+                    bootstrap_method,
+                    arguments,
+                })
+            }
+            _ => return Err(Error::InvalidCPItem(index)),
+        })
+    }
+
+    fn invoke_dynamic(&self, index: u16) -> Result<InvokeDynamic> {
+        match *self.constant_pool.get(index)? {
+            Item::InvokeDynamic { .. } => {}
+            _ => return Err(Error::InvalidCPItem(index)),
+        }
+
+        let (bootstrap_method, arguments, (name, desc)) = self.bootstrap_method(index)?;
+        Ok(InvokeDynamic {
+            name,
+            desc: desc.parse()?,
+            bootstrap_method,
+            arguments,
+        })
+    }
+
+    /// Returns the bootstrap method, its arguments and the name and the descriptor
+    /// of an `Item::Dynamic { .. }` or an `Item::InvokeDynamic { .. }`.
+    fn bootstrap_method(
+        &self,
+        index: u16,
+    ) -> Result<(MethodHandle, Vec<Constant>, (String, String))> {
+        let (bootstrap_method, name_and_type) = self.constant_pool
+            .get_bootstrap_method(index, self.bootstrap_methods)?;
+
+        let mut arguments = Vec::with_capacity(bootstrap_method.arguments.len());
+        for argument in &bootstrap_method.arguments {
+            arguments.push(self.constant(*argument)?);
+        }
+
+        Ok((
+            self.method_handle(bootstrap_method.method_ref)?,
+            arguments,
+            self.name_and_type(name_and_type)?,
+        ))
+    }
+
+    /// Converts the attributes of a class, a field, a method or a code attribute.
+    fn attributes(&self, attributes: Vec<basic::Attribute>) -> Result<Vec<Attribute>> {
+        let mut lifted = Vec::with_capacity(attributes.len());
+        for attribute in attributes {
+            lifted.push(self.attribute(attribute)?);
+        }
+        Ok(lifted)
+    }
+
+    fn attribute(&self, attribute: basic::Attribute) -> Result<Attribute> {
+        use basic::Attribute::*;
+
+        Ok(match attribute {
+            AnnotationDefault(value) => Attribute::AnnotationDefault(self.element_value(value)?),
+            ConstantValue(index) => Attribute::ConstantValue(self.constant(index)?),
+            Deprecated => Attribute::Deprecated,
+            EnclosingMethod {
+                class_index,
+                method_index,
+            } => {
+                let method = if method_index == 0 {
+                    None
+                } else {
+                    let (name, desc) = self.name_and_type(method_index)?;
+                    Some((name, desc.parse()?))
+                };
+
+                Attribute::EnclosingMethod {
+                    class: self.class_name(class_index)?,
+                    method,
+                }
+            }
+            Exceptions(exceptions) => {
+                let mut names = Vec::with_capacity(exceptions.len());
+                for exception in exceptions {
+                    names.push(self.class_name(exception)?);
+                }
+                Attribute::Exceptions(names)
+            }
+            InnerClasses(classes) => {
+                let mut lifted = Vec::with_capacity(classes.len());
+                for class in classes {
+                    lifted.push(InnerClass {
+                        inner_class: self.class_name(class.inner_class_info)?,
+                        outer_class: self.class_name_opt(class.outer_class_info)?,
+                        inner_name: self.utf8_opt(class.inner_name)?,
+                        inner_class_access_flags: class.inner_class_access_flags,
+                    });
+                }
+                Attribute::InnerClasses(lifted)
+            }
+            MethodParameters(parameters) => {
+                let mut lifted = Vec::with_capacity(parameters.len());
+                for parameter in parameters {
+                    lifted.push(MethodParameter {
+                        name: self.utf8_opt(parameter.name)?,
+                        access_flags: parameter.access_flags,
+                    });
+                }
+                Attribute::MethodParameters(lifted)
+            }
+            Module {
+                name,
+                flags,
+                version,
+                requires,
+                exports,
+                opens,
+                uses,
+                provides,
+            } => {
+                let mut module = super::Module {
+                    name: self.module_name(name)?,
+                    flags,
+                    version: self.utf8_opt(version)?,
+                    requires: Vec::with_capacity(requires.len()),
+                    exports: Vec::with_capacity(exports.len()),
+                    opens: Vec::with_capacity(opens.len()),
+                    uses: Vec::with_capacity(uses.len()),
+                    provides: Vec::with_capacity(provides.len()),
+                };
+
+                for requirement in requires {
+                    module.requires.push(Requirement {
+                        module: self.module_name(requirement.index)?,
+                        flags: requirement.flags,
+                        version: self.utf8_opt(requirement.version)?,
+                    });
+                }
+                for export in exports {
+                    module.exports.push(Export {
+                        package: self.package_name(export.index)?,
+                        flags: export.flags,
+                        to: self.module_names(export.to)?,
+                    });
+                }
+                for opening in opens {
+                    module.opens.push(Opening {
+                        package: self.package_name(opening.index)?,
+                        flags: opening.flags,
+                        to: self.module_names(opening.to)?,
+                    });
+                }
+                for service in uses {
+                    module.uses.push(self.class_name(service)?);
+                }
+                for provider in provides {
+                    let mut with = Vec::with_capacity(provider.with.len());
+                    for implementation in provider.with {
+                        with.push(self.class_name(implementation)?);
+                    }
+                    module.provides.push(Provider {
+                        service: self.class_name(provider.index)?,
+                        with,
+                    });
+                }
+
+                Attribute::Module(module)
+            }
+            ModuleMainClass(index) => Attribute::ModuleMainClass(self.class_name(index)?),
+            ModulePackages(packages) => {
+                let mut names = Vec::with_capacity(packages.len());
+                for package in packages {
+                    names.push(self.package_name(package)?);
+                }
+                Attribute::ModulePackages(names)
+            }
+            NestHost(index) => Attribute::NestHost(self.class_name(index)?),
+            NestMembers(members) => {
+                let mut names = Vec::with_capacity(members.len());
+                for member in members {
+                    names.push(self.class_name(member)?);
+                }
+                Attribute::NestMembers(names)
+            }
+            PermittedSubclasses(subclasses) => {
+                let mut names = Vec::with_capacity(subclasses.len());
+                for subclass in subclasses {
+                    names.push(self.class_name(subclass)?);
+                }
+                Attribute::PermittedSubclasses(names)
+            }
+            Record(components) => {
+                let mut lifted = Vec::with_capacity(components.len());
+                for component in components {
+                    lifted.push(RecordComponent {
+                        name: self.utf8(component.name)?,
+                        desc: self.utf8(component.descriptor)?.parse()?,
+                        attributes: self.attributes(component.attributes)?,
+                    });
+                }
+                Attribute::Record(lifted)
+            }
+            RuntimeVisibleAnnotations(annotations) => {
+                Attribute::RuntimeVisibleAnnotations(self.annotations(annotations)?)
+            }
+            RuntimeInvisibleAnnotations(annotations) => {
+                Attribute::RuntimeInvisibleAnnotations(self.annotations(annotations)?)
+            }
+            RuntimeVisibleParameterAnnotations(parameters) => {
+                Attribute::RuntimeVisibleParameterAnnotations(self.parameter_annotations(parameters)?)
+            }
+            RuntimeInvisibleParameterAnnotations(parameters) => {
+                Attribute::RuntimeInvisibleParameterAnnotations(
+                    self.parameter_annotations(parameters)?,
+                )
+            }
+            RuntimeVisibleTypeAnnotations(annotations) => {
+                Attribute::RuntimeVisibleTypeAnnotations(self.type_annotations(annotations)?)
+            }
+            RuntimeInvisibleTypeAnnotations(annotations) => {
+                Attribute::RuntimeInvisibleTypeAnnotations(self.type_annotations(annotations)?)
+            }
+            Signature(index) => Attribute::Signature(self.utf8(index)?),
+            Synthetic => Attribute::Synthetic,
+            SourceFile(index) => Attribute::SourceFile(self.utf8(index)?),
+            SourceDebugExtension(content) => Attribute::SourceDebugExtension(content),
+            Unknown(name, content) => Attribute::Unknown(self.utf8(name)?, content),
+            BootstrapMethods(_)
+            | Code { .. }
+            | LineNumberTable(_)
+            | LocalVariableTable(_)
+            | LocalVariableTypeTable(_)
+            | StackMapTable(_) => return Err(Error::UnexpectedAttribute),
+        })
+    }
+
+    fn module_names(&self, indices: Vec<u16>) -> Result<Vec<String>> {
+        let mut names = Vec::with_capacity(indices.len());
+        for index in indices {
+            names.push(self.module_name(index)?);
+        }
+        Ok(names)
+    }
+
+    fn annotations(&self, annotations: Vec<basic::Annotation>) -> Result<Vec<Annotation>> {
+        let mut lifted = Vec::with_capacity(annotations.len());
+        for annotation in annotations {
+            lifted.push(self.annotation(annotation)?);
+        }
+        Ok(lifted)
+    }
+
+    fn parameter_annotations(
+        &self,
+        parameters: Vec<Vec<basic::Annotation>>,
+    ) -> Result<Vec<Vec<Annotation>>> {
+        let mut lifted = Vec::with_capacity(parameters.len());
+        for annotations in parameters {
+            lifted.push(self.annotations(annotations)?);
+        }
+        Ok(lifted)
+    }
+
+    /// Converts type annotations, which are not located in the code.
+    fn type_annotations(
+        &self,
+        annotations: Vec<basic::TypeAnnotation>,
+    ) -> Result<Vec<super::TypeAnnotation>> {
+        let mut lifted = Vec::with_capacity(annotations.len());
+        for annotation in annotations {
+            lifted.push(super::TypeAnnotation {
+                target_type: TargetType::Other(annotation.target_type),
+                target_path: annotation.target_path,
+                annotation: self.annotation(annotation.annotation)?,
+            });
+        }
+        Ok(lifted)
+    }
+
+    fn annotation(&self, annotation: basic::Annotation) -> Result<Annotation> {
+        let mut element_value_pairs = Vec::with_capacity(annotation.element_value_pairs.len());
+        for (name, value) in annotation.element_value_pairs {
+            element_value_pairs.push((self.utf8(name)?, self.element_value(value)?));
+        }
+
+        Ok(Annotation {
+            type_desc: self.utf8(annotation.type_index)?.parse()?,
+            element_value_pairs,
+        })
+    }
+
+    fn element_value(&self, value: basic::ElementValue) -> Result<ElementValue> {
+        use basic::ElementValue::*;
+
+        Ok(match value {
+            Byte(index) => ElementValue::Byte(self.integer(index)? as i8),
+            Short(index) => ElementValue::Short(self.integer(index)? as i16),
+            Char(index) => ElementValue::Char(self.integer(index)? as u16),
+            Int(index) => ElementValue::Int(self.integer(index)?),
+            Long(index) => match *self.constant_pool.get(index)? {
+                Item::Long(value) => ElementValue::Long(value),
+                _ => return Err(Error::InvalidCPItem(index)),
+            },
+            Float(index) => match *self.constant_pool.get(index)? {
+                Item::Float(value) => ElementValue::Float(value),
+                _ => return Err(Error::InvalidCPItem(index)),
+            },
+            Double(index) => match *self.constant_pool.get(index)? {
+                Item::Double(value) => ElementValue::Double(value),
+                _ => return Err(Error::InvalidCPItem(index)),
+            },
+            Boolean(index) => ElementValue::Boolean(self.integer(index)? != 0),
+            String(index) => ElementValue::String(self.utf8(index)?),
+            Enum {
+                type_name,
+                const_name,
+            } => ElementValue::Enum {
+                type_desc: self.utf8(type_name)?.parse()?,
+                const_name: self.utf8(const_name)?,
+            },
+            Class(index) => {
+                let desc = self.utf8(index)?;
+                if desc == "V" {
+                    ElementValue::Class(None)
+                } else {
+                    ElementValue::Class(Some(desc.parse()?))
+                }
+            }
+            Annotation(annotation) => {
+                ElementValue::Annotation(Box::new(self.annotation(*annotation)?))
+            }
+            Array(values) => {
+                let mut lifted = Vec::with_capacity(values.len());
+                for value in values {
+                    lifted.push(self.element_value(value)?);
+                }
+                ElementValue::Array(lifted)
+            }
+        })
+    }
+
+    fn integer(&self, index: u16) -> Result<i32> {
+        match *self.constant_pool.get(index)? {
+            Item::Integer(value) => Ok(value),
+            _ => Err(Error::InvalidCPItem(index)),
+        }
+    }
+}
+
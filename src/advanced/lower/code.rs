@@ -0,0 +1,616 @@
+//! Converts the advanced `Code` back to a basic `Attribute::Code`.
+
+use std::collections::{BTreeMap, HashMap};
+
+use basic::{self, Pool};
+use result::*;
+use super::Lowerer;
+use super::super::code::*;
+
+impl Code {
+    /// Converts this code back to an `Attribute::Code`, computing the offset of every label.
+    /// Every item referenced is pushed to the constant pool,
+    /// the bootstrap methods of `invokedynamic` instructions are pushed to `bootstrap_methods`,
+    /// which is the content of the `BootstrapMethods` attribute of the class.
+    ///
+    /// Jumps are encoded in their short form, if possible,
+    /// the padding of switches is recomputed.
+    /// A conditional jump, which is too far, is replaced by the negated jump over a `goto_w`.
+    /// The instruction following the `goto_w` becomes a branch target,
+    /// whose frame can only be computed knowing the method declaring the code,
+    /// so `Class::into_basic` has to be used for code with frames in this case.
+    /// Line numbers, local variables, frames and type annotations are written
+    /// to their own attributes, if they are not empty.
+    /// The names of these attributes are pushed to the constant pool.
+    ///
+    /// An error is returned, if a label has not been placed exactly once,
+    /// if an `Instruction::Simple` references the code or the constant pool,
+    /// if frames or ranges are not in order, if the code is too large
+    /// or if a frame would be missing after widening a conditional jump.
+    pub fn into_attribute(
+        self,
+        constant_pool: &mut Pool,
+        bootstrap_methods: &mut Vec<basic::BootstrapMethod>,
+    ) -> Result<basic::Attribute> {
+        let (code, targets) = Lowerer {
+            constant_pool,
+            bootstrap_methods,
+        }.code(self)?;
+        match targets.first() {
+            Some(&target) => Err(Error::MissingFrame(target)),
+            None => Ok(code),
+        }
+    }
+}
+
+impl<'a> Lowerer<'a> {
+    /// Converts code back to an `Attribute::Code`.
+    /// The offsets of all instructions following a widened conditional jump are returned
+    /// as well, if the code has frames, since a frame has to be inserted there.
+    pub(super) fn code(&mut self, code: Code) -> Result<(basic::Attribute, Vec<u32>)> {
+        // resolved instructions are replaced by their basic form first,
+        // since their length depends on the indices in the constant pool
+        let mut lowered = Vec::with_capacity(code.instructions.len());
+        for insn in code.instructions {
+            lowered.push(self.instruction(insn)?);
+        }
+
+        let (layout, wide) = compute_layout(&lowered)?;
+
+        let mut instructions = basic::Instructions::new();
+        let mut targets = Vec::new();
+        for (i, insn) in lowered.into_iter().enumerate() {
+            let at = layout.offsets[i];
+            match insn {
+                Instruction::Label(_) => {}
+                Instruction::Simple(insn) => {
+                    instructions.insert(at, insn);
+                }
+                Instruction::Jump(jump, label) => {
+                    let offset = layout.relative(at, label)?;
+                    if !wide[i] {
+                        instructions.insert(at, short_jump(jump, offset));
+                    } else if let Some(negated) = jump.negate() {
+                        // jump over the goto_w, if the condition does not hold
+                        instructions.insert(at, short_jump(negated, 8));
+                        instructions.insert(at + 3, basic::Instruction::GoToW(offset - 3));
+                        if !code.frames.is_empty() {
+                            targets.push(at + 8);
+                        }
+                    } else if jump == Jump::GoTo {
+                        instructions.insert(at, basic::Instruction::GoToW(offset));
+                    } else {
+                        instructions.insert(at, basic::Instruction::JSRW(offset));
+                    }
+                }
+                Instruction::TableSwitch {
+                    default,
+                    low,
+                    high,
+                    targets,
+                } => {
+                    let mut offsets = Vec::with_capacity(targets.len());
+                    for target in targets {
+                        offsets.push(layout.relative(at, target)?);
+                    }
+
+                    instructions.insert(
+                        at,
+                        basic::Instruction::TableSwitch {
+                            default: layout.relative(at, default)?,
+                            low,
+                            high,
+                            offsets,
+                            padding: [0; 3],
+                        },
+                    );
+                }
+                Instruction::LookupSwitch { default, targets } => {
+                    let mut offsets = BTreeMap::new();
+                    for (key, target) in targets {
+                        offsets.insert(key, layout.relative(at, target)?);
+                    }
+
+                    instructions.insert(
+                        at,
+                        basic::Instruction::LookupSwitch {
+                            default: layout.relative(at, default)?,
+                            offsets,
+                            padding: [0; 3],
+                        },
+                    );
+                }
+                _ => unreachable!("resolved instructions have been lowered before"),
+            }
+        }
+
+        let mut exceptions = Vec::with_capacity(code.exceptions.len());
+        for exception in code.exceptions {
+            exceptions.push(basic::Exception {
+                start: layout.get(exception.start)?,
+                end: layout.get(exception.end)?,
+                handler: layout.get(exception.handler)?,
+                catch_type: self.class_opt(exception.catch_type)?,
+            });
+        }
+
+        let mut attributes = Vec::with_capacity(code.attributes.len() + 6);
+
+        if !code.line_numbers.is_empty() {
+            self.name("LineNumberTable")?;
+
+            let mut table = Vec::with_capacity(code.line_numbers.len());
+            for line_number in code.line_numbers {
+                table.push(basic::LineNumber {
+                    start: layout.get(line_number.start)?,
+                    line_number: line_number.line_number,
+                });
+            }
+            attributes.push(basic::Attribute::LineNumberTable(table));
+        }
+
+        if !code.local_variables.is_empty() {
+            self.name("LocalVariableTable")?;
+
+            let mut table = Vec::with_capacity(code.local_variables.len());
+            for variable in code.local_variables {
+                let (start, length) = layout.range(variable.start, variable.end)?;
+                table.push(basic::LocalVariable {
+                    start,
+                    length,
+                    name: self.utf8(variable.name)?,
+                    descriptor: self.utf8(variable.descriptor.to_string())?,
+                    index: variable.index,
+                });
+            }
+            attributes.push(basic::Attribute::LocalVariableTable(table));
+        }
+
+        if !code.local_variable_types.is_empty() {
+            self.name("LocalVariableTypeTable")?;
+
+            let mut table = Vec::with_capacity(code.local_variable_types.len());
+            for variable in code.local_variable_types {
+                let (start, length) = layout.range(variable.start, variable.end)?;
+                table.push(basic::LocalVariableType {
+                    start,
+                    length,
+                    name: self.utf8(variable.name)?,
+                    signature: self.utf8(variable.signature)?,
+                    index: variable.index,
+                });
+            }
+            attributes.push(basic::Attribute::LocalVariableTypeTable(table));
+        }
+
+        if !code.frames.is_empty() {
+            self.name("StackMapTable")?;
+
+            let mut table = Vec::with_capacity(code.frames.len());
+            let mut previous = None;
+            for frame in code.frames {
+                table.push(lower_frame(self, &layout, &mut previous, frame)?);
+            }
+            attributes.push(basic::Attribute::StackMapTable(table));
+        }
+
+        if !code.visible_type_annotations.is_empty() {
+            self.name("RuntimeVisibleTypeAnnotations")?;
+
+            let mut annotations = Vec::with_capacity(code.visible_type_annotations.len());
+            for annotation in code.visible_type_annotations {
+                annotations.push(lower_type_annotation(self, &layout, annotation)?);
+            }
+            attributes.push(basic::Attribute::RuntimeVisibleTypeAnnotations(annotations));
+        }
+
+        if !code.invisible_type_annotations.is_empty() {
+            self.name("RuntimeInvisibleTypeAnnotations")?;
+
+            let mut annotations = Vec::with_capacity(code.invisible_type_annotations.len());
+            for annotation in code.invisible_type_annotations {
+                annotations.push(lower_type_annotation(self, &layout, annotation)?);
+            }
+            attributes.push(basic::Attribute::RuntimeInvisibleTypeAnnotations(annotations));
+        }
+
+        attributes.extend(self.attributes(code.attributes)?);
+
+        let code = basic::Attribute::Code {
+            max_stack: code.max_stack,
+            max_locals: code.max_locals,
+            instructions,
+            exceptions,
+            attributes,
+        };
+        Ok((code, targets))
+    }
+}
+
+impl<'a> Lowerer<'a> {
+    /// Replaces an instruction referencing the constant pool by its basic form.
+    fn instruction(&mut self, insn: Instruction) -> Result<Instruction> {
+        use basic::Instruction::*;
+
+        Ok(Instruction::Simple(match insn {
+            Instruction::Simple(ref simple) if is_branch(simple) || references_pool(simple) => {
+                return Err(Error::UnexpectedInstruction(simple.mnemonic()));
+            }
+            Instruction::Constant(constant) => {
+                let double = constant.is_double();
+                let index = self.constant(constant)?;
+                if double {
+                    LDC2W(index)
+                } else if index <= u16::from(u8::MAX) {
+                    LDC(index)
+                } else {
+                    LDCW(index)
+                }
+            }
+            Instruction::Field(insn, field) => {
+                let index = self.field_ref(field)?;
+                match insn {
+                    FieldInsn::GetStatic => GetStatic(index),
+                    FieldInsn::PutStatic => PutStatic(index),
+                    FieldInsn::GetField => GetField(index),
+                    FieldInsn::PutField => PutField(index),
+                }
+            }
+            Instruction::Method(insn, method) => {
+                // the receiver and the arguments
+                let count = 1 + method.desc.params.iter().map(|param| param.size()).sum::<u16>();
+                let index = self.method_ref(method)?;
+                match insn {
+                    MethodInsn::InvokeVirtual => InvokeVirtual(index),
+                    MethodInsn::InvokeSpecial => InvokeSpecial(index),
+                    MethodInsn::InvokeStatic => InvokeStatic(index),
+                    MethodInsn::InvokeInterface => InvokeInterface(index, count as u8, 0),
+                }
+            }
+            Instruction::InvokeDynamic(call_site) => {
+                InvokeDynamic(self.invoke_dynamic(call_site)?, 0)
+            }
+            Instruction::Type(insn, name) => {
+                let index = self.class(name)?;
+                match insn {
+                    TypeInsn::New => New(index),
+                    TypeInsn::ANewArray => ANewArray(index),
+                    TypeInsn::CheckCast => CheckCast(index),
+                    TypeInsn::InstanceOf => InstanceOf(index),
+                }
+            }
+            Instruction::MultiANewArray(name, dimensions) => {
+                MultiANewArray(self.class(name)?, dimensions)
+            }
+            insn => return Ok(insn),
+        }))
+    }
+}
+
+/// The offsets of all instructions and labels.
+struct Layout {
+    /// The offset of every instruction (and label) by its index
+    offsets: Vec<u32>,
+    labels: HashMap<Label, u32>,
+}
+
+impl Layout {
+    /// Returns the offset of a label.
+    fn get(&self, label: Label) -> Result<u16> {
+        // the code is at most 65535 bytes large, so every offset fits in a u16
+        self.labels
+            .get(&label)
+            .map(|offset| *offset as u16)
+            .ok_or(Error::InvalidLabel)
+    }
+
+    /// Returns the offset of a label relative to an instruction located at `at`.
+    fn relative(&self, at: u32, label: Label) -> Result<i32> {
+        Ok(i32::from(self.get(label)?) - at as i32)
+    }
+
+    /// Returns the start and the length of a range.
+    fn range(&self, start: Label, end: Label) -> Result<(u16, u16)> {
+        let start = self.get(start)?;
+        let end = self.get(end)?;
+        if end < start {
+            return Err(Error::InvalidOffset(u32::from(end)));
+        }
+
+        Ok((start, end - start))
+    }
+}
+
+/// Computes the offsets of all instructions and labels
+/// and decides which jumps have to be encoded in their wide form.
+fn compute_layout(instructions: &[Instruction]) -> Result<(Layout, Vec<bool>)> {
+    // Start with every jump being short and widen jumps, which are too far.
+    // Widening a jump moves all following instructions,
+    // so this is repeated until nothing changes anymore.
+    // Since jumps are never narrowed again, this terminates.
+    let mut wide = vec![false; instructions.len()];
+    loop {
+        let layout = layout(instructions, &wide)?;
+
+        let mut changed = false;
+        for (i, insn) in instructions.iter().enumerate() {
+            if let Instruction::Jump(_, label) = *insn {
+                if !wide[i] {
+                    let offset = layout.relative(layout.offsets[i], label)?;
+                    if offset < i32::from(i16::MIN) || offset > i32::from(i16::MAX) {
+                        wide[i] = true;
+                        changed = true;
+                    }
+                }
+            }
+        }
+
+        if !changed {
+            return Ok((layout, wide));
+        }
+    }
+}
+
+/// Computes the offsets of all instructions and labels,
+/// `wide` specifies which jumps are encoded in their wide form.
+fn layout(instructions: &[Instruction], wide: &[bool]) -> Result<Layout> {
+    let mut offsets = Vec::with_capacity(instructions.len());
+    let mut labels = HashMap::new();
+
+    let mut at = 0;
+    for (i, insn) in instructions.iter().enumerate() {
+        offsets.push(at);
+
+        at += match *insn {
+            Instruction::Label(label) => {
+                if labels.insert(label, at).is_some() {
+                    return Err(Error::InvalidLabel);
+                }
+                0
+            }
+            // the offsets of a branch would be written as is
+            Instruction::Simple(ref insn) if is_branch(insn) => {
+                return Err(Error::UnexpectedInstruction(insn.mnemonic()));
+            }
+            Instruction::Simple(ref insn) => basic::instruction_len(at, insn)?,
+            Instruction::Jump(jump, _) => if !wide[i] {
+                3
+            } else if jump.negate().is_some() {
+                // negated jump + goto_w
+                8
+            } else {
+                5
+            },
+            Instruction::TableSwitch { ref targets, .. } => {
+                1 + padding(at) + 12 + 4 * targets.len() as u32
+            }
+            Instruction::LookupSwitch { ref targets, .. } => {
+                1 + padding(at) + 8 + 8 * targets.len() as u32
+            }
+            _ => unreachable!("resolved instructions have been lowered before"),
+        };
+
+        if at > u32::from(u16::MAX) {
+            return Err(Error::TooLarge);
+        }
+    }
+
+    Ok(Layout { offsets, labels })
+}
+
+/// Returns the padding of a switch located at `at`
+fn padding(at: u32) -> u32 {
+    3 - (at & 3)
+}
+
+/// Returns true if an instruction jumps to an offset, which is `Instruction::Jump`
+/// or a switch in the advanced code.
+fn is_branch(insn: &basic::Instruction) -> bool {
+    use basic::Instruction::*;
+
+    matches!(
+        *insn,
+        IfEq(_) | IfNE(_) | IfLT(_) | IfGE(_) | IfGT(_) | IfLE(_)
+            | IfICmpEq(_) | IfICmpNE(_) | IfICmpLT(_) | IfICmpGE(_) | IfICmpGT(_) | IfICmpLE(_)
+            | IfACmpEq(_) | IfACmpNE(_) | IfNull(_) | IfNonNull(_)
+            | GoTo(_) | GoToW(_) | JSR(_) | JSRW(_)
+            | TableSwitch { .. } | LookupSwitch { .. }
+    )
+}
+
+/// Returns true if an instruction references the constant pool,
+/// which is a constant, a field, a method, a call site or a type in the advanced code.
+fn references_pool(insn: &basic::Instruction) -> bool {
+    use basic::Instruction::*;
+
+    matches!(
+        *insn,
+        LDC(_) | LDCW(_) | LDC2W(_)
+            | GetStatic(_) | PutStatic(_) | GetField(_) | PutField(_)
+            | InvokeVirtual(_) | InvokeSpecial(_) | InvokeStatic(_) | InvokeInterface(..)
+            | InvokeDynamic(..)
+            | New(_) | ANewArray(_) | CheckCast(_) | InstanceOf(_) | MultiANewArray(..)
+    )
+}
+
+/// Returns the short form of a jump.
+fn short_jump(jump: Jump, offset: i32) -> basic::Instruction {
+    use basic::Instruction::*;
+
+    let short = offset as i16;
+    match jump {
+        Jump::IfEq => IfEq(short),
+        Jump::IfNE => IfNE(short),
+        Jump::IfLT => IfLT(short),
+        Jump::IfGE => IfGE(short),
+        Jump::IfGT => IfGT(short),
+        Jump::IfLE => IfLE(short),
+        Jump::IfICmpEq => IfICmpEq(short),
+        Jump::IfICmpNE => IfICmpNE(short),
+        Jump::IfICmpLT => IfICmpLT(short),
+        Jump::IfICmpGE => IfICmpGE(short),
+        Jump::IfICmpGT => IfICmpGT(short),
+        Jump::IfICmpLE => IfICmpLE(short),
+        Jump::IfACmpEq => IfACmpEq(short),
+        Jump::IfACmpNE => IfACmpNE(short),
+        Jump::IfNull => IfNull(short),
+        Jump::IfNonNull => IfNonNull(short),
+        Jump::GoTo => GoTo(offset),
+        Jump::JSR => JSR(offset),
+    }
+}
+
+/// Converts a frame, `previous` is the offset of the previous frame.
+fn lower_frame(
+    lowerer: &mut Lowerer,
+    layout: &Layout,
+    previous: &mut Option<u16>,
+    frame: StackMapFrame,
+) -> Result<basic::StackMapFrame> {
+    let offset = layout.get(frame.label())?;
+    let offset_delta = match *previous {
+        Some(previous) if offset <= previous => {
+            return Err(Error::InvalidOffset(u32::from(offset)));
+        }
+        Some(previous) => offset - previous - 1,
+        None => offset,
+    };
+    *previous = Some(offset);
+
+    Ok(match frame {
+        StackMapFrame::Same { .. } => basic::StackMapFrame::Same {
+            offset_delta,
+            extended: false,
+        },
+        StackMapFrame::Same1 { stack, .. } => basic::StackMapFrame::Same1 {
+            offset_delta,
+            stack: lower_verification_type(lowerer, layout, stack)?,
+            extended: false,
+        },
+        StackMapFrame::Chop { count, .. } => basic::StackMapFrame::Chop {
+            offset_delta,
+            count,
+        },
+        StackMapFrame::Append { locals, .. } => basic::StackMapFrame::Append {
+            offset_delta,
+            locals: lower_verification_types(lowerer, layout, locals)?,
+        },
+        StackMapFrame::Full { locals, stack, .. } => basic::StackMapFrame::Full {
+            offset_delta,
+            locals: lower_verification_types(lowerer, layout, locals)?,
+            stack: lower_verification_types(lowerer, layout, stack)?,
+        },
+    })
+}
+
+fn lower_verification_types(
+    lowerer: &mut Lowerer,
+    layout: &Layout,
+    types: Vec<VerificationType>,
+) -> Result<Vec<basic::VerificationType>> {
+    let mut lowered = Vec::with_capacity(types.len());
+    for verification_type in types {
+        lowered.push(lower_verification_type(lowerer, layout, verification_type)?);
+    }
+    Ok(lowered)
+}
+
+fn lower_verification_type(
+    lowerer: &mut Lowerer,
+    layout: &Layout,
+    verification_type: VerificationType,
+) -> Result<basic::VerificationType> {
+    use basic::VerificationType::*;
+
+    Ok(match verification_type {
+        VerificationType::Top => Top,
+        VerificationType::Integer => Integer,
+        VerificationType::Float => Float,
+        VerificationType::Double => Double,
+        VerificationType::Long => Long,
+        VerificationType::Null => Null,
+        VerificationType::UninitializedThis => UninitializedThis,
+        VerificationType::Object(name) => Object(lowerer.class(name)?),
+        VerificationType::Uninitialized(label) => Uninitialized(layout.get(label)?),
+    })
+}
+
+fn lower_type_annotation(
+    lowerer: &mut Lowerer,
+    layout: &Layout,
+    annotation: TypeAnnotation,
+) -> Result<basic::TypeAnnotation> {
+    use basic::TargetType::*;
+
+    let target_type = match annotation.target_type {
+        TargetType::LocalVariable(table) => {
+            LocalVariable(lower_local_variable_targets(layout, table)?)
+        }
+        TargetType::ResourceVariable(table) => {
+            ResourceVariable(lower_local_variable_targets(layout, table)?)
+        }
+        TargetType::Catch(index) => Catch(index),
+        TargetType::OffsetInstanceOf(label) => OffsetInstanceOf(layout.get(label)?),
+        TargetType::OffsetNew(label) => OffsetNew(layout.get(label)?),
+        TargetType::OffsetNewRef(label) => OffsetNewRef(layout.get(label)?),
+        TargetType::OffsetRef(label) => OffsetRef(layout.get(label)?),
+        TargetType::TypeArgumentCast {
+            label,
+            type_argument,
+        } => TypeArgumentCast {
+            offset: layout.get(label)?,
+            type_argument,
+        },
+        TargetType::TypeArgumentMethod {
+            label,
+            type_argument,
+        } => TypeArgumentMethod {
+            offset: layout.get(label)?,
+            type_argument,
+        },
+        TargetType::TypeArgumentConstructor {
+            label,
+            type_argument,
+        } => TypeArgumentConstructor {
+            offset: layout.get(label)?,
+            type_argument,
+        },
+        TargetType::TypeArgumentNewRef {
+            label,
+            type_argument,
+        } => TypeArgumentNewRef {
+            offset: layout.get(label)?,
+            type_argument,
+        },
+        TargetType::TypeArgumentRef {
+            label,
+            type_argument,
+        } => TypeArgumentRef {
+            offset: layout.get(label)?,
+            type_argument,
+        },
+        TargetType::Other(target_type) => target_type,
+    };
+
+    Ok(basic::TypeAnnotation {
+        target_type,
+        target_path: annotation.target_path,
+        annotation: lowerer.annotation(annotation.annotation)?,
+    })
+}
+
+fn lower_local_variable_targets(
+    layout: &Layout,
+    table: Vec<LocalVariableTarget>,
+) -> Result<Vec<basic::LocalVariableTarget>> {
+    let mut targets = Vec::with_capacity(table.len());
+    for target in table {
+        let (start, length) = layout.range(target.start, target.end)?;
+        targets.push(basic::LocalVariableTarget {
+            start,
+            length,
+            index: target.index,
+        });
+    }
+    Ok(targets)
+}
@@ -0,0 +1,557 @@
+//! Converts the advanced `Class` back to a basic `Class` and a new constant pool.
+
+mod code;
+
+use basic::{self, Item, Pool};
+use result::*;
+use super::class::*;
+use super::code::TargetType;
+use super::constant::*;
+
+impl Class {
+    /// Converts this class to a basic class and a new constant pool,
+    /// which contains every item referenced by the class.
+    ///
+    /// The `BootstrapMethods` attribute is rebuilt from the `invokedynamic` instructions
+    /// and appended to the attributes of the class, if any bootstrap method is used.
+    ///
+    /// Code is converted like `Code::into_attribute` does,
+    /// if a conditional jump has to be widened, a frame is inserted after the `goto_w`.
+    ///
+    /// An error is returned, if the constant pool becomes too large
+    /// or if the code of a method cannot be converted.
+    pub fn into_basic(self) -> Result<(Pool, basic::Class)> {
+        let mut constant_pool = Pool::new();
+        let mut bootstrap_methods = Vec::new();
+
+        let mut lowerer = Lowerer {
+            constant_pool: &mut constant_pool,
+            bootstrap_methods: &mut bootstrap_methods,
+        };
+
+        let this_class = lowerer.class(self.name)?;
+        let super_name = match self.super_name {
+            Some(super_name) => lowerer.class(super_name)?,
+            None => 0,
+        };
+
+        let mut interfaces = Vec::with_capacity(self.interfaces.len());
+        for interface in self.interfaces {
+            interfaces.push(lowerer.class(interface)?);
+        }
+
+        let mut fields = Vec::with_capacity(self.fields.len());
+        for field in self.fields {
+            fields.push(basic::Field {
+                access_flags: field.access_flags,
+                name: lowerer.utf8(field.name)?,
+                desc: lowerer.utf8(field.desc.to_string())?,
+                attributes: lowerer.attributes(field.attributes)?,
+            });
+        }
+
+        let mut methods = Vec::with_capacity(self.methods.len());
+        for method in self.methods {
+            let name = lowerer.utf8(method.name)?;
+            let desc = lowerer.utf8(method.desc.to_string())?;
+
+            let mut attributes = Vec::with_capacity(method.attributes.len() + 1);
+            let mut targets = Vec::new();
+            if let Some(code) = method.code {
+                lowerer.name("Code")?;
+                let (code, code_targets) = lowerer.code(code)?;
+                attributes.push(code);
+                targets = code_targets;
+            }
+            attributes.extend(lowerer.attributes(method.attributes)?);
+
+            let mut method = basic::Method {
+                access_flags: method.access_flags,
+                name,
+                desc,
+                attributes,
+            };
+            if !targets.is_empty() {
+                basic::insert_frames(lowerer.constant_pool, this_class, &mut method, &targets)?;
+            }
+            methods.push(method);
+        }
+
+        let mut class = basic::Class {
+            minor_version: self.minor_version,
+            major_version: self.major_version,
+            access_flags: self.access_flags,
+            name: this_class,
+            super_name,
+            interfaces,
+            fields,
+            methods,
+            attributes: lowerer.attributes(self.attributes)?,
+        };
+
+        if !bootstrap_methods.is_empty() {
+            constant_pool.push(Item::UTF8("BootstrapMethods".to_owned()))?;
+            class
+                .attributes
+                .push(basic::Attribute::BootstrapMethods(bootstrap_methods));
+        }
+
+        Ok((constant_pool, class))
+    }
+}
+
+/// Pushes resolved items to the constant pool
+/// and bootstrap methods to the `BootstrapMethods` attribute.
+struct Lowerer<'a> {
+    constant_pool: &'a mut Pool,
+    bootstrap_methods: &'a mut Vec<basic::BootstrapMethod>,
+}
+
+impl<'a> Lowerer<'a> {
+    fn utf8(&mut self, string: String) -> Result<u16> {
+        self.constant_pool.push(Item::UTF8(string))
+    }
+
+    /// Returns 0 if there is no string, the index of the utf8 item otherwise.
+    fn utf8_opt(&mut self, string: Option<String>) -> Result<u16> {
+        match string {
+            Some(string) => self.utf8(string),
+            None => Ok(0),
+        }
+    }
+
+    /// Pushes the name of an attribute.
+    fn name(&mut self, name: &str) -> Result<u16> {
+        self.utf8(name.to_owned())
+    }
+
+    fn class(&mut self, name: String) -> Result<u16> {
+        let name = self.utf8(name)?;
+        self.constant_pool.push(Item::Class(name))
+    }
+
+    /// Returns 0 if there is no class, the index of the class item otherwise.
+    fn class_opt(&mut self, name: Option<String>) -> Result<u16> {
+        match name {
+            Some(name) => self.class(name),
+            None => Ok(0),
+        }
+    }
+
+    fn classes(&mut self, names: Vec<String>) -> Result<Vec<u16>> {
+        let mut indices = Vec::with_capacity(names.len());
+        for name in names {
+            indices.push(self.class(name)?);
+        }
+        Ok(indices)
+    }
+
+    fn module(&mut self, name: String) -> Result<u16> {
+        let name = self.utf8(name)?;
+        self.constant_pool.push(Item::Module(name))
+    }
+
+    fn modules(&mut self, names: Vec<String>) -> Result<Vec<u16>> {
+        let mut indices = Vec::with_capacity(names.len());
+        for name in names {
+            indices.push(self.module(name)?);
+        }
+        Ok(indices)
+    }
+
+    fn package(&mut self, name: String) -> Result<u16> {
+        let name = self.utf8(name)?;
+        self.constant_pool.push(Item::Package(name))
+    }
+
+    fn name_and_type(&mut self, name: String, desc: String) -> Result<u16> {
+        let name = self.utf8(name)?;
+        let desc = self.utf8(desc)?;
+        self.constant_pool.push(Item::NameAndType { name, desc })
+    }
+
+    fn field_ref(&mut self, field: FieldRef) -> Result<u16> {
+        let class = self.class(field.owner)?;
+        let name_and_type = self.name_and_type(field.name, field.desc.to_string())?;
+        self.constant_pool.push(Item::FieldRef {
+            class,
+            name_and_type,
+        })
+    }
+
+    fn method_ref(&mut self, method: MethodRef) -> Result<u16> {
+        let class = self.class(method.owner)?;
+        let name_and_type = self.name_and_type(method.name, method.desc.to_string())?;
+        self.constant_pool.push(if method.interface {
+            Item::InterfaceMethodRef {
+                class,
+                name_and_type,
+            }
+        } else {
+            Item::MethodRef {
+                class,
+                name_and_type,
+            }
+        })
+    }
+
+    fn method_handle(&mut self, handle: MethodHandle) -> Result<u16> {
+        let index = match handle.reference {
+            Reference::Field(field) => self.field_ref(field)?,
+            Reference::Method(method) => self.method_ref(method)?,
+        };
+        self.constant_pool.push(Item::MethodHandle {
+            kind: handle.kind,
+            index,
+        })
+    }
+
+    fn constant(&mut self, constant: Constant) -> Result<u16> {
+        let item = match constant {
+            Constant::Integer(value) => Item::Integer(value),
+            Constant::Float(value) => Item::Float(value),
+            Constant::Long(value) => Item::Long(value),
+            Constant::Double(value) => Item::Double(value),
+            Constant::String(string) => Item::String(self.utf8(string)?),
+            Constant::UTF16(units) => Item::String(self.constant_pool.push(Item::UTF16(units))?),
+            Constant::Class(name) => return self.class(name),
+            Constant::MethodType(desc) => Item::MethodType(self.utf8(desc.to_string())?),
+            Constant::MethodHandle(handle) => return self.method_handle(handle),
+            Constant::Dynamic(dynamic) => {
+                let bootstrap_method_attribute =
+                    self.bootstrap_method(dynamic.bootstrap_method, dynamic.arguments)?;
+                let name_and_type = self.name_and_type(dynamic.name, dynamic.desc.to_string())?;
+                Item::Dynamic {
+                    bootstrap_method_attribute,
+                    name_and_type,
+                }
+            }
+        };
+        self.constant_pool.push(item)
+    }
+
+    /// Pushes the bootstrap method of a call site, if it does not exist yet,
+    /// and its `Item::InvokeDynamic { .. }`.
+    fn invoke_dynamic(&mut self, call_site: InvokeDynamic) -> Result<u16> {
+        let bootstrap_method_attribute =
+            self.bootstrap_method(call_site.bootstrap_method, call_site.arguments)?;
+        let name_and_type = self.name_and_type(call_site.name, call_site.desc.to_string())?;
+        self.constant_pool.push(Item::InvokeDynamic {
+            bootstrap_method_attribute,
+            name_and_type,
+        })
+    }
+
+    /// Pushes a bootstrap method, if it does not exist yet, and returns its index.
+    fn bootstrap_method(&mut self, handle: MethodHandle, constants: Vec<Constant>) -> Result<u16> {
+        let method_ref = self.method_handle(handle)?;
+        let mut arguments = Vec::with_capacity(constants.len());
+        for constant in constants {
+            arguments.push(self.constant(constant)?);
+        }
+
+        let existing = self.bootstrap_methods
+            .iter()
+            .position(|method| method.method_ref == method_ref && method.arguments == arguments);
+        let bootstrap_method_attribute = match existing {
+            Some(index) => index,
+            None => {
+                self.bootstrap_methods.push(basic::BootstrapMethod {
+                    method_ref,
+                    arguments,
+                });
+                self.bootstrap_methods.len() - 1
+            }
+        };
+        if bootstrap_method_attribute > usize::from(u16::MAX) {
+            return Err(Error::TooLarge);
+        }
+        Ok(bootstrap_method_attribute as u16)
+    }
+
+    /// Converts the attributes of a class, a field, a method or a code attribute.
+    fn attributes(&mut self, attributes: Vec<Attribute>) -> Result<Vec<basic::Attribute>> {
+        let mut lowered = Vec::with_capacity(attributes.len());
+        for attribute in attributes {
+            lowered.push(self.attribute(attribute)?);
+        }
+        Ok(lowered)
+    }
+
+    fn attribute(&mut self, attribute: Attribute) -> Result<basic::Attribute> {
+        use basic::Attribute::*;
+
+        Ok(match attribute {
+            Attribute::AnnotationDefault(value) => {
+                self.name("AnnotationDefault")?;
+                AnnotationDefault(self.element_value(value)?)
+            }
+            Attribute::ConstantValue(constant) => {
+                self.name("ConstantValue")?;
+                ConstantValue(self.constant(constant)?)
+            }
+            Attribute::Deprecated => {
+                self.name("Deprecated")?;
+                Deprecated
+            }
+            Attribute::EnclosingMethod { class, method } => {
+                self.name("EnclosingMethod")?;
+                EnclosingMethod {
+                    class_index: self.class(class)?,
+                    method_index: match method {
+                        Some((name, desc)) => self.name_and_type(name, desc.to_string())?,
+                        None => 0,
+                    },
+                }
+            }
+            Attribute::Exceptions(exceptions) => {
+                self.name("Exceptions")?;
+                Exceptions(self.classes(exceptions)?)
+            }
+            Attribute::InnerClasses(classes) => {
+                self.name("InnerClasses")?;
+                let mut lowered = Vec::with_capacity(classes.len());
+                for class in classes {
+                    lowered.push(basic::InnerClass {
+                        inner_class_info: self.class(class.inner_class)?,
+                        outer_class_info: self.class_opt(class.outer_class)?,
+                        inner_name: self.utf8_opt(class.inner_name)?,
+                        inner_class_access_flags: class.inner_class_access_flags,
+                    });
+                }
+                InnerClasses(lowered)
+            }
+            Attribute::MethodParameters(parameters) => {
+                self.name("MethodParameters")?;
+                let mut lowered = Vec::with_capacity(parameters.len());
+                for parameter in parameters {
+                    lowered.push(basic::MethodParameter {
+                        name: self.utf8_opt(parameter.name)?,
+                        access_flags: parameter.access_flags,
+                    });
+                }
+                MethodParameters(lowered)
+            }
+            Attribute::Module(module) => {
+                self.name("Module")?;
+
+                let mut requires = Vec::with_capacity(module.requires.len());
+                for requirement in module.requires {
+                    requires.push(basic::Requirement {
+                        index: self.module(requirement.module)?,
+                        flags: requirement.flags,
+                        version: self.utf8_opt(requirement.version)?,
+                    });
+                }
+                let mut exports = Vec::with_capacity(module.exports.len());
+                for export in module.exports {
+                    exports.push(basic::Export {
+                        index: self.package(export.package)?,
+                        flags: export.flags,
+                        to: self.modules(export.to)?,
+                    });
+                }
+                let mut opens = Vec::with_capacity(module.opens.len());
+                for opening in module.opens {
+                    opens.push(basic::Opening {
+                        index: self.package(opening.package)?,
+                        flags: opening.flags,
+                        to: self.modules(opening.to)?,
+                    });
+                }
+                let uses = self.classes(module.uses)?;
+                let mut provides = Vec::with_capacity(module.provides.len());
+                for provider in module.provides {
+                    provides.push(basic::Provider {
+                        index: self.class(provider.service)?,
+                        with: self.classes(provider.with)?,
+                    });
+                }
+
+                Module {
+                    name: self.module(module.name)?,
+                    flags: module.flags,
+                    version: self.utf8_opt(module.version)?,
+                    requires,
+                    exports,
+                    opens,
+                    uses,
+                    provides,
+                }
+            }
+            Attribute::ModuleMainClass(name) => {
+                self.name("ModuleMainClass")?;
+                ModuleMainClass(self.class(name)?)
+            }
+            Attribute::ModulePackages(packages) => {
+                self.name("ModulePackages")?;
+                let mut indices = Vec::with_capacity(packages.len());
+                for package in packages {
+                    indices.push(self.package(package)?);
+                }
+                ModulePackages(indices)
+            }
+            Attribute::NestHost(name) => {
+                self.name("NestHost")?;
+                NestHost(self.class(name)?)
+            }
+            Attribute::NestMembers(members) => {
+                self.name("NestMembers")?;
+                NestMembers(self.classes(members)?)
+            }
+            Attribute::PermittedSubclasses(subclasses) => {
+                self.name("PermittedSubclasses")?;
+                PermittedSubclasses(self.classes(subclasses)?)
+            }
+            Attribute::Record(components) => {
+                self.name("Record")?;
+                let mut lowered = Vec::with_capacity(components.len());
+                for component in components {
+                    lowered.push(basic::RecordComponent {
+                        name: self.utf8(component.name)?,
+                        descriptor: self.utf8(component.desc.to_string())?,
+                        attributes: self.attributes(component.attributes)?,
+                    });
+                }
+                Record(lowered)
+            }
+            Attribute::RuntimeVisibleAnnotations(annotations) => {
+                self.name("RuntimeVisibleAnnotations")?;
+                RuntimeVisibleAnnotations(self.annotations(annotations)?)
+            }
+            Attribute::RuntimeInvisibleAnnotations(annotations) => {
+                self.name("RuntimeInvisibleAnnotations")?;
+                RuntimeInvisibleAnnotations(self.annotations(annotations)?)
+            }
+            Attribute::RuntimeVisibleParameterAnnotations(parameters) => {
+                self.name("RuntimeVisibleParameterAnnotations")?;
+                RuntimeVisibleParameterAnnotations(self.parameter_annotations(parameters)?)
+            }
+            Attribute::RuntimeInvisibleParameterAnnotations(parameters) => {
+                self.name("RuntimeInvisibleParameterAnnotations")?;
+                RuntimeInvisibleParameterAnnotations(self.parameter_annotations(parameters)?)
+            }
+            Attribute::RuntimeVisibleTypeAnnotations(annotations) => {
+                self.name("RuntimeVisibleTypeAnnotations")?;
+                RuntimeVisibleTypeAnnotations(self.type_annotations(annotations)?)
+            }
+            Attribute::RuntimeInvisibleTypeAnnotations(annotations) => {
+                self.name("RuntimeInvisibleTypeAnnotations")?;
+                RuntimeInvisibleTypeAnnotations(self.type_annotations(annotations)?)
+            }
+            Attribute::Signature(signature) => {
+                self.name("Signature")?;
+                Signature(self.utf8(signature)?)
+            }
+            Attribute::Synthetic => {
+                self.name("Synthetic")?;
+                Synthetic
+            }
+            Attribute::SourceFile(name) => {
+                self.name("SourceFile")?;
+                SourceFile(self.utf8(name)?)
+            }
+            Attribute::SourceDebugExtension(content) => {
+                self.name("SourceDebugExtension")?;
+                SourceDebugExtension(content)
+            }
+            Attribute::Unknown(name, content) => Unknown(self.utf8(name)?, content),
+        })
+    }
+
+    fn annotations(&mut self, annotations: Vec<Annotation>) -> Result<Vec<basic::Annotation>> {
+        let mut lowered = Vec::with_capacity(annotations.len());
+        for annotation in annotations {
+            lowered.push(self.annotation(annotation)?);
+        }
+        Ok(lowered)
+    }
+
+    fn parameter_annotations(
+        &mut self,
+        parameters: Vec<Vec<Annotation>>,
+    ) -> Result<Vec<Vec<basic::Annotation>>> {
+        let mut lowered = Vec::with_capacity(parameters.len());
+        for annotations in parameters {
+            lowered.push(self.annotations(annotations)?);
+        }
+        Ok(lowered)
+    }
+
+    /// Converts type annotations, which are not located in the code,
+    /// so their target type must not reference a label.
+    fn type_annotations(
+        &mut self,
+        annotations: Vec<super::TypeAnnotation>,
+    ) -> Result<Vec<basic::TypeAnnotation>> {
+        let mut lowered = Vec::with_capacity(annotations.len());
+        for annotation in annotations {
+            let target_type = match annotation.target_type {
+                TargetType::Other(target_type) => target_type,
+                _ => return Err(Error::InvalidLabel),
+            };
+
+            lowered.push(basic::TypeAnnotation {
+                target_type,
+                target_path: annotation.target_path,
+                annotation: self.annotation(annotation.annotation)?,
+            });
+        }
+        Ok(lowered)
+    }
+
+    fn annotation(&mut self, annotation: Annotation) -> Result<basic::Annotation> {
+        let type_index = self.utf8(annotation.type_desc.to_string())?;
+
+        let mut element_value_pairs = Vec::with_capacity(annotation.element_value_pairs.len());
+        for (name, value) in annotation.element_value_pairs {
+            element_value_pairs.push((self.utf8(name)?, self.element_value(value)?));
+        }
+
+        Ok(basic::Annotation {
+            type_index,
+            element_value_pairs,
+        })
+    }
+
+    fn element_value(&mut self, value: ElementValue) -> Result<basic::ElementValue> {
+        use basic::ElementValue::*;
+
+        Ok(match value {
+            ElementValue::Byte(value) => Byte(self.constant_pool.push(Item::Integer(value.into()))?),
+            ElementValue::Short(value) => {
+                Short(self.constant_pool.push(Item::Integer(value.into()))?)
+            }
+            ElementValue::Char(value) => Char(self.constant_pool.push(Item::Integer(value.into()))?),
+            ElementValue::Int(value) => Int(self.constant_pool.push(Item::Integer(value))?),
+            ElementValue::Long(value) => Long(self.constant_pool.push(Item::Long(value))?),
+            ElementValue::Float(value) => Float(self.constant_pool.push(Item::Float(value))?),
+            ElementValue::Double(value) => Double(self.constant_pool.push(Item::Double(value))?),
+            ElementValue::Boolean(value) => {
+                Boolean(self.constant_pool.push(Item::Integer(value.into()))?)
+            }
+            ElementValue::String(string) => String(self.utf8(string)?),
+            ElementValue::Enum {
+                type_desc,
+                const_name,
+            } => Enum {
+                type_name: self.utf8(type_desc.to_string())?,
+                const_name: self.utf8(const_name)?,
+            },
+            ElementValue::Class(desc) => Class(self.utf8(match desc {
+                Some(desc) => desc.to_string(),
+                None => "V".to_owned(),
+            })?),
+            ElementValue::Annotation(annotation) => {
+                Annotation(Box::new(self.annotation(*annotation)?))
+            }
+            ElementValue::Array(values) => {
+                let mut lowered = Vec::with_capacity(values.len());
+                for value in values {
+                    lowered.push(self.element_value(value)?);
+                }
+                Array(lowered)
+            }
+        })
+    }
+}
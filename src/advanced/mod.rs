@@ -0,0 +1,638 @@
+//! The advanced module provides a more convenient representation of a class file.
+//! Unlike in the basic module, names, descriptors and constants are resolved,
+//! so no constant pool is required,
+//! and positions in the code are referenced using labels,
+//! so instructions can be inserted or removed freely.
+
+mod class;
+mod code;
+mod constant;
+mod lift;
+mod lower;
+
+pub use self::class::*;
+pub use self::code::*;
+pub use self::constant::*;
+
+use basic;
+use result::*;
+
+/// Parses a class file and resolves every index into the constant pool.
+pub fn parse(input: &[u8]) -> Result<Class> {
+    let (constant_pool, class) = basic::parse(input)?;
+    Class::from_basic(&constant_pool, class)
+}
+
+/// Writes a class to bytes, building a new constant pool.
+pub fn write(class: Class) -> Result<Vec<u8>> {
+    let (constant_pool, class) = class.into_basic()?;
+    basic::write(&constant_pool, &class)
+}
+
+#[cfg(test)]
+mod test {
+    use basic::{self, Pool};
+    use super::*;
+
+    /// Replaces every `Code` attribute of a class
+    /// by converting it to the advanced representation and back.
+    fn convert_all(pool: &mut Pool, class: &mut basic::Class) {
+        let mut none = Vec::new();
+        let bootstrap_methods = class
+            .attributes
+            .iter_mut()
+            .filter_map(|attribute| match *attribute {
+                basic::Attribute::BootstrapMethods(ref mut methods) => Some(methods),
+                _ => None,
+            })
+            .next()
+            .unwrap_or(&mut none);
+
+        for method in &mut class.methods {
+            let attributes = ::std::mem::take(&mut method.attributes);
+            for attribute in attributes {
+                let attribute = match attribute {
+                    code @ basic::Attribute::Code { .. } => {
+                        Code::from_attribute(code, pool, bootstrap_methods)
+                            .unwrap()
+                            .into_attribute(pool, bootstrap_methods)
+                            .unwrap()
+                    }
+                    attribute => attribute,
+                };
+                method.attributes.push(attribute);
+            }
+        }
+    }
+
+    /// Parses a class file, converts all code attributes and writes it back again,
+    /// the output must resolve to the same class as the input.
+    /// The bytes may differ, since references to duplicate constant pool entries
+    /// are replaced by references to the first one.
+    fn round_trip(bytes: &[u8]) {
+        let (mut pool, mut class) = basic::parse(bytes).unwrap();
+        convert_all(&mut pool, &mut class);
+        let written = basic::write(&pool, &class).unwrap();
+        assert_eq!(
+            format!("{:?}", parse(&written).unwrap()),
+            format!("{:?}", parse(bytes).unwrap())
+        );
+    }
+
+    /// Resolves a class file and writes it using a new constant pool,
+    /// parsing the output again must result in the same class.
+    fn resolve(bytes: &[u8]) {
+        let class = parse(bytes).unwrap();
+        let expected = format!("{:?}", class);
+
+        let written = write(class).unwrap();
+        let class = parse(&written).unwrap();
+        assert_eq!(format!("{:?}", class), expected);
+
+        // the constant pool is built in a deterministic order
+        assert!(write(class).unwrap() == written);
+    }
+
+    #[test]
+    fn convert_resources() {
+        round_trip(include_bytes!("../../examples/resources/AnyAnnotation.class"));
+        round_trip(include_bytes!("../../examples/resources/CustomLinkedList.class"));
+        round_trip(include_bytes!("../../examples/resources/CustomLinkedList$Element.class"));
+        round_trip(include_bytes!("../../examples/resources/CustomLinkedList$Iter.class"));
+        round_trip(include_bytes!("../../examples/resources/Fibonacci.class"));
+        round_trip(include_bytes!("../../examples/resources/FizzBuzzStream.class"));
+        round_trip(include_bytes!("../../examples/resources/Nestmates.class"));
+        round_trip(include_bytes!("../../examples/resources/Nestmates$Inner.class"));
+        round_trip(include_bytes!("../../examples/resources/Records.class"));
+        round_trip(include_bytes!("../../examples/resources/Records$Component.class"));
+        round_trip(include_bytes!("../../examples/resources/Sealed.class"));
+        round_trip(include_bytes!("../../examples/resources/Sealed$Leaf.class"));
+        round_trip(include_bytes!("../../examples/resources/Sealed$Node.class"));
+        round_trip(include_bytes!("../../examples/resources/RoundTrip.class"));
+        round_trip(include_bytes!("../../examples/resources/Surrogates.class"));
+    }
+
+    #[test]
+    fn resolve_resources() {
+        resolve(include_bytes!("../../examples/resources/AnyAnnotation.class"));
+        resolve(include_bytes!("../../examples/resources/CustomLinkedList.class"));
+        resolve(include_bytes!("../../examples/resources/CustomLinkedList$Element.class"));
+        resolve(include_bytes!("../../examples/resources/CustomLinkedList$Iter.class"));
+        resolve(include_bytes!("../../examples/resources/Fibonacci.class"));
+        resolve(include_bytes!("../../examples/resources/FizzBuzzStream.class"));
+        resolve(include_bytes!("../../examples/resources/Nestmates.class"));
+        resolve(include_bytes!("../../examples/resources/Nestmates$Inner.class"));
+        resolve(include_bytes!("../../examples/resources/Records.class"));
+        resolve(include_bytes!("../../examples/resources/Records$Component.class"));
+        resolve(include_bytes!("../../examples/resources/Sealed.class"));
+        resolve(include_bytes!("../../examples/resources/Sealed$Leaf.class"));
+        resolve(include_bytes!("../../examples/resources/Sealed$Node.class"));
+        resolve(include_bytes!("../../examples/resources/RoundTrip.class"));
+        resolve(include_bytes!("../../examples/resources/Surrogates.class"));
+    }
+
+    #[test]
+    fn unpaired_surrogates() {
+        let class = parse(include_bytes!("../../examples/resources/Surrogates.class")).unwrap();
+        let constant = |name: &str| {
+            let field = class.fields.iter().find(|field| field.name == name).unwrap();
+            match field.attributes[..] {
+                [Attribute::ConstantValue(ref constant)] => constant.clone(),
+                ref other => panic!("unexpected attributes: {:?}", other),
+            }
+        };
+
+        assert_eq!(constant("HIGH"), Constant::UTF16(vec![0xD83D]));
+        assert_eq!(constant("LOW"), Constant::UTF16(vec![0x61, 0xDE00, 0x62]));
+        assert_eq!(constant("PAIR"), Constant::String("\u{1F600}".to_owned()));
+    }
+
+    #[test]
+    fn resolved_instructions() {
+        let class = parse(include_bytes!("../../examples/resources/FizzBuzzStream.class")).unwrap();
+        let main = class
+            .methods
+            .iter()
+            .find(|method| method.name == "main")
+            .unwrap();
+        let code = main.code.as_ref().unwrap();
+
+        let mut call_sites = 0;
+        for insn in &code.instructions {
+            match *insn {
+                Instruction::Simple(ref insn) => match *insn {
+                    basic::Instruction::LDC(_)
+                    | basic::Instruction::LDCW(_)
+                    | basic::Instruction::LDC2W(_)
+                    | basic::Instruction::GetStatic(_)
+                    | basic::Instruction::InvokeStatic(_)
+                    | basic::Instruction::InvokeInterface(..)
+                    | basic::Instruction::InvokeDynamic(..)
+                    | basic::Instruction::New(_) => panic!("unresolved instruction: {:?}", insn),
+                    _ => {}
+                },
+                Instruction::InvokeDynamic(ref call_site) => {
+                    assert_eq!(
+                        call_site.bootstrap_method.reference,
+                        Reference::Method(MethodRef {
+                            owner: "java/lang/invoke/LambdaMetafactory".to_owned(),
+                            name: "metafactory".to_owned(),
+                            desc: "(Ljava/lang/invoke/MethodHandles$Lookup;Ljava/lang/String;\
+                                   Ljava/lang/invoke/MethodType;Ljava/lang/invoke/MethodType;\
+                                   Ljava/lang/invoke/MethodHandle;Ljava/lang/invoke/MethodType;)\
+                                   Ljava/lang/invoke/CallSite;"
+                                .parse()
+                                .unwrap(),
+                            interface: false,
+                        })
+                    );
+                    assert_eq!(call_site.arguments.len(), 3);
+                    call_sites += 1;
+                }
+                _ => {}
+            }
+        }
+        assert!(call_sites > 0);
+    }
+
+    #[test]
+    fn insert_instructions() {
+        let bytes = include_bytes!("../../examples/resources/Fibonacci.class");
+        let (mut pool, mut class) = basic::parse(bytes).unwrap();
+
+        for method in &mut class.methods {
+            let attributes = ::std::mem::take(&mut method.attributes);
+            for attribute in attributes {
+                let attribute = match attribute {
+                    code @ basic::Attribute::Code { .. } => {
+                        let mut code = Code::from_attribute(code, &pool, &[]).unwrap();
+                        // insert a nop in front of every instruction
+                        let mut instructions = Vec::new();
+                        for insn in code.instructions.drain(..) {
+                            match insn {
+                                Instruction::Label(_) => {}
+                                _ => instructions.push(Instruction::Simple(basic::Instruction::NOP)),
+                            }
+                            instructions.push(insn);
+                        }
+                        code.instructions = instructions;
+                        code.into_attribute(&mut pool, &mut Vec::new()).unwrap()
+                    }
+                    attribute => attribute,
+                };
+                method.attributes.push(attribute);
+            }
+        }
+
+        // every jump must point to the nop inserted in front of its former target
+        let bytes = basic::write(&pool, &class).unwrap();
+        let (_, class) = basic::parse(&bytes).unwrap();
+        for method in &class.methods {
+            for attribute in &method.attributes {
+                if let basic::Attribute::Code {
+                    ref instructions,
+                    ref exceptions,
+                    ref attributes,
+                    ..
+                } = *attribute
+                {
+                    for (at, insn) in instructions {
+                        let offset = match *insn {
+                            basic::Instruction::IfNE(offset)
+                            | basic::Instruction::IfICmpGT(offset) => i32::from(offset),
+                            basic::Instruction::GoTo(offset) => offset,
+                            _ => continue,
+                        };
+                        let target = (at as i32 + offset) as u32;
+                        match instructions.get(target) {
+                            Some(&basic::Instruction::NOP) => {}
+                            insn => panic!("jump at {} to {}: {:?}", at, target, insn),
+                        }
+                    }
+
+                    assert!(exceptions.is_empty());
+                    for attribute in attributes {
+                        if let basic::Attribute::StackMapTable(ref frames) = *attribute {
+                            assert!(!frames.is_empty());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn wide_jumps() {
+        let mut pool = Pool::new();
+        let mut code = Code::new(1, 1);
+        let start = code.new_label();
+        let end = code.new_label();
+
+        code.instructions.push(Instruction::Label(start));
+        code.instructions.push(Instruction::Simple(basic::Instruction::ILoad0));
+        code.instructions.push(Instruction::Jump(Jump::IfLT, end));
+        for _ in 0..40_000 {
+            code.instructions.push(Instruction::Simple(basic::Instruction::NOP));
+        }
+        code.instructions.push(Instruction::Jump(Jump::GoTo, start));
+        code.instructions.push(Instruction::Label(end));
+        code.instructions.push(Instruction::Simple(basic::Instruction::Return));
+
+        let instructions = match code.into_attribute(&mut pool, &mut Vec::new()).unwrap() {
+            basic::Attribute::Code { instructions, .. } => instructions,
+            attribute => panic!("unexpected attribute: {:?}", attribute),
+        };
+
+        // the conditional jump is negated and jumps over a goto_w
+        match instructions.get(1) {
+            Some(&basic::Instruction::IfGE(8)) => {}
+            insn => panic!("unexpected instruction: {:?}", insn),
+        }
+        match instructions.get(4) {
+            Some(&basic::Instruction::GoToW(offset)) => assert_eq!(offset, 40_010),
+            insn => panic!("unexpected instruction: {:?}", insn),
+        }
+        match instructions.get(40_009) {
+            Some(&basic::Instruction::GoToW(offset)) => assert_eq!(offset, -40_009),
+            insn => panic!("unexpected instruction: {:?}", insn),
+        }
+        match instructions.last() {
+            Some((40_014, &basic::Instruction::Return)) => {}
+            insn => panic!("unexpected instruction: {:?}", insn),
+        }
+    }
+
+    /// Creates the code of a method `(I)V` looping while its argument is zero,
+    /// where the conditional jump out of the loop has to be widened.
+    fn far_loop() -> Code {
+        let mut code = Code::new(1, 2);
+        let start = code.new_label();
+        let end = code.new_label();
+
+        code.instructions.push(Instruction::Simple(basic::Instruction::IConst0));
+        code.instructions.push(Instruction::Simple(basic::Instruction::IStore1));
+        code.instructions.push(Instruction::Label(start));
+        code.instructions.push(Instruction::Simple(basic::Instruction::ILoad0));
+        code.instructions.push(Instruction::Jump(Jump::IfNE, end));
+        for _ in 0..40_000 {
+            code.instructions.push(Instruction::Simple(basic::Instruction::NOP));
+        }
+        code.instructions.push(Instruction::Jump(Jump::GoTo, start));
+        code.instructions.push(Instruction::Label(end));
+        code.instructions.push(Instruction::Simple(basic::Instruction::Return));
+
+        code.frames.push(StackMapFrame::Append {
+            label: start,
+            locals: vec![VerificationType::Integer],
+        });
+        code.frames.push(StackMapFrame::Chop {
+            label: end,
+            count: 1,
+        });
+        code
+    }
+
+    #[test]
+    fn wide_jump_frames() {
+        use result::Error;
+
+        // the frame after the goto_w cannot be computed without the method
+        match far_loop().into_attribute(&mut Pool::new(), &mut Vec::new()) {
+            Err(Error::MissingFrame(11)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let class = Class {
+            minor_version: 0,
+            major_version: 52,
+            access_flags: basic::ClassAccessFlags::PUBLIC | basic::ClassAccessFlags::SUPER,
+            name: "Loop".to_owned(),
+            super_name: Some("java/lang/Object".to_owned()),
+            interfaces: Vec::new(),
+            fields: Vec::new(),
+            methods: vec![
+                Method {
+                    access_flags: basic::MethodAccessFlags::STATIC,
+                    name: "test".to_owned(),
+                    desc: "(I)V".parse().unwrap(),
+                    code: Some(far_loop()),
+                    attributes: Vec::new(),
+                },
+            ],
+            attributes: Vec::new(),
+        };
+        let (pool, class) = class.into_basic().unwrap();
+
+        let frames = class.methods[0]
+            .attributes
+            .iter()
+            .find_map(|attribute| match *attribute {
+                basic::Attribute::Code { ref attributes, .. } => {
+                    attributes.iter().find_map(|attribute| match *attribute {
+                        basic::Attribute::StackMapTable(ref frames) => Some(frames),
+                        _ => None,
+                    })
+                }
+                _ => None,
+            })
+            .unwrap();
+
+        // ifeq at 3 jumps over the goto_w at 6 to the frame inserted at 11,
+        // where the argument and the local variable are integers,
+        // the frame of the loop exit is written in its full form after it
+        assert_eq!(frames.len(), 3);
+        match frames[1] {
+            basic::StackMapFrame::Full {
+                offset_delta: 8,
+                ref locals,
+                ref stack,
+            } => {
+                assert_eq!(format!("{:?}", locals), "[Integer, Integer]");
+                assert!(stack.is_empty());
+            }
+            ref frame => panic!("unexpected frame: {:?}", frame),
+        }
+        match frames[2] {
+            basic::StackMapFrame::Full {
+                offset_delta: 40_004,
+                ref locals,
+                ref stack,
+            } => {
+                assert_eq!(format!("{:?}", locals), "[Integer]");
+                assert!(stack.is_empty());
+            }
+            ref frame => panic!("unexpected frame: {:?}", frame),
+        }
+
+        // the class can still be written
+        basic::write(&pool, &class).unwrap();
+    }
+
+    #[test]
+    fn switch_padding() {
+        let mut pool = Pool::new();
+        let mut code = Code::new(1, 1);
+        let default = code.new_label();
+        let case = code.new_label();
+
+        code.instructions.push(Instruction::Simple(basic::Instruction::ILoad0));
+        code.instructions.push(Instruction::TableSwitch {
+            default,
+            low: 0,
+            high: 0,
+            targets: vec![case],
+        });
+        code.instructions.push(Instruction::Label(case));
+        code.instructions.push(Instruction::Simple(basic::Instruction::NOP));
+        code.instructions.push(Instruction::Label(default));
+        code.instructions.push(Instruction::Simple(basic::Instruction::Return));
+
+        // the switch is located at 1, so it is padded by 2 bytes
+        match code.into_attribute(&mut pool, &mut Vec::new()).unwrap() {
+            basic::Attribute::Code { instructions, .. } => {
+                match instructions.get(1) {
+                    Some(&basic::Instruction::TableSwitch {
+                        default,
+                        ref offsets,
+                        ..
+                    }) => {
+                        assert_eq!(default, 20);
+                        assert_eq!(offsets, &vec![19]);
+                    }
+                    insn => panic!("unexpected instruction: {:?}", insn),
+                }
+                assert!(instructions.get(20).is_some());
+            }
+            attribute => panic!("unexpected attribute: {:?}", attribute),
+        }
+    }
+
+    #[test]
+    fn invalid_labels() {
+        use result::Error;
+
+        let mut pool = Pool::new();
+
+        // never placed
+        let mut code = Code::new(0, 0);
+        let label = code.new_label();
+        code.instructions.push(Instruction::Jump(Jump::GoTo, label));
+        match code.into_attribute(&mut pool, &mut Vec::new()) {
+            Err(Error::InvalidLabel) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // placed twice
+        let mut code = Code::new(0, 0);
+        let label = code.new_label();
+        code.instructions.push(Instruction::Label(label));
+        code.instructions.push(Instruction::Simple(basic::Instruction::Return));
+        code.instructions.push(Instruction::Label(label));
+        match code.into_attribute(&mut pool, &mut Vec::new()) {
+            Err(Error::InvalidLabel) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // branch into the middle of an instruction
+        let mut instructions = basic::Instructions::new();
+        instructions.insert(0, basic::Instruction::GoTo(1));
+        instructions.insert(3, basic::Instruction::Return);
+        let attribute = basic::Attribute::Code {
+            max_stack: 0,
+            max_locals: 0,
+            instructions,
+            exceptions: Vec::new(),
+            attributes: Vec::new(),
+        };
+        match Code::from_attribute(attribute, &pool, &[]) {
+            Err(Error::InvalidOffset(1)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unexpected_simple_instructions() {
+        let mut pool = Pool::new();
+        let index = pool.push(basic::Item::Integer(1)).unwrap();
+
+        // a jump, a switch and a constant have their own variants
+        let simple = vec![
+            basic::Instruction::GoTo(3),
+            basic::Instruction::LookupSwitch {
+                default: 0,
+                offsets: ::std::collections::BTreeMap::new(),
+                padding: [0; 3],
+            },
+            basic::Instruction::LDC(index),
+        ];
+        for insn in simple {
+            let mnemonic = insn.mnemonic();
+            let mut code = Code::new(1, 0);
+            code.instructions.push(Instruction::Simple(insn));
+            code.instructions.push(Instruction::Simple(basic::Instruction::Return));
+            match code.into_attribute(&mut pool, &mut Vec::new()) {
+                Err(Error::UnexpectedInstruction(name)) => assert_eq!(name, mnemonic),
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+    }
+
+    /// Creates a dynamic constant computed by a bootstrap method of `ConstantBootstraps`,
+    /// which takes no static arguments.
+    fn dynamic(name: &str, desc: &str, bootstrap_method: &str) -> Constant {
+        Constant::Dynamic(Dynamic {
+            name: name.to_owned(),
+            desc: desc.parse().unwrap(),
+            bootstrap_method: MethodHandle {
+                kind: basic::ReferenceKind::InvokeStatic,
+                reference: Reference::Method(MethodRef {
+                    owner: "java/lang/invoke/ConstantBootstraps".to_owned(),
+                    name: bootstrap_method.to_owned(),
+                    desc: "(Ljava/lang/invoke/MethodHandles$Lookup;Ljava/lang/String;\
+                           Ljava/lang/Class;)Ljava/lang/Object;"
+                        .parse()
+                        .unwrap(),
+                    interface: false,
+                }),
+            },
+            arguments: Vec::new(),
+        })
+    }
+
+    #[test]
+    fn dynamic_constants() {
+        let print = |desc: &str| {
+            Instruction::Method(
+                MethodInsn::InvokeVirtual,
+                MethodRef {
+                    owner: "java/io/PrintStream".to_owned(),
+                    name: "println".to_owned(),
+                    desc: desc.parse().unwrap(),
+                    interface: false,
+                },
+            )
+        };
+        let out = || {
+            Instruction::Field(
+                FieldInsn::GetStatic,
+                FieldRef {
+                    owner: "java/lang/System".to_owned(),
+                    name: "out".to_owned(),
+                    desc: "Ljava/io/PrintStream;".parse().unwrap(),
+                },
+            )
+        };
+
+        // prints `Long.MAX_VALUE` and `null`
+        let mut code = Code::new(3, 1);
+        code.instructions = vec![
+            out(),
+            Instruction::Constant(dynamic("MAX_VALUE", "J", "getStaticFinal")),
+            print("(J)V"),
+            out(),
+            Instruction::Constant(dynamic("_", "Ljava/lang/String;", "nullConstant")),
+            print("(Ljava/lang/Object;)V"),
+            Instruction::Simple(basic::Instruction::Return),
+        ];
+        let class = Class {
+            minor_version: 0,
+            major_version: 55,
+            access_flags: basic::ClassAccessFlags::PUBLIC | basic::ClassAccessFlags::SUPER,
+            name: "Condy".to_owned(),
+            super_name: Some("java/lang/Object".to_owned()),
+            interfaces: Vec::new(),
+            fields: Vec::new(),
+            methods: vec![
+                Method {
+                    access_flags: basic::MethodAccessFlags::PUBLIC | basic::MethodAccessFlags::STATIC,
+                    name: "main".to_owned(),
+                    desc: "([Ljava/lang/String;)V".parse().unwrap(),
+                    code: Some(code),
+                    attributes: Vec::new(),
+                },
+            ],
+            attributes: Vec::new(),
+        };
+        let bytes = write(class).unwrap();
+
+        let (pool, class) = basic::parse(&bytes).unwrap();
+        assert!(basic::validate(&pool, &class).is_empty());
+        assert_eq!(class.bootstrap_methods().len(), 2);
+
+        let instructions = match class.methods[0].attributes[0] {
+            basic::Attribute::Code {
+                ref instructions, ..
+            } => instructions,
+            ref other => panic!("unexpected attribute: {:?}", other),
+        };
+        let mut loaded = Vec::new();
+        for (_, insn) in instructions {
+            let index = match *insn {
+                basic::Instruction::LDC(index) | basic::Instruction::LDC2W(index) => index,
+                _ => continue,
+            };
+            let (bootstrap_method, name_and_type) = pool
+                .get_bootstrap_method(index, class.bootstrap_methods())
+                .unwrap();
+            assert!(bootstrap_method.arguments.is_empty());
+            match *pool.get(name_and_type).unwrap() {
+                basic::Item::NameAndType { name, .. } => {
+                    loaded.push(pool.get_utf8(name).unwrap())
+                }
+                ref other => panic!("unexpected item: {:?}", other),
+            }
+        }
+        assert_eq!(loaded, vec!["MAX_VALUE", "_"]);
+        match instructions.get(3) {
+            Some(&basic::Instruction::LDC2W(_)) => {}
+            other => panic!("unexpected instruction: {:?}", other),
+        }
+        let desc = "([Ljava/lang/String;)V".parse().unwrap();
+        assert_eq!(
+            basic::compute_maxs(&pool, &desc, true, instructions, &[]).unwrap(),
+            (3, 1)
+        );
+
+        resolve(&bytes);
+    }
+}
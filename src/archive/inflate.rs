@@ -0,0 +1,358 @@
+//! The inflate module decompresses data compressed with DEFLATE (RFC 1951),
+//! which is how most entries of a zip archive are stored.
+
+use result::*;
+
+/// The base lengths of the length codes 257 to 285
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115,
+    131, 163, 195, 227, 258,
+];
+
+/// The amount of extra bits of the length codes 257 to 285
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+/// The base distances of the distance codes 0 to 29
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+
+/// The amount of extra bits of the distance codes 0 to 29
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// The order in which the lengths of the code length alphabet are stored
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+/// The longest code allowed
+const MAX_BITS: usize = 15;
+
+/// The most bytes a single compressed byte can be decompressed to
+const MAX_RATIO: usize = 1032;
+
+/// Decompresses raw DEFLATE data (without a zlib or gzip header).
+///
+/// `size` is the expected size of the output, which is read from an untrusted header:
+/// The initial capacity is limited by what the data can be decompressed to
+/// and decompressing fails as soon as the output gets larger.
+pub fn inflate(bytes: &[u8], size: usize) -> Result<Vec<u8>> {
+    let mut input = Bits {
+        bytes,
+        position: 0,
+        buffer: 0,
+        count: 0,
+    };
+    let mut out = Output {
+        bytes: Vec::with_capacity(size.min(bytes.len().saturating_mul(MAX_RATIO))),
+        size,
+    };
+
+    loop {
+        let last = input.bits(1)? == 1;
+        match input.bits(2)? {
+            0 => stored(&mut input, &mut out)?,
+            1 => {
+                let (lengths, distances) = fixed()?;
+                codes(&mut input, &mut out, &lengths, &distances)?;
+            }
+            2 => {
+                let (lengths, distances) = dynamic(&mut input)?;
+                codes(&mut input, &mut out, &lengths, &distances)?;
+            }
+            _ => return Err(Error::InvalidArchive("reserved block type")),
+        }
+
+        if last {
+            return Ok(out.bytes);
+        }
+    }
+}
+
+/// The decompressed bytes, which may not exceed the expected size.
+struct Output {
+    bytes: Vec<u8>,
+    size: usize,
+}
+
+impl Output {
+    /// Fails if `additional` more bytes would exceed the expected size
+    fn reserve(&self, additional: usize) -> Result<()> {
+        if additional > self.size - self.bytes.len() {
+            Err(Error::InvalidArchive("decompressed data is larger than expected"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Reads bits starting with the least significant bit of every byte.
+struct Bits<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    buffer: u32,
+    count: u32,
+}
+
+impl<'a> Bits<'a> {
+    /// Reads `count` bits (at most 16), the first bit read is the least significant one
+    fn bits(&mut self, count: u32) -> Result<u32> {
+        while self.count < count {
+            let byte = *self.bytes
+                .get(self.position)
+                .ok_or(Error::InvalidArchive("compressed data ends unexpectedly"))?;
+            self.position += 1;
+            self.buffer |= u32::from(byte) << self.count;
+            self.count += 8;
+        }
+
+        let value = self.buffer & ((1 << count) - 1);
+        self.buffer >>= count;
+        self.count -= count;
+        Ok(value)
+    }
+
+    /// Discards the remaining bits of the current byte
+    fn align(&mut self) {
+        self.buffer = 0;
+        self.count = 0;
+    }
+}
+
+/// A canonical huffman code, given by the amount of codes of every length
+/// and the symbols ordered by their code.
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// Builds the code from the code length of every symbol, where 0 means unused.
+    /// Incomplete codes are allowed, but decoding an unused code fails.
+    fn new(lengths: &[u8]) -> Result<Huffman> {
+        let mut counts = [0; MAX_BITS + 1];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+
+        // more codes of a length than available
+        let mut left: i32 = 1;
+        for &count in &counts[1..] {
+            left = (left << 1) - i32::from(count);
+            if left < 0 {
+                return Err(Error::InvalidArchive("over-subscribed huffman code"));
+            }
+        }
+
+        let mut offsets = [0; MAX_BITS + 1];
+        for length in 1..MAX_BITS {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[length as usize] as usize] = symbol as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+
+        Ok(Huffman { counts, symbols })
+    }
+
+    /// Decodes a single symbol, reading one bit after another
+    fn decode(&self, input: &mut Bits) -> Result<u16> {
+        // the first code and the index of the first symbol of the current length
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for &count in &self.counts[1..] {
+            code |= input.bits(1)? as i32;
+            let count = i32::from(count);
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(Error::InvalidArchive("invalid huffman code"))
+    }
+}
+
+/// Copies an uncompressed block
+fn stored(input: &mut Bits, out: &mut Output) -> Result<()> {
+    input.align();
+    let length = input.bits(16)?;
+    let complement = input.bits(16)?;
+    if length != !complement & 0xFFFF {
+        return Err(Error::InvalidArchive("invalid length of stored block"));
+    }
+
+    let start = input.position;
+    let end = start + length as usize;
+    let data = input
+        .bytes
+        .get(start..end)
+        .ok_or(Error::InvalidArchive("compressed data ends unexpectedly"))?;
+    out.reserve(data.len())?;
+    out.bytes.extend_from_slice(data);
+    input.position = end;
+    Ok(())
+}
+
+/// The codes of a block compressed with fixed huffman codes
+fn fixed() -> Result<(Huffman, Huffman)> {
+    let mut lengths = [0; 288];
+    for (symbol, length) in lengths.iter_mut().enumerate() {
+        *length = match symbol {
+            0..=143 => 8,
+            144..=255 => 9,
+            256..=279 => 7,
+            _ => 8,
+        };
+    }
+    Ok((Huffman::new(&lengths)?, Huffman::new(&[5; 30])?))
+}
+
+/// Reads the codes of a block compressed with dynamic huffman codes
+fn dynamic(input: &mut Bits) -> Result<(Huffman, Huffman)> {
+    let literal_count = input.bits(5)? as usize + 257;
+    let distance_count = input.bits(5)? as usize + 1;
+    let code_length_count = input.bits(4)? as usize + 4;
+    if literal_count > 286 || distance_count > 30 {
+        return Err(Error::InvalidArchive("too many huffman codes"));
+    }
+
+    let mut code_lengths = [0; 19];
+    for &symbol in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_lengths[symbol] = input.bits(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_lengths)?;
+
+    let total = literal_count + distance_count;
+    let mut lengths = Vec::with_capacity(total);
+    while lengths.len() < total {
+        let (length, repeat) = match code_lengths.decode(input)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths
+                    .last()
+                    .ok_or(Error::InvalidArchive("no code length to repeat"))?;
+                (previous, 3 + input.bits(2)? as usize)
+            }
+            17 => (0, 3 + input.bits(3)? as usize),
+            _ => (0, 11 + input.bits(7)? as usize),
+        };
+        if lengths.len() + repeat > total {
+            return Err(Error::InvalidArchive("too many code lengths"));
+        }
+        lengths.resize(lengths.len() + repeat, length);
+    }
+
+    if lengths[256] == 0 {
+        return Err(Error::InvalidArchive("missing end of block code"));
+    }
+    Ok((
+        Huffman::new(&lengths[..literal_count])?,
+        Huffman::new(&lengths[literal_count..])?,
+    ))
+}
+
+/// Decodes literals and back references until the end of the block
+fn codes(
+    input: &mut Bits,
+    out: &mut Output,
+    lengths: &Huffman,
+    distances: &Huffman,
+) -> Result<()> {
+    loop {
+        let symbol = lengths.decode(input)?;
+        if symbol < 256 {
+            out.reserve(1)?;
+            out.bytes.push(symbol as u8);
+            continue;
+        }
+        if symbol == 256 {
+            return Ok(());
+        }
+
+        let symbol = symbol as usize - 257;
+        if symbol >= LENGTH_BASE.len() {
+            return Err(Error::InvalidArchive("invalid length code"));
+        }
+        let length =
+            LENGTH_BASE[symbol] as usize + input.bits(u32::from(LENGTH_EXTRA[symbol]))? as usize;
+
+        let symbol = distances.decode(input)? as usize;
+        if symbol >= DISTANCE_BASE.len() {
+            return Err(Error::InvalidArchive("invalid distance code"));
+        }
+        let distance = DISTANCE_BASE[symbol] as usize
+            + input.bits(u32::from(DISTANCE_EXTRA[symbol]))? as usize;
+        if distance > out.bytes.len() {
+            return Err(Error::InvalidArchive("distance too far back"));
+        }
+        out.reserve(length)?;
+
+        // the source may overlap with the bytes copied
+        let start = out.bytes.len() - distance;
+        for i in start..start + length {
+            let byte = out.bytes[i];
+            out.bytes.push(byte);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Decodes a hex string
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn blocks() {
+        let text = b"hello hello hello";
+
+        // stored
+        let bytes = hex("011100eeff68656c6c6f2068656c6c6f2068656c6c6f");
+        assert_eq!(inflate(&bytes, text.len()).unwrap(), &text[..]);
+
+        // fixed codes with an overlapping back reference
+        let bytes = hex("cb48cdc9c957c8409000");
+        assert_eq!(inflate(&bytes, text.len()).unwrap(), &text[..]);
+
+        // a smaller output than expected is checked by the caller
+        assert_eq!(inflate(&bytes, 1 << 62).unwrap(), &text[..]);
+
+        // dynamic codes are tested with the entries of a jar
+    }
+
+    #[test]
+    fn errors() {
+        // reserved block type
+        assert!(inflate(&[0x07], 0).is_err());
+        // truncated
+        assert!(inflate(&hex("cb48cdc9c957"), 0).is_err());
+        // the length of a stored block does not match its complement
+        assert!(inflate(&hex("0111001111"), 0).is_err());
+        // back reference before the start
+        assert!(inflate(&hex("03020000"), 0).is_err());
+        // more output than expected, by literals, a back reference or a stored block
+        assert!(inflate(&hex("cb48cdc9c957c8409000"), 5).is_err());
+        assert!(inflate(&hex("cb48cdc9c957c8409000"), 16).is_err());
+        assert!(inflate(&hex("011100eeff68656c6c6f2068656c6c6f2068656c6c6f"), 16).is_err());
+    }
+}
@@ -0,0 +1,182 @@
+//! The jar module reads the classes and the manifest of a jar.
+
+use std::fs;
+use std::path::Path;
+use std::slice;
+
+use super::manifest::Manifest;
+use super::zip::*;
+use basic::{self, Class, Pool};
+use result::*;
+
+/// The name of the manifest entry
+pub const MANIFEST_NAME: &str = "META-INF/MANIFEST.MF";
+
+/// A jar (or any other zip archive containing classes), which has been read into memory.
+///
+/// # Examples
+///
+/// ```
+/// use jbcrs::archive::JarReader;
+///
+/// let jar = JarReader::new(include_bytes!("../../examples/resources/Classes.jar").to_vec())
+///     .unwrap();
+///
+/// let manifest = jar.manifest().unwrap().unwrap();
+/// assert_eq!(manifest.get("Main-Class"), Some("Fibonacci"));
+///
+/// for (name, class) in jar.classes() {
+///     let (pool, class) = class.unwrap();
+///     assert_eq!(
+///         format!("{}.class", pool.get_class_name(class.name).unwrap()),
+///         name
+///     );
+/// }
+///
+/// let resources: Vec<&str> = jar.resources().map(|entry| entry.name.as_str()).collect();
+/// assert_eq!(resources, vec!["META-INF/MANIFEST.MF", "data/hello.txt"]);
+/// ```
+#[derive(Debug)]
+pub struct JarReader {
+    archive: ZipArchive,
+}
+
+impl JarReader {
+    /// Reads the central directory of a jar
+    pub fn new(bytes: Vec<u8>) -> Result<JarReader> {
+        Ok(JarReader {
+            archive: ZipArchive::new(bytes)?,
+        })
+    }
+
+    /// Reads a jar from a file
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<JarReader> {
+        JarReader::new(fs::read(path).map_err(Error::IO)?)
+    }
+
+    /// Returns the zip archive, to read any entry
+    pub fn archive(&self) -> &ZipArchive {
+        &self.archive
+    }
+
+    /// Reads and parses the manifest, `None` is returned if there is none
+    pub fn manifest(&self) -> Result<Option<Manifest>> {
+        let bytes = match self.archive.read_by_name(MANIFEST_NAME)? {
+            Some(bytes) => bytes,
+            None => return Ok(None),
+        };
+        let text = String::from_utf8(bytes)
+            .map_err(|_| Error::InvalidArchive("manifest is not valid utf-8"))?;
+        Manifest::parse(&text).map(Some)
+    }
+
+    /// Decompresses and parses the class of an entry
+    pub fn read_class(&self, entry: &ZipEntry) -> Result<(Pool, Class)> {
+        let bytes = self.archive.read(entry)?;
+        basic::parse(&bytes)
+    }
+
+    /// Iterates over every class, given by the name of its entry.
+    /// An entry, which cannot be decompressed or parsed, does not stop the iteration,
+    /// its error is returned instead.
+    ///
+    /// Entries not ending with `.class` (and directories) are skipped,
+    /// they are returned by `resources`.
+    pub fn classes(&self) -> Classes<'_> {
+        Classes {
+            reader: self,
+            entries: self.archive.entries().iter(),
+        }
+    }
+
+    /// Iterates over every entry, which is neither a class nor a directory.
+    pub fn resources(&self) -> Resources<'_> {
+        Resources {
+            entries: self.archive.entries().iter(),
+        }
+    }
+}
+
+/// Returns true if an entry is a class
+pub fn is_class(entry: &ZipEntry) -> bool {
+    !entry.is_dir() && entry.name.ends_with(".class")
+}
+
+/// An iterator over the classes of a jar, created by `JarReader::classes`.
+pub struct Classes<'a> {
+    reader: &'a JarReader,
+    entries: slice::Iter<'a, ZipEntry>,
+}
+
+impl<'a> Iterator for Classes<'a> {
+    type Item = (&'a str, Result<(Pool, Class)>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.entries.find(|entry| is_class(entry))?;
+        Some((&entry.name, self.reader.read_class(entry)))
+    }
+}
+
+/// An iterator over the entries of a jar, which are neither classes nor directories,
+/// created by `JarReader::resources`.
+pub struct Resources<'a> {
+    entries: slice::Iter<'a, ZipEntry>,
+}
+
+impl<'a> Iterator for Resources<'a> {
+    type Item = &'a ZipEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.entries.find(|entry| !entry.is_dir() && !is_class(entry))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn classes() {
+        let jar = JarReader::new(include_bytes!("../../examples/resources/Classes.jar").to_vec())
+            .unwrap();
+
+        let manifest = jar.manifest().unwrap().unwrap();
+        assert_eq!(manifest.get("Manifest-Version"), Some("1.0"));
+        assert_eq!(manifest.get("Main-Class"), Some("Fibonacci"));
+        assert!(manifest.entries.is_empty());
+
+        let classes: Vec<(&str, String)> = jar.classes()
+            .map(|(name, class)| {
+                let (pool, class) = class.unwrap();
+                (name, pool.get_class_name(class.name).unwrap())
+            })
+            .collect();
+        assert_eq!(
+            classes,
+            vec![
+                ("Fibonacci.class", "Fibonacci".to_owned()),
+                ("Sealed.class", "Sealed".to_owned()),
+                ("Sealed$Leaf.class", "Sealed$Leaf".to_owned()),
+                ("Sealed$Node.class", "Sealed$Node".to_owned()),
+            ]
+        );
+
+        let resources: Vec<&str> = jar.resources().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(resources, vec!["META-INF/MANIFEST.MF", "data/hello.txt"]);
+    }
+
+    #[test]
+    fn broken_class() {
+        // a jar without a manifest, whose only class is not a class file
+        let jar = JarReader::new(stored_zip(&[("Broken.class", b"not a class")])).unwrap();
+        assert!(jar.manifest().unwrap().is_none());
+
+        let mut classes = jar.classes();
+        match classes.next() {
+            Some(("Broken.class", Err(Error::NotAClass))) => {}
+            Some((name, _)) => panic!("unexpected class {}", name),
+            None => panic!("no class"),
+        }
+        assert!(classes.next().is_none());
+    }
+}
@@ -0,0 +1,691 @@
+//! The jimage module reads the `lib/modules` file of a java runtime image,
+//! which contains the classes and resources of every module of the runtime.
+
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+use std::fs;
+use std::path::Path;
+use std::vec;
+
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+
+use super::inflate::inflate;
+use basic::{self, Class, Pool};
+use result::*;
+
+const MAGIC: u32 = 0xCAFE_DADA;
+const MAJOR_VERSION: u32 = 1;
+
+/// The size of the header: magic, version, flags, resource count, table length,
+/// size of the locations and size of the strings
+const HEADER_SIZE: usize = 7 * 4;
+
+/// The seed and the multiplier of the hash function
+const HASH_MULTIPLIER: u32 = 0x0100_0193;
+
+/// The magic of a header preceding a compressed resource
+const COMPRESSED_MAGIC: u32 = 0xCAFE_FAFA;
+/// The size of a header preceding a compressed resource
+const COMPRESSED_HEADER_SIZE: usize = 29;
+
+// the kinds of attributes of a location
+const ATTRIBUTE_MODULE: usize = 1;
+const ATTRIBUTE_PARENT: usize = 2;
+const ATTRIBUTE_BASE: usize = 3;
+const ATTRIBUTE_EXTENSION: usize = 4;
+const ATTRIBUTE_OFFSET: usize = 5;
+const ATTRIBUTE_COMPRESSED: usize = 6;
+const ATTRIBUTE_UNCOMPRESSED: usize = 7;
+const ATTRIBUTE_COUNT: usize = 8;
+
+/// The pseudo modules holding the directory structure of the image
+const DIRECTORY_MODULES: &[&str] = &["modules", "packages"];
+
+/// A single resource of a jimage, whose name is `/module/parent/base.extension`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JimageEntry {
+    /// The name of the module, like `java.base`
+    pub module: String,
+    /// The directory of the resource inside of the module, like `java/lang`
+    pub parent: String,
+    /// The name of the resource without the directory and the extension, like `Object`
+    pub base: String,
+    /// The extension, like `class`
+    pub extension: String,
+    /// The size of the uncompressed content
+    pub size: u64,
+    compressed_size: u64,
+    offset: u64,
+}
+
+impl JimageEntry {
+    /// Returns the full name, like `/java.base/java/lang/Object.class`
+    pub fn name(&self) -> String {
+        let mut name = String::new();
+        if !self.module.is_empty() {
+            name.push('/');
+            name.push_str(&self.module);
+            name.push('/');
+        }
+        if !self.parent.is_empty() {
+            name.push_str(&self.parent);
+            name.push('/');
+        }
+        name.push_str(&self.base);
+        if !self.extension.is_empty() {
+            name.push('.');
+            name.push_str(&self.extension);
+        }
+        name
+    }
+
+    /// Returns true if this entry is a class
+    pub fn is_class(&self) -> bool {
+        self.extension == "class"
+    }
+
+    /// Returns the name of the class in internal form, like `java/lang/Object`,
+    /// or `None` if this entry is not a class
+    pub fn class_name(&self) -> Option<String> {
+        if !self.is_class() {
+            None
+        } else if self.parent.is_empty() {
+            Some(self.base.clone())
+        } else {
+            Some(format!("{}/{}", self.parent, self.base))
+        }
+    }
+
+    /// Returns true if this entry describes the directory structure of the image
+    /// (`/modules/...` and `/packages/...`) instead of being a resource of a module
+    fn is_directory(&self) -> bool {
+        DIRECTORY_MODULES.contains(&self.module.as_str())
+    }
+}
+
+/// The `lib/modules` file of a java runtime image, which has been read into memory.
+///
+/// Resources are found by their full name using the perfect hash table of the image.
+/// Resources compressed with `zip` are decompressed,
+/// other compressions (like string sharing) are not supported.
+///
+/// # Examples
+///
+/// ```no_run
+/// use jbcrs::archive::JimageReader;
+///
+/// let image = JimageReader::open("/usr/lib/jvm/java-17-openjdk-amd64/lib/modules").unwrap();
+/// assert!(image.modules().unwrap().contains(&"java.base".to_owned()));
+///
+/// let (pool, class) = image.read_class("java.base", "java/lang/Object").unwrap();
+/// assert_eq!(pool.get_class_name(class.name).unwrap(), "java/lang/Object");
+/// ```
+#[derive(Debug)]
+pub struct JimageReader {
+    bytes: Vec<u8>,
+    big_endian: bool,
+    table_length: usize,
+    /// The start of the redirect table
+    redirect: usize,
+    /// The start of the table containing the offsets of the locations
+    offsets: usize,
+    /// The start and the end of the locations
+    locations: (usize, usize),
+    /// The start and the end of the strings
+    strings: (usize, usize),
+    /// The start of the content of the resources
+    content: usize,
+}
+
+impl JimageReader {
+    /// Checks the header of a jimage, which may be in little or big endian byte order
+    pub fn new(bytes: Vec<u8>) -> Result<JimageReader> {
+        let magic = slice(&bytes, 0, 4)?;
+        let big_endian = if LittleEndian::read_u32(magic) == MAGIC {
+            false
+        } else if BigEndian::read_u32(magic) == MAGIC {
+            true
+        } else {
+            return Err(Error::InvalidArchive("not a jimage"));
+        };
+
+        let mut image = JimageReader {
+            bytes,
+            big_endian,
+            table_length: 0,
+            redirect: 0,
+            offsets: 0,
+            locations: (0, 0),
+            strings: (0, 0),
+            content: 0,
+        };
+
+        if image.u32_at(4)? >> 16 != MAJOR_VERSION {
+            return Err(Error::InvalidArchive("unsupported version of jimage"));
+        }
+        let table_length = image.u32_at(16)? as usize;
+        let locations_size = image.u32_at(20)? as usize;
+        let strings_size = image.u32_at(24)? as usize;
+
+        image.table_length = table_length;
+        image.redirect = HEADER_SIZE;
+        image.offsets = image.redirect + table_length * 4;
+        image.locations = (image.offsets + table_length * 4, 0);
+        image.locations.1 = image.locations.0 + locations_size;
+        image.strings = (image.locations.1, image.locations.1 + strings_size);
+        image.content = image.strings.1;
+        if image.content > image.bytes.len() {
+            return Err(Error::InvalidArchive("unexpected end of jimage"));
+        }
+
+        Ok(image)
+    }
+
+    /// Reads a jimage, like `lib/modules`
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<JimageReader> {
+        JimageReader::new(fs::read(path).map_err(Error::IO)?)
+    }
+
+    /// Returns every resource of every module in the order of the hash table,
+    /// the entries describing the directory structure are left out
+    pub fn entries(&self) -> Result<Vec<JimageEntry>> {
+        let mut entries = Vec::with_capacity(self.table_length);
+        for i in 0..self.table_length {
+            let entry = self.entry_at(i)?;
+            if !entry.is_directory() {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Finds a resource by its full name, like `/java.base/java/lang/Object.class`
+    pub fn find(&self, name: &str) -> Result<Option<JimageEntry>> {
+        if self.table_length == 0 {
+            return Ok(None);
+        }
+
+        let index = hash(name, HASH_MULTIPLIER) as usize % self.table_length;
+        let redirect = self.u32_at(self.redirect + index * 4)? as i32;
+        let index = if redirect < 0 {
+            (-1 - redirect) as usize
+        } else if redirect > 0 {
+            hash(name, redirect as u32) as usize % self.table_length
+        } else {
+            return Ok(None);
+        };
+        if index >= self.table_length {
+            return Err(Error::InvalidArchive("invalid redirect of jimage"));
+        }
+
+        // the hash table is perfect for the names it contains, but not for other names
+        let entry = self.entry_at(index)?;
+        if entry.name() == name {
+            Ok(Some(entry))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Reads the content of a resource and decompresses it, if necessary
+    pub fn read(&self, entry: &JimageEntry) -> Result<Vec<u8>> {
+        let start = to_usize(entry.offset)?
+            .checked_add(self.content)
+            .ok_or(Error::InvalidArchive("unexpected end of jimage"))?;
+        if entry.compressed_size == 0 {
+            return Ok(slice(&self.bytes, start, to_usize(entry.size)?)?.to_vec());
+        }
+
+        // compressions may be stacked, each one with its own header
+        let mut content = slice(&self.bytes, start, to_usize(entry.compressed_size)?)?.to_vec();
+        while content.len() >= COMPRESSED_HEADER_SIZE
+            && self.read_u32(&content) == COMPRESSED_MAGIC
+        {
+            let compressed_size = to_usize(self.read_u64(&content[4..]))?;
+            let size = to_usize(self.read_u64(&content[12..]))?;
+            let decompressor = self.string(self.read_u32(&content[20..]) as usize)?;
+            let data = slice(&content, COMPRESSED_HEADER_SIZE, compressed_size)?;
+            content = match decompressor.as_str() {
+                "zip" => inflate_zlib(data, size)?,
+                _ => {
+                    return Err(Error::InvalidArchive(
+                        "unsupported compression of jimage resource",
+                    ))
+                }
+            };
+        }
+
+        if content.len() as u64 != entry.size {
+            return Err(Error::InvalidArchive("size of jimage resource does not match"));
+        }
+        Ok(content)
+    }
+
+    /// Returns the name of every module, in ascending order
+    pub fn modules(&self) -> Result<Vec<String>> {
+        let modules: BTreeSet<String> = self.entries()?
+            .into_iter()
+            .map(|entry| entry.module)
+            .filter(|module| !module.is_empty())
+            .collect();
+        Ok(modules.into_iter().collect())
+    }
+
+    /// Returns every package of a module containing a class,
+    /// in internal form and in ascending order
+    pub fn packages(&self, module: &str) -> Result<Vec<String>> {
+        let packages: BTreeSet<String> = self.entries()?
+            .into_iter()
+            .filter(|entry| entry.module == module && entry.is_class() && !entry.parent.is_empty())
+            .map(|entry| entry.parent)
+            .collect();
+        Ok(packages.into_iter().collect())
+    }
+
+    /// Reads and parses a class of a module given in internal form, like `java/lang/Object`.
+    /// `Error::ClassNotFound` is returned, if there is no such class
+    pub fn read_class(&self, module: &str, name: &str) -> Result<(Pool, Class)> {
+        match self.find(&format!("/{}/{}.class", module, name))? {
+            Some(entry) => basic::parse(&self.read(&entry)?),
+            None => Err(Error::ClassNotFound(name.to_owned())),
+        }
+    }
+
+    /// Iterates over every class of every module.
+    /// A class, which cannot be decompressed or parsed, does not stop the iteration,
+    /// its error is returned instead.
+    pub fn classes(&self) -> Result<JimageClasses<'_>> {
+        Ok(JimageClasses {
+            image: self,
+            entries: self.entries()?.into_iter(),
+        })
+    }
+
+    /// Decodes the location at an index of the hash table
+    fn entry_at(&self, index: usize) -> Result<JimageEntry> {
+        let offset = self.u32_at(self.offsets + index * 4)? as usize;
+        let attributes = self.attributes(offset)?;
+        let string = |kind: usize| self.string(attributes[kind] as usize);
+        Ok(JimageEntry {
+            module: string(ATTRIBUTE_MODULE)?,
+            parent: string(ATTRIBUTE_PARENT)?,
+            base: string(ATTRIBUTE_BASE)?,
+            extension: string(ATTRIBUTE_EXTENSION)?,
+            size: attributes[ATTRIBUTE_UNCOMPRESSED],
+            compressed_size: attributes[ATTRIBUTE_COMPRESSED],
+            offset: attributes[ATTRIBUTE_OFFSET],
+        })
+    }
+
+    /// Decodes the attributes of a location, missing ones are 0.
+    /// Every attribute starts with a byte holding its kind (upper 5 bits)
+    /// and its length - 1 (lower 3 bits), followed by the big endian value.
+    fn attributes(&self, offset: usize) -> Result<[u64; ATTRIBUTE_COUNT]> {
+        let locations = &self.bytes[self.locations.0..self.locations.1];
+        let mut attributes = [0; ATTRIBUTE_COUNT];
+        let mut position = offset;
+        loop {
+            let byte = *locations
+                .get(position)
+                .ok_or(Error::InvalidArchive("invalid location of jimage"))?;
+            position += 1;
+            if byte <= 0x7 {
+                return Ok(attributes);
+            }
+
+            let kind = (byte >> 3) as usize;
+            if kind >= ATTRIBUTE_COUNT {
+                return Err(Error::InvalidArchive("invalid location of jimage"));
+            }
+            let length = (byte & 0x7) as usize + 1;
+            attributes[kind] = slice(locations, position, length)?
+                .iter()
+                .fold(0, |value, &byte| value << 8 | u64::from(byte));
+            position += length;
+        }
+    }
+
+    /// Reads a string terminated by a 0 from the strings of the image
+    fn string(&self, offset: usize) -> Result<String> {
+        let strings = &self.bytes[self.strings.0..self.strings.1];
+        let rest = strings
+            .get(offset..)
+            .ok_or(Error::InvalidArchive("invalid string offset of jimage"))?;
+        let end = rest.iter()
+            .position(|&byte| byte == 0)
+            .ok_or(Error::InvalidArchive("invalid string offset of jimage"))?;
+        String::from_utf8(rest[..end].to_vec())
+            .map_err(|_| Error::InvalidArchive("invalid string in jimage"))
+    }
+
+    fn u32_at(&self, position: usize) -> Result<u32> {
+        Ok(self.read_u32(slice(&self.bytes, position, 4)?))
+    }
+
+    fn read_u32(&self, bytes: &[u8]) -> u32 {
+        if self.big_endian {
+            BigEndian::read_u32(bytes)
+        } else {
+            LittleEndian::read_u32(bytes)
+        }
+    }
+
+    fn read_u64(&self, bytes: &[u8]) -> u64 {
+        if self.big_endian {
+            BigEndian::read_u64(bytes)
+        } else {
+            LittleEndian::read_u64(bytes)
+        }
+    }
+}
+
+/// The hash function of the image, applied to the modified utf-8 encoding of a name
+fn hash(name: &str, seed: u32) -> u32 {
+    let mut hash = seed;
+    let mut update = |byte: u32| hash = hash.wrapping_mul(HASH_MULTIPLIER) ^ byte;
+    for unit in name.encode_utf16() {
+        let unit = u32::from(unit);
+        if unit == 0 {
+            update(0xC0);
+            update(0x80);
+        } else if unit < 0x80 {
+            update(unit);
+        } else if unit < 0x800 {
+            update(0xC0 | unit >> 6);
+            update(0x80 | (unit & 0x3F));
+        } else {
+            update(0xE0 | unit >> 12);
+            update(0x80 | (unit >> 6 & 0x3F));
+            update(0x80 | (unit & 0x3F));
+        }
+    }
+    hash & 0x7FFF_FFFF
+}
+
+/// Decompresses data with a zlib header, the checksum at the end is not verified
+fn inflate_zlib(bytes: &[u8], size: usize) -> Result<Vec<u8>> {
+    let valid = bytes.len() >= 2
+        && bytes[0] & 0x0F == 8
+        && (u16::from(bytes[0]) << 8 | u16::from(bytes[1])) % 31 == 0;
+    if !valid {
+        return Err(Error::InvalidArchive("invalid zlib header"));
+    }
+    inflate(&bytes[2..], size)
+}
+
+/// Returns `length` bytes starting at `start` or an error if the image is too short
+fn slice(bytes: &[u8], start: usize, length: usize) -> Result<&[u8]> {
+    start
+        .checked_add(length)
+        .and_then(|end| bytes.get(start..end))
+        .ok_or(Error::InvalidArchive("unexpected end of jimage"))
+}
+
+/// Converts a size or offset, which might not fit into memory
+fn to_usize(value: u64) -> Result<usize> {
+    usize::try_from(value).map_err(|_| Error::InvalidArchive("jimage too large"))
+}
+
+/// An iterator over the classes of a jimage, created by `JimageReader::classes`.
+pub struct JimageClasses<'a> {
+    image: &'a JimageReader,
+    entries: vec::IntoIter<JimageEntry>,
+}
+
+impl<'a> Iterator for JimageClasses<'a> {
+    type Item = (JimageEntry, Result<(Pool, Class)>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.entries.find(JimageEntry::is_class)?;
+        let class = self.image
+            .read(&entry)
+            .and_then(|bytes| basic::parse(&bytes));
+        Some((entry, class))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use byteorder::WriteBytesExt;
+    use std::collections::HashMap;
+
+    /// Builds a jimage containing resources given by their full name,
+    /// the content is stored with a header as is, if it starts with the compressed magic.
+    fn image<B: ByteOrder>(resources: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut strings = vec![0];
+        let mut string_offsets: HashMap<String, u64> = HashMap::new();
+        string_offsets.insert(String::new(), 0);
+        let mut string = |s: &str| -> u64 {
+            *string_offsets.entry(s.to_owned()).or_insert_with(|| {
+                let offset = strings.len() as u64;
+                strings.extend_from_slice(s.as_bytes());
+                strings.push(0);
+                offset
+            })
+        };
+        // the decompressor is referenced by the content
+        assert_eq!(string("zip"), 1);
+
+        let mut locations = Vec::new();
+        let mut location_offsets = Vec::new();
+        let mut content = Vec::new();
+        for &(name, data) in resources {
+            let (module, path) = name[1..].split_at(name[1..].find('/').unwrap());
+            let path = &path[1..];
+            let (parent, file) = match path.rfind('/') {
+                Some(i) => (&path[..i], &path[i + 1..]),
+                None => ("", path),
+            };
+            let (base, extension) = match file.rfind('.') {
+                Some(i) => (&file[..i], &file[i + 1..]),
+                None => (file, ""),
+            };
+
+            let compressed = data.len() >= 4 && B::read_u32(data) == COMPRESSED_MAGIC;
+            let size = if compressed {
+                B::read_u64(&data[12..])
+            } else {
+                data.len() as u64
+            };
+
+            let mut attributes = [0; ATTRIBUTE_COUNT];
+            attributes[ATTRIBUTE_MODULE] = string(module);
+            attributes[ATTRIBUTE_PARENT] = string(parent);
+            attributes[ATTRIBUTE_BASE] = string(base);
+            attributes[ATTRIBUTE_EXTENSION] = string(extension);
+            attributes[ATTRIBUTE_OFFSET] = content.len() as u64;
+            attributes[ATTRIBUTE_COMPRESSED] = if compressed { data.len() as u64 } else { 0 };
+            attributes[ATTRIBUTE_UNCOMPRESSED] = size;
+            content.extend_from_slice(data);
+
+            location_offsets.push(locations.len() as u32);
+            for (kind, &value) in attributes.iter().enumerate() {
+                if value != 0 {
+                    let length = (8 - value.leading_zeros() as usize / 8).max(1);
+                    locations.push((kind << 3) as u8 | (length - 1) as u8);
+                    for i in (0..length).rev() {
+                        locations.push((value >> (i * 8)) as u8);
+                    }
+                }
+            }
+            locations.push(0);
+        }
+
+        // the perfect hash table: buckets with collisions get a seed,
+        // which maps all of their names to free slots
+        let length = resources.len();
+        let mut buckets: Vec<Vec<usize>> = vec![Vec::new(); length];
+        for (i, &(name, _)) in resources.iter().enumerate() {
+            buckets[hash(name, HASH_MULTIPLIER) as usize % length].push(i);
+        }
+        let mut order: Vec<usize> = (0..length).collect();
+        order.sort_by_key(|&bucket| ::std::cmp::Reverse(buckets[bucket].len()));
+
+        let mut redirect = vec![0i32; length];
+        let mut slots: Vec<Option<usize>> = vec![None; length];
+        for bucket in order {
+            match buckets[bucket].len() {
+                0 => {}
+                1 => {
+                    let slot = slots.iter().position(Option::is_none).unwrap();
+                    slots[slot] = buckets[bucket].first().cloned();
+                    redirect[bucket] = -1 - slot as i32;
+                }
+                _ => {
+                    // the low bits of a hash hardly depend on the seed,
+                    // so this fails for some names if the length is a power of two
+                    let seed = (1..0x1_0000)
+                        .find(|&seed| {
+                            let mut used = Vec::new();
+                            buckets[bucket].iter().all(|&i| {
+                                let slot = hash(resources[i].0, seed) as usize % length;
+                                let free = slots[slot].is_none() && !used.contains(&slot);
+                                used.push(slot);
+                                free
+                            })
+                        })
+                        .expect("no seed found");
+                    for &i in &buckets[bucket] {
+                        slots[hash(resources[i].0, seed) as usize % length] = Some(i);
+                    }
+                    redirect[bucket] = seed as i32;
+                }
+            }
+        }
+
+        let mut bytes = Vec::new();
+        for &value in &[
+            MAGIC,
+            MAJOR_VERSION << 16,
+            0,
+            length as u32,
+            length as u32,
+            locations.len() as u32,
+            strings.len() as u32,
+        ] {
+            bytes.write_u32::<B>(value).unwrap();
+        }
+        for &value in &redirect {
+            bytes.write_i32::<B>(value).unwrap();
+        }
+        for slot in &slots {
+            bytes.write_u32::<B>(location_offsets[slot.unwrap()]).unwrap();
+        }
+        bytes.extend_from_slice(&locations);
+        bytes.extend_from_slice(&strings);
+        bytes.extend_from_slice(&content);
+        bytes
+    }
+
+    /// A resource compressed with the `zip` decompressor
+    fn compressed<B: ByteOrder>() -> Vec<u8> {
+        let data = [
+            0x78, 0x9C, 0xCB, 0x48, 0xCD, 0xC9, 0xC9, 0x57, 0xC8, 0x40, 0x90, 0x00, 0x3A, 0x2E,
+            0x06, 0x7D,
+        ];
+        let mut bytes = Vec::new();
+        bytes.write_u32::<B>(COMPRESSED_MAGIC).unwrap();
+        bytes.write_u64::<B>(data.len() as u64).unwrap();
+        bytes.write_u64::<B>(17).unwrap();
+        // the offset of the name of the decompressor, of the content and whether it is terminal
+        bytes.write_u32::<B>(1).unwrap();
+        bytes.write_u32::<B>(0).unwrap();
+        bytes.write_u8(1).unwrap();
+        bytes.extend_from_slice(&data);
+        bytes
+    }
+
+    fn check<B: ByteOrder>() {
+        let compressed = compressed::<B>();
+        let image = JimageReader::new(image::<B>(&[
+            (
+                "/java.base/java/lang/Fibonacci.class",
+                include_bytes!("../../examples/resources/Fibonacci.class"),
+            ),
+            (
+                "/java.base/java/lang/Sealed.class",
+                include_bytes!("../../examples/resources/Sealed.class"),
+            ),
+            (
+                "/java.base/module-info.class",
+                include_bytes!("../../examples/resources/Records.class"),
+            ),
+            ("/java.base/java/lang/hello.txt", &compressed),
+            (
+                "/fixture/a/Sealed$Leaf.class",
+                include_bytes!("../../examples/resources/Sealed$Leaf.class"),
+            ),
+            ("/fixture/a/b/README", b"readme"),
+            ("/fixture/a/c.properties", b"c=d"),
+            ("/packages/java.lang/java.base", &[0; 8]),
+            ("/modules/fixture/a", b""),
+        ])).unwrap();
+
+        assert_eq!(
+            image.modules().unwrap(),
+            vec!["fixture".to_owned(), "java.base".to_owned()]
+        );
+        assert_eq!(image.packages("java.base").unwrap(), vec!["java/lang".to_owned()]);
+        assert_eq!(image.packages("fixture").unwrap(), vec!["a".to_owned()]);
+
+        let mut classes: Vec<(String, String)> = image
+            .classes()
+            .unwrap()
+            .map(|(entry, class)| {
+                let (pool, class) = class.unwrap();
+                (entry.name(), pool.get_class_name(class.name).unwrap())
+            })
+            .collect();
+        classes.sort();
+        assert_eq!(
+            classes,
+            vec![
+                ("/fixture/a/Sealed$Leaf.class".to_owned(), "Sealed$Leaf".to_owned()),
+                ("/java.base/java/lang/Fibonacci.class".to_owned(), "Fibonacci".to_owned()),
+                ("/java.base/java/lang/Sealed.class".to_owned(), "Sealed".to_owned()),
+                ("/java.base/module-info.class".to_owned(), "Records".to_owned()),
+            ]
+        );
+
+        let (pool, class) = image.read_class("java.base", "java/lang/Fibonacci").unwrap();
+        assert_eq!(pool.get_class_name(class.name).unwrap(), "Fibonacci");
+        assert!(image.read_class("fixture", "java/lang/Fibonacci").is_err());
+
+        let entry = image.find("/java.base/java/lang/hello.txt").unwrap().unwrap();
+        assert_eq!(entry.class_name(), None);
+        assert_eq!(image.read(&entry).unwrap(), b"hello hello hello");
+        let entry = image.find("/fixture/a/b/README").unwrap().unwrap();
+        assert_eq!((entry.parent.as_str(), entry.extension.as_str()), ("a/b", ""));
+        assert_eq!(image.read(&entry).unwrap(), b"readme");
+        let entry = image.find("/fixture/a/Sealed$Leaf.class").unwrap().unwrap();
+        assert_eq!(entry.class_name(), Some("a/Sealed$Leaf".to_owned()));
+
+        for name in &["/java.base/java/lang/Missing.class", "/fixture/a", "", "/"] {
+            assert_eq!(image.find(name).unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn little_endian() {
+        check::<LittleEndian>();
+    }
+
+    #[test]
+    fn big_endian() {
+        check::<BigEndian>();
+    }
+
+    #[test]
+    fn hashes() {
+        // computed with jdk.internal.jimage.ImageStringsReader
+        assert_eq!(hash("/java.base/java/lang/Object.class", HASH_MULTIPLIER), 0x7B31_F51F);
+        assert_eq!(hash("\u{0}\u{e9}\u{20ac}\u{1F600}", HASH_MULTIPLIER), 0x195F_5E1C);
+    }
+
+    #[test]
+    fn not_a_jimage() {
+        assert!(JimageReader::new(vec![0xCA, 0xFE, 0xBA, 0xBE]).is_err());
+        assert!(JimageReader::new(image::<LittleEndian>(&[])[..HEADER_SIZE - 1].to_vec()).is_err());
+    }
+}
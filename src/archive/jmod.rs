@@ -0,0 +1,247 @@
+//! The jmod module reads the classes of a jmod file,
+//! which is a zip archive prefixed with a header.
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::slice;
+
+use super::zip::*;
+use basic::{self, Attribute, Class, Item, Pool};
+use result::*;
+
+/// The header of every jmod file: `JM` followed by the major and minor version
+const JMOD_MAGIC: &[u8] = &[0x4A, 0x4D, 0x01, 0x00];
+
+/// The section containing the classes and resources of the module
+pub const CLASSES_SECTION: &str = "classes/";
+
+/// A jmod file, which has been read into memory.
+///
+/// Entries are grouped into sections by their first directory,
+/// like `classes/`, `conf/`, `lib/` or `legal/`.
+/// Class names are given relative to the `classes/` section.
+#[derive(Debug)]
+pub struct JmodReader {
+    archive: ZipArchive,
+}
+
+impl JmodReader {
+    /// Checks the header and reads the central directory of a jmod file
+    pub fn new(bytes: Vec<u8>) -> Result<JmodReader> {
+        if !bytes.starts_with(JMOD_MAGIC) {
+            return Err(Error::InvalidArchive("not a jmod file"));
+        }
+        Ok(JmodReader {
+            archive: ZipArchive::new(bytes)?,
+        })
+    }
+
+    /// Reads a jmod file, like `jmods/java.base.jmod`
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<JmodReader> {
+        JmodReader::new(fs::read(path).map_err(Error::IO)?)
+    }
+
+    /// Returns the zip archive, to read any entry of any section
+    pub fn archive(&self) -> &ZipArchive {
+        &self.archive
+    }
+
+    /// Reads and parses a class given in internal form, like `java/lang/Object`.
+    /// `Error::ClassNotFound` is returned, if there is no such class
+    pub fn read_class(&self, name: &str) -> Result<(Pool, Class)> {
+        match self.archive.by_name(&format!("{}{}.class", CLASSES_SECTION, name)) {
+            Some(entry) => basic::parse(&self.archive.read(entry)?),
+            None => Err(Error::ClassNotFound(name.to_owned())),
+        }
+    }
+
+    /// Reads and parses `module-info.class`
+    pub fn module_info(&self) -> Result<(Pool, Class)> {
+        self.read_class("module-info")
+    }
+
+    /// Returns the name of the module, as declared by its `Module` attribute
+    pub fn module_name(&self) -> Result<String> {
+        let (pool, class) = self.module_info()?;
+        module_name(&pool, &class)
+    }
+
+    /// Returns every package containing a class, in internal form and in ascending order
+    pub fn packages(&self) -> Vec<String> {
+        let packages: BTreeSet<&str> = self.archive
+            .entries()
+            .iter()
+            .filter_map(class_name)
+            .filter_map(|name| name.rfind('/').map(|i| &name[..i]))
+            .collect();
+        packages.into_iter().map(str::to_owned).collect()
+    }
+
+    /// Iterates over every class (including `module-info.class`),
+    /// given by the name of its entry relative to the `classes/` section.
+    /// An entry, which cannot be decompressed or parsed, does not stop the iteration,
+    /// its error is returned instead.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use jbcrs::archive::JmodReader;
+    ///
+    /// let jmod = JmodReader::open("/usr/lib/jvm/java-17-openjdk-amd64/jmods/java.base.jmod")
+    ///     .unwrap();
+    /// assert_eq!(jmod.module_name().unwrap(), "java.base");
+    ///
+    /// for (name, class) in jmod.classes() {
+    ///     let (pool, class) = class.unwrap();
+    ///     println!("{}: {}", name, pool.get_class_name(class.name).unwrap());
+    /// }
+    /// ```
+    pub fn classes(&self) -> JmodClasses<'_> {
+        JmodClasses {
+            archive: &self.archive,
+            entries: self.archive.entries().iter(),
+        }
+    }
+}
+
+/// Returns the name of the entry relative to the `classes/` section, if it is a class
+fn class_name(entry: &ZipEntry) -> Option<&str> {
+    let name = entry.name.strip_prefix(CLASSES_SECTION)?;
+    if !entry.is_dir() && name.ends_with(".class") {
+        Some(name)
+    } else {
+        None
+    }
+}
+
+/// Returns the name of a module declared by the `Module` attribute of a `module-info` class
+pub fn module_name(constant_pool: &Pool, class: &Class) -> Result<String> {
+    let name = class
+        .attributes
+        .iter()
+        .find_map(|attribute| match *attribute {
+            Attribute::Module { name, .. } => Some(name),
+            _ => None,
+        })
+        .ok_or(Error::UnexpectedAttribute)?;
+    match *constant_pool.get(name)? {
+        Item::Module(name) => constant_pool.get_utf8(name),
+        _ => Err(Error::InvalidCPItem(name)),
+    }
+}
+
+/// An iterator over the classes of a jmod file, created by `JmodReader::classes`.
+pub struct JmodClasses<'a> {
+    archive: &'a ZipArchive,
+    entries: slice::Iter<'a, ZipEntry>,
+}
+
+impl<'a> Iterator for JmodClasses<'a> {
+    type Item = (&'a str, Result<(Pool, Class)>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for entry in &mut self.entries {
+            if let Some(name) = class_name(entry) {
+                let class = self.archive
+                    .read(entry)
+                    .and_then(|bytes| basic::parse(&bytes));
+                return Some((name, class));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use basic::{write, ClassAccessFlags, ModuleFlags};
+
+    /// Creates a `module-info` class declaring a module
+    fn module_info(name: &str) -> Vec<u8> {
+        let mut pool = Pool::new();
+        let class_name = pool.push(Item::UTF8("module-info".to_owned())).unwrap();
+        let class_name = pool.push(Item::Class(class_name)).unwrap();
+        let module_name = pool.push(Item::UTF8(name.to_owned())).unwrap();
+        let module_name = pool.push(Item::Module(module_name)).unwrap();
+        pool.push(Item::UTF8("Module".to_owned())).unwrap();
+
+        let class = Class {
+            minor_version: 0,
+            major_version: 53,
+            access_flags: ClassAccessFlags::MODULE,
+            name: class_name,
+            super_name: 0,
+            interfaces: Vec::new(),
+            fields: Vec::new(),
+            methods: Vec::new(),
+            attributes: vec![Attribute::Module {
+                name: module_name,
+                flags: ModuleFlags::empty(),
+                version: 0,
+                requires: Vec::new(),
+                exports: Vec::new(),
+                opens: Vec::new(),
+                uses: Vec::new(),
+                provides: Vec::new(),
+            }],
+        };
+        write(&pool, &class).unwrap()
+    }
+
+    /// A jmod file built by hand
+    fn jmod() -> Vec<u8> {
+        let mut bytes = JMOD_MAGIC.to_vec();
+        bytes.extend(stored_zip(&[
+            ("classes/module-info.class", &module_info("fixture")),
+            (
+                "classes/Fibonacci.class",
+                include_bytes!("../../examples/resources/Fibonacci.class"),
+            ),
+            (
+                "classes/a/b/Sealed.class",
+                include_bytes!("../../examples/resources/Sealed.class"),
+            ),
+            ("classes/a/b/data.txt", b"data"),
+            ("conf/fixture.properties", b"a=b"),
+            ("legal/LICENSE", b"none"),
+        ]));
+        bytes
+    }
+
+    #[test]
+    fn classes() {
+        let jmod = JmodReader::new(jmod()).unwrap();
+        assert_eq!(jmod.module_name().unwrap(), "fixture");
+        assert_eq!(jmod.packages(), vec!["a/b".to_owned()]);
+
+        let classes: Vec<(&str, String)> = jmod.classes()
+            .map(|(name, class)| {
+                let (pool, class) = class.unwrap();
+                (name, pool.get_class_name(class.name).unwrap())
+            })
+            .collect();
+        assert_eq!(
+            classes,
+            vec![
+                ("module-info.class", "module-info".to_owned()),
+                ("Fibonacci.class", "Fibonacci".to_owned()),
+                ("a/b/Sealed.class", "Sealed".to_owned()),
+            ]
+        );
+
+        let (pool, class) = jmod.read_class("Fibonacci").unwrap();
+        assert_eq!(pool.get_class_name(class.name).unwrap(), "Fibonacci");
+        assert!(jmod.read_class("a/b/data").is_err());
+        assert_eq!(
+            jmod.archive().read_by_name("conf/fixture.properties").unwrap().unwrap(),
+            b"a=b"
+        );
+    }
+
+    #[test]
+    fn not_a_jmod() {
+        assert!(JmodReader::new(stored_zip(&[("a.txt", b"a")])).is_err());
+    }
+}
@@ -0,0 +1,153 @@
+//! The manifest module parses the `META-INF/MANIFEST.MF` of a jar.
+
+use result::*;
+
+/// The manifest of a jar, consisting of the main section,
+/// which applies to the whole jar, and sections of individual entries.
+///
+/// Attributes are kept in the order they are written,
+/// but their names are compared ignoring the case, like java does.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Manifest {
+    /// The attributes of the main section, like `Manifest-Version` or `Main-Class`
+    pub main_attributes: Vec<(String, String)>,
+    /// The sections of individual entries by the value of their `Name` attribute,
+    /// which is not part of the attributes
+    pub entries: Vec<(String, Vec<(String, String)>)>,
+}
+
+impl Manifest {
+    /// Parses a manifest.
+    /// Lines may end with `\r\n` or `\n`, values may be continued on the next line,
+    /// if it starts with a single space.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jbcrs::archive::Manifest;
+    ///
+    /// let manifest = Manifest::parse(
+    ///     "Manifest-Version: 1.0\r\nMain-Class: a.very.long.packa\r\n ge.Main\r\n\r\n\
+    ///      Name: a/Secret.class\r\nSealed: true\r\n",
+    /// ).unwrap();
+    /// assert_eq!(manifest.get("main-class"), Some("a.very.long.package.Main"));
+    /// assert_eq!(manifest.get_entry("a/Secret.class", "Sealed"), Some("true"));
+    /// ```
+    pub fn parse(text: &str) -> Result<Manifest> {
+        let mut sections: Vec<Vec<(String, String)>> = vec![Vec::new()];
+        let mut new_section = false;
+        for line in text.lines() {
+            if line.is_empty() {
+                new_section = true;
+            } else if let Some(continued) = line.strip_prefix(' ') {
+                if new_section {
+                    return Err(Error::InvalidArchive("manifest continues an empty line"));
+                }
+                match sections.last_mut().and_then(|section| section.last_mut()) {
+                    Some(&mut (_, ref mut value)) => value.push_str(continued),
+                    None => {
+                        return Err(Error::InvalidArchive(
+                            "manifest continues a missing attribute",
+                        ))
+                    }
+                }
+            } else {
+                let colon = line.find(':')
+                    .ok_or(Error::InvalidArchive("manifest attribute without a value"))?;
+                let name = &line[..colon];
+                let value = &line[colon + 1..];
+                let value = value.strip_prefix(' ').unwrap_or(value);
+
+                if new_section {
+                    sections.push(Vec::new());
+                    new_section = false;
+                }
+                sections
+                    .last_mut()
+                    .unwrap()
+                    .push((name.to_owned(), value.to_owned()));
+            }
+        }
+
+        let mut sections = sections.into_iter();
+        let main_attributes = sections.next().unwrap_or_default();
+        let mut entries = Vec::new();
+        for mut attributes in sections {
+            let position = attributes
+                .iter()
+                .position(|(name, _)| name.eq_ignore_ascii_case("Name"))
+                .ok_or(Error::InvalidArchive("manifest section without a name"))?;
+            let (_, name) = attributes.remove(position);
+            entries.push((name, attributes));
+        }
+
+        Ok(Manifest {
+            main_attributes,
+            entries,
+        })
+    }
+
+    /// Returns the value of an attribute of the main section
+    pub fn get(&self, name: &str) -> Option<&str> {
+        find(&self.main_attributes, name)
+    }
+
+    /// Returns the value of an attribute of the section of an entry
+    pub fn get_entry(&self, entry: &str, name: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(entry_name, _)| entry_name == entry)
+            .and_then(|(_, attributes)| find(attributes, name))
+    }
+}
+
+/// Finds an attribute ignoring the case of its name
+fn find<'a>(attributes: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|(attribute, _)| attribute.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sections() {
+        let manifest = Manifest::parse(
+            "Manifest-Version: 1.0\nCreated-By: hand\n\n\nName: a/B.class\nX-Empty:\n\
+             X-Long: 12\n 34\n 5\n\nname: c/\nSealed: true\n",
+        ).unwrap();
+        assert_eq!(
+            manifest,
+            Manifest {
+                main_attributes: vec![
+                    ("Manifest-Version".to_owned(), "1.0".to_owned()),
+                    ("Created-By".to_owned(), "hand".to_owned()),
+                ],
+                entries: vec![
+                    (
+                        "a/B.class".to_owned(),
+                        vec![
+                            ("X-Empty".to_owned(), "".to_owned()),
+                            ("X-Long".to_owned(), "12345".to_owned()),
+                        ],
+                    ),
+                    (
+                        "c/".to_owned(),
+                        vec![("Sealed".to_owned(), "true".to_owned())],
+                    ),
+                ],
+            }
+        );
+        assert_eq!(manifest.get("created-by"), Some("hand"));
+        assert_eq!(manifest.get("Main-Class"), None);
+        assert_eq!(manifest.get_entry("a/B.class", "x-long"), Some("12345"));
+        assert_eq!(manifest.get_entry("c/", "X-Long"), None);
+
+        assert!(Manifest::parse(" continued").is_err());
+        assert!(Manifest::parse("Manifest-Version 1.0").is_err());
+        assert!(Manifest::parse("A: b\n\nSealed: true").is_err());
+    }
+}
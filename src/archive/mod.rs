@@ -0,0 +1,19 @@
+//! The archive module reads classes from archives, like jar files.
+//!
+//! Zip archives are read into memory and decompressed without any further dependency,
+//! entries may be stored or compressed with DEFLATE.
+
+mod inflate;
+mod jar;
+mod jimage;
+mod jmod;
+mod manifest;
+mod versioned;
+mod zip;
+
+pub use self::jar::*;
+pub use self::jimage::*;
+pub use self::jmod::*;
+pub use self::manifest::*;
+pub use self::versioned::*;
+pub use self::zip::*;
@@ -0,0 +1,373 @@
+//! The versioned module resolves the entries of a multi-release jar
+//! for a specific java release.
+
+use std::collections::{btree_map, BTreeMap};
+
+use super::jar::*;
+use super::zip::*;
+use basic::{Class, Pool};
+use result::*;
+
+/// The directory containing the version-specific entries
+pub const VERSIONS_PREFIX: &str = "META-INF/versions/";
+
+/// The first release, which supports multi-release jars
+const FIRST_RELEASE: u16 = 9;
+
+impl JarReader {
+    /// Returns true if the manifest declares the jar as `Multi-Release: true`
+    pub fn is_multi_release(&self) -> Result<bool> {
+        Ok(self.manifest()?
+            .and_then(|manifest| {
+                manifest
+                    .get("Multi-Release")
+                    .map(|value| value.eq_ignore_ascii_case("true"))
+            })
+            .unwrap_or(false))
+    }
+
+    /// Creates a view of the jar, as a JVM running `release` (like 17) sees it.
+    ///
+    /// If the jar is a multi-release jar, every entry below `META-INF/versions/N/`
+    /// with 9 <= N <= `release` replaces the entry of the same name without the prefix,
+    /// the highest version wins.
+    /// Entries of a jar, which is not a multi-release jar,
+    /// and entries below a directory, which is not a version (like `8/` or `x/`),
+    /// keep their full name, just like `java.util.jar.JarFile` finds them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jbcrs::archive::JarReader;
+    ///
+    /// let jar = JarReader::new(include_bytes!("../../examples/resources/Classes.jar").to_vec())
+    ///     .unwrap();
+    /// let view = jar.versioned(17).unwrap();
+    ///
+    /// let (pool, class) = view.read_class("Sealed$Leaf").unwrap();
+    /// assert_eq!(pool.get_class_name(class.name).unwrap(), "Sealed$Leaf");
+    /// assert!(view.read_class("Missing").is_err());
+    ///
+    /// // not a multi-release jar
+    /// assert!(view.overrides().is_empty());
+    /// ```
+    pub fn versioned(&self, release: u16) -> Result<VersionedJar<'_>> {
+        let multi_release = self.is_multi_release()?;
+        let mut entries: BTreeMap<String, Resolved> = BTreeMap::new();
+        let mut versions: BTreeMap<String, Vec<u16>> = BTreeMap::new();
+        for entry in self.archive().entries() {
+            if entry.is_dir() {
+                continue;
+            }
+
+            let versioned = if multi_release {
+                entry.name.strip_prefix(VERSIONS_PREFIX).and_then(split_version)
+            } else {
+                None
+            };
+            let (name, version) = match versioned {
+                Some((name, version)) => {
+                    versions.entry(name.to_owned()).or_default().push(version);
+                    if version > release {
+                        continue;
+                    }
+                    (name, Some(version))
+                }
+                None => (entry.name.as_str(), None),
+            };
+
+            // base entries come first, since `None` is less than any version
+            let resolved = entries.entry(name.to_owned()).or_insert(Resolved { version, entry });
+            if version > resolved.version {
+                *resolved = Resolved { version, entry };
+            }
+        }
+
+        for list in versions.values_mut() {
+            list.sort_unstable();
+            list.dedup();
+        }
+
+        Ok(VersionedJar {
+            reader: self,
+            release,
+            multi_release,
+            entries,
+            versions,
+        })
+    }
+}
+
+/// Splits `N/name` into the name and the version, if the version is valid
+fn split_version(rest: &str) -> Option<(&str, u16)> {
+    let slash = rest.find('/')?;
+    let version = rest[..slash].parse().ok()?;
+    let name = &rest[slash + 1..];
+    if version < FIRST_RELEASE || name.is_empty() {
+        None
+    } else {
+        Some((name, version))
+    }
+}
+
+/// The entry chosen for a name and the version it belongs to,
+/// `None` for the base entry
+#[derive(Debug)]
+struct Resolved<'a> {
+    version: Option<u16>,
+    entry: &'a ZipEntry,
+}
+
+/// A class with version-specific entries in a multi-release jar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionOverride {
+    /// The name of the class in internal form
+    pub name: String,
+    /// Every version with an entry of the class, in ascending order,
+    /// including versions newer than the release of the view
+    pub versions: Vec<u16>,
+    /// The version chosen for the release of the view,
+    /// `None` if the base entry is used
+    pub selected: Option<u16>,
+}
+
+/// A view of a jar for a specific java release, created by `JarReader::versioned`.
+///
+/// Versioned entries are looked up by their name without the `META-INF/versions/N/` prefix.
+#[derive(Debug)]
+pub struct VersionedJar<'a> {
+    reader: &'a JarReader,
+    release: u16,
+    multi_release: bool,
+    entries: BTreeMap<String, Resolved<'a>>,
+    /// All versions of every name, which has versioned entries
+    versions: BTreeMap<String, Vec<u16>>,
+}
+
+impl<'a> VersionedJar<'a> {
+    /// Returns the release the entries are resolved for
+    pub fn release(&self) -> u16 {
+        self.release
+    }
+
+    /// Returns true if the jar is a multi-release jar
+    pub fn is_multi_release(&self) -> bool {
+        self.multi_release
+    }
+
+    /// Returns the entry chosen for a name, like `a/B.class`
+    pub fn entry(&self, name: &str) -> Option<&'a ZipEntry> {
+        self.entries.get(name).map(|resolved| resolved.entry)
+    }
+
+    /// Reads the entry chosen for a name, `None` is returned if there is no such entry
+    pub fn read(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        match self.entry(name) {
+            Some(entry) => self.reader.archive().read(entry).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the name of every entry visible in this release
+    /// without the version prefix, in ascending order
+    pub fn names(&self) -> Vec<&str> {
+        self.entries.keys().map(String::as_str).collect()
+    }
+
+    /// Returns the version of the entry chosen for a name,
+    /// `Some(None)` if the base entry is used
+    pub fn version(&self, name: &str) -> Option<Option<u16>> {
+        self.entries.get(name).map(|resolved| resolved.version)
+    }
+
+    /// Reads and parses a class given in internal form, like `java/lang/Object`.
+    /// `Error::ClassNotFound` is returned, if there is no such class
+    pub fn read_class(&self, name: &str) -> Result<(Pool, Class)> {
+        match self.entry(&format!("{}.class", name)) {
+            Some(entry) => self.reader.read_class(entry),
+            None => Err(Error::ClassNotFound(name.to_owned())),
+        }
+    }
+
+    /// Iterates over every class visible in this release,
+    /// given by the name of its entry without the version prefix, in ascending order.
+    pub fn classes(&self) -> VersionedClasses<'_> {
+        VersionedClasses {
+            reader: self.reader,
+            entries: self.entries.iter(),
+        }
+    }
+
+    /// Returns every class with version-specific entries, ordered by name.
+    /// Classes, which only exist in a versioned directory, are listed as well.
+    pub fn overrides(&self) -> Vec<VersionOverride> {
+        self.versions
+            .iter()
+            .filter(|(name, _)| name.ends_with(".class"))
+            .map(|(name, versions)| VersionOverride {
+                name: name[..name.len() - ".class".len()].to_owned(),
+                versions: versions.clone(),
+                selected: self.version(name).unwrap_or(None),
+            })
+            .collect()
+    }
+}
+
+/// An iterator over the classes of a jar in a specific release,
+/// created by `VersionedJar::classes`.
+pub struct VersionedClasses<'a> {
+    reader: &'a JarReader,
+    entries: btree_map::Iter<'a, String, Resolved<'a>>,
+}
+
+impl<'a> Iterator for VersionedClasses<'a> {
+    type Item = (&'a str, Result<(Pool, Class)>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (name, resolved) = self.entries.find(|(_, resolved)| is_class(resolved.entry))?;
+        Some((name, self.reader.read_class(resolved.entry)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A multi-release jar, whose classes have the wrong names to tell them apart
+    fn jar(multi_release: bool) -> JarReader {
+        let manifest = if multi_release {
+            "Manifest-Version: 1.0\r\nMulti-Release: true\r\n\r\n"
+        } else {
+            "Manifest-Version: 1.0\r\n\r\n"
+        };
+        let fibonacci = include_bytes!("../../examples/resources/Fibonacci.class");
+        let sealed = include_bytes!("../../examples/resources/Sealed.class");
+        let leaf = include_bytes!("../../examples/resources/Sealed$Leaf.class");
+        let node = include_bytes!("../../examples/resources/Sealed$Node.class");
+
+        JarReader::new(stored_zip(&[
+            ("META-INF/MANIFEST.MF", manifest.as_bytes()),
+            ("a/A.class", fibonacci),
+            ("a/B.class", fibonacci),
+            ("a/res.txt", b"base"),
+            ("META-INF/versions/11/a/A.class", sealed),
+            ("META-INF/versions/17/a/A.class", leaf),
+            ("META-INF/versions/11/a/C.class", node),
+            ("META-INF/versions/11/a/res.txt", b"11"),
+            ("META-INF/versions/8/a/B.class", leaf),
+            ("META-INF/versions/x/a/B.class", leaf),
+        ])).unwrap()
+    }
+
+    /// The names of the classes actually read, by the name of their entry
+    fn classes(view: &VersionedJar) -> Vec<(String, String)> {
+        view.classes()
+            .map(|(name, class)| {
+                let (pool, class) = class.unwrap();
+                (name.to_owned(), pool.get_class_name(class.name).unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn releases() {
+        let jar = jar(true);
+        assert!(jar.is_multi_release().unwrap());
+
+        let view = jar.versioned(8).unwrap();
+        assert_eq!(
+            classes(&view),
+            vec![
+                ("META-INF/versions/8/a/B.class".to_owned(), "Sealed$Leaf".to_owned()),
+                ("META-INF/versions/x/a/B.class".to_owned(), "Sealed$Leaf".to_owned()),
+                ("a/A.class".to_owned(), "Fibonacci".to_owned()),
+                ("a/B.class".to_owned(), "Fibonacci".to_owned()),
+            ]
+        );
+        let entry = view.entry("a/res.txt").unwrap();
+        assert_eq!(jar.archive().read(entry).unwrap(), b"base");
+
+        let view = jar.versioned(11).unwrap();
+        assert_eq!(
+            classes(&view),
+            vec![
+                ("META-INF/versions/8/a/B.class".to_owned(), "Sealed$Leaf".to_owned()),
+                ("META-INF/versions/x/a/B.class".to_owned(), "Sealed$Leaf".to_owned()),
+                ("a/A.class".to_owned(), "Sealed".to_owned()),
+                ("a/B.class".to_owned(), "Fibonacci".to_owned()),
+                ("a/C.class".to_owned(), "Sealed$Node".to_owned()),
+            ]
+        );
+        assert_eq!(view.read("a/res.txt").unwrap().unwrap(), b"11");
+        assert_eq!(
+            view.names(),
+            vec![
+                "META-INF/MANIFEST.MF",
+                "META-INF/versions/8/a/B.class",
+                "META-INF/versions/x/a/B.class",
+                "a/A.class",
+                "a/B.class",
+                "a/C.class",
+                "a/res.txt",
+            ]
+        );
+        assert_eq!(view.version("a/A.class"), Some(Some(11)));
+        assert_eq!(view.version("a/B.class"), Some(None));
+        assert_eq!(view.version("a/D.class"), None);
+        assert_eq!(view.version("META-INF/versions/8/a/B.class"), Some(None));
+        assert_eq!(view.entry("META-INF/versions/11/a/A.class"), None);
+
+        let view = jar.versioned(21).unwrap();
+        let (pool, class) = view.read_class("a/A").unwrap();
+        assert_eq!(pool.get_class_name(class.name).unwrap(), "Sealed$Leaf");
+        match view.read_class("a/D") {
+            Err(Error::ClassNotFound(ref name)) if name == "a/D" => {}
+            _ => panic!("a/D should not be found"),
+        }
+
+        let view = jar.versioned(15).unwrap();
+        assert_eq!(
+            view.overrides(),
+            vec![
+                VersionOverride {
+                    name: "a/A".to_owned(),
+                    versions: vec![11, 17],
+                    selected: Some(11),
+                },
+                VersionOverride {
+                    name: "a/C".to_owned(),
+                    versions: vec![11],
+                    selected: Some(11),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn not_multi_release() {
+        let jar = jar(false);
+        assert!(!jar.is_multi_release().unwrap());
+
+        // the versioned entries are ordinary entries
+        let view = jar.versioned(17).unwrap();
+        assert!(!view.is_multi_release());
+        assert_eq!(
+            classes(&view),
+            vec![
+                ("META-INF/versions/11/a/A.class".to_owned(), "Sealed".to_owned()),
+                ("META-INF/versions/11/a/C.class".to_owned(), "Sealed$Node".to_owned()),
+                ("META-INF/versions/17/a/A.class".to_owned(), "Sealed$Leaf".to_owned()),
+                ("META-INF/versions/8/a/B.class".to_owned(), "Sealed$Leaf".to_owned()),
+                ("META-INF/versions/x/a/B.class".to_owned(), "Sealed$Leaf".to_owned()),
+                ("a/A.class".to_owned(), "Fibonacci".to_owned()),
+                ("a/B.class".to_owned(), "Fibonacci".to_owned()),
+            ]
+        );
+        assert_eq!(view.read("a/res.txt").unwrap().unwrap(), b"base");
+        assert_eq!(
+            view.read("META-INF/versions/11/a/res.txt").unwrap().unwrap(),
+            b"11"
+        );
+        assert!(view.overrides().is_empty());
+    }
+}
@@ -0,0 +1,493 @@
+//! The zip module reads the central directory of a zip archive
+//! and decompresses its entries.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use super::inflate::inflate;
+use result::*;
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4B50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4B50;
+const END_SIGNATURE: u32 = 0x0605_4B50;
+const ZIP64_END_SIGNATURE: u32 = 0x0606_4B50;
+const ZIP64_LOCATOR_SIGNATURE: u32 = 0x0706_4B50;
+
+/// The id of the extra field holding the 64 bit sizes and offset
+const ZIP64_EXTRA: u16 = 0x0001;
+
+/// The size of the end of central directory record without the comment
+const END_SIZE: usize = 22;
+
+/// The size of the zip64 end of central directory record without extensible data
+const ZIP64_END_SIZE: usize = 56;
+
+/// The compression method of an entry, which is stored as is
+pub const STORED: u16 = 0;
+/// The compression method of an entry, which is compressed with DEFLATE
+pub const DEFLATED: u16 = 8;
+
+/// A single file or directory in a zip archive, as listed in the central directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZipEntry {
+    /// The name, including the path; names of directories end with a `/`
+    pub name: String,
+    /// The compression method, `STORED` and `DEFLATED` are supported
+    pub method: u16,
+    /// The size of the compressed data
+    pub compressed_size: u64,
+    /// The size of the uncompressed data
+    pub size: u64,
+    /// The CRC-32 of the uncompressed data
+    pub crc32: u32,
+    flags: u16,
+    offset: u64,
+}
+
+impl ZipEntry {
+    /// Returns true if this entry is a directory
+    pub fn is_dir(&self) -> bool {
+        self.name.ends_with('/')
+    }
+}
+
+/// A zip archive, which has been read into memory.
+///
+/// Only the central directory is parsed when creating it,
+/// entries are decompressed as they are read.
+/// Archives larger than 4 GiB or with more than 65535 entries (zip64) are supported,
+/// encrypted entries and archives spanning multiple disks are not.
+#[derive(Debug)]
+pub struct ZipArchive {
+    bytes: Vec<u8>,
+    entries: Vec<ZipEntry>,
+    /// The index of every entry by its name, the first one wins
+    names: HashMap<String, usize>,
+}
+
+impl ZipArchive {
+    /// Reads the central directory of a zip archive.
+    ///
+    /// Data prepended to the archive (like the header of a jmod file) is skipped,
+    /// offsets are taken relative to the actual start of the central directory.
+    pub fn new(bytes: Vec<u8>) -> Result<ZipArchive> {
+        let end = find_end(&bytes)?;
+        let mut count = u64::from(read_u16(&bytes, end + 10)?);
+        let mut size = u64::from(read_u32(&bytes, end + 12)?);
+        let mut offset = u64::from(read_u32(&bytes, end + 16)?);
+        // the central directory is directly followed by this position
+        let mut directory_end = end;
+
+        if count == 0xFFFF || size == 0xFFFF_FFFF || offset == 0xFFFF_FFFF {
+            // the zip64 locator directly precedes the end of central directory record,
+            // which is usually preceded by the zip64 end of central directory record
+            if end >= 20 && read_u32(&bytes, end - 20)? == ZIP64_LOCATOR_SIGNATURE {
+                let record = if end >= 20 + ZIP64_END_SIZE
+                    && read_u32(&bytes, end - 20 - ZIP64_END_SIZE)? == ZIP64_END_SIGNATURE
+                {
+                    end - 20 - ZIP64_END_SIZE
+                } else {
+                    to_usize(read_u64(&bytes, end - 20 + 8)?)?
+                };
+                if read_u32(&bytes, record)? != ZIP64_END_SIGNATURE {
+                    return Err(Error::InvalidArchive("invalid zip64 end of central directory"));
+                }
+                count = read_u64(&bytes, record + 32)?;
+                size = read_u64(&bytes, record + 40)?;
+                offset = read_u64(&bytes, record + 48)?;
+                directory_end = record;
+            }
+        }
+
+        let size = to_usize(size)?;
+        let prefix = directory_end
+            .checked_sub(size)
+            .and_then(|start| start.checked_sub(to_usize(offset).ok()?))
+            .ok_or(Error::InvalidArchive("invalid offset of central directory"))?;
+
+        let directory = slice(&bytes, directory_end - size, size)?;
+        let mut entries = Vec::new();
+        let mut position = 0;
+        for _ in 0..count {
+            let mut entry = read_central_header(directory, &mut position)?;
+            entry.offset += prefix as u64;
+            entries.push(entry);
+        }
+
+        let mut names = HashMap::with_capacity(entries.len());
+        for (i, entry) in entries.iter().enumerate() {
+            names.entry(entry.name.clone()).or_insert(i);
+        }
+
+        Ok(ZipArchive {
+            bytes,
+            entries,
+            names,
+        })
+    }
+
+    /// Returns every entry in the order of the central directory
+    pub fn entries(&self) -> &[ZipEntry] {
+        &self.entries
+    }
+
+    /// Finds an entry by its name
+    pub fn by_name(&self, name: &str) -> Option<&ZipEntry> {
+        self.names.get(name).map(|&i| &self.entries[i])
+    }
+
+    /// Decompresses an entry and checks its size and CRC-32
+    pub fn read(&self, entry: &ZipEntry) -> Result<Vec<u8>> {
+        if entry.flags & 1 != 0 {
+            return Err(Error::InvalidArchive("encrypted entries are not supported"));
+        }
+
+        let offset = to_usize(entry.offset)?;
+        if read_u32(&self.bytes, offset)? != LOCAL_HEADER_SIGNATURE {
+            return Err(Error::InvalidArchive("invalid local header"));
+        }
+        let name_length = read_u16(&self.bytes, offset + 26)? as usize;
+        let extra_length = read_u16(&self.bytes, offset + 28)? as usize;
+        let data = slice(
+            &self.bytes,
+            offset + 30 + name_length + extra_length,
+            to_usize(entry.compressed_size)?,
+        )?;
+
+        let content = match entry.method {
+            STORED => data.to_vec(),
+            DEFLATED => inflate(data, to_usize(entry.size)?)?,
+            method => return Err(Error::UnsupportedCompression(method)),
+        };
+
+        if content.len() as u64 != entry.size {
+            return Err(Error::InvalidArchive("size of entry does not match"));
+        }
+        if crc32(&content) != entry.crc32 {
+            return Err(Error::InvalidArchive("checksum of entry does not match"));
+        }
+        Ok(content)
+    }
+
+    /// Finds an entry by its name and decompresses it,
+    /// `None` is returned if there is no such entry
+    pub fn read_by_name(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        match self.by_name(name) {
+            Some(entry) => self.read(entry).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Finds the end of central directory record, which is followed by a comment of up to 65535 bytes
+fn find_end(bytes: &[u8]) -> Result<usize> {
+    if bytes.len() < END_SIZE {
+        return Err(Error::InvalidArchive("not a zip archive"));
+    }
+
+    let last = bytes.len() - END_SIZE;
+    let first = last.saturating_sub(0xFFFF);
+    (first..=last)
+        .rev()
+        .find(|&i| LittleEndian::read_u32(&bytes[i..]) == END_SIGNATURE)
+        .ok_or(Error::InvalidArchive("not a zip archive"))
+}
+
+/// Reads a file header of the central directory
+fn read_central_header(directory: &[u8], position: &mut usize) -> Result<ZipEntry> {
+    let start = *position;
+    if read_u32(directory, start)? != CENTRAL_HEADER_SIGNATURE {
+        return Err(Error::InvalidArchive("invalid central directory header"));
+    }
+
+    let flags = read_u16(directory, start + 8)?;
+    let method = read_u16(directory, start + 10)?;
+    let crc32 = read_u32(directory, start + 16)?;
+    let mut compressed_size = u64::from(read_u32(directory, start + 20)?);
+    let mut size = u64::from(read_u32(directory, start + 24)?);
+    let name_length = read_u16(directory, start + 28)? as usize;
+    let extra_length = read_u16(directory, start + 30)? as usize;
+    let comment_length = read_u16(directory, start + 32)? as usize;
+    let mut offset = u64::from(read_u32(directory, start + 42)?);
+
+    let name = slice(directory, start + 46, name_length)?;
+    let name = String::from_utf8_lossy(name).into_owned();
+
+    // only the values, which do not fit into 32 bits, are in the zip64 extra field
+    let mut extra = slice(directory, start + 46 + name_length, extra_length)?;
+    while extra.len() >= 4 {
+        let id = LittleEndian::read_u16(extra);
+        let length = LittleEndian::read_u16(&extra[2..]) as usize;
+        let data = slice(extra, 4, length)?;
+        if id == ZIP64_EXTRA {
+            let mut values = data.chunks(8).filter(|value| value.len() == 8);
+            for value in &mut [&mut size, &mut compressed_size, &mut offset] {
+                if **value == 0xFFFF_FFFF {
+                    **value = LittleEndian::read_u64(values
+                        .next()
+                        .ok_or(Error::InvalidArchive("invalid zip64 extra field"))?);
+                }
+            }
+        }
+        extra = &extra[4 + length..];
+    }
+
+    *position = start + 46 + name_length + extra_length + comment_length;
+    Ok(ZipEntry {
+        name,
+        method,
+        compressed_size,
+        size,
+        crc32,
+        flags,
+        offset,
+    })
+}
+
+/// Returns `length` bytes starting at `start` or an error if the archive is too short
+fn slice(bytes: &[u8], start: usize, length: usize) -> Result<&[u8]> {
+    start
+        .checked_add(length)
+        .and_then(|end| bytes.get(start..end))
+        .ok_or(Error::InvalidArchive("unexpected end of archive"))
+}
+
+fn read_u16(bytes: &[u8], start: usize) -> Result<u16> {
+    slice(bytes, start, 2).map(LittleEndian::read_u16)
+}
+
+fn read_u32(bytes: &[u8], start: usize) -> Result<u32> {
+    slice(bytes, start, 4).map(LittleEndian::read_u32)
+}
+
+fn read_u64(bytes: &[u8], start: usize) -> Result<u64> {
+    slice(bytes, start, 8).map(LittleEndian::read_u64)
+}
+
+/// Converts a size or offset, which might not fit into memory
+fn to_usize(value: u64) -> Result<usize> {
+    usize::try_from(value).map_err(|_| Error::InvalidArchive("archive too large"))
+}
+
+/// The lookup table of the CRC-32 used by zip (polynomial 0xEDB88320)
+const CRC_TABLE: [u32; 256] = crc_table();
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// Computes the CRC-32 of some bytes
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc = CRC_TABLE[((crc ^ u32::from(byte)) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+/// Creates a zip archive, whose entries are stored, to build test fixtures by hand
+#[cfg(test)]
+pub fn stored_zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+    use byteorder::WriteBytesExt;
+
+    let mut bytes = Vec::new();
+    let mut directory = Vec::new();
+    for &(name, data) in entries {
+        let offset = bytes.len() as u32;
+
+        // the fields shared by the local and the central header:
+        // version needed, flags, method, time, date, crc, sizes, name and extra length
+        let mut common = Vec::new();
+        for &value in &[20, 0, STORED, 0, 0] {
+            common.write_u16::<LittleEndian>(value).unwrap();
+        }
+        common.write_u32::<LittleEndian>(crc32(data)).unwrap();
+        common.write_u32::<LittleEndian>(data.len() as u32).unwrap();
+        common.write_u32::<LittleEndian>(data.len() as u32).unwrap();
+        common.write_u16::<LittleEndian>(name.len() as u16).unwrap();
+        common.write_u16::<LittleEndian>(0).unwrap();
+
+        bytes.write_u32::<LittleEndian>(LOCAL_HEADER_SIGNATURE).unwrap();
+        bytes.extend_from_slice(&common);
+        bytes.extend_from_slice(name.as_bytes());
+        bytes.extend_from_slice(data);
+
+        directory.write_u32::<LittleEndian>(CENTRAL_HEADER_SIGNATURE).unwrap();
+        directory.write_u16::<LittleEndian>(20).unwrap();
+        directory.extend_from_slice(&common);
+        // comment length, disk, internal and external attributes
+        directory.extend_from_slice(&[0; 10]);
+        directory.write_u32::<LittleEndian>(offset).unwrap();
+        directory.extend_from_slice(name.as_bytes());
+    }
+
+    let offset = bytes.len() as u32;
+    bytes.extend_from_slice(&directory);
+    bytes.write_u32::<LittleEndian>(END_SIGNATURE).unwrap();
+    bytes.write_u32::<LittleEndian>(0).unwrap();
+    bytes.write_u16::<LittleEndian>(entries.len() as u16).unwrap();
+    bytes.write_u16::<LittleEndian>(entries.len() as u16).unwrap();
+    bytes.write_u32::<LittleEndian>(directory.len() as u32).unwrap();
+    bytes.write_u32::<LittleEndian>(offset).unwrap();
+    bytes.write_u16::<LittleEndian>(0).unwrap();
+    bytes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn entries() {
+        let archive =
+            ZipArchive::new(include_bytes!("../../examples/resources/Classes.jar").to_vec())
+                .unwrap();
+        let names: Vec<&str> = archive
+            .entries()
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "META-INF/",
+                "META-INF/MANIFEST.MF",
+                "Fibonacci.class",
+                "Sealed.class",
+                "Sealed$Leaf.class",
+                "Sealed$Node.class",
+                "data/hello.txt",
+            ]
+        );
+        assert!(archive.entries()[0].is_dir());
+        assert_eq!(archive.read(&archive.entries()[0]).unwrap(), Vec::<u8>::new());
+
+        // deflated with dynamic codes
+        let entry = archive.by_name("Fibonacci.class").unwrap();
+        assert_eq!(entry.method, DEFLATED);
+        assert_eq!(
+            archive.read(entry).unwrap(),
+            &include_bytes!("../../examples/resources/Fibonacci.class")[..]
+        );
+
+        // stored
+        let entry = archive.by_name("Sealed$Node.class").unwrap();
+        assert_eq!(entry.method, STORED);
+        assert_eq!(
+            archive.read(entry).unwrap(),
+            &include_bytes!("../../examples/resources/Sealed$Node.class")[..]
+        );
+
+        assert_eq!(
+            archive.read_by_name("data/hello.txt").unwrap().unwrap(),
+            b"hello, jar\n"
+        );
+        assert!(archive.read_by_name("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn corrupt() {
+        assert!(ZipArchive::new(b"PK".to_vec()).is_err());
+
+        // flip a byte of the stored data, so the checksum does not match
+        let mut bytes = include_bytes!("../../examples/resources/Classes.jar").to_vec();
+        let position = bytes
+            .windows(11)
+            .position(|window| window == b"hello, jar\n")
+            .unwrap();
+        bytes[position] = b'H';
+        let archive = ZipArchive::new(bytes).unwrap();
+        assert!(archive.read_by_name("data/hello.txt").is_err());
+        assert!(archive.read_by_name("Fibonacci.class").is_ok());
+    }
+
+    #[test]
+    fn stored() {
+        let archive = ZipArchive::new(stored_zip(&[("a/", b""), ("a/b.txt", b"b")])).unwrap();
+        assert!(archive.by_name("a/").unwrap().is_dir());
+        assert_eq!(archive.read_by_name("a/b.txt").unwrap().unwrap(), b"b");
+    }
+
+    #[test]
+    fn prefixed() {
+        let mut bytes = b"prefix".to_vec();
+        bytes.extend(stored_zip(&[("a.txt", b"a")]));
+        let archive = ZipArchive::new(bytes).unwrap();
+        assert_eq!(archive.read_by_name("a.txt").unwrap().unwrap(), b"a");
+    }
+
+    #[test]
+    fn declared_size() {
+        use byteorder::WriteBytesExt;
+
+        // an empty block compressed with fixed codes,
+        // whose size is declared as 2^62 in the zip64 extra field
+        let data = [0x03, 0x00];
+        let mut common = Vec::new();
+        for &value in &[45, 0, DEFLATED, 0, 0] {
+            common.write_u16::<LittleEndian>(value).unwrap();
+        }
+        common.write_u32::<LittleEndian>(crc32(b"")).unwrap();
+        common.write_u32::<LittleEndian>(data.len() as u32).unwrap();
+        common.write_u32::<LittleEndian>(0xFFFF_FFFF).unwrap();
+        common.write_u16::<LittleEndian>(1).unwrap();
+        common.write_u16::<LittleEndian>(12).unwrap();
+        let mut extra = Vec::new();
+        extra.write_u16::<LittleEndian>(ZIP64_EXTRA).unwrap();
+        extra.write_u16::<LittleEndian>(8).unwrap();
+        extra.write_u64::<LittleEndian>(1 << 62).unwrap();
+
+        let mut bytes = Vec::new();
+        bytes.write_u32::<LittleEndian>(LOCAL_HEADER_SIGNATURE).unwrap();
+        bytes.extend_from_slice(&common);
+        bytes.extend_from_slice(b"a");
+        bytes.extend_from_slice(&extra);
+        bytes.extend_from_slice(&data);
+
+        let offset = bytes.len() as u32;
+        bytes.write_u32::<LittleEndian>(CENTRAL_HEADER_SIGNATURE).unwrap();
+        bytes.write_u16::<LittleEndian>(45).unwrap();
+        bytes.extend_from_slice(&common);
+        bytes.extend_from_slice(&[0; 10]);
+        bytes.write_u32::<LittleEndian>(0).unwrap();
+        bytes.extend_from_slice(b"a");
+        bytes.extend_from_slice(&extra);
+        let size = bytes.len() as u32 - offset;
+
+        bytes.write_u32::<LittleEndian>(END_SIGNATURE).unwrap();
+        bytes.write_u32::<LittleEndian>(0).unwrap();
+        bytes.write_u16::<LittleEndian>(1).unwrap();
+        bytes.write_u16::<LittleEndian>(1).unwrap();
+        bytes.write_u32::<LittleEndian>(size).unwrap();
+        bytes.write_u32::<LittleEndian>(offset).unwrap();
+        bytes.write_u16::<LittleEndian>(0).unwrap();
+
+        let archive = ZipArchive::new(bytes).unwrap();
+        assert_eq!(archive.by_name("a").unwrap().size, 1 << 62);
+        assert!(archive.read_by_name("a").is_err());
+    }
+
+    #[test]
+    fn checksum() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+}
@@ -0,0 +1,1186 @@
+use std::collections::{BTreeSet, HashMap};
+
+use types::*;
+use super::*;
+use super::super::OBJECT;
+
+const THROWABLE: &str = "java/lang/Throwable";
+
+/// Resolves information about classes, which is required when computing frames.
+/// All names are internal names (e.g. `java/lang/String`).
+pub trait ClassResolver {
+    /// Returns the super class of a class, or `None` if it is `java/lang/Object`.
+    /// The super class of an interface is `java/lang/Object`.
+    fn super_class(&self, name: &str) -> Result<Option<String>>;
+
+    /// Returns true if the class is an interface.
+    fn is_interface(&self, name: &str) -> Result<bool>;
+
+    /// Returns the most specific common super class of two classes.
+    /// Just like the verifier does, interfaces are treated like `java/lang/Object`.
+    fn common_super_class(&self, a: &str, b: &str) -> Result<String> {
+        if a == b {
+            return Ok(a.to_owned());
+        }
+        if self.is_interface(a)? || self.is_interface(b)? {
+            return Ok(OBJECT.to_owned());
+        }
+
+        // collect all super classes of `a`, the checks protect against circular hierarchies
+        let mut supers = Vec::new();
+        let mut current = Some(a.to_owned());
+        while let Some(name) = current {
+            if supers.contains(&name) {
+                break;
+            }
+            current = self.super_class(&name)?;
+            supers.push(name);
+        }
+
+        // the first super class of `b`, which is a super class of `a` as well
+        let mut visited = Vec::new();
+        let mut current = Some(b.to_owned());
+        while let Some(name) = current {
+            if supers.contains(&name) {
+                return Ok(name);
+            }
+            if visited.contains(&name) {
+                break;
+            }
+            current = self.super_class(&name)?;
+            visited.push(name);
+        }
+
+        Ok(OBJECT.to_owned())
+    }
+}
+
+/// Computes the frames of the `StackMapTable` of a method by inferring the type
+/// of every local variable and every value on the operand stack.
+/// Frames are computed for every branch target, exception handler
+/// and every instruction following an unconditional branch.
+/// Every frame is compressed relative to the previous one.
+///
+/// Classes referenced by `VerificationType::Object(_)` are pushed to the constant pool.
+/// `this_class` is the index to the `Item::Class(_)` of the class declaring the method.
+/// If the method has no code, no frames are returned.
+///
+/// An error is returned, if an index to the constant pool is invalid,
+/// if the code contains unreachable instructions, subroutines (`jsr` and `ret`)
+/// or if the operand stack is inconsistent.
+pub fn compute_frames<R: ClassResolver>(
+    constant_pool: &mut Pool,
+    resolver: &R,
+    this_class: u16,
+    method: &Method,
+) -> Result<Vec<StackMapFrame>> {
+    let (instructions, exceptions) = match method.attributes.iter().find_map(|attribute| {
+        match *attribute {
+            Attribute::Code {
+                ref instructions,
+                ref exceptions,
+                ..
+            } => Some((instructions, exceptions)),
+            _ => None,
+        }
+    }) {
+        Some(code) => code,
+        None => return Ok(Vec::new()),
+    };
+
+    let this_name = constant_pool.get_class_name(this_class)?;
+    let initial = initial_frame(constant_pool, &this_name, method)?;
+
+    let mut analyzer = Analyzer {
+        interpreter: Interpreter {
+            constant_pool,
+            this_name: &this_name,
+        },
+        resolver,
+        instructions,
+        frames: HashMap::new(),
+        queue: Vec::new(),
+    };
+    if let Some((start, _)) = instructions.first() {
+        analyzer.merge(start, &initial)?;
+    }
+    analyzer.run(exceptions)?;
+
+    // every instruction must have been reached, otherwise no frame could be computed
+    for (at, _) in instructions {
+        if !analyzer.frames.contains_key(&at) {
+            return Err(Error::UnreachableCode(at));
+        }
+    }
+
+    // collect all offsets requiring a frame
+    let mut offsets = BTreeSet::new();
+    for exception in exceptions {
+        offsets.insert(u32::from(exception.handler));
+    }
+    for (at, insn) in instructions {
+        offsets.extend(branch_targets(instructions, at, insn)?);
+        if !falls_through(insn) {
+            offsets.extend(instructions.next(at).map(|(next, _)| next));
+        }
+    }
+
+    let frames = analyzer.frames;
+    let mut table = Vec::with_capacity(offsets.len());
+    let mut previous_locals = initial.compressed_locals();
+    let mut previous_offset = None;
+    for offset in offsets {
+        let frame = &frames[&offset];
+        let locals = frame.compressed_locals();
+        let stack = compress(&frame.stack);
+
+        let offset_delta = match previous_offset {
+            Some(previous) => offset - previous - 1,
+            None => offset,
+        } as u16;
+        previous_offset = Some(offset);
+
+        table.push(if stack.is_empty() && locals == previous_locals {
+            StackMapFrame::Same {
+                offset_delta,
+                extended: false,
+            }
+        } else if stack.len() == 1 && locals == previous_locals {
+            StackMapFrame::Same1 {
+                offset_delta,
+                stack: stack[0].to_verification_type(constant_pool)?,
+                extended: false,
+            }
+        } else if stack.is_empty() && locals.len() < previous_locals.len()
+            && previous_locals.len() - locals.len() <= 3
+            && previous_locals.starts_with(&locals)
+        {
+            StackMapFrame::Chop {
+                offset_delta,
+                count: (previous_locals.len() - locals.len()) as u8,
+            }
+        } else if stack.is_empty() && locals.len() > previous_locals.len()
+            && locals.len() - previous_locals.len() <= 3
+            && locals.starts_with(&previous_locals)
+        {
+            StackMapFrame::Append {
+                offset_delta,
+                locals: to_verification_types(constant_pool, &locals[previous_locals.len()..])?,
+            }
+        } else {
+            StackMapFrame::Full {
+                offset_delta,
+                locals: to_verification_types(constant_pool, &locals)?,
+                stack: to_verification_types(constant_pool, &stack)?,
+            }
+        });
+
+        previous_locals = locals;
+    }
+
+    Ok(table)
+}
+
+/// Computes the frames of a method using `compute_frames`
+/// and replaces the `StackMapTable` of its code.
+/// If no frames are required, the `StackMapTable` is removed.
+pub fn update_frames<R: ClassResolver>(
+    constant_pool: &mut Pool,
+    resolver: &R,
+    this_class: u16,
+    method: &mut Method,
+) -> Result<()> {
+    let frames = compute_frames(constant_pool, resolver, this_class, method)?;
+    if !frames.is_empty() {
+        constant_pool.push(Item::UTF8("StackMapTable".to_owned()))?;
+    }
+
+    for attribute in &mut method.attributes {
+        if let Attribute::Code {
+            ref mut attributes,
+            ..
+        } = *attribute
+        {
+            attributes.retain(|attribute| !matches!(*attribute, Attribute::StackMapTable(_)));
+            if !frames.is_empty() {
+                attributes.push(Attribute::StackMapTable(frames));
+            }
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Inserts a frame in front of every instruction at `offsets`,
+/// which has no frame in the `StackMapTable` of the method yet.
+/// The instructions in front of such an offset up to the previous frame
+/// (or the start of the method) are executed in sequence,
+/// so they must not be reached by a branch,
+/// like the negated jump and the `goto_w` a far conditional jump is replaced by.
+/// Every frame inserted and every frame following it are written in their full form.
+/// If the code has no `StackMapTable`, no frames are inserted.
+///
+/// An error is returned, if an index to the constant pool is invalid,
+/// if a frame is malformed or if the operand stack underflows.
+pub(crate) fn insert_frames(
+    constant_pool: &mut Pool,
+    this_class: u16,
+    method: &mut Method,
+    offsets: &[u32],
+) -> Result<()> {
+    let this_name = constant_pool.get_class_name(this_class)?;
+    let initial = initial_frame(constant_pool, &this_name, method)?;
+
+    let (instructions, table) = match method.attributes.iter_mut().find_map(|attribute| {
+        match *attribute {
+            Attribute::Code {
+                ref instructions,
+                ref mut attributes,
+                ..
+            } => Some((instructions, attributes)),
+            _ => None,
+        }
+    }) {
+        Some((instructions, attributes)) => match attributes.iter_mut().find_map(|attribute| {
+            match *attribute {
+                Attribute::StackMapTable(ref mut table) => Some(table),
+                _ => None,
+            }
+        }) {
+            Some(table) => (instructions, table),
+            None => return Ok(()),
+        },
+        None => return Ok(()),
+    };
+
+    // the frame at every offset, which is none for inserted frames
+    let mut frames = expand_frames(constant_pool, &initial, table)?
+        .into_iter()
+        .zip(table.drain(..).map(Some))
+        .collect::<Vec<_>>();
+
+    let interpreter = Interpreter {
+        constant_pool,
+        this_name: &this_name,
+    };
+    let mut offsets = offsets.to_vec();
+    offsets.sort_unstable();
+    for offset in offsets {
+        let index = frames
+            .iter()
+            .position(|&((at, _), _)| at >= offset)
+            .unwrap_or(frames.len());
+        if frames.get(index).is_some_and(|&((at, _), _)| at == offset) {
+            continue;
+        }
+
+        let (start, mut frame) = match index.checked_sub(1) {
+            Some(previous) => frames[previous].0.clone(),
+            None => (0, initial.clone()),
+        };
+        for (at, insn) in instructions.range(start..offset) {
+            interpreter.execute(at, insn, &mut frame)?;
+        }
+        frames.insert(index, ((offset, frame), None));
+    }
+
+    let mut previous_offset = None;
+    let mut previous_inserted = false;
+    for ((offset, frame), original) in frames {
+        let offset_delta = match previous_offset {
+            Some(previous) => offset - previous - 1,
+            None => offset,
+        } as u16;
+        previous_offset = Some(offset);
+
+        let inserted = original.is_none();
+        table.push(match original {
+            Some(original) if !previous_inserted => original,
+            _ => StackMapFrame::Full {
+                offset_delta,
+                locals: to_verification_types(constant_pool, &frame.compressed_locals())?,
+                stack: to_verification_types(constant_pool, &compress(&frame.stack))?,
+            },
+        });
+        previous_inserted = inserted;
+    }
+
+    Ok(())
+}
+
+/// Returns the frame implicitly present at the start of a method.
+fn initial_frame(constant_pool: &Pool, this_name: &str, method: &Method) -> Result<Frame> {
+    let name = constant_pool.get_utf8(method.name)?;
+    let desc: MethodDescriptor = constant_pool.get_utf8(method.desc)?.parse()?;
+
+    let mut initial = Frame {
+        locals: Vec::new(),
+        stack: Vec::new(),
+    };
+    if !method.access_flags.contains(MethodAccessFlags::STATIC) {
+        if name == "<init>" && this_name != OBJECT {
+            initial.locals.push(Value::UninitializedThis);
+        } else {
+            initial.locals.push(Value::Object(this_name.to_owned()));
+        }
+    }
+    for param in &desc.params {
+        initial.store(initial.locals.len() as u16, Value::from_desc(param));
+    }
+    Ok(initial)
+}
+
+/// Returns the offset and the frame of every entry of a `StackMapTable`,
+/// where `initial` is the frame at the start of the method.
+fn expand_frames(
+    constant_pool: &Pool,
+    initial: &Frame,
+    table: &[StackMapFrame],
+) -> Result<Vec<(u32, Frame)>> {
+    let mut frames = Vec::with_capacity(table.len());
+    let mut locals = initial.compressed_locals();
+    let mut previous_offset = None;
+    for entry in table {
+        let offset_delta = match *entry {
+            StackMapFrame::Same { offset_delta, .. }
+            | StackMapFrame::Same1 { offset_delta, .. }
+            | StackMapFrame::Chop { offset_delta, .. }
+            | StackMapFrame::Append { offset_delta, .. }
+            | StackMapFrame::Full { offset_delta, .. } => u32::from(offset_delta),
+        };
+        let offset = match previous_offset {
+            Some(previous) => previous + offset_delta + 1,
+            None => offset_delta,
+        };
+        previous_offset = Some(offset);
+
+        let stack = match *entry {
+            StackMapFrame::Same { .. } => Vec::new(),
+            StackMapFrame::Same1 { ref stack, .. } => {
+                vec![Value::from_verification_type(constant_pool, stack)?]
+            }
+            StackMapFrame::Chop { count, .. } => {
+                let len = locals
+                    .len()
+                    .checked_sub(count as usize)
+                    .ok_or(Error::InvalidStack(offset))?;
+                locals.truncate(len);
+                Vec::new()
+            }
+            StackMapFrame::Append {
+                locals: ref appended,
+                ..
+            } => {
+                locals.extend(from_verification_types(constant_pool, appended)?);
+                Vec::new()
+            }
+            StackMapFrame::Full {
+                locals: ref full,
+                ref stack,
+                ..
+            } => {
+                locals = from_verification_types(constant_pool, full)?;
+                from_verification_types(constant_pool, stack)?
+            }
+        };
+
+        let mut frame = Frame {
+            locals: Vec::new(),
+            stack: Vec::new(),
+        };
+        for value in &locals {
+            frame.store(frame.locals.len() as u16, value.clone());
+        }
+        for value in stack {
+            frame.push(value);
+        }
+        frames.push((offset, frame));
+    }
+    Ok(frames)
+}
+
+/// The type of a single slot of a local variable or of the operand stack.
+/// Longs and doubles take up two slots, where the second one is `Top`.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Top,
+    Integer,
+    Float,
+    Long,
+    Double,
+    Null,
+    UninitializedThis,
+    /// Created by the `new` instruction located at that offset.
+    Uninitialized(u32),
+    /// The internal name of a class or the descriptor of an array.
+    Object(String),
+}
+
+impl Value {
+    fn from_desc(desc: &TypeDescriptor) -> Value {
+        if desc.dimensions > 0 {
+            return Value::Object(desc.to_string());
+        }
+
+        match desc.base_type {
+            Type::Boolean | Type::Byte | Type::Short | Type::Char | Type::Int => Value::Integer,
+            Type::Long => Value::Long,
+            Type::Float => Value::Float,
+            Type::Double => Value::Double,
+            Type::Reference(ref name) => Value::Object(name.clone()),
+        }
+    }
+
+    /// Returns true if the value takes up two slots.
+    fn is_wide(&self) -> bool {
+        matches!(*self, Value::Long | Value::Double)
+    }
+
+    fn from_verification_type(
+        constant_pool: &Pool,
+        verification_type: &VerificationType,
+    ) -> Result<Value> {
+        Ok(match *verification_type {
+            VerificationType::Top => Value::Top,
+            VerificationType::Integer => Value::Integer,
+            VerificationType::Float => Value::Float,
+            VerificationType::Long => Value::Long,
+            VerificationType::Double => Value::Double,
+            VerificationType::Null => Value::Null,
+            VerificationType::UninitializedThis => Value::UninitializedThis,
+            VerificationType::Uninitialized(at) => Value::Uninitialized(u32::from(at)),
+            VerificationType::Object(index) => Value::Object(constant_pool.get_class_name(index)?),
+        })
+    }
+
+    fn to_verification_type(&self, constant_pool: &mut Pool) -> Result<VerificationType> {
+        Ok(match *self {
+            Value::Top => VerificationType::Top,
+            Value::Integer => VerificationType::Integer,
+            Value::Float => VerificationType::Float,
+            Value::Long => VerificationType::Long,
+            Value::Double => VerificationType::Double,
+            Value::Null => VerificationType::Null,
+            Value::UninitializedThis => VerificationType::UninitializedThis,
+            Value::Uninitialized(at) => VerificationType::Uninitialized(at as u16),
+            Value::Object(ref name) => {
+                let name_index = constant_pool.push(Item::UTF8(name.clone()))?;
+                VerificationType::Object(constant_pool.push(Item::Class(name_index))?)
+            }
+        })
+    }
+}
+
+/// Removes the second slot of every long and double.
+fn compress(values: &[Value]) -> Vec<Value> {
+    let mut compressed = Vec::with_capacity(values.len());
+    let mut iter = values.iter();
+    while let Some(value) = iter.next() {
+        if value.is_wide() {
+            iter.next();
+        }
+        compressed.push(value.clone());
+    }
+    compressed
+}
+
+fn to_verification_types(
+    constant_pool: &mut Pool,
+    values: &[Value],
+) -> Result<Vec<VerificationType>> {
+    let mut types = Vec::with_capacity(values.len());
+    for value in values {
+        types.push(value.to_verification_type(constant_pool)?);
+    }
+    Ok(types)
+}
+
+fn from_verification_types(
+    constant_pool: &Pool,
+    types: &[VerificationType],
+) -> Result<Vec<Value>> {
+    let mut values = Vec::with_capacity(types.len());
+    for verification_type in types {
+        values.push(Value::from_verification_type(constant_pool, verification_type)?);
+    }
+    Ok(values)
+}
+
+/// The types of the local variables and the operand stack in front of an instruction.
+#[derive(Debug, Clone, PartialEq)]
+struct Frame {
+    locals: Vec<Value>,
+    stack: Vec<Value>,
+}
+
+impl Frame {
+    /// Returns the local variables as they are written in a frame,
+    /// trailing `Top`s are removed.
+    fn compressed_locals(&self) -> Vec<Value> {
+        let mut end = self.locals.len();
+        while end > 0 && self.locals[end - 1] == Value::Top {
+            // the second slot of a long or double must be kept
+            if end >= 2 && self.locals[end - 2].is_wide() {
+                break;
+            }
+            end -= 1;
+        }
+        compress(&self.locals[..end])
+    }
+
+    fn push(&mut self, value: Value) {
+        let wide = value.is_wide();
+        self.stack.push(value);
+        if wide {
+            self.stack.push(Value::Top);
+        }
+    }
+
+    fn pop(&mut self, at: u32) -> Result<Value> {
+        self.stack.pop().ok_or(Error::InvalidStack(at))
+    }
+
+    fn pop_n(&mut self, at: u32, count: usize) -> Result<()> {
+        if count > self.stack.len() {
+            return Err(Error::InvalidStack(at));
+        }
+        let len = self.stack.len() - count;
+        self.stack.truncate(len);
+        Ok(())
+    }
+
+    /// Pops a value of a specific size and pushes another one
+    fn replace(&mut self, at: u32, count: usize, value: Value) -> Result<()> {
+        self.pop_n(at, count)?;
+        self.push(value);
+        Ok(())
+    }
+
+    fn load(&self, at: u32, index: u16) -> Result<Value> {
+        self.locals
+            .get(index as usize)
+            .cloned()
+            .ok_or(Error::InvalidStack(at))
+    }
+
+    fn store(&mut self, index: u16, value: Value) {
+        let index = index as usize;
+        let wide = value.is_wide();
+        let end = index + if wide { 2 } else { 1 };
+        if self.locals.len() < end {
+            self.locals.resize(end, Value::Top);
+        }
+
+        // overwriting the second slot of a long or double invalidates the first one
+        if index > 0 && self.locals[index - 1].is_wide() {
+            self.locals[index - 1] = Value::Top;
+        }
+        self.locals[index] = value;
+        if wide {
+            self.locals[index + 1] = Value::Top;
+        }
+    }
+
+    /// Replaces every occurrence of an uninitialized value after a constructor was called.
+    fn initialize(&mut self, uninitialized: &Value, initialized: &Value) {
+        for value in self.locals.iter_mut().chain(self.stack.iter_mut()) {
+            if value == uninitialized {
+                *value = initialized.clone();
+            }
+        }
+    }
+}
+
+struct Analyzer<'a, R: 'a + ClassResolver> {
+    interpreter: Interpreter<'a>,
+    resolver: &'a R,
+    instructions: &'a Instructions,
+    /// The frame in front of every instruction visited so far
+    frames: HashMap<u32, Frame>,
+    /// Instructions, whose frame has changed
+    queue: Vec<u32>,
+}
+
+impl<'a, R: ClassResolver> Analyzer<'a, R> {
+    /// Follows every path through the code until no frame changes anymore.
+    fn run(&mut self, exceptions: &[Exception]) -> Result<()> {
+        while let Some(at) = self.queue.pop() {
+            let insn = self.instructions.get(at).ok_or(Error::InvalidOffset(at))?;
+            let mut frame = self.frames[&at].clone();
+
+            // the locals in front of every instruction covered by a handler
+            // must be compatible with the locals of the handler
+            for exception in exceptions {
+                if u32::from(exception.start) <= at && at < u32::from(exception.end) {
+                    let catch_type = if exception.catch_type == 0 {
+                        THROWABLE.to_owned()
+                    } else {
+                        self.interpreter
+                            .constant_pool
+                            .get_class_name(exception.catch_type)?
+                    };
+                    let handler = Frame {
+                        locals: frame.locals.clone(),
+                        stack: vec![Value::Object(catch_type)],
+                    };
+
+                    let handler_at = u32::from(exception.handler);
+                    if self.instructions.get(handler_at).is_none() {
+                        return Err(Error::InvalidOffset(handler_at));
+                    }
+                    self.merge(handler_at, &handler)?;
+                }
+            }
+
+            self.interpreter.execute(at, insn, &mut frame)?;
+            for successor in successors(self.instructions, at, insn)? {
+                self.merge(successor, &frame)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Merges a frame into the frame in front of an instruction.
+    /// If the frame changes, the instruction is visited again.
+    fn merge(&mut self, at: u32, frame: &Frame) -> Result<()> {
+        let merged = match self.frames.get(&at) {
+            Some(existing) => {
+                if existing.stack.len() != frame.stack.len() {
+                    return Err(Error::InvalidStack(at));
+                }
+
+                let mut stack = Vec::with_capacity(frame.stack.len());
+                for (a, b) in existing.stack.iter().zip(&frame.stack) {
+                    let value = self.merge_value(a, b)?;
+                    // the stack must not contain incompatible values
+                    if value == Value::Top && (*a != Value::Top || *b != Value::Top) {
+                        return Err(Error::InvalidStack(at));
+                    }
+                    stack.push(value);
+                }
+
+                let len = existing.locals.len().min(frame.locals.len());
+                let mut locals = Vec::with_capacity(len);
+                for (a, b) in existing.locals.iter().zip(&frame.locals) {
+                    locals.push(self.merge_value(a, b)?);
+                }
+
+                let merged = Frame { locals, stack };
+                if merged == *existing {
+                    return Ok(());
+                }
+                merged
+            }
+            None => frame.clone(),
+        };
+
+        self.frames.insert(at, merged);
+        self.queue.push(at);
+        Ok(())
+    }
+
+    /// Returns the most specific type both values are assignable to.
+    fn merge_value(&self, a: &Value, b: &Value) -> Result<Value> {
+        if a == b {
+            return Ok(a.clone());
+        }
+
+        Ok(match (a, b) {
+            (&Value::Null, &Value::Object(_)) => b.clone(),
+            (&Value::Object(_), &Value::Null) => a.clone(),
+            (Value::Object(a), Value::Object(b)) => Value::Object(self.merge_references(a, b)?),
+            _ => Value::Top,
+        })
+    }
+
+    /// Returns the common super type of two classes or arrays.
+    fn merge_references(&self, a: &str, b: &str) -> Result<String> {
+        if a == b {
+            return Ok(a.to_owned());
+        }
+
+        let a_array = a.starts_with('[');
+        let b_array = b.starts_with('[');
+        if a_array && b_array {
+            // arrays of references are covariant
+            let (a, b) = (&a[1..], &b[1..]);
+            if is_reference_desc(a) && is_reference_desc(b) {
+                let component = self.merge_references(&internal_name(a), &internal_name(b))?;
+                return Ok(format!("[{}", reference_desc(&component)));
+            }
+            Ok(OBJECT.to_owned())
+        } else if a_array || b_array {
+            Ok(OBJECT.to_owned())
+        } else {
+            self.resolver.common_super_class(a, b)
+        }
+    }
+
+}
+
+/// Executes single instructions, without following any branch.
+struct Interpreter<'a> {
+    constant_pool: &'a Pool,
+    this_name: &'a str,
+}
+
+impl<'a> Interpreter<'a> {
+    /// Computes the frame after executing an instruction.
+    fn execute(&self, at: u32, insn: &Instruction, frame: &mut Frame) -> Result<()> {
+        use self::Instruction::*;
+
+        match *insn {
+            NOP | IInc(..) | GoTo(_) | GoToW(_) | Return | BreakPoint | ImpDep1 | ImpDep2 => {}
+
+            AConstNull => frame.push(Value::Null),
+            IConstM1 | IConst0 | IConst1 | IConst2 | IConst3 | IConst4 | IConst5 | BIPush(_)
+            | SIPush(_) => frame.push(Value::Integer),
+            LConst0 | LConst1 => frame.push(Value::Long),
+            FConst0 | FConst1 | FConst2 => frame.push(Value::Float),
+            DConst0 | DConst1 => frame.push(Value::Double),
+            LDC(index) | LDCW(index) | LDC2W(index) => {
+                frame.push(self.constant_type(index)?);
+            }
+
+            ILoad(_) | ILoad0 | ILoad1 | ILoad2 | ILoad3 => frame.push(Value::Integer),
+            LLoad(_) | LLoad0 | LLoad1 | LLoad2 | LLoad3 => frame.push(Value::Long),
+            FLoad(_) | FLoad0 | FLoad1 | FLoad2 | FLoad3 => frame.push(Value::Float),
+            DLoad(_) | DLoad0 | DLoad1 | DLoad2 | DLoad3 => frame.push(Value::Double),
+            ALoad(index) => {
+                let value = frame.load(at, index)?;
+                frame.push(value);
+            }
+            ALoad0 | ALoad1 | ALoad2 | ALoad3 => {
+                let index = match *insn {
+                    ALoad0 => 0,
+                    ALoad1 => 1,
+                    ALoad2 => 2,
+                    _ => 3,
+                };
+                let value = frame.load(at, index)?;
+                frame.push(value);
+            }
+
+            IALoad | BALoad | CALoad | SALoad => frame.replace(at, 2, Value::Integer)?,
+            LALoad => frame.replace(at, 2, Value::Long)?,
+            FALoad => frame.replace(at, 2, Value::Float)?,
+            DALoad => frame.replace(at, 2, Value::Double)?,
+            AALoad => {
+                frame.pop(at)?;
+                let component = match frame.pop(at)? {
+                    Value::Object(ref array) if array.starts_with('[') => {
+                        Value::Object(internal_name(&array[1..]))
+                    }
+                    _ => Value::Null,
+                };
+                frame.push(component);
+            }
+
+            IStore(index) => self.store(at, frame, index, 1)?,
+            LStore(index) | DStore(index) => self.store(at, frame, index, 2)?,
+            FStore(index) | AStore(index) => self.store(at, frame, index, 1)?,
+            IStore0 | FStore0 | AStore0 => self.store(at, frame, 0, 1)?,
+            IStore1 | FStore1 | AStore1 => self.store(at, frame, 1, 1)?,
+            IStore2 | FStore2 | AStore2 => self.store(at, frame, 2, 1)?,
+            IStore3 | FStore3 | AStore3 => self.store(at, frame, 3, 1)?,
+            LStore0 | DStore0 => self.store(at, frame, 0, 2)?,
+            LStore1 | DStore1 => self.store(at, frame, 1, 2)?,
+            LStore2 | DStore2 => self.store(at, frame, 2, 2)?,
+            LStore3 | DStore3 => self.store(at, frame, 3, 2)?,
+
+            IAStore | FAStore | AAStore | BAStore | CAStore | SAStore => frame.pop_n(at, 3)?,
+            LAStore | DAStore => frame.pop_n(at, 4)?,
+
+            Pop => frame.pop_n(at, 1)?,
+            Pop2 => frame.pop_n(at, 2)?,
+            Dup => {
+                let v1 = frame.pop(at)?;
+                frame.stack.push(v1.clone());
+                frame.stack.push(v1);
+            }
+            DupX1 => {
+                let v1 = frame.pop(at)?;
+                let v2 = frame.pop(at)?;
+                frame.stack.extend(vec![v1.clone(), v2, v1]);
+            }
+            DupX2 => {
+                let v1 = frame.pop(at)?;
+                let v2 = frame.pop(at)?;
+                let v3 = frame.pop(at)?;
+                frame.stack.extend(vec![v1.clone(), v3, v2, v1]);
+            }
+            Dup2 => {
+                let v1 = frame.pop(at)?;
+                let v2 = frame.pop(at)?;
+                frame.stack.extend(vec![v2.clone(), v1.clone(), v2, v1]);
+            }
+            Dup2X1 => {
+                let v1 = frame.pop(at)?;
+                let v2 = frame.pop(at)?;
+                let v3 = frame.pop(at)?;
+                frame.stack.extend(vec![v2.clone(), v1.clone(), v3, v2, v1]);
+            }
+            Dup2X2 => {
+                let v1 = frame.pop(at)?;
+                let v2 = frame.pop(at)?;
+                let v3 = frame.pop(at)?;
+                let v4 = frame.pop(at)?;
+                frame.stack.extend(vec![v2.clone(), v1.clone(), v4, v3, v2, v1]);
+            }
+            Swap => {
+                let v1 = frame.pop(at)?;
+                let v2 = frame.pop(at)?;
+                frame.stack.push(v1);
+                frame.stack.push(v2);
+            }
+
+            IAdd | ISub | IMul | IDiv | IRem | IShL | IShR | IUShR | IAnd | IOr | IXOr
+            | FCmpL | FCmpG => frame.replace(at, 2, Value::Integer)?,
+            LAdd | LSub | LMul | LDiv | LRem | LAnd | LOr | LXOr => {
+                frame.replace(at, 4, Value::Long)?
+            }
+            FAdd | FSub | FMul | FDiv | FRem => frame.replace(at, 2, Value::Float)?,
+            DAdd | DSub | DMul | DDiv | DRem => frame.replace(at, 4, Value::Double)?,
+            LShL | LShR | LUShR => frame.replace(at, 3, Value::Long)?,
+            LCmp | DCmpL | DCmpG => frame.replace(at, 4, Value::Integer)?,
+
+            INeg | F2I | I2B | I2C | I2S => frame.replace(at, 1, Value::Integer)?,
+            FNeg | I2F => frame.replace(at, 1, Value::Float)?,
+            LNeg | D2L => frame.replace(at, 2, Value::Long)?,
+            DNeg | L2D => frame.replace(at, 2, Value::Double)?,
+            I2L | F2L => frame.replace(at, 1, Value::Long)?,
+            I2D | F2D => frame.replace(at, 1, Value::Double)?,
+            L2I | D2I => frame.replace(at, 2, Value::Integer)?,
+            L2F | D2F => frame.replace(at, 2, Value::Float)?,
+
+            IfEq(_) | IfNE(_) | IfLT(_) | IfGE(_) | IfGT(_) | IfLE(_) | IfNull(_)
+            | IfNonNull(_) | TableSwitch { .. } | LookupSwitch { .. } | IReturn | FReturn
+            | AReturn | AThrow | MonitorEnter | MonitorExit => frame.pop_n(at, 1)?,
+            IfICmpEq(_) | IfICmpNE(_) | IfICmpLT(_) | IfICmpGE(_) | IfICmpGT(_) | IfICmpLE(_)
+            | IfACmpEq(_) | IfACmpNE(_) | LReturn | DReturn => frame.pop_n(at, 2)?,
+
+            JSR(_) => return Err(Error::InvalidInstruction { op_code: 0xA8, at }),
+            Ret(_) => return Err(Error::InvalidInstruction { op_code: 0xA9, at }),
+            JSRW(_) => return Err(Error::InvalidInstruction { op_code: 0xC9, at }),
+
+            GetStatic(index) => {
+                let desc: TypeDescriptor = member_descriptor(self.constant_pool, index)?.parse()?;
+                frame.push(Value::from_desc(&desc));
+            }
+            PutStatic(index) => {
+                let desc: TypeDescriptor = member_descriptor(self.constant_pool, index)?.parse()?;
+                frame.pop_n(at, desc.size() as usize)?;
+            }
+            GetField(index) => {
+                let desc: TypeDescriptor = member_descriptor(self.constant_pool, index)?.parse()?;
+                frame.replace(at, 1, Value::from_desc(&desc))?;
+            }
+            PutField(index) => {
+                let desc: TypeDescriptor = member_descriptor(self.constant_pool, index)?.parse()?;
+                frame.pop_n(at, desc.size() as usize + 1)?;
+            }
+
+            InvokeVirtual(index)
+            | InvokeSpecial(index)
+            | InvokeStatic(index)
+            | InvokeInterface(index, ..)
+            | InvokeDynamic(index, _) => {
+                let (name, desc) = member_name_and_type(self.constant_pool, index)?;
+                let desc: MethodDescriptor = desc.parse()?;
+
+                let params: usize = desc.params.iter().map(|param| param.size() as usize).sum();
+                frame.pop_n(at, params)?;
+
+                match *insn {
+                    InvokeStatic(_) | InvokeDynamic(..) => {}
+                    InvokeSpecial(_) if name == "<init>" => {
+                        let receiver = frame.pop(at)?;
+                        let initialized = match receiver {
+                            Value::UninitializedThis => Value::Object(self.this_name.to_owned()),
+                            Value::Uninitialized(_) => Value::Object(self.owner(index)?),
+                            _ => return Err(Error::InvalidStack(at)),
+                        };
+                        frame.initialize(&receiver, &initialized);
+                    }
+                    _ => frame.pop_n(at, 1)?,
+                }
+
+                if let Some(ref ret) = desc.return_type {
+                    frame.push(Value::from_desc(ret));
+                }
+            }
+
+            New(_) => frame.push(Value::Uninitialized(at)),
+            NewArray(ref array_type) => {
+                let desc = match *array_type {
+                    ArrayType::Boolean => "[Z",
+                    ArrayType::Char => "[C",
+                    ArrayType::Float => "[F",
+                    ArrayType::Double => "[D",
+                    ArrayType::Byte => "[B",
+                    ArrayType::Short => "[S",
+                    ArrayType::Int => "[I",
+                    ArrayType::Long => "[J",
+                };
+                frame.replace(at, 1, Value::Object(desc.to_owned()))?;
+            }
+            ANewArray(index) => {
+                let component = self.constant_pool.get_class_name(index)?;
+                let desc = format!("[{}", reference_desc(&component));
+                frame.replace(at, 1, Value::Object(desc))?;
+            }
+            MultiANewArray(index, dimensions) => {
+                let desc = self.constant_pool.get_class_name(index)?;
+                frame.replace(at, dimensions as usize, Value::Object(desc))?;
+            }
+            ArrayLength | InstanceOf(_) => frame.replace(at, 1, Value::Integer)?,
+            CheckCast(index) => {
+                let name = self.constant_pool.get_class_name(index)?;
+                frame.replace(at, 1, Value::Object(name))?;
+            }
+
+            Wide(ref insn) => return self.execute(at, insn, frame),
+        }
+
+        Ok(())
+    }
+
+    /// Pops a value of a specific size and stores it in a local variable.
+    fn store(&self, at: u32, frame: &mut Frame, index: u16, size: usize) -> Result<()> {
+        if size == 2 {
+            frame.pop(at)?;
+        }
+        let value = frame.pop(at)?;
+        frame.store(index, value);
+        Ok(())
+    }
+
+    /// Returns the type of a constant loaded by `ldc`.
+    fn constant_type(&self, index: u16) -> Result<Value> {
+        Ok(match *self.constant_pool.get(index)? {
+            Item::Integer(_) => Value::Integer,
+            Item::Float(_) => Value::Float,
+            Item::Long(_) => Value::Long,
+            Item::Double(_) => Value::Double,
+            Item::String(_) => Value::Object("java/lang/String".to_owned()),
+            Item::Class(_) => Value::Object("java/lang/Class".to_owned()),
+            Item::MethodType(_) => Value::Object("java/lang/invoke/MethodType".to_owned()),
+            Item::MethodHandle { .. } => {
+                Value::Object("java/lang/invoke/MethodHandle".to_owned())
+            }
+            Item::Dynamic { .. } => {
+                let desc: TypeDescriptor = member_descriptor(self.constant_pool, index)?.parse()?;
+                Value::from_desc(&desc)
+            }
+            _ => return Err(Error::InvalidCPItem(index)),
+        })
+    }
+
+    /// Returns the name of the class declaring the method an instruction refers to.
+    fn owner(&self, index: u16) -> Result<String> {
+        match *self.constant_pool.get(index)? {
+            Item::MethodRef { class, .. } | Item::InterfaceMethodRef { class, .. } => {
+                self.constant_pool.get_class_name(class)
+            }
+            _ => Err(Error::InvalidCPItem(index)),
+        }
+    }
+}
+
+/// Returns true if a field descriptor describes a class or an array.
+fn is_reference_desc(desc: &str) -> bool {
+    desc.starts_with('L') || desc.starts_with('[')
+}
+
+/// Converts a field descriptor of a class or an array
+/// to the name used by `Item::Class(_)`.
+fn internal_name(desc: &str) -> String {
+    if desc.starts_with('L') && desc.ends_with(';') {
+        desc[1..desc.len() - 1].to_owned()
+    } else {
+        desc.to_owned()
+    }
+}
+
+/// Converts a name used by `Item::Class(_)` to a field descriptor.
+fn reference_desc(name: &str) -> String {
+    if name.starts_with('[') {
+        name.to_owned()
+    } else {
+        format!("L{};", name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use basic::{parse, write};
+
+    /// Knows every class to be a direct subclass of `java/lang/Object`,
+    /// except for a few ones of the standard library.
+    struct TestResolver;
+
+    impl ClassResolver for TestResolver {
+        fn super_class(&self, name: &str) -> Result<Option<String>> {
+            Ok(match name {
+                OBJECT => None,
+                "java/lang/Integer" | "java/lang/Long" => Some("java/lang/Number".to_owned()),
+                "java/lang/RuntimeException" => Some("java/lang/Exception".to_owned()),
+                "java/lang/IllegalStateException" | "java/lang/IllegalArgumentException" => {
+                    Some("java/lang/RuntimeException".to_owned())
+                }
+                "java/lang/Exception" => Some(THROWABLE.to_owned()),
+                _ => Some(OBJECT.to_owned()),
+            })
+        }
+
+        fn is_interface(&self, name: &str) -> Result<bool> {
+            Ok(name == "java/lang/Runnable")
+        }
+    }
+
+    /// Returns the offsets of all frames
+    fn frame_offsets(frames: &[StackMapFrame]) -> Vec<u32> {
+        let mut offsets = Vec::new();
+        for frame in frames {
+            let offset_delta = match *frame {
+                StackMapFrame::Same { offset_delta, .. }
+                | StackMapFrame::Same1 { offset_delta, .. }
+                | StackMapFrame::Chop { offset_delta, .. }
+                | StackMapFrame::Append { offset_delta, .. }
+                | StackMapFrame::Full { offset_delta, .. } => u32::from(offset_delta),
+            };
+            let offset = match offsets.last() {
+                Some(previous) => previous + offset_delta + 1,
+                None => offset_delta,
+            };
+            offsets.push(offset);
+        }
+        offsets
+    }
+
+    /// Computes the frames of every method,
+    /// frames must be located at the same offsets as the ones javac computed.
+    fn compare(bytes: &[u8]) {
+        fn offsets(method: &Method) -> Vec<u32> {
+            for attribute in &method.attributes {
+                if let Attribute::Code { ref attributes, .. } = *attribute {
+                    for attribute in attributes {
+                        if let Attribute::StackMapTable(ref frames) = *attribute {
+                            return frame_offsets(frames);
+                        }
+                    }
+                }
+            }
+            Vec::new()
+        }
+
+        let (mut pool, mut class) = parse(bytes).unwrap();
+        for method in &mut class.methods {
+            let expected = offsets(method);
+            update_frames(&mut pool, &TestResolver, class.name, method).unwrap();
+            assert_eq!(offsets(method), expected);
+        }
+
+        // the class can still be written
+        write(&pool, &class).unwrap();
+    }
+
+    #[test]
+    fn compute_resources() {
+        compare(include_bytes!("../../../examples/resources/AnyAnnotation.class"));
+        compare(include_bytes!("../../../examples/resources/CustomLinkedList.class"));
+        compare(include_bytes!("../../../examples/resources/CustomLinkedList$Element.class"));
+        compare(include_bytes!("../../../examples/resources/CustomLinkedList$Iter.class"));
+        compare(include_bytes!("../../../examples/resources/Fibonacci.class"));
+        compare(include_bytes!("../../../examples/resources/Frames.class"));
+        compare(include_bytes!("../../../examples/resources/FizzBuzzStream.class"));
+        compare(include_bytes!("../../../examples/resources/Nestmates.class"));
+        compare(include_bytes!("../../../examples/resources/Nestmates$Inner.class"));
+        compare(include_bytes!("../../../examples/resources/Records.class"));
+        compare(include_bytes!("../../../examples/resources/Records$Component.class"));
+        compare(include_bytes!("../../../examples/resources/Sealed.class"));
+        compare(include_bytes!("../../../examples/resources/Sealed$Leaf.class"));
+        compare(include_bytes!("../../../examples/resources/Sealed$Node.class"));
+        compare(include_bytes!("../../../examples/resources/RoundTrip.class"));
+    }
+
+    /// Creates a static method `test(Z)Ljava/lang/Object;` in a class `Test`
+    fn method(pool: &mut Pool, instructions: Instructions) -> (u16, Method) {
+        let name = pool.push(Item::UTF8("Test".to_owned())).unwrap();
+        let this_class = pool.push(Item::Class(name)).unwrap();
+        let method = Method {
+            access_flags: MethodAccessFlags::STATIC,
+            name: pool.push(Item::UTF8("test".to_owned())).unwrap(),
+            desc: pool
+                .push(Item::UTF8("(Z)Ljava/lang/Object;".to_owned()))
+                .unwrap(),
+            attributes: vec![
+                Attribute::Code {
+                    max_stack: 1,
+                    max_locals: 1,
+                    instructions,
+                    exceptions: Vec::new(),
+                    attributes: Vec::new(),
+                },
+            ],
+        };
+        (this_class, method)
+    }
+
+    #[test]
+    fn merge_types() {
+        let mut pool = Pool::new();
+        let integer_name = pool.push(Item::UTF8("java/lang/Integer".to_owned())).unwrap();
+        let integer = pool.push(Item::Class(integer_name)).unwrap();
+        let long_name = pool.push(Item::UTF8("java/lang/Long".to_owned())).unwrap();
+        let long = pool.push(Item::Class(long_name)).unwrap();
+
+        // return b ? (Integer) null : (Long) null;
+        let mut instructions = Instructions::new();
+        instructions.insert(0, Instruction::ILoad0);
+        instructions.insert(1, Instruction::IfEq(10));
+        instructions.insert(4, Instruction::AConstNull);
+        instructions.insert(5, Instruction::CheckCast(integer));
+        instructions.insert(8, Instruction::GoTo(7));
+        instructions.insert(11, Instruction::AConstNull);
+        instructions.insert(12, Instruction::CheckCast(long));
+        instructions.insert(15, Instruction::AReturn);
+
+        let (this_class, method) = method(&mut pool, instructions);
+        let frames = compute_frames(&mut pool, &TestResolver, this_class, &method).unwrap();
+        assert_eq!(frames.len(), 2);
+        match frames[0] {
+            StackMapFrame::Same {
+                offset_delta: 11,
+                extended: false,
+            } => {}
+            ref frame => panic!("unexpected frame: {:?}", frame),
+        }
+        match frames[1] {
+            StackMapFrame::Same1 {
+                offset_delta: 3,
+                stack: VerificationType::Object(index),
+                extended: false,
+            } => assert_eq!(pool.get_class_name(index).unwrap(), "java/lang/Number"),
+            ref frame => panic!("unexpected frame: {:?}", frame),
+        }
+    }
+
+    #[test]
+    fn unreachable_code() {
+        let mut pool = Pool::new();
+        let mut instructions = Instructions::new();
+        instructions.insert(0, Instruction::AConstNull);
+        instructions.insert(1, Instruction::AReturn);
+        instructions.insert(2, Instruction::NOP);
+
+        let (this_class, method) = method(&mut pool, instructions);
+        match compute_frames(&mut pool, &TestResolver, this_class, &method) {
+            Err(Error::UnreachableCode(2)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,382 @@
+use std::collections::HashMap;
+
+use types::*;
+use super::*;
+
+/// Computes the `max_stack` and `max_locals` of the code of a method.
+///
+/// The parameters of the method (and `this`, if the method is not static)
+/// are included in the local variables.
+/// Only instructions reachable from the start of the code or from an exception handler
+/// are taken into account for the operand stack.
+/// Longs and doubles take up two slots.
+///
+/// An error is returned, if an index to the constant pool is invalid,
+/// if a branch does not point to an instruction
+/// or if the stack underflows or its height is not consistent.
+///
+/// # Examples
+///
+/// ```
+/// use jbcrs::MethodDescriptor;
+/// use jbcrs::basic::{compute_maxs, Instruction, Instructions, Pool};
+///
+/// let mut instructions = Instructions::new();
+/// instructions.insert(0, Instruction::LLoad0);
+/// instructions.insert(1, Instruction::LLoad0);
+/// instructions.insert(2, Instruction::LAdd);
+/// instructions.insert(3, Instruction::LReturn);
+///
+/// let desc: MethodDescriptor = "(J)J".parse().unwrap();
+/// let maxs = compute_maxs(&Pool::new(), &desc, true, &instructions, &[]).unwrap();
+/// assert_eq!(maxs, (4, 2));
+/// ```
+pub fn compute_maxs(
+    constant_pool: &Pool,
+    desc: &MethodDescriptor,
+    is_static: bool,
+    instructions: &Instructions,
+    exceptions: &[Exception],
+) -> Result<(u16, u16)> {
+    let max_stack = compute_max_stack(constant_pool, instructions, exceptions)?;
+
+    let mut max_locals = if is_static { 0 } else { 1 };
+    for param in &desc.params {
+        max_locals += u32::from(param.size());
+    }
+    for (_, insn) in instructions {
+        if let Some(end) = local_end(insn) {
+            max_locals = max_locals.max(end);
+        }
+    }
+    if max_locals > u32::from(u16::MAX) {
+        return Err(Error::TooLarge);
+    }
+
+    Ok((max_stack, max_locals as u16))
+}
+
+/// Computes the `max_stack` and `max_locals` of an `Attribute::Code` and updates them.
+/// See `compute_maxs` for more details.
+pub fn update_maxs(
+    constant_pool: &Pool,
+    desc: &MethodDescriptor,
+    is_static: bool,
+    code: &mut Attribute,
+) -> Result<()> {
+    match *code {
+        Attribute::Code {
+            ref mut max_stack,
+            ref mut max_locals,
+            ref instructions,
+            ref exceptions,
+            ..
+        } => {
+            let (stack, locals) =
+                compute_maxs(constant_pool, desc, is_static, instructions, exceptions)?;
+            *max_stack = stack;
+            *max_locals = locals;
+            Ok(())
+        }
+        _ => Err(Error::UnexpectedAttribute),
+    }
+}
+
+/// Computes the maximum height of the operand stack
+/// by following every path through the code.
+fn compute_max_stack(
+    constant_pool: &Pool,
+    instructions: &Instructions,
+    exceptions: &[Exception],
+) -> Result<u16> {
+    // the height of the stack in front of every instruction visited
+    let mut heights: HashMap<u32, u32> = HashMap::new();
+    let mut queue = Vec::new();
+
+    // pushes an offset to the queue, if it has not been visited yet
+    fn visit(
+        heights: &mut HashMap<u32, u32>,
+        queue: &mut Vec<u32>,
+        at: u32,
+        height: u32,
+    ) -> Result<()> {
+        match heights.get(&at) {
+            Some(&previous) if previous != height => return Err(Error::InvalidStack(at)),
+            Some(_) => return Ok(()),
+            None => {}
+        }
+
+        heights.insert(at, height);
+        queue.push(at);
+        Ok(())
+    }
+
+    if let Some((start, _)) = instructions.first() {
+        visit(&mut heights, &mut queue, start, 0)?;
+    }
+    // the exception is the only value on the stack of a handler
+    for exception in exceptions {
+        let handler = u32::from(exception.handler);
+        if instructions.get(handler).is_none() {
+            return Err(Error::InvalidOffset(handler));
+        }
+        visit(&mut heights, &mut queue, handler, 1)?;
+    }
+
+    let mut max_stack = 0;
+    while let Some(at) = queue.pop() {
+        let insn = instructions.get(at).ok_or(Error::InvalidOffset(at))?;
+        let height = heights[&at];
+
+        let (pop, push) = stack_effect(constant_pool, insn)?;
+        if pop > height {
+            return Err(Error::InvalidStack(at));
+        }
+        let after = height - pop + push;
+        // a handler may pop the exception first, so the height in front counts as well
+        max_stack = max_stack.max(height).max(after);
+
+        let next = instructions.next(at).map(|(next, _)| next);
+        for successor in successors(instructions, at, insn)? {
+            // a subroutine returns without the return address on the stack
+            let height = match *insn {
+                Instruction::JSR(_) | Instruction::JSRW(_) if Some(successor) == next => height,
+                _ => after,
+            };
+            visit(&mut heights, &mut queue, successor, height)?;
+        }
+    }
+
+    if max_stack > u32::from(u16::MAX) {
+        return Err(Error::TooLarge);
+    }
+    Ok(max_stack as u16)
+}
+
+/// Returns the index after the last local variable slot an instruction accesses.
+fn local_end(insn: &Instruction) -> Option<u32> {
+    use self::Instruction::*;
+
+    Some(match *insn {
+        ILoad(index) | FLoad(index) | ALoad(index) | IStore(index) | FStore(index)
+        | AStore(index) | IInc(index, _) | Ret(index) => u32::from(index) + 1,
+        LLoad(index) | DLoad(index) | LStore(index) | DStore(index) => u32::from(index) + 2,
+
+        ILoad0 | FLoad0 | ALoad0 | IStore0 | FStore0 | AStore0 => 1,
+        ILoad1 | FLoad1 | ALoad1 | IStore1 | FStore1 | AStore1 => 2,
+        ILoad2 | FLoad2 | ALoad2 | IStore2 | FStore2 | AStore2 => 3,
+        ILoad3 | FLoad3 | ALoad3 | IStore3 | FStore3 | AStore3 => 4,
+
+        LLoad0 | DLoad0 | LStore0 | DStore0 => 2,
+        LLoad1 | DLoad1 | LStore1 | DStore1 => 3,
+        LLoad2 | DLoad2 | LStore2 | DStore2 => 4,
+        LLoad3 | DLoad3 | LStore3 | DStore3 => 5,
+
+        Wide(ref insn) => return local_end(insn),
+        _ => return None,
+    })
+}
+
+/// Returns the amount of slots an instruction pops from and pushes on the operand stack.
+fn stack_effect(constant_pool: &Pool, insn: &Instruction) -> Result<(u32, u32)> {
+    use self::Instruction::*;
+
+    Ok(match *insn {
+        NOP | IInc(..) | GoTo(_) | GoToW(_) | Ret(_) | Return | BreakPoint | ImpDep1
+        | ImpDep2 => (0, 0),
+
+        AConstNull | IConstM1 | IConst0 | IConst1 | IConst2 | IConst3 | IConst4 | IConst5
+        | FConst0 | FConst1 | FConst2 | BIPush(_) | SIPush(_) | New(_) | JSR(_) | JSRW(_) => {
+            (0, 1)
+        }
+        LConst0 | LConst1 | DConst0 | DConst1 | LDC2W(_) => (0, 2),
+        LDC(index) | LDCW(index) => if constant_pool.get(index)?.is_double() {
+            (0, 2)
+        } else {
+            (0, 1)
+        },
+
+        ILoad(_) | FLoad(_) | ALoad(_) | ILoad0 | ILoad1 | ILoad2 | ILoad3 | FLoad0 | FLoad1
+        | FLoad2 | FLoad3 | ALoad0 | ALoad1 | ALoad2 | ALoad3 => (0, 1),
+        LLoad(_) | DLoad(_) | LLoad0 | LLoad1 | LLoad2 | LLoad3 | DLoad0 | DLoad1 | DLoad2
+        | DLoad3 => (0, 2),
+
+        IALoad | FALoad | AALoad | BALoad | CALoad | SALoad => (2, 1),
+        LALoad | DALoad => (2, 2),
+
+        IStore(_) | FStore(_) | AStore(_) | IStore0 | IStore1 | IStore2 | IStore3 | FStore0
+        | FStore1 | FStore2 | FStore3 | AStore0 | AStore1 | AStore2 | AStore3 => (1, 0),
+        LStore(_) | DStore(_) | LStore0 | LStore1 | LStore2 | LStore3 | DStore0 | DStore1
+        | DStore2 | DStore3 => (2, 0),
+
+        IAStore | FAStore | AAStore | BAStore | CAStore | SAStore => (3, 0),
+        LAStore | DAStore => (4, 0),
+
+        Pop => (1, 0),
+        Pop2 => (2, 0),
+        Dup => (1, 2),
+        DupX1 => (2, 3),
+        DupX2 => (3, 4),
+        Dup2 => (2, 4),
+        Dup2X1 => (3, 5),
+        Dup2X2 => (4, 6),
+        Swap => (2, 2),
+
+        IAdd | FAdd | ISub | FSub | IMul | FMul | IDiv | FDiv | IRem | FRem | IShL | IShR
+        | IUShR | IAnd | IOr | IXOr | FCmpL | FCmpG => (2, 1),
+        LAdd | DAdd | LSub | DSub | LMul | DMul | LDiv | DDiv | LRem | DRem | LAnd | LOr
+        | LXOr => (4, 2),
+        LShL | LShR | LUShR => (3, 2),
+        LCmp | DCmpL | DCmpG => (4, 1),
+
+        INeg | FNeg | I2F | F2I | I2B | I2C | I2S => (1, 1),
+        LNeg | DNeg | L2D | D2L => (2, 2),
+        I2L | I2D | F2L | F2D => (1, 2),
+        L2I | L2F | D2I | D2F => (2, 1),
+
+        IfEq(_) | IfNE(_) | IfLT(_) | IfGE(_) | IfGT(_) | IfLE(_) | IfNull(_) | IfNonNull(_) => {
+            (1, 0)
+        }
+        IfICmpEq(_) | IfICmpNE(_) | IfICmpLT(_) | IfICmpGE(_) | IfICmpGT(_) | IfICmpLE(_)
+        | IfACmpEq(_) | IfACmpNE(_) => (2, 0),
+        TableSwitch { .. } | LookupSwitch { .. } => (1, 0),
+
+        IReturn | FReturn | AReturn | AThrow | MonitorEnter | MonitorExit => (1, 0),
+        LReturn | DReturn => (2, 0),
+
+        GetStatic(index) => (0, field_size(constant_pool, index)?),
+        PutStatic(index) => (field_size(constant_pool, index)?, 0),
+        GetField(index) => (1, field_size(constant_pool, index)?),
+        PutField(index) => (1 + field_size(constant_pool, index)?, 0),
+
+        InvokeVirtual(index) | InvokeSpecial(index) | InvokeInterface(index, ..) => {
+            let (params, ret) = method_sizes(constant_pool, index)?;
+            (params + 1, ret)
+        }
+        InvokeStatic(index) | InvokeDynamic(index, _) => method_sizes(constant_pool, index)?,
+
+        NewArray(_) | ANewArray(_) | ArrayLength | CheckCast(_) | InstanceOf(_) => (1, 1),
+        MultiANewArray(_, dimensions) => (u32::from(dimensions), 1),
+
+        Wide(ref insn) => return stack_effect(constant_pool, insn),
+    })
+}
+
+/// Returns the size of the field an instruction refers to.
+fn field_size(constant_pool: &Pool, index: u16) -> Result<u32> {
+    let desc: TypeDescriptor = member_descriptor(constant_pool, index)?.parse()?;
+    Ok(u32::from(desc.size()))
+}
+
+/// Returns the size of all parameters and the size of the return value
+/// of the method an instruction refers to.
+fn method_sizes(constant_pool: &Pool, index: u16) -> Result<(u32, u32)> {
+    let desc: MethodDescriptor = member_descriptor(constant_pool, index)?.parse()?;
+    let params = desc.params.iter().map(|param| u32::from(param.size())).sum();
+    let ret = desc.return_type.map_or(0, |ret| u32::from(ret.size()));
+    Ok((params, ret))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use basic::parse;
+
+    /// Computes the maximums of every method and compares them to the ones javac computed.
+    fn compare(bytes: &[u8]) {
+        let (pool, class) = parse(bytes).unwrap();
+        for method in &class.methods {
+            let desc: MethodDescriptor = pool.get_utf8(method.desc).unwrap().parse().unwrap();
+            let is_static = method.access_flags.contains(MethodAccessFlags::STATIC);
+
+            for attribute in &method.attributes {
+                if let Attribute::Code {
+                    max_stack,
+                    max_locals,
+                    ref instructions,
+                    ref exceptions,
+                    ..
+                } = *attribute
+                {
+                    let maxs =
+                        compute_maxs(&pool, &desc, is_static, instructions, exceptions).unwrap();
+                    assert_eq!(maxs, (max_stack, max_locals));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn compute_resources() {
+        compare(include_bytes!("../../../examples/resources/AnyAnnotation.class"));
+        compare(include_bytes!("../../../examples/resources/CustomLinkedList.class"));
+        compare(include_bytes!("../../../examples/resources/CustomLinkedList$Element.class"));
+        compare(include_bytes!("../../../examples/resources/CustomLinkedList$Iter.class"));
+        compare(include_bytes!("../../../examples/resources/Fibonacci.class"));
+        compare(include_bytes!("../../../examples/resources/FizzBuzzStream.class"));
+        compare(include_bytes!("../../../examples/resources/Nestmates.class"));
+        compare(include_bytes!("../../../examples/resources/Nestmates$Inner.class"));
+        compare(include_bytes!("../../../examples/resources/Records.class"));
+        compare(include_bytes!("../../../examples/resources/Records$Component.class"));
+        compare(include_bytes!("../../../examples/resources/Sealed.class"));
+        compare(include_bytes!("../../../examples/resources/Sealed$Leaf.class"));
+        compare(include_bytes!("../../../examples/resources/Sealed$Node.class"));
+        compare(include_bytes!("../../../examples/resources/RoundTrip.class"));
+    }
+
+    #[test]
+    fn invalid_stack() {
+        let desc: MethodDescriptor = "()V".parse().unwrap();
+
+        // underflow
+        let mut instructions = Instructions::new();
+        instructions.insert(0, Instruction::Pop);
+        instructions.insert(1, Instruction::Return);
+        match compute_maxs(&Pool::new(), &desc, true, &instructions, &[]) {
+            Err(Error::InvalidStack(0)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // a loop pushing a value every iteration
+        let mut instructions = Instructions::new();
+        instructions.insert(0, Instruction::IConst0);
+        instructions.insert(1, Instruction::GoTo(-1));
+        match compute_maxs(&Pool::new(), &desc, true, &instructions, &[]) {
+            Err(Error::InvalidStack(0)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn handler_entry() {
+        // static void f() { try { g(); } catch (E e) {} }
+        let mut pool = Pool::new();
+        let class = pool.push(Item::UTF8("A".to_owned())).unwrap();
+        let class = pool.push(Item::Class(class)).unwrap();
+        let name = pool.push(Item::UTF8("g".to_owned())).unwrap();
+        let desc = pool.push(Item::UTF8("()V".to_owned())).unwrap();
+        let name_and_type = pool.push(Item::NameAndType { name, desc }).unwrap();
+        let method = pool.push(Item::MethodRef {
+            class,
+            name_and_type,
+        }).unwrap();
+
+        let mut instructions = Instructions::new();
+        instructions.insert(0, Instruction::InvokeStatic(method));
+        instructions.insert(3, Instruction::Return);
+        instructions.insert(4, Instruction::AStore0);
+        instructions.insert(5, Instruction::Return);
+        let exceptions = [
+            Exception {
+                start: 0,
+                end: 3,
+                handler: 4,
+                catch_type: 0,
+            },
+        ];
+
+        let desc: MethodDescriptor = "()V".parse().unwrap();
+        let maxs = compute_maxs(&pool, &desc, true, &instructions, &exceptions).unwrap();
+        assert_eq!(maxs, (1, 1));
+    }
+}
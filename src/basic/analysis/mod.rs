@@ -0,0 +1,111 @@
+//! The analysis module computes information about the code of a method,
+//! which would be tedious to compute by hand.
+
+mod frames;
+mod maxs;
+
+use super::constpool::*;
+use super::tree::*;
+use result::*;
+
+pub use self::frames::*;
+pub use self::maxs::*;
+
+/// Returns the name and the descriptor of the field or method an instruction refers to.
+/// The index must point to a `FieldRef`, `MethodRef`, `InterfaceMethodRef`
+/// or an `InvokeDynamic` item, or of a `Dynamic` item.
+fn member_name_and_type(constant_pool: &Pool, index: u16) -> Result<(String, String)> {
+    let name_and_type = match *constant_pool.get(index)? {
+        Item::FieldRef { name_and_type, .. }
+        | Item::MethodRef { name_and_type, .. }
+        | Item::InterfaceMethodRef { name_and_type, .. }
+        | Item::Dynamic { name_and_type, .. }
+        | Item::InvokeDynamic { name_and_type, .. } => name_and_type,
+        _ => return Err(Error::InvalidCPItem(index)),
+    };
+
+    match *constant_pool.get(name_and_type)? {
+        Item::NameAndType { name, desc } => {
+            Ok((constant_pool.get_utf8(name)?, constant_pool.get_utf8(desc)?))
+        }
+        _ => Err(Error::InvalidCPItem(name_and_type)),
+    }
+}
+
+/// Returns the descriptor of the field or method an instruction refers to.
+fn member_descriptor(constant_pool: &Pool, index: u16) -> Result<String> {
+    Ok(member_name_and_type(constant_pool, index)?.1)
+}
+
+/// Returns the offset of the target of a branch located at `at`.
+/// The target must be the start of an instruction.
+fn branch_target(instructions: &Instructions, at: u32, offset: i32) -> Result<u32> {
+    let target = i64::from(at) + i64::from(offset);
+    if target < 0 || instructions.get(target as u32).is_none() {
+        return Err(Error::InvalidOffset(at));
+    }
+    Ok(target as u32)
+}
+
+/// Returns the offsets of all instructions an instruction may branch to explicitly.
+fn branch_targets(instructions: &Instructions, at: u32, insn: &Instruction) -> Result<Vec<u32>> {
+    use self::Instruction::*;
+
+    Ok(match *insn {
+        IfEq(offset) | IfNE(offset) | IfLT(offset) | IfGE(offset) | IfGT(offset)
+        | IfLE(offset) | IfICmpEq(offset) | IfICmpNE(offset) | IfICmpLT(offset)
+        | IfICmpGE(offset) | IfICmpGT(offset) | IfICmpLE(offset) | IfACmpEq(offset)
+        | IfACmpNE(offset) | IfNull(offset) | IfNonNull(offset) => {
+            vec![branch_target(instructions, at, offset.into())?]
+        }
+        GoTo(offset) | GoToW(offset) | JSR(offset) | JSRW(offset) => {
+            vec![branch_target(instructions, at, offset)?]
+        }
+        TableSwitch {
+            default,
+            ref offsets,
+            ..
+        } => {
+            let mut targets = vec![branch_target(instructions, at, default)?];
+            for offset in offsets {
+                targets.push(branch_target(instructions, at, *offset)?);
+            }
+            targets
+        }
+        LookupSwitch {
+            default,
+            ref offsets,
+            ..
+        } => {
+            let mut targets = vec![branch_target(instructions, at, default)?];
+            for offset in offsets.values() {
+                targets.push(branch_target(instructions, at, *offset)?);
+            }
+            targets
+        }
+        _ => Vec::new(),
+    })
+}
+
+/// Returns true if execution may continue with the next instruction.
+/// A subroutine called by `jsr` returns to the next instruction.
+fn falls_through(insn: &Instruction) -> bool {
+    use self::Instruction::*;
+
+    match *insn {
+        GoTo(_) | GoToW(_) | TableSwitch { .. } | LookupSwitch { .. } | IReturn | LReturn
+        | FReturn | DReturn | AReturn | Return | AThrow | Ret(_) => false,
+        Wide(ref insn) => falls_through(insn),
+        _ => true,
+    }
+}
+
+/// Returns the offsets of all instructions, which may be executed after an instruction,
+/// excluding exception handlers.
+fn successors(instructions: &Instructions, at: u32, insn: &Instruction) -> Result<Vec<u32>> {
+    let mut successors = branch_targets(instructions, at, insn)?;
+    if falls_through(insn) {
+        successors.extend(instructions.next(at).map(|(next, _)| next));
+    }
+    Ok(successors)
+}
@@ -0,0 +1,2472 @@
+//! The assemble module reads a class from text, in the format `disassemble` writes.
+//!
+//! Every line belongs to the nearest line above it, which is indented less.
+//! Comments start with `//` and blank lines are ignored.
+//! Everything, which `disassemble` renders from the constant pool
+//! (declarations and comments), is informational only:
+//! Indices into the constant pool are given as `#n` and flags by their bits,
+//! like `(0x0021) ACC_PUBLIC, ACC_SUPER`, or by their names only, like `ACC_PUBLIC, ACC_SUPER`.
+//!
+//! - The first line is the declaration of the class, the lines below it are
+//!   `minor version: n`, `major version: n`, `flags: ...`, `this_class: #n`,
+//!   `super_class: #n` and `interfaces: #a, #b`.
+//! - `Constant pool:` is followed by the items in order, like `#1 = Utf8 "Foo"`.
+//!   Every item must be at the index given, duplicate items are kept.
+//! - `{` is followed by the fields and methods, each of them is a declaration with
+//!   `kind: field` or `kind: method`, `name: #n`, `descriptor: #n`, `flags: ...`
+//!   and its attributes below it.
+//!   Without a kind a member is a method, if its descriptor is a method descriptor.
+//! - Every other line on the top level is an attribute of the class.
+//!
+//! Attributes are written like `disassemble` renders them,
+//! `Unknown #n:` is followed by the raw bytes in hex.
+//! The names of all other attributes are added to the constant pool,
+//! if they are missing.
+//!
+//! Below `Code:` there is `stack=n, locals=n` and the instructions, one per line.
+//! An instruction may be preceded by labels, like `loop: iload_0` or `14: iload_0`,
+//! which may also stand on their own.
+//! Jumps, switches, exception handlers, line numbers and local variables
+//! either reference a label or an absolute offset.
+//! A number references a label of the same name, if there is one,
+//! so the offsets rendered by `disassemble` keep working, when instructions are changed.
+//! The encoding of an instruction is chosen by its mnemonic,
+//! jumps are not widened automatically.
+//! The padding of a switch is zero, unless it is given in hex like `tableswitch padding 00 01 {`,
+//! the reserved operands of `invokeinterface` and `invokedynamic` may follow their other operands.
+
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+use result::*;
+use super::constpool::*;
+use super::disassemble::{CLASS_FLAGS, EXPORTS_FLAGS, FIELD_FLAGS, INNER_CLASS_FLAGS,
+                         METHOD_FLAGS, MODULE_FLAGS, PARAMETER_FLAGS, REQUIRES_FLAGS};
+use super::tree::*;
+use super::writer::instruction_len;
+
+/// The names of all attributes, which are not `Attribute::Unknown`.
+const ATTRIBUTES: &[&str] = &[
+    "AnnotationDefault",
+    "BootstrapMethods",
+    "Code",
+    "ConstantValue",
+    "Deprecated",
+    "EnclosingMethod",
+    "Exceptions",
+    "InnerClasses",
+    "LineNumberTable",
+    "LocalVariableTable",
+    "LocalVariableTypeTable",
+    "MethodParameters",
+    "Module",
+    "ModuleMainClass",
+    "ModulePackages",
+    "NestHost",
+    "NestMembers",
+    "PermittedSubclasses",
+    "Record",
+    "RuntimeVisibleAnnotations",
+    "RuntimeInvisibleAnnotations",
+    "RuntimeVisibleParameterAnnotations",
+    "RuntimeInvisibleParameterAnnotations",
+    "RuntimeVisibleTypeAnnotations",
+    "RuntimeInvisibleTypeAnnotations",
+    "Signature",
+    "SourceDebugExtension",
+    "SourceFile",
+    "StackMapTable",
+    "Synthetic",
+];
+
+/// The offsets of the labels in the code.
+type Labels = HashMap<String, u32>;
+
+/// Assembles a class from text, in the format `disassemble` writes
+/// (see the module documentation).
+/// The output of `disassemble` can always be assembled again.
+///
+/// # Examples
+///
+/// ```
+/// use jbcrs::basic::{self, Attribute, Instruction};
+///
+/// let (pool, class) = basic::assemble(
+///     r#"
+/// public class Countdown
+///   major version: 49
+///   flags: ACC_PUBLIC, ACC_SUPER
+///   this_class: #2
+///   super_class: #4
+/// Constant pool:
+///   #1 = Utf8 "Countdown"
+///   #2 = Class #1
+///   #3 = Utf8 "java/lang/Object"
+///   #4 = Class #3
+///   #5 = Utf8 "count"
+///   #6 = Utf8 "(I)I"
+/// {
+///   static int count(int);
+///     name: #5
+///     descriptor: #6
+///     flags: ACC_STATIC
+///     Code:
+///       stack=1, locals=1
+///       loop:
+///         iload_0
+///         ifle end
+///         iinc 0, -1
+///         goto loop
+///       end:
+///         iload_0
+///         ireturn
+/// }
+/// "#,
+/// ).unwrap();
+///
+/// match class.methods[0].attributes[0] {
+///     Attribute::Code { ref instructions, .. } => {
+///         assert!(matches!(instructions.get(1), Some(Instruction::IfLE(9))));
+///         assert!(matches!(instructions.get(7), Some(Instruction::GoTo(-7))));
+///     }
+///     _ => panic!("no code"),
+/// }
+///
+/// // the name of the code attribute has been added
+/// let bytes = basic::write(&pool, &class).unwrap();
+/// assert_eq!(basic::parse(&bytes).unwrap().0.len(), 8);
+/// ```
+pub fn assemble(text: &str) -> Result<(Pool, Class)> {
+    let nodes = parse_lines(text);
+    let (declaration, nodes) = match nodes.split_first() {
+        Some(split) => split,
+        None => return Err(error(1, "the declaration of the class is missing".to_owned())),
+    };
+
+    let mut assembler = Assembler {
+        constant_pool: Pool::new(),
+        attribute_names: Vec::new(),
+    };
+    for node in nodes {
+        if node.text == "Constant pool:" {
+            assembler.constant_pool(node)?;
+        }
+    }
+
+    let mut class = assembler.class(declaration)?;
+    for node in nodes {
+        match &node.text[..] {
+            "Constant pool:" | "}" => {}
+            "{" => assembler.members(node, &mut class)?,
+            _ => class.attributes.push(assembler.attribute(node, None)?),
+        }
+    }
+
+    let mut constant_pool = assembler.constant_pool;
+    for name in assembler.attribute_names {
+        constant_pool.push(Item::UTF8(name.to_owned()))?;
+    }
+    Ok((constant_pool, class))
+}
+
+/// Creates an error at a line.
+fn error(line: usize, message: String) -> Error {
+    Error::InvalidAssembly { line, message }
+}
+
+/// A line without its comment and the lines below it, which are indented deeper.
+struct Node {
+    line: usize,
+    text: String,
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn cursor(&self) -> Cursor<'_> {
+        Cursor {
+            text: &self.text,
+            line: self.line,
+        }
+    }
+
+    /// Returns every line below this one in order, regardless of their indentation,
+    /// since rows of tables are aligned to the right.
+    fn rows(&self) -> Vec<&Node> {
+        fn collect<'a>(node: &'a Node, rows: &mut Vec<&'a Node>) {
+            for child in &node.children {
+                rows.push(child);
+                collect(child, rows);
+            }
+        }
+
+        let mut rows = Vec::new();
+        collect(self, &mut rows);
+        rows
+    }
+}
+
+/// Splits the text into lines and nests them by their indentation.
+fn parse_lines(text: &str) -> Vec<Node> {
+    /// Closes every open line, which is indented at least as deep as `indent`.
+    fn close(open: &mut Vec<(usize, Node)>, roots: &mut Vec<Node>, indent: usize) {
+        while let Some(&(depth, _)) = open.last() {
+            if depth < indent {
+                break;
+            }
+            let (_, node) = open.pop().unwrap();
+            match open.last_mut() {
+                Some(&mut (_, ref mut parent)) => parent.children.push(node),
+                None => roots.push(node),
+            }
+        }
+    }
+
+    let mut roots = Vec::new();
+    let mut open = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = strip_comment(line);
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        let indent = line.len() - line.trim_start().len();
+        close(&mut open, &mut roots, indent);
+        open.push((
+            indent,
+            Node {
+                line: i + 1,
+                text: trimmed.to_owned(),
+                children: Vec::new(),
+            },
+        ));
+    }
+    close(&mut open, &mut roots, 0);
+    roots
+}
+
+/// Removes a comment, which does not start inside a string.
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if quoted {
+            match c {
+                '\\' => escaped = true,
+                '"' => quoted = false,
+                _ => {}
+            }
+        } else if c == '"' {
+            quoted = true;
+        } else if line[i..].starts_with("//") {
+            return &line[..i];
+        }
+    }
+    line
+}
+
+/// The target of a jump or an offset into the code.
+enum Target {
+    Label(String),
+    Offset(i64),
+}
+
+/// Resolves a target, a number references a label of the same name, if there is one.
+fn resolve(labels: Option<&Labels>, target: &Target, line: usize) -> Result<i64> {
+    match *target {
+        Target::Label(ref label) => match labels.and_then(|labels| labels.get(label)) {
+            Some(offset) => Ok(i64::from(*offset)),
+            None => Err(error(line, format!("unknown label `{}`", label))),
+        },
+        Target::Offset(offset) => {
+            match labels.and_then(|labels| labels.get(&offset.to_string())) {
+                Some(offset) => Ok(i64::from(*offset)),
+                None => Ok(offset),
+            }
+        }
+    }
+}
+
+/// Reads the tokens of a single line.
+struct Cursor<'a> {
+    text: &'a str,
+    line: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn error(&self, message: String) -> Error {
+        error(self.line, message)
+    }
+
+    /// Creates an error, if something else than `expected` has been found.
+    fn unexpected(&self, expected: &str) -> Error {
+        if self.text.is_empty() {
+            self.error(format!("expected {}, found the end of the line", expected))
+        } else {
+            self.error(format!("expected {}, found `{}`", expected, self.text))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.text = self.text.trim_start();
+    }
+
+    fn is_empty(&mut self) -> bool {
+        self.skip_whitespace();
+        self.text.is_empty()
+    }
+
+    /// Returns an error, if the line does not end here.
+    fn finish(&mut self) -> Result<()> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self.unexpected("the end of the line"))
+        }
+    }
+
+    /// Skips `token` and returns true, if the text continues with it.
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.text.starts_with(token) {
+            self.text = &self.text[token.len()..];
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, token: &str) -> Result<()> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("`{}`", token)))
+        }
+    }
+
+    fn take_while<F: Fn(char) -> bool>(&mut self, predicate: F) -> &'a str {
+        self.skip_whitespace();
+        let end = self
+            .text
+            .find(|c: char| !predicate(c))
+            .unwrap_or(self.text.len());
+        let (taken, rest) = self.text.split_at(end);
+        self.text = rest;
+        taken
+    }
+
+    /// Reads a name, a mnemonic or a label.
+    fn word(&mut self) -> &'a str {
+        self.take_while(|c| c.is_alphanumeric() || c == '_' || c == '$')
+    }
+
+    /// Reads everything up to the next `:` and skips the `:`.
+    fn key(&mut self) -> Result<&'a str> {
+        match self.text.find(':') {
+            Some(i) => {
+                let key = self.text[..i].trim();
+                self.text = &self.text[i + 1..];
+                Ok(key)
+            }
+            None => Err(self.unexpected("`:`")),
+        }
+    }
+
+    /// Reads a label followed by a `:`, if there is one.
+    fn label(&mut self) -> Option<&'a str> {
+        let start = self.text;
+        let label = self.word();
+        if !label.is_empty() && self.eat(":") {
+            Some(label)
+        } else {
+            self.text = start;
+            None
+        }
+    }
+
+    fn number<T: FromStr>(&mut self) -> Result<T> {
+        let start = self.text;
+        let number = self.take_while(|c| c.is_ascii_digit() || c == '-' || c == '+');
+        match number.parse() {
+            Ok(number) => Ok(number),
+            Err(_) => {
+                self.text = start;
+                Err(self.unexpected("a number"))
+            }
+        }
+    }
+
+    /// Reads a hexadecimal number prefixed with `0x`.
+    fn hex(&mut self) -> Result<u64> {
+        self.expect("0x")?;
+        let start = self.text;
+        let digits = self.take_while(|c| c.is_ascii_hexdigit());
+        u64::from_str_radix(digits, 16).map_err(|_| {
+            self.text = start;
+            self.unexpected("a hexadecimal number")
+        })
+    }
+
+    /// Reads an index into the constant pool, like `#5`.
+    fn index(&mut self) -> Result<u16> {
+        if self.eat("#") {
+            self.number()
+        } else {
+            Err(self.unexpected("an index"))
+        }
+    }
+
+    /// Reads indices separated by `,`.
+    fn indices(&mut self) -> Result<Vec<u16>> {
+        let mut indices = vec![self.index()?];
+        while self.eat(",") {
+            indices.push(self.index()?);
+        }
+        Ok(indices)
+    }
+
+    /// Reads flags given by their bits, like `(0x0009)`,
+    /// and their names, like `ACC_PUBLIC, ACC_STATIC`.
+    /// If the bits are given, the names are ignored.
+    fn flags(&mut self, names: &[(u16, &str)]) -> Result<u16> {
+        let bits = if self.eat("(") {
+            let bits = self.hex()?;
+            self.expect(")")?;
+            Some(u16::try_from(bits).map_err(|_| self.error(format!("invalid flags {:#x}", bits)))?)
+        } else {
+            None
+        };
+
+        let mut named = 0;
+        while self.text.trim_start().starts_with("ACC_") {
+            let name = self.word();
+            match names.iter().find(|&&(_, flag_name)| flag_name == name) {
+                Some(&(flag, _)) => named |= flag,
+                None if bits.is_none() => return Err(self.error(format!("unknown flag `{}`", name))),
+                None => {}
+            }
+            self.eat(",");
+        }
+        Ok(bits.unwrap_or(named))
+    }
+
+    /// Reads a string in quotes, with the escapes of rust.
+    fn string(&mut self) -> Result<String> {
+        let units = self.utf16()?;
+        String::from_utf16(&units).map_err(|_| self.error("unpaired surrogate".to_owned()))
+    }
+
+    /// Reads a string like `string`, but returns its UTF-16 code units,
+    /// so unpaired surrogates like `\u{d800}` are allowed.
+    fn utf16(&mut self) -> Result<Vec<u16>> {
+        if !self.eat("\"") {
+            return Err(self.unexpected("a string"));
+        }
+
+        let text = self.text;
+        let mut chars = text.char_indices();
+        let mut units = Vec::new();
+        let mut buffer = [0; 2];
+        while let Some((i, c)) = chars.next() {
+            let c = match c {
+                '"' => {
+                    self.text = &text[i + 1..];
+                    return Ok(units);
+                }
+                '\\' => match chars.next() {
+                    Some((_, 'n')) => '\n',
+                    Some((_, 'r')) => '\r',
+                    Some((_, 't')) => '\t',
+                    Some((_, '0')) => '\0',
+                    Some((_, '\\')) => '\\',
+                    Some((_, '"')) => '"',
+                    Some((_, '\'')) => '\'',
+                    Some((_, 'u')) => {
+                        let mut hex = String::new();
+                        let mut closed = false;
+                        if let Some((_, '{')) = chars.next() {
+                            for (_, c) in chars.by_ref() {
+                                if c == '}' {
+                                    closed = true;
+                                    break;
+                                }
+                                hex.push(c);
+                            }
+                        }
+                        match u32::from_str_radix(&hex, 16) {
+                            // surrogates are kept as they are
+                            Ok(unit @ 0xD800..=0xDFFF) if closed => {
+                                units.push(unit as u16);
+                                continue;
+                            }
+                            Ok(c) if closed => ::std::char::from_u32(c)
+                                .ok_or_else(|| self.error("invalid unicode escape".to_owned()))?,
+                            _ => return Err(self.error("invalid unicode escape".to_owned())),
+                        }
+                    }
+                    _ => return Err(self.error("invalid escape".to_owned())),
+                },
+                c => c,
+            };
+            units.extend_from_slice(c.encode_utf16(&mut buffer));
+        }
+        Err(self.error("unterminated string".to_owned()))
+    }
+
+    /// Reads a float like `1.5f`, `Infinity`, `-Infinity` or `NaN(0x7fc00000)`.
+    fn float(&mut self) -> Result<f32> {
+        if self.eat("NaN(") {
+            let bits = self.hex()?;
+            self.expect(")")?;
+            return u32::try_from(bits)
+                .map(f32::from_bits)
+                .map_err(|_| self.error(format!("invalid float {:#x}", bits)));
+        }
+
+        let start = self.text;
+        match self.take_while(|c| !c.is_whitespace()).trim_end_matches('f') {
+            "Infinity" => Ok(f32::INFINITY),
+            "-Infinity" => Ok(f32::NEG_INFINITY),
+            value => value.parse().map_err(|_| {
+                self.text = start;
+                self.unexpected("a float")
+            }),
+        }
+    }
+
+    /// Reads a double like `1.5d`, `Infinity`, `-Infinity` or `NaN(0x7ff8000000000000)`.
+    fn double(&mut self) -> Result<f64> {
+        if self.eat("NaN(") {
+            let bits = self.hex()?;
+            self.expect(")")?;
+            return Ok(f64::from_bits(bits));
+        }
+
+        let start = self.text;
+        match self.take_while(|c| !c.is_whitespace()).trim_end_matches('d') {
+            "Infinity" => Ok(f64::INFINITY),
+            "-Infinity" => Ok(f64::NEG_INFINITY),
+            value => value.parse().map_err(|_| {
+                self.text = start;
+                self.unexpected("a double")
+            }),
+        }
+    }
+
+    /// Reads a long like `5l`.
+    fn long(&mut self) -> Result<i64> {
+        let start = self.text;
+        self.take_while(|c| !c.is_whitespace())
+            .trim_end_matches('l')
+            .parse()
+            .map_err(|_| {
+                self.text = start;
+                self.unexpected("a long")
+            })
+    }
+
+    /// Reads the target of a jump, either a label or an offset.
+    fn target(&mut self) -> Result<Target> {
+        self.skip_whitespace();
+        if self.text.starts_with(|c: char| c == '-' || c.is_ascii_digit()) {
+            Ok(Target::Offset(self.number()?))
+        } else {
+            match self.word() {
+                "" => Err(self.unexpected("a label or an offset")),
+                label => Ok(Target::Label(label.to_owned())),
+            }
+        }
+    }
+
+    /// Reads an offset into the code, which may be given by a label.
+    fn offset(&mut self, labels: Option<&Labels>) -> Result<u16> {
+        let target = self.target()?;
+        let offset = resolve(labels, &target, self.line)?;
+        u16::try_from(offset).map_err(|_| self.error(format!("invalid offset {}", offset)))
+    }
+
+    /// Reads an annotation like `#5(#6=I#7,#8=s#9)`.
+    fn annotation(&mut self) -> Result<Annotation> {
+        let type_index = self.index()?;
+        self.expect("(")?;
+        let mut element_value_pairs = Vec::new();
+        if !self.eat(")") {
+            loop {
+                let name = self.index()?;
+                self.expect("=")?;
+                element_value_pairs.push((name, self.element_value()?));
+                if self.eat(")") {
+                    break;
+                }
+                self.expect(",")?;
+            }
+        }
+
+        Ok(Annotation {
+            type_index,
+            element_value_pairs,
+        })
+    }
+
+    /// Reads an element value, prefixed by its tag, like `I#7`.
+    fn element_value(&mut self) -> Result<ElementValue> {
+        self.skip_whitespace();
+        let tag = match self.text.chars().next() {
+            Some(tag) => tag,
+            None => return Err(self.unexpected("an element value")),
+        };
+        self.text = &self.text[tag.len_utf8()..];
+
+        Ok(match tag {
+            'B' => ElementValue::Byte(self.index()?),
+            'S' => ElementValue::Short(self.index()?),
+            'C' => ElementValue::Char(self.index()?),
+            'I' => ElementValue::Int(self.index()?),
+            'J' => ElementValue::Long(self.index()?),
+            'F' => ElementValue::Float(self.index()?),
+            'D' => ElementValue::Double(self.index()?),
+            'Z' => ElementValue::Boolean(self.index()?),
+            's' => ElementValue::String(self.index()?),
+            'e' => {
+                let type_name = self.index()?;
+                self.expect(".")?;
+                ElementValue::Enum {
+                    type_name,
+                    const_name: self.index()?,
+                }
+            }
+            'c' => ElementValue::Class(self.index()?),
+            '@' => ElementValue::Annotation(Box::new(self.annotation()?)),
+            '[' => {
+                let mut values = Vec::new();
+                if !self.eat("]") {
+                    loop {
+                        values.push(self.element_value()?);
+                        if self.eat("]") {
+                            break;
+                        }
+                        self.expect(",")?;
+                    }
+                }
+                ElementValue::Array(values)
+            }
+            _ => return Err(self.error(format!("unknown element value tag `{}`", tag))),
+        })
+    }
+
+    /// Reads the target and the path of a type annotation,
+    /// like `METHOD_FORMAL_PARAMETER, param_index=0, location=[ARRAY]`.
+    fn type_target(&mut self) -> Result<(TargetType, Vec<TypePathElement>)> {
+        let kind = self.word();
+        let mut values = Vec::new();
+        let mut variables = Vec::new();
+        let mut path = Vec::new();
+        while self.eat(",") {
+            if self.is_empty() || self.text.starts_with(',') {
+                // an empty list of local variables
+                continue;
+            } else if self.eat("{") {
+                let mut fields = [0; 3];
+                for (i, name) in ["start_pc", "length", "index"].iter().enumerate() {
+                    if i != 0 {
+                        self.expect(",")?;
+                    }
+                    self.expect(name)?;
+                    self.expect("=")?;
+                    fields[i] = self.number()?;
+                }
+                self.expect("}")?;
+                variables.push(LocalVariableTarget {
+                    start: fields[0],
+                    length: fields[1],
+                    index: fields[2],
+                });
+            } else if self.eat("location=[") {
+                if !self.eat("]") {
+                    loop {
+                        path.push(self.type_path_element()?);
+                        if self.eat("]") {
+                            break;
+                        }
+                        self.expect(",")?;
+                    }
+                }
+            } else {
+                let name = self.word();
+                self.expect("=")?;
+                values.push((name, self.number::<u16>()?));
+            }
+        }
+
+        let value = |name: &str| -> Result<u16> {
+            match values.iter().find(|&&(value_name, _)| value_name == name) {
+                Some(&(_, value)) => Ok(value),
+                None => Err(self.error(format!("`{}` of {} is missing", name, kind))),
+            }
+        };
+        let byte = |name: &str| -> Result<u8> {
+            let value = value(name)?;
+            u8::try_from(value).map_err(|_| self.error(format!("{} is out of range", name)))
+        };
+
+        let target_type = match kind {
+            "CLASS_TYPE_PARAMETER" => TargetType::TypeParameterClass(byte("param_index")?),
+            "METHOD_TYPE_PARAMETER" => TargetType::TypeParameterMethod(byte("param_index")?),
+            "CLASS_EXTENDS" => TargetType::SuperType(value("type_index")?),
+            "CLASS_TYPE_PARAMETER_BOUND" => TargetType::TypeParameterBoundClass {
+                type_parameter: byte("param_index")?,
+                bound_index: byte("bound_index")?,
+            },
+            "METHOD_TYPE_PARAMETER_BOUND" => TargetType::TypeParameterBoundMethod {
+                type_parameter: byte("param_index")?,
+                bound_index: byte("bound_index")?,
+            },
+            "FIELD" => TargetType::EmptyField,
+            "METHOD_RETURN" => TargetType::EmptyReturn,
+            "METHOD_RECEIVER" => TargetType::EmptyReceiver,
+            "METHOD_FORMAL_PARAMETER" => TargetType::FormalParameter(byte("param_index")?),
+            "THROWS" => TargetType::Throws(value("type_index")?),
+            "LOCAL_VARIABLE" => TargetType::LocalVariable(variables),
+            "RESOURCE_VARIABLE" => TargetType::ResourceVariable(variables),
+            "EXCEPTION_PARAMETER" => TargetType::Catch(value("exception_index")?),
+            "INSTANCEOF" => TargetType::OffsetInstanceOf(value("offset")?),
+            "NEW" => TargetType::OffsetNew(value("offset")?),
+            "CONSTRUCTOR_REFERENCE" => TargetType::OffsetNewRef(value("offset")?),
+            "METHOD_REFERENCE" => TargetType::OffsetRef(value("offset")?),
+            "CAST" => TargetType::TypeArgumentCast {
+                offset: value("offset")?,
+                type_argument: byte("type_index")?,
+            },
+            "METHOD_INVOCATION_TYPE_ARGUMENT" => TargetType::TypeArgumentMethod {
+                offset: value("offset")?,
+                type_argument: byte("type_index")?,
+            },
+            "CONSTRUCTOR_INVOCATION_TYPE_ARGUMENT" => TargetType::TypeArgumentConstructor {
+                offset: value("offset")?,
+                type_argument: byte("type_index")?,
+            },
+            "CONSTRUCTOR_REFERENCE_TYPE_ARGUMENT" => TargetType::TypeArgumentNewRef {
+                offset: value("offset")?,
+                type_argument: byte("type_index")?,
+            },
+            "METHOD_REFERENCE_TYPE_ARGUMENT" => TargetType::TypeArgumentRef {
+                offset: value("offset")?,
+                type_argument: byte("type_index")?,
+            },
+            _ => return Err(self.error(format!("unknown target type `{}`", kind))),
+        };
+        Ok((target_type, path))
+    }
+
+    /// Reads an element of the path of a type annotation, like `TYPE_ARGUMENT(1)`.
+    fn type_path_element(&mut self) -> Result<TypePathElement> {
+        let path_kind = match self.word() {
+            "ARRAY" => TypePathKind::ArrayType,
+            "INNER_TYPE" => TypePathKind::NestedType,
+            "WILDCARD" => TypePathKind::WildcardType,
+            "TYPE_ARGUMENT" => TypePathKind::Type,
+            kind => return Err(self.error(format!("unknown type path kind `{}`", kind))),
+        };
+        let argument_index = if self.eat("(") {
+            let index = self.number()?;
+            self.expect(")")?;
+            index
+        } else {
+            0
+        };
+
+        Ok(TypePathElement {
+            path_kind,
+            argument_index,
+        })
+    }
+
+    /// Reads the types of the locals or the stack of a frame, like `[ int, class #5 ]`.
+    fn verification_types(&mut self) -> Result<Vec<VerificationType>> {
+        self.expect("[")?;
+        let mut types = Vec::new();
+        if self.eat("]") {
+            return Ok(types);
+        }
+
+        loop {
+            types.push(match self.word() {
+                "top" => VerificationType::Top,
+                "int" => VerificationType::Integer,
+                "float" => VerificationType::Float,
+                "double" => VerificationType::Double,
+                "long" => VerificationType::Long,
+                "null" => VerificationType::Null,
+                "uninitialized_this" => VerificationType::UninitializedThis,
+                "class" => VerificationType::Object(self.index()?),
+                "uninitialized" => VerificationType::Uninitialized(self.number()?),
+                name => return Err(self.error(format!("unknown verification type `{}`", name))),
+            });
+            if self.eat("]") {
+                return Ok(types);
+            }
+            self.expect(",")?;
+        }
+    }
+
+    /// Reads a package exported or opened by a module, like `(0x0000) #5 to #6, #7`.
+    fn module_package(&mut self) -> Result<(u16, ExportsFlags, Vec<u16>)> {
+        let flags = ExportsFlags::from_bits_retain(self.flags(EXPORTS_FLAGS)?);
+        let index = self.index()?;
+        let to = if self.eat("to") {
+            self.indices()?
+        } else {
+            Vec::new()
+        };
+        Ok((index, flags, to))
+    }
+}
+
+/// Reads every row below a line.
+fn rows<T, F>(node: &Node, mut read: F) -> Result<Vec<T>>
+where
+    F: FnMut(&mut Cursor) -> Result<T>,
+{
+    let mut values = Vec::new();
+    for row in node.rows() {
+        let mut cursor = row.cursor();
+        values.push(read(&mut cursor)?);
+        cursor.finish()?;
+    }
+    Ok(values)
+}
+
+/// Returns true, if a line starts an attribute.
+fn is_attribute(text: &str) -> bool {
+    let name = text.split(|c: char| c == ':' || c.is_whitespace()).next();
+    name == Some("Unknown") || ATTRIBUTES.contains(&name.unwrap_or(""))
+}
+
+/// The parts of a field, a method or a record component.
+struct Member<'a> {
+    /// Whether the member is a method or a field, if it is given.
+    kind: Option<bool>,
+    name: u16,
+    desc: u16,
+    /// The flags are read later, since their names depend on the kind of the member.
+    flags: Option<Cursor<'a>>,
+    attributes: Vec<Attribute>,
+}
+
+impl<'a> Member<'a> {
+    /// Reads the flags, which are empty if they are not given.
+    fn access_flags(&mut self, names: &[(u16, &str)]) -> Result<u16> {
+        match self.flags.take() {
+            Some(mut cursor) => {
+                let flags = cursor.flags(names)?;
+                cursor.finish()?;
+                Ok(flags)
+            }
+            None => Ok(0),
+        }
+    }
+}
+
+struct Assembler {
+    constant_pool: Pool,
+    /// The names of all attributes assembled, which are added to the constant pool.
+    attribute_names: Vec<&'static str>,
+}
+
+impl Assembler {
+    fn constant_pool(&mut self, node: &Node) -> Result<()> {
+        for row in node.rows() {
+            let mut cursor = row.cursor();
+            let index = cursor.index()?;
+            if index != self.constant_pool.len() {
+                return Err(cursor.error(format!(
+                    "expected the item at #{}, found #{}",
+                    self.constant_pool.len(),
+                    index
+                )));
+            }
+            cursor.expect("=")?;
+
+            let kind = cursor.word();
+            let item = match kind {
+                "Utf8" => Item::from_utf16(cursor.utf16()?),
+                "Integer" => Item::Integer(cursor.number()?),
+                "Float" => Item::Float(cursor.float()?),
+                "Long" => Item::Long(cursor.long()?),
+                "Double" => Item::Double(cursor.double()?),
+                "Class" => Item::Class(cursor.index()?),
+                "String" => Item::String(cursor.index()?),
+                "Fieldref" | "Methodref" | "InterfaceMethodref" => {
+                    let class = cursor.index()?;
+                    cursor.expect(".")?;
+                    let name_and_type = cursor.index()?;
+                    match kind {
+                        "Fieldref" => Item::FieldRef {
+                            class,
+                            name_and_type,
+                        },
+                        "Methodref" => Item::MethodRef {
+                            class,
+                            name_and_type,
+                        },
+                        _ => Item::InterfaceMethodRef {
+                            class,
+                            name_and_type,
+                        },
+                    }
+                }
+                "NameAndType" => {
+                    let name = cursor.index()?;
+                    cursor.expect(":")?;
+                    Item::NameAndType {
+                        name,
+                        desc: cursor.index()?,
+                    }
+                }
+                "MethodHandle" => {
+                    let kind = match cursor.number()? {
+                        1 => ReferenceKind::GetField,
+                        2 => ReferenceKind::GetStatic,
+                        3 => ReferenceKind::PutField,
+                        4 => ReferenceKind::PutStatic,
+                        5 => ReferenceKind::InvokeVirtual,
+                        6 => ReferenceKind::InvokeStatic,
+                        7 => ReferenceKind::InvokeSpecial,
+                        8 => ReferenceKind::NewInvokeSpecial,
+                        9 => ReferenceKind::InvokeInterface,
+                        kind => return Err(cursor.error(format!("invalid reference kind {}", kind))),
+                    };
+                    cursor.expect(":")?;
+                    Item::MethodHandle {
+                        kind,
+                        index: cursor.index()?,
+                    }
+                }
+                "MethodType" => Item::MethodType(cursor.index()?),
+                "Dynamic" | "InvokeDynamic" => {
+                    let bootstrap_method_attribute = cursor.index()?;
+                    cursor.expect(":")?;
+                    let name_and_type = cursor.index()?;
+                    if kind == "Dynamic" {
+                        Item::Dynamic {
+                            bootstrap_method_attribute,
+                            name_and_type,
+                        }
+                    } else {
+                        Item::InvokeDynamic {
+                            bootstrap_method_attribute,
+                            name_and_type,
+                        }
+                    }
+                }
+                "Module" => Item::Module(cursor.index()?),
+                "Package" => Item::Package(cursor.index()?),
+                kind => return Err(cursor.error(format!("unknown constant `{}`", kind))),
+            };
+            cursor.finish()?;
+            self.constant_pool.push_duplicate(item)?;
+        }
+        Ok(())
+    }
+
+    /// Reads the declaration of the class and the lines below it.
+    fn class(&mut self, node: &Node) -> Result<Class> {
+        let mut minor_version = 0;
+        let mut major_version = None;
+        let mut access_flags = ClassAccessFlags::empty();
+        let mut name = None;
+        let mut super_name = 0;
+        let mut interfaces = Vec::new();
+        for child in &node.children {
+            let mut cursor = child.cursor();
+            match cursor.key()? {
+                "minor version" => minor_version = cursor.number()?,
+                "major version" => major_version = Some(cursor.number()?),
+                "flags" => {
+                    access_flags = ClassAccessFlags::from_bits_retain(cursor.flags(CLASS_FLAGS)?)
+                }
+                "this_class" => name = Some(cursor.index()?),
+                "super_class" => super_name = cursor.index()?,
+                "interfaces" => interfaces = cursor.indices()?,
+                key => return Err(cursor.error(format!("unknown property `{}`", key))),
+            }
+            cursor.finish()?;
+        }
+
+        Ok(Class {
+            minor_version,
+            major_version: major_version
+                .ok_or_else(|| error(node.line, "the major version is missing".to_owned()))?,
+            access_flags,
+            name: name.ok_or_else(|| error(node.line, "this_class is missing".to_owned()))?,
+            super_name,
+            interfaces,
+            fields: Vec::new(),
+            methods: Vec::new(),
+            attributes: Vec::new(),
+        })
+    }
+
+    /// Reads the fields and methods below `{`.
+    fn members(&mut self, node: &Node, class: &mut Class) -> Result<()> {
+        for child in &node.children {
+            let mut member = self.member(child)?;
+            let method = match member.kind {
+                Some(kind) => kind,
+                None => match self.constant_pool.get_utf8(member.desc) {
+                    Ok(desc) => desc.starts_with('('),
+                    // fall back to the declaration, if the descriptor is invalid
+                    Err(_) => child.text.contains('(') || child.text.contains('{'),
+                },
+            };
+
+            if method {
+                let access_flags =
+                    MethodAccessFlags::from_bits_retain(member.access_flags(METHOD_FLAGS)?);
+                class.methods.push(Method {
+                    access_flags,
+                    name: member.name,
+                    desc: member.desc,
+                    attributes: member.attributes,
+                });
+            } else {
+                let access_flags =
+                    FieldAccessFlags::from_bits_retain(member.access_flags(FIELD_FLAGS)?);
+                class.fields.push(Field {
+                    access_flags,
+                    name: member.name,
+                    desc: member.desc,
+                    attributes: member.attributes,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the lines below a member or a record component.
+    fn member<'a>(&mut self, node: &'a Node) -> Result<Member<'a>> {
+        let mut kind = None;
+        let mut name = None;
+        let mut desc = None;
+        let mut flags = None;
+        let mut attributes = Vec::new();
+        for child in &node.children {
+            if is_attribute(&child.text) {
+                attributes.push(self.attribute(child, None)?);
+                continue;
+            }
+
+            let mut cursor = child.cursor();
+            match cursor.key()? {
+                "kind" => {
+                    kind = Some(match cursor.word() {
+                        "field" => false,
+                        "method" => true,
+                        kind => return Err(cursor.error(format!("unknown kind `{}`", kind))),
+                    })
+                }
+                "name" => name = Some(cursor.index()?),
+                "descriptor" => desc = Some(cursor.index()?),
+                "flags" => {
+                    flags = Some(cursor);
+                    continue;
+                }
+                key => return Err(cursor.error(format!("unknown property `{}`", key))),
+            }
+            cursor.finish()?;
+        }
+
+        Ok(Member {
+            kind,
+            name: name.ok_or_else(|| error(node.line, "the name is missing".to_owned()))?,
+            desc: desc.ok_or_else(|| error(node.line, "the descriptor is missing".to_owned()))?,
+            flags,
+            attributes,
+        })
+    }
+
+    /// Reads an attribute, `labels` are given inside the code only.
+    fn attribute(&mut self, node: &Node, labels: Option<&Labels>) -> Result<Attribute> {
+        let mut cursor = node.cursor();
+        let name = cursor.word();
+        if name == "Unknown" {
+            let index = cursor.index()?;
+            // the length is implied by the bytes
+            cursor.key()?;
+            let mut bytes = Vec::new();
+            for row in node.rows() {
+                for byte in row.text.split_whitespace() {
+                    bytes.push(u8::from_str_radix(byte, 16).map_err(|_| {
+                        error(row.line, format!("expected a hexadecimal byte, found `{}`", byte))
+                    })?);
+                }
+            }
+            return Ok(Attribute::Unknown(index, bytes));
+        }
+
+        let name = match ATTRIBUTES.iter().find(|attribute| **attribute == name) {
+            Some(name) => *name,
+            None => return Err(cursor.error(format!("unknown attribute `{}`", name))),
+        };
+        self.attribute_names.push(name);
+        cursor.expect(":")?;
+
+        let attribute = match name {
+            "AnnotationDefault" => Attribute::AnnotationDefault(cursor.element_value()?),
+            "BootstrapMethods" => {
+                let mut methods = Vec::with_capacity(node.children.len());
+                for child in &node.children {
+                    let mut cursor = child.cursor();
+                    cursor.number::<usize>()?;
+                    cursor.expect(":")?;
+                    let method_ref = cursor.index()?;
+                    cursor.finish()?;
+
+                    let mut arguments = Vec::new();
+                    for row in child.rows() {
+                        if row.text != "Method arguments:" {
+                            let mut cursor = row.cursor();
+                            arguments.push(cursor.index()?);
+                            cursor.finish()?;
+                        }
+                    }
+                    methods.push(BootstrapMethod {
+                        method_ref,
+                        arguments,
+                    });
+                }
+                Attribute::BootstrapMethods(methods)
+            }
+            "Code" => {
+                cursor.finish()?;
+                return self.code(node);
+            }
+            "ConstantValue" => Attribute::ConstantValue(cursor.index()?),
+            "Deprecated" => {
+                cursor.eat("true");
+                Attribute::Deprecated
+            }
+            "EnclosingMethod" => {
+                let class_index = cursor.index()?;
+                cursor.expect(".")?;
+                Attribute::EnclosingMethod {
+                    class_index,
+                    method_index: cursor.index()?,
+                }
+            }
+            "Exceptions" => Attribute::Exceptions(rows(node, |cursor| {
+                cursor.expect("throws")?;
+                cursor.index()
+            })?),
+            "InnerClasses" => Attribute::InnerClasses(rows(node, |cursor| {
+                let flags = cursor.flags(INNER_CLASS_FLAGS)?;
+                let inner_name = cursor.index()?;
+                cursor.expect("=")?;
+                let inner_class_info = cursor.index()?;
+                cursor.expect("of")?;
+                let outer_class_info = cursor.index()?;
+                cursor.eat(";");
+                Ok(InnerClass {
+                    inner_class_info,
+                    outer_class_info,
+                    inner_name,
+                    inner_class_access_flags: InnerClassAccessFlags::from_bits_retain(flags),
+                })
+            })?),
+            "LineNumberTable" => Attribute::LineNumberTable(rows(node, |cursor| {
+                cursor.expect("line")?;
+                let line_number = cursor.number()?;
+                cursor.expect(":")?;
+                Ok(LineNumber {
+                    start: cursor.offset(labels)?,
+                    line_number,
+                })
+            })?),
+            "LocalVariableTable" => Attribute::LocalVariableTable(local_variables(node, labels)?),
+            "LocalVariableTypeTable" => Attribute::LocalVariableTypeTable(
+                local_variables(node, labels)?
+                    .into_iter()
+                    .map(|variable| LocalVariableType {
+                        start: variable.start,
+                        length: variable.length,
+                        name: variable.name,
+                        signature: variable.descriptor,
+                        index: variable.index,
+                    })
+                    .collect(),
+            ),
+            "MethodParameters" => Attribute::MethodParameters(rows(node, |cursor| {
+                let flags = cursor.flags(PARAMETER_FLAGS)?;
+                Ok(MethodParameter {
+                    name: cursor.index()?,
+                    access_flags: ParameterFlags::from_bits_retain(flags),
+                })
+            })?),
+            "Module" => {
+                cursor.finish()?;
+                return module(node);
+            }
+            "ModuleMainClass" => Attribute::ModuleMainClass(cursor.index()?),
+            "ModulePackages" => Attribute::ModulePackages(rows(node, |cursor| cursor.index())?),
+            "NestHost" => Attribute::NestHost(cursor.index()?),
+            "NestMembers" => Attribute::NestMembers(rows(node, |cursor| cursor.index())?),
+            "PermittedSubclasses" => {
+                Attribute::PermittedSubclasses(rows(node, |cursor| cursor.index())?)
+            }
+            "Record" => {
+                let mut components = Vec::with_capacity(node.children.len());
+                for child in &node.children {
+                    let component = self.member(child)?;
+                    if let Some(cursor) = component.flags {
+                        return Err(cursor.error("a record component has no flags".to_owned()));
+                    }
+                    if component.kind.is_some() {
+                        return Err(error(child.line, "a record component has no kind".to_owned()));
+                    }
+                    components.push(RecordComponent {
+                        name: component.name,
+                        descriptor: component.desc,
+                        attributes: component.attributes,
+                    });
+                }
+                Attribute::Record(components)
+            }
+            "RuntimeVisibleAnnotations" => Attribute::RuntimeVisibleAnnotations(annotations(node)?),
+            "RuntimeInvisibleAnnotations" => {
+                Attribute::RuntimeInvisibleAnnotations(annotations(node)?)
+            }
+            "RuntimeVisibleParameterAnnotations" => {
+                Attribute::RuntimeVisibleParameterAnnotations(parameter_annotations(node)?)
+            }
+            "RuntimeInvisibleParameterAnnotations" => {
+                Attribute::RuntimeInvisibleParameterAnnotations(parameter_annotations(node)?)
+            }
+            "RuntimeVisibleTypeAnnotations" => {
+                Attribute::RuntimeVisibleTypeAnnotations(type_annotations(node)?)
+            }
+            "RuntimeInvisibleTypeAnnotations" => {
+                Attribute::RuntimeInvisibleTypeAnnotations(type_annotations(node)?)
+            }
+            "Signature" => Attribute::Signature(cursor.index()?),
+            "SourceDebugExtension" => Attribute::SourceDebugExtension(cursor.string()?),
+            "SourceFile" => Attribute::SourceFile(cursor.index()?),
+            "StackMapTable" => {
+                // the number of entries is implied by the frames
+                cursor.text = "";
+                let mut frames = Vec::with_capacity(node.children.len());
+                for child in &node.children {
+                    frames.push(stack_map_frame(child)?);
+                }
+                Attribute::StackMapTable(frames)
+            }
+            "Synthetic" => {
+                cursor.eat("true");
+                Attribute::Synthetic
+            }
+            _ => unreachable!(),
+        };
+        cursor.finish()?;
+        Ok(attribute)
+    }
+
+    fn code(&mut self, node: &Node) -> Result<Attribute> {
+        let mut max_stack = 0;
+        let mut max_locals = 0;
+        let mut lines = Vec::new();
+        let mut exception_rows = Vec::new();
+        let mut attribute_nodes = Vec::new();
+        for child in &node.children {
+            if child.text.starts_with("stack=") {
+                let mut cursor = child.cursor();
+                cursor.expect("stack=")?;
+                max_stack = cursor.number()?;
+                cursor.expect(",")?;
+                cursor.expect("locals=")?;
+                max_locals = cursor.number()?;
+                // the size of the arguments is implied by the descriptor
+                if cursor.eat(",") {
+                    cursor.expect("args_size=")?;
+                    cursor.number::<u16>()?;
+                }
+                cursor.finish()?;
+                lines.extend(child.rows());
+            } else if child.text == "Exception table:" {
+                exception_rows.extend(child.rows());
+            } else if is_attribute(&child.text) {
+                attribute_nodes.push(child);
+            } else {
+                lines.push(child);
+                lines.extend(child.rows());
+            }
+        }
+
+        let (instructions, labels) = self.instructions(&lines)?;
+
+        let mut exceptions = Vec::with_capacity(exception_rows.len());
+        for row in exception_rows {
+            if row.text.starts_with("from") {
+                continue;
+            }
+
+            let mut cursor = row.cursor();
+            let start = cursor.offset(Some(&labels))?;
+            let end = cursor.offset(Some(&labels))?;
+            let handler = cursor.offset(Some(&labels))?;
+            let catch_type = if cursor.eat("any") {
+                0
+            } else {
+                cursor.index()?
+            };
+            cursor.finish()?;
+            exceptions.push(Exception {
+                start,
+                end,
+                handler,
+                catch_type,
+            });
+        }
+
+        let mut attributes = Vec::with_capacity(attribute_nodes.len());
+        for child in attribute_nodes {
+            attributes.push(self.attribute(child, Some(&labels))?);
+        }
+
+        Ok(Attribute::Code {
+            max_stack,
+            max_locals,
+            instructions,
+            exceptions,
+            attributes,
+        })
+    }
+
+    /// Reads the instructions, computes their offsets and resolves all jumps.
+    fn instructions(&self, lines: &[&Node]) -> Result<(Instructions, Labels)> {
+        let mut pending = Vec::with_capacity(lines.len());
+        let mut positions = HashMap::new();
+        let mut lines = lines.iter();
+        while let Some(line) = lines.next() {
+            let mut cursor = line.cursor();
+            while let Some(label) = cursor.label() {
+                if positions.insert(label.to_owned(), pending.len()).is_some() {
+                    return Err(cursor.error(format!("label `{}` is placed twice", label)));
+                }
+            }
+            if cursor.is_empty() {
+                continue;
+            }
+
+            let mnemonic = cursor.word();
+            let (insn, jump) = match mnemonic {
+                "tableswitch" | "lookupswitch" => {
+                    let padding = switch_padding(&mut cursor)?;
+                    let mut default = None;
+                    let mut targets = Vec::new();
+                    loop {
+                        let row = match lines.next() {
+                            Some(row) => row,
+                            None => return Err(cursor.error("the switch is not closed".to_owned())),
+                        };
+                        let mut cursor = row.cursor();
+                        if cursor.eat("}") {
+                            cursor.finish()?;
+                            break;
+                        } else if cursor.eat("default") {
+                            cursor.expect(":")?;
+                            default = Some(cursor.target()?);
+                        } else {
+                            let key = cursor.number()?;
+                            cursor.expect(":")?;
+                            targets.push((key, cursor.target()?));
+                        }
+                        cursor.finish()?;
+                    }
+
+                    let default = default
+                        .ok_or_else(|| cursor.error("the default of the switch is missing".to_owned()))?;
+                    switch(mnemonic == "tableswitch", default, targets, padding, &cursor)?
+                }
+                _ => instruction(mnemonic, &mut cursor)?,
+            };
+            cursor.finish()?;
+            pending.push(Pending {
+                line: line.line,
+                insn,
+                jump,
+            });
+        }
+
+        // the length of an instruction does not depend on its targets
+        let mut offsets = Vec::with_capacity(pending.len() + 1);
+        let mut offset = 0;
+        for p in &pending {
+            offsets.push(offset);
+            offset += instruction_len(offset, &p.insn)
+                .map_err(|e| error(p.line, format!("{:?}", e)))?;
+        }
+        offsets.push(offset);
+        let labels: Labels = positions
+            .into_iter()
+            .map(|(label, position)| (label, offsets[position]))
+            .collect();
+
+        let mut instructions = Instructions::new();
+        for (p, &offset) in pending.into_iter().zip(&offsets) {
+            let line = p.line;
+            let relative = |target: &Target| -> Result<i32> {
+                let target = resolve(Some(&labels), target, line)?;
+                i32::try_from(target - i64::from(offset))
+                    .map_err(|_| error(line, format!("invalid target {}", target)))
+            };
+
+            let insn = match p.jump {
+                Jump::None => p.insn,
+                Jump::Branch(make, ref target) => match make(relative(target)?) {
+                    Some(insn) => insn,
+                    None => {
+                        return Err(error(
+                            line,
+                            format!("the target of {} is too far away", p.insn.mnemonic()),
+                        ))
+                    }
+                },
+                Jump::TableSwitch {
+                    low,
+                    high,
+                    ref default,
+                    ref targets,
+                    padding,
+                } => Instruction::TableSwitch {
+                    default: relative(default)?,
+                    low,
+                    high,
+                    offsets: targets.iter().map(relative).collect::<Result<_>>()?,
+                    padding,
+                },
+                Jump::LookupSwitch {
+                    ref default,
+                    ref targets,
+                    padding,
+                } => {
+                    let mut offsets = BTreeMap::new();
+                    for &(key, ref target) in targets {
+                        offsets.insert(key, relative(target)?);
+                    }
+                    Instruction::LookupSwitch {
+                        default: relative(default)?,
+                        offsets,
+                        padding,
+                    }
+                }
+            };
+            instructions.insert(offset, insn);
+        }
+
+        Ok((instructions, labels))
+    }
+}
+
+/// An instruction, whose targets have not been resolved yet.
+struct Pending {
+    line: usize,
+    /// The instruction itself or a placeholder of the same length
+    insn: Instruction,
+    jump: Jump,
+}
+
+/// The targets of an instruction.
+enum Jump {
+    None,
+    /// A jump, which is created from the relative offset,
+    /// `None` is returned if it does not fit.
+    Branch(fn(i32) -> Option<Instruction>, Target),
+    TableSwitch {
+        low: i32,
+        high: i32,
+        default: Target,
+        targets: Vec<Target>,
+        padding: [u8; 3],
+    },
+    LookupSwitch {
+        default: Target,
+        targets: Vec<(i32, Target)>,
+        padding: [u8; 3],
+    },
+}
+
+/// Reads the padding of a switch, like `padding 01 00 02`, if it is given, and the `{`.
+fn switch_padding(cursor: &mut Cursor) -> Result<[u8; 3]> {
+    let mut padding = [0; 3];
+    if cursor.eat("padding") {
+        let mut count = 0;
+        while !cursor.eat("{") {
+            let byte = u8::from_str_radix(cursor.word(), 16)
+                .map_err(|_| cursor.unexpected("a hexadecimal byte or `{`"))?;
+            match padding.get_mut(count) {
+                Some(padding) => *padding = byte,
+                None => return Err(cursor.error("the padding has at most 3 bytes".to_owned())),
+            }
+            count += 1;
+        }
+    } else {
+        cursor.expect("{")?;
+    }
+    Ok(padding)
+}
+
+/// Creates a placeholder for a switch with the targets given by their keys.
+fn switch(
+    table: bool,
+    default: Target,
+    targets: Vec<(i32, Target)>,
+    padding: [u8; 3],
+    cursor: &Cursor,
+) -> Result<(Instruction, Jump)> {
+    if table {
+        let low = match targets.first() {
+            Some(&(low, _)) => low,
+            None => return Err(cursor.error("a tableswitch needs at least one key".to_owned())),
+        };
+        let mut high = low;
+        for (i, &(key, _)) in targets.iter().enumerate() {
+            if i64::from(key) != i64::from(low) + i as i64 {
+                return Err(cursor.error("the keys of a tableswitch must be consecutive".to_owned()));
+            }
+            high = key;
+        }
+
+        let insn = Instruction::TableSwitch {
+            default: 0,
+            low,
+            high,
+            offsets: vec![0; targets.len()],
+            padding,
+        };
+        let targets = targets.into_iter().map(|(_, target)| target).collect();
+        Ok((
+            insn,
+            Jump::TableSwitch {
+                low,
+                high,
+                default,
+                targets,
+                padding,
+            },
+        ))
+    } else {
+        let mut offsets = BTreeMap::new();
+        for &(key, _) in &targets {
+            if offsets.insert(key, 0).is_some() {
+                return Err(cursor.error(format!("the key {} is used twice", key)));
+            }
+        }
+        let insn = Instruction::LookupSwitch {
+            default: 0,
+            offsets,
+            padding,
+        };
+        Ok((
+            insn,
+            Jump::LookupSwitch {
+                default,
+                targets,
+                padding,
+            },
+        ))
+    }
+}
+
+/// Reads the operands of an instruction, which is not a switch.
+fn instruction(mnemonic: &str, cursor: &mut Cursor) -> Result<(Instruction, Jump)> {
+    use self::Instruction::*;
+
+    if let Some(insn) = simple_instruction(mnemonic) {
+        return Ok((insn, Jump::None));
+    }
+    if let Some(make) = branch_instruction(mnemonic) {
+        let target = cursor.target()?;
+        return Ok((make(0).unwrap(), Jump::Branch(make, target)));
+    }
+
+    let insn = match mnemonic {
+        "bipush" => BIPush(cursor.number()?),
+        "sipush" => SIPush(cursor.number()?),
+        "iinc" => {
+            let index = cursor.number()?;
+            cursor.expect(",")?;
+            IInc(index, cursor.number()?)
+        }
+        "invokeinterface" => {
+            let index = cursor.index()?;
+            cursor.expect(",")?;
+            let count = cursor.number()?;
+            let reserved = if cursor.eat(",") { cursor.number()? } else { 0 };
+            InvokeInterface(index, count, reserved)
+        }
+        "invokedynamic" => {
+            let index = cursor.index()?;
+            let reserved = if cursor.eat(",") { cursor.number()? } else { 0 };
+            InvokeDynamic(index, reserved)
+        }
+        "multianewarray" => {
+            let index = cursor.index()?;
+            cursor.expect(",")?;
+            MultiANewArray(index, cursor.number()?)
+        }
+        "newarray" => NewArray(match cursor.word() {
+            "boolean" => ArrayType::Boolean,
+            "char" => ArrayType::Char,
+            "float" => ArrayType::Float,
+            "double" => ArrayType::Double,
+            "byte" => ArrayType::Byte,
+            "short" => ArrayType::Short,
+            "int" => ArrayType::Int,
+            "long" => ArrayType::Long,
+            name => return Err(cursor.error(format!("unknown array type `{}`", name))),
+        }),
+        "wide" => {
+            let mnemonic = cursor.word();
+            match instruction(mnemonic, cursor)? {
+                (insn, Jump::None) => Wide(Box::new(insn)),
+                _ => return Err(cursor.error(format!("{} cannot be wide", mnemonic))),
+            }
+        }
+        _ => {
+            if let Some(make) = constant_instruction(mnemonic) {
+                make(cursor.index()?)
+            } else if let Some(make) = local_instruction(mnemonic) {
+                make(cursor.number()?)
+            } else {
+                return Err(cursor.error(format!("unknown instruction `{}`", mnemonic)));
+            }
+        }
+    };
+    Ok((insn, Jump::None))
+}
+
+/// Returns an instruction without any operands.
+fn simple_instruction(mnemonic: &str) -> Option<Instruction> {
+    use self::Instruction::*;
+
+    Some(match mnemonic {
+        "nop" => NOP,
+        "aconst_null" => AConstNull,
+        "iconst_m1" => IConstM1,
+        "iconst_0" => IConst0,
+        "iconst_1" => IConst1,
+        "iconst_2" => IConst2,
+        "iconst_3" => IConst3,
+        "iconst_4" => IConst4,
+        "iconst_5" => IConst5,
+        "lconst_0" => LConst0,
+        "lconst_1" => LConst1,
+        "fconst_0" => FConst0,
+        "fconst_1" => FConst1,
+        "fconst_2" => FConst2,
+        "dconst_0" => DConst0,
+        "dconst_1" => DConst1,
+        "iload_0" => ILoad0,
+        "iload_1" => ILoad1,
+        "iload_2" => ILoad2,
+        "iload_3" => ILoad3,
+        "lload_0" => LLoad0,
+        "lload_1" => LLoad1,
+        "lload_2" => LLoad2,
+        "lload_3" => LLoad3,
+        "fload_0" => FLoad0,
+        "fload_1" => FLoad1,
+        "fload_2" => FLoad2,
+        "fload_3" => FLoad3,
+        "dload_0" => DLoad0,
+        "dload_1" => DLoad1,
+        "dload_2" => DLoad2,
+        "dload_3" => DLoad3,
+        "aload_0" => ALoad0,
+        "aload_1" => ALoad1,
+        "aload_2" => ALoad2,
+        "aload_3" => ALoad3,
+        "iaload" => IALoad,
+        "laload" => LALoad,
+        "faload" => FALoad,
+        "daload" => DALoad,
+        "aaload" => AALoad,
+        "baload" => BALoad,
+        "caload" => CALoad,
+        "saload" => SALoad,
+        "istore_0" => IStore0,
+        "istore_1" => IStore1,
+        "istore_2" => IStore2,
+        "istore_3" => IStore3,
+        "lstore_0" => LStore0,
+        "lstore_1" => LStore1,
+        "lstore_2" => LStore2,
+        "lstore_3" => LStore3,
+        "fstore_0" => FStore0,
+        "fstore_1" => FStore1,
+        "fstore_2" => FStore2,
+        "fstore_3" => FStore3,
+        "dstore_0" => DStore0,
+        "dstore_1" => DStore1,
+        "dstore_2" => DStore2,
+        "dstore_3" => DStore3,
+        "astore_0" => AStore0,
+        "astore_1" => AStore1,
+        "astore_2" => AStore2,
+        "astore_3" => AStore3,
+        "iastore" => IAStore,
+        "lastore" => LAStore,
+        "fastore" => FAStore,
+        "dastore" => DAStore,
+        "aastore" => AAStore,
+        "bastore" => BAStore,
+        "castore" => CAStore,
+        "sastore" => SAStore,
+        "pop" => Pop,
+        "pop2" => Pop2,
+        "dup" => Dup,
+        "dup_x1" => DupX1,
+        "dup_x2" => DupX2,
+        "dup2" => Dup2,
+        "dup2_x1" => Dup2X1,
+        "dup2_x2" => Dup2X2,
+        "swap" => Swap,
+        "iadd" => IAdd,
+        "ladd" => LAdd,
+        "fadd" => FAdd,
+        "dadd" => DAdd,
+        "isub" => ISub,
+        "lsub" => LSub,
+        "fsub" => FSub,
+        "dsub" => DSub,
+        "imul" => IMul,
+        "lmul" => LMul,
+        "fmul" => FMul,
+        "dmul" => DMul,
+        "idiv" => IDiv,
+        "ldiv" => LDiv,
+        "fdiv" => FDiv,
+        "ddiv" => DDiv,
+        "irem" => IRem,
+        "lrem" => LRem,
+        "frem" => FRem,
+        "drem" => DRem,
+        "ineg" => INeg,
+        "lneg" => LNeg,
+        "fneg" => FNeg,
+        "dneg" => DNeg,
+        "ishl" => IShL,
+        "lshl" => LShL,
+        "ishr" => IShR,
+        "lshr" => LShR,
+        "iushr" => IUShR,
+        "lushr" => LUShR,
+        "iand" => IAnd,
+        "land" => LAnd,
+        "ior" => IOr,
+        "lor" => LOr,
+        "ixor" => IXOr,
+        "lxor" => LXOr,
+        "i2l" => I2L,
+        "i2f" => I2F,
+        "i2d" => I2D,
+        "l2i" => L2I,
+        "l2f" => L2F,
+        "l2d" => L2D,
+        "f2i" => F2I,
+        "f2l" => F2L,
+        "f2d" => F2D,
+        "d2i" => D2I,
+        "d2l" => D2L,
+        "d2f" => D2F,
+        "i2b" => I2B,
+        "i2c" => I2C,
+        "i2s" => I2S,
+        "lcmp" => LCmp,
+        "fcmpl" => FCmpL,
+        "fcmpg" => FCmpG,
+        "dcmpl" => DCmpL,
+        "dcmpg" => DCmpG,
+        "ireturn" => IReturn,
+        "lreturn" => LReturn,
+        "freturn" => FReturn,
+        "dreturn" => DReturn,
+        "areturn" => AReturn,
+        "return" => Return,
+        "arraylength" => ArrayLength,
+        "athrow" => AThrow,
+        "monitorenter" => MonitorEnter,
+        "monitorexit" => MonitorExit,
+        "breakpoint" => BreakPoint,
+        "impdep1" => ImpDep1,
+        "impdep2" => ImpDep2,
+        _ => return None,
+    })
+}
+
+/// Returns the constructor of an instruction taking an index into the constant pool.
+fn constant_instruction(mnemonic: &str) -> Option<fn(u16) -> Instruction> {
+    use self::Instruction::*;
+
+    let make: fn(u16) -> Instruction = match mnemonic {
+        "ldc" => LDC,
+        "ldc_w" => LDCW,
+        "ldc2_w" => LDC2W,
+        "getstatic" => GetStatic,
+        "putstatic" => PutStatic,
+        "getfield" => GetField,
+        "putfield" => PutField,
+        "invokevirtual" => InvokeVirtual,
+        "invokespecial" => InvokeSpecial,
+        "invokestatic" => InvokeStatic,
+        "new" => New,
+        "anewarray" => ANewArray,
+        "checkcast" => CheckCast,
+        "instanceof" => InstanceOf,
+        _ => return None,
+    };
+    Some(make)
+}
+
+/// Returns the constructor of an instruction taking the index of a local variable.
+fn local_instruction(mnemonic: &str) -> Option<fn(u16) -> Instruction> {
+    use self::Instruction::*;
+
+    let make: fn(u16) -> Instruction = match mnemonic {
+        "iload" => ILoad,
+        "lload" => LLoad,
+        "fload" => FLoad,
+        "dload" => DLoad,
+        "aload" => ALoad,
+        "istore" => IStore,
+        "lstore" => LStore,
+        "fstore" => FStore,
+        "dstore" => DStore,
+        "astore" => AStore,
+        "ret" => Ret,
+        _ => return None,
+    };
+    Some(make)
+}
+
+/// Returns the constructor of a jump taking the relative offset of its target.
+fn branch_instruction(mnemonic: &str) -> Option<fn(i32) -> Option<Instruction>> {
+    use self::Instruction::*;
+
+    fn narrow(relative: i32) -> Option<i16> {
+        i16::try_from(relative).ok()
+    }
+
+    let make: fn(i32) -> Option<Instruction> = match mnemonic {
+        "ifeq" => |relative| narrow(relative).map(IfEq),
+        "ifne" => |relative| narrow(relative).map(IfNE),
+        "iflt" => |relative| narrow(relative).map(IfLT),
+        "ifge" => |relative| narrow(relative).map(IfGE),
+        "ifgt" => |relative| narrow(relative).map(IfGT),
+        "ifle" => |relative| narrow(relative).map(IfLE),
+        "if_icmpeq" => |relative| narrow(relative).map(IfICmpEq),
+        "if_icmpne" => |relative| narrow(relative).map(IfICmpNE),
+        "if_icmplt" => |relative| narrow(relative).map(IfICmpLT),
+        "if_icmpge" => |relative| narrow(relative).map(IfICmpGE),
+        "if_icmpgt" => |relative| narrow(relative).map(IfICmpGT),
+        "if_icmple" => |relative| narrow(relative).map(IfICmpLE),
+        "if_acmpeq" => |relative| narrow(relative).map(IfACmpEq),
+        "if_acmpne" => |relative| narrow(relative).map(IfACmpNE),
+        "ifnull" => |relative| narrow(relative).map(IfNull),
+        "ifnonnull" => |relative| narrow(relative).map(IfNonNull),
+        "goto" => |relative| narrow(relative).map(|relative| GoTo(i32::from(relative))),
+        "jsr" => |relative| narrow(relative).map(|relative| JSR(i32::from(relative))),
+        "goto_w" => |relative| Some(GoToW(relative)),
+        "jsr_w" => |relative| Some(JSRW(relative)),
+        _ => return None,
+    };
+    Some(make)
+}
+
+/// Reads the rows of a `LocalVariableTable` or a `LocalVariableTypeTable`,
+/// the descriptor is the signature in the latter.
+fn local_variables(node: &Node, labels: Option<&Labels>) -> Result<Vec<LocalVariable>> {
+    let mut variables = Vec::new();
+    for row in node.rows() {
+        if row.text.starts_with("Start") {
+            continue;
+        }
+
+        let mut cursor = row.cursor();
+        let start = cursor.offset(labels)?;
+        let length = cursor.number()?;
+        let index = cursor.number()?;
+        let name = cursor.index()?;
+        let descriptor = cursor.index()?;
+        cursor.finish()?;
+        variables.push(LocalVariable {
+            start,
+            length,
+            name,
+            descriptor,
+            index,
+        });
+    }
+    Ok(variables)
+}
+
+/// Reads annotations like `0: #5()`.
+fn annotations(node: &Node) -> Result<Vec<Annotation>> {
+    rows(node, |cursor| {
+        cursor.number::<usize>()?;
+        cursor.expect(":")?;
+        cursor.annotation()
+    })
+}
+
+/// Reads the annotations below `parameter 0:` and so on.
+fn parameter_annotations(node: &Node) -> Result<Vec<Vec<Annotation>>> {
+    let mut parameters = Vec::with_capacity(node.children.len());
+    for child in &node.children {
+        let mut cursor = child.cursor();
+        cursor.expect("parameter")?;
+        cursor.number::<usize>()?;
+        cursor.expect(":")?;
+        cursor.finish()?;
+        parameters.push(annotations(child)?);
+    }
+    Ok(parameters)
+}
+
+/// Reads type annotations like `0: #5(): FIELD, location=[ARRAY]`.
+fn type_annotations(node: &Node) -> Result<Vec<TypeAnnotation>> {
+    rows(node, |cursor| {
+        cursor.number::<usize>()?;
+        cursor.expect(":")?;
+        let annotation = cursor.annotation()?;
+        cursor.expect(":")?;
+        let (target_type, target_path) = cursor.type_target()?;
+        Ok(TypeAnnotation {
+            target_type,
+            target_path,
+            annotation,
+        })
+    })
+}
+
+fn stack_map_frame(node: &Node) -> Result<StackMapFrame> {
+    let mut cursor = node.cursor();
+    cursor.expect("frame_type")?;
+    cursor.expect("=")?;
+    let kind = cursor.word();
+    cursor.finish()?;
+
+    let mut offset_delta = None;
+    let mut count = None;
+    let mut locals = Vec::new();
+    let mut stack = Vec::new();
+    for child in &node.children {
+        let mut cursor = child.cursor();
+        let key = cursor.word();
+        cursor.expect("=")?;
+        match key {
+            "offset_delta" => offset_delta = Some(cursor.number()?),
+            "count" => count = Some(cursor.number()?),
+            "locals" => locals = cursor.verification_types()?,
+            "stack" => stack = cursor.verification_types()?,
+            _ => return Err(cursor.error(format!("unknown property `{}`", key))),
+        }
+        cursor.finish()?;
+    }
+
+    let offset_delta =
+        offset_delta.ok_or_else(|| error(node.line, "offset_delta is missing".to_owned()))?;
+    Ok(match kind {
+        "same" | "same_extended" => StackMapFrame::Same {
+            offset_delta,
+            extended: kind == "same_extended",
+        },
+        "same_locals_1_stack_item" | "same_locals_1_stack_item_extended" => {
+            if stack.len() != 1 {
+                return Err(error(node.line, "expected a single item on the stack".to_owned()));
+            }
+            StackMapFrame::Same1 {
+                offset_delta,
+                stack: stack.remove(0),
+                extended: kind == "same_locals_1_stack_item_extended",
+            }
+        }
+        "chop" => StackMapFrame::Chop {
+            offset_delta,
+            count: count.ok_or_else(|| error(node.line, "count is missing".to_owned()))?,
+        },
+        "append" => StackMapFrame::Append {
+            offset_delta,
+            locals,
+        },
+        "full_frame" => StackMapFrame::Full {
+            offset_delta,
+            locals,
+            stack,
+        },
+        _ => return Err(error(node.line, format!("unknown frame type `{}`", kind))),
+    })
+}
+
+fn module(node: &Node) -> Result<Attribute> {
+    let mut name = None;
+    let mut flags = ModuleFlags::empty();
+    let mut version = 0;
+    let mut requires = Vec::new();
+    let mut exports = Vec::new();
+    let mut opens = Vec::new();
+    let mut uses = Vec::new();
+    let mut provides = Vec::new();
+    for child in &node.children {
+        let mut cursor = child.cursor();
+        match cursor.key()? {
+            "name" => name = Some(cursor.index()?),
+            "flags" => flags = ModuleFlags::from_bits_retain(cursor.flags(MODULE_FLAGS)?),
+            "version" => version = cursor.index()?,
+            "requires" => {
+                requires = rows(child, |cursor| {
+                    let flags = RequiresFlags::from_bits_retain(cursor.flags(REQUIRES_FLAGS)?);
+                    let index = cursor.index()?;
+                    cursor.expect("version")?;
+                    Ok(Requirement {
+                        index,
+                        flags,
+                        version: cursor.index()?,
+                    })
+                })?
+            }
+            "exports" => {
+                exports = rows(child, |cursor| {
+                    let (index, flags, to) = cursor.module_package()?;
+                    Ok(Export { index, flags, to })
+                })?
+            }
+            "opens" => {
+                opens = rows(child, |cursor| {
+                    let (index, flags, to) = cursor.module_package()?;
+                    Ok(Opening { index, flags, to })
+                })?
+            }
+            "uses" => uses = rows(child, |cursor| cursor.index())?,
+            "provides" => {
+                provides = rows(child, |cursor| {
+                    let index = cursor.index()?;
+                    cursor.expect("with")?;
+                    Ok(Provider {
+                        index,
+                        with: cursor.indices()?,
+                    })
+                })?
+            }
+            key => return Err(cursor.error(format!("unknown property `{}`", key))),
+        }
+        cursor.finish()?;
+    }
+
+    Ok(Attribute::Module {
+        name: name.ok_or_else(|| error(node.line, "the name of the module is missing".to_owned()))?,
+        flags,
+        version,
+        requires,
+        exports,
+        opens,
+        uses,
+        provides,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{disassemble, parse, write};
+    use super::*;
+
+    #[test]
+    fn resources() {
+        for bytes in &[
+            &include_bytes!("../../examples/resources/AnyAnnotation.class")[..],
+            &include_bytes!("../../examples/resources/CustomLinkedList.class")[..],
+            &include_bytes!("../../examples/resources/CustomLinkedList$Element.class")[..],
+            &include_bytes!("../../examples/resources/CustomLinkedList$Iter.class")[..],
+            &include_bytes!("../../examples/resources/Fibonacci.class")[..],
+            &include_bytes!("../../examples/resources/Frames.class")[..],
+            &include_bytes!("../../examples/resources/FizzBuzzStream.class")[..],
+            &include_bytes!("../../examples/resources/Nestmates.class")[..],
+            &include_bytes!("../../examples/resources/Nestmates$Inner.class")[..],
+            &include_bytes!("../../examples/resources/Records.class")[..],
+            &include_bytes!("../../examples/resources/Records$Component.class")[..],
+            &include_bytes!("../../examples/resources/RoundTrip.class")[..],
+            &include_bytes!("../../examples/resources/Sealed.class")[..],
+            &include_bytes!("../../examples/resources/Sealed$Leaf.class")[..],
+            &include_bytes!("../../examples/resources/Sealed$Node.class")[..],
+            &include_bytes!("../../examples/resources/Surrogates.class")[..],
+        ] {
+            let (pool, class) = parse(bytes).unwrap();
+            let text = disassemble(&pool, &class);
+            let (pool, class) = assemble(&text).unwrap();
+            assert_eq!(disassemble(&pool, &class), text);
+            assert!(write(&pool, &class).unwrap() == *bytes);
+        }
+    }
+
+    #[test]
+    fn member_kinds() {
+        // a method with a field descriptor and a field with a method descriptor
+        let mut pool = Pool::new();
+        let name = pool.push(Item::UTF8("A".to_owned())).unwrap();
+        let name = pool.push(Item::Class(name)).unwrap();
+        let member = pool.push(Item::UTF8("value".to_owned())).unwrap();
+        let field_desc = pool.push(Item::UTF8("I".to_owned())).unwrap();
+        let method_desc = pool.push(Item::UTF8("()I".to_owned())).unwrap();
+        let class = Class {
+            minor_version: 0,
+            major_version: 52,
+            access_flags: ClassAccessFlags::PUBLIC,
+            name,
+            super_name: 0,
+            interfaces: Vec::new(),
+            fields: vec![
+                Field {
+                    access_flags: FieldAccessFlags::PUBLIC,
+                    name: member,
+                    desc: method_desc,
+                    attributes: Vec::new(),
+                },
+            ],
+            methods: vec![
+                Method {
+                    access_flags: MethodAccessFlags::ABSTRACT,
+                    name: member,
+                    desc: field_desc,
+                    attributes: Vec::new(),
+                },
+            ],
+            attributes: Vec::new(),
+        };
+        let bytes = write(&pool, &class).unwrap();
+
+        let (pool, class) = parse(&bytes).unwrap();
+        let (pool, class) = assemble(&disassemble(&pool, &class)).unwrap();
+        assert_eq!((class.fields.len(), class.methods.len()), (1, 1));
+        assert_eq!(class.methods[0].desc, field_desc);
+        assert!(write(&pool, &class).unwrap() == bytes);
+    }
+
+    #[test]
+    fn labels() {
+        let (pool, class) = assemble(
+            r#"
+class Labels // the declaration is not used
+  major version: 52
+  flags: (0x0020) ACC_PUBLIC // the bits win
+  this_class: #2
+Constant pool:
+  #1 = Utf8 "Labels\u{1F600}\t"
+  #2 = Class #1
+  #3 = Utf8 "f"
+  #4 = Utf8 "(I)I"
+  #5 = Long 7l
+  #7 = Float NaN(0x7fc00001)
+  #8 = Utf8 "// not a comment"
+{
+  static int f(int);
+    name: #3
+    descriptor: #4
+    flags: ACC_STATIC
+    Code:
+      stack=2, locals=1
+      start:
+        iload_0
+        tableswitch {
+          1: one
+          2: 20
+          default: end
+        }
+        wide iinc 0, 1000
+        goto_w end
+      20:
+      one:
+        iload 0
+        lookupswitch padding 00 7f {
+          -5: start
+          7: end
+          default: end
+        }
+      end:
+        iload_0
+        ireturn
+      Exception table:
+        start end end any
+      LineNumberTable:
+        line 3: start
+        line 4: end
+}
+"#,
+        ).unwrap();
+
+        assert_eq!(pool.get_utf8(1).unwrap(), "Labels\u{1F600}\t");
+        assert_eq!(pool.get_utf8(8).unwrap(), "// not a comment");
+        match *pool.get(7).unwrap() {
+            Item::Float(value) => assert_eq!(value.to_bits(), 0x7fc0_0001),
+            ref item => panic!("unexpected item {:?}", item),
+        }
+        // the name of the code attribute and the line number table has been added
+        assert_eq!(pool.get_utf8(9).unwrap(), "Code");
+        assert_eq!(pool.get_utf8(10).unwrap(), "LineNumberTable");
+        assert_eq!(class.access_flags, ClassAccessFlags::SUPER);
+        assert_eq!(class.methods[0].access_flags, MethodAccessFlags::STATIC);
+
+        match class.methods[0].attributes[0] {
+            Attribute::Code {
+                ref instructions,
+                ref exceptions,
+                ref attributes,
+                ..
+            } => {
+                let offsets: Vec<u32> = instructions.iter().map(|(offset, _)| offset).collect();
+                assert_eq!(offsets, vec![0, 1, 24, 30, 35, 37, 64, 65]);
+                match instructions.get(1) {
+                    Some(&Instruction::TableSwitch {
+                        default,
+                        low: 1,
+                        high: 2,
+                        ref offsets,
+                        padding,
+                    }) => {
+                        assert_eq!(default, 63);
+                        assert_eq!(padding, [0; 3]);
+                        assert_eq!(offsets, &[34, 34]);
+                    }
+                    insn => panic!("unexpected instruction {:?}", insn),
+                }
+                assert!(matches!(instructions.get(30), Some(Instruction::GoToW(34))));
+                match instructions.get(37) {
+                    Some(&Instruction::LookupSwitch {
+                        default,
+                        ref offsets,
+                        padding,
+                    }) => {
+                        assert_eq!(default, 27);
+                        assert_eq!(padding, [0, 0x7f, 0]);
+                        assert_eq!(offsets.iter().collect::<Vec<_>>(), vec![(&-5, &-37), (&7, &27)]);
+                    }
+                    insn => panic!("unexpected instruction {:?}", insn),
+                }
+                assert_eq!(exceptions[0].start, 0);
+                assert_eq!(exceptions[0].end, 64);
+                match attributes[0] {
+                    Attribute::LineNumberTable(ref lines) => assert_eq!(lines[1].start, 64),
+                    ref attribute => panic!("unexpected attribute {:?}", attribute),
+                }
+            }
+            ref attribute => panic!("unexpected attribute {:?}", attribute),
+        }
+
+        let bytes = write(&pool, &class).unwrap();
+        let (pool, class) = parse(&bytes).unwrap();
+        let text = disassemble(&pool, &class);
+        assert!(text.contains("         35: iload 0\n"), "{}", text);
+        assert!(text.contains("                 -5: 0\n"), "{}", text);
+        assert!(text.contains("         37: lookupswitch padding 00 7f {"), "{}", text);
+    }
+
+    #[test]
+    fn member_kind() {
+        let text = r#"
+class A
+  major version: 52
+  this_class: #2
+Constant pool:
+  #1 = Utf8 "A"
+  #2 = Class #1
+  #3 = Utf8 "I"
+  #4 = Utf8 "()V"
+{
+  int value;
+    kind: method
+    name: #1
+    descriptor: #3
+    flags: ACC_SYNCHRONIZED
+
+  void value();
+    kind: field
+    name: #1
+    descriptor: #4
+    flags: ACC_VOLATILE
+
+  int guessed;
+    name: #1
+    descriptor: #3
+}
+"#;
+        let (pool, class) = assemble(text).unwrap();
+        assert_eq!(class.methods.len(), 1);
+        assert_eq!(class.methods[0].desc, 3);
+        assert_eq!(class.methods[0].access_flags, MethodAccessFlags::SYNCHRONIZED);
+        assert_eq!(class.fields.len(), 2);
+        assert_eq!(class.fields[0].desc, 4);
+        assert_eq!(class.fields[0].access_flags, FieldAccessFlags::VOLATILE);
+        assert_eq!(class.fields[1].desc, 3);
+
+        let (_, class) = parse(&write(&pool, &class).unwrap()).unwrap();
+        assert_eq!((class.fields.len(), class.methods.len()), (2, 1));
+    }
+
+    #[test]
+    fn reserved_operands() {
+        let text = r#"
+class A
+  major version: 52
+  this_class: #2
+Constant pool:
+  #1 = Utf8 "A"
+  #2 = Class #1
+  #3 = Utf8 "()V"
+{
+  void m();
+    kind: method
+    name: #1
+    descriptor: #3
+    Code:
+      stack=0, locals=0
+        invokeinterface #2, 1
+        invokeinterface #2, 1, 7
+        invokedynamic #2
+        invokedynamic #2, 258
+        return
+}
+"#;
+        let (pool, class) = assemble(text).unwrap();
+        match class.methods[0].attributes[0] {
+            Attribute::Code {
+                ref instructions,
+                ..
+            } => {
+                assert!(matches!(instructions.get(0), Some(Instruction::InvokeInterface(2, 1, 0))));
+                assert!(matches!(instructions.get(5), Some(Instruction::InvokeInterface(2, 1, 7))));
+                assert!(matches!(instructions.get(10), Some(Instruction::InvokeDynamic(2, 0))));
+                assert!(matches!(instructions.get(15), Some(Instruction::InvokeDynamic(2, 258))));
+            }
+            ref attribute => panic!("unexpected attribute {:?}", attribute),
+        }
+
+        let text = disassemble(&pool, &class);
+        assert!(text.contains("          0: invokeinterface #2, 1 "), "{}", text);
+        assert!(text.contains("          5: invokeinterface #2, 1, 7 "), "{}", text);
+        assert!(text.contains("         10: invokedynamic #2 "), "{}", text);
+        assert!(text.contains("         15: invokedynamic #2, 258 "), "{}", text);
+    }
+
+    #[test]
+    fn module() {
+        let text = r#"
+module foo
+  major version: 53
+  flags: ACC_MODULE
+  this_class: #2
+Constant pool:
+  #1 = Utf8 "module-info"
+  #2 = Class #1
+  #3 = Utf8 "foo"
+  #4 = Module #3
+  #5 = Utf8 "java.base"
+  #6 = Module #5
+  #7 = Utf8 "foo/api"
+  #8 = Package #7
+  #9 = Utf8 "foo/Service"
+  #10 = Class #9
+  #11 = Utf8 "foo/Impl"
+  #12 = Class #11
+{
+}
+Module:
+  name: #4
+  flags: ACC_OPEN
+  version: #0
+  requires:
+    ACC_MANDATED #6 version #0
+  exports:
+    #8 to #4, #6
+  opens:
+    (0x1000) #8
+  uses:
+    #10
+  provides:
+    #10 with #12
+ModulePackages:
+  #8
+Unknown #3: length = 0
+  01 02 ff
+"#;
+        let (pool, class) = assemble(text).unwrap();
+        match class.attributes[0] {
+            Attribute::Module {
+                name,
+                flags,
+                ref requires,
+                ref exports,
+                ref opens,
+                ref uses,
+                ref provides,
+                ..
+            } => {
+                assert_eq!(name, 4);
+                assert_eq!(flags, ModuleFlags::OPEN);
+                assert_eq!(requires[0].flags, RequiresFlags::MANDATED);
+                assert_eq!(exports[0].to, vec![4, 6]);
+                assert_eq!(opens[0].flags, ExportsFlags::SYNTHETIC);
+                assert_eq!(uses, &[10]);
+                assert_eq!(provides[0].with, vec![12]);
+            }
+            ref attribute => panic!("unexpected attribute {:?}", attribute),
+        }
+        match class.attributes[2] {
+            Attribute::Unknown(3, ref bytes) => assert_eq!(bytes, &[1, 2, 255]),
+            ref attribute => panic!("unexpected attribute {:?}", attribute),
+        }
+
+        // the disassembled class can be assembled again
+        let bytes = write(&pool, &class).unwrap();
+        let (pool, class) = parse(&bytes).unwrap();
+        let text = disassemble(&pool, &class);
+        let (pool, class) = assemble(&text).unwrap();
+        assert!(write(&pool, &class).unwrap() == bytes);
+    }
+
+    #[test]
+    fn errors() {
+        fn line(text: &str) -> usize {
+            match assemble(text) {
+                Err(Error::InvalidAssembly { line, .. }) => line,
+                result => panic!("unexpected result {:?}", result.map(|_| ())),
+            }
+        }
+
+        const HEADER: &str = "class A\n  major version: 52\n  this_class: #2\n\
+                              Constant pool:\n  #1 = Utf8 \"A\"\n  #2 = Class #1\n";
+
+        assert_eq!(line(""), 1);
+        assert_eq!(line("class A\n  this_class: #1\n"), 1);
+        assert_eq!(line("class A\n  major version: 52\n  this_class: 1\n"), 3);
+        assert_eq!(line("class A\nConstant pool:\n  #2 = Utf8 \"A\"\n"), 3);
+        assert_eq!(line(&format!("{}  #3 = Utf8 \"open\n", HEADER)), 7);
+        assert_eq!(line(&format!("{}Bogus: #1\n", HEADER)), 7);
+        assert_eq!(line(&format!("{}SourceFile: #1 #2\n", HEADER)), 7);
+        assert_eq!(line(&format!("{}{{\n  A;\n    kind: class\n}}\n", HEADER)), 9);
+
+        let method = |code: &str| {
+            format!(
+                "{}  #3 = Utf8 \"()V\"\n{{\n  void m();\n    name: #1\n    descriptor: #3\n    \
+                 Code:\n      stack=0, locals=0\n{}}}\n",
+                HEADER, code
+            )
+        };
+        assert_eq!(line(&method("        goto nowhere\n")), 14);
+        assert_eq!(line(&method("        a: nop\n        a: return\n")), 15);
+        assert_eq!(line(&method("        frobnicate\n")), 14);
+        assert_eq!(line(&method("        wide goto 0\n")), 14);
+        assert_eq!(line(&method("        tableswitch {\n          1: 0\n          3: 0\n")), 14);
+        assert_eq!(line(&method("        tableswitch padding 1 2 3 4 {\n        }\n")), 14);
+    }
+}
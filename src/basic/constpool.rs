@@ -5,6 +5,7 @@ use std::rc::Rc;
 use std::slice::Iter;
 
 use result::*;
+use super::tree::BootstrapMethod;
 
 /// A constant pool item
 #[derive(Debug, Clone)]
@@ -12,6 +13,11 @@ pub enum Item {
     /// An UTF-8 encoded string.
     /// Inside the class file itself, a modified format is used.
     UTF8(String),
+    /// A modified UTF-8 string containing unpaired surrogates,
+    /// which cannot be represented as a `String`.
+    /// Its UTF-16 code units are stored instead,
+    /// `Pool::get_utf8` returns `Error::InvalidUTF8` for it.
+    UTF16(Vec<u16>),
     /// An `int`.
     Integer(i32),
     /// A `float`.
@@ -81,6 +87,17 @@ pub enum Item {
     /// The entry at that index must be an `Item::UTF8(_)`
     /// representing a method descriptor.
     MethodType(u16),
+    /// Describes a dynamically computed constant,
+    /// whose value is produced by a bootstrap method when it is loaded the first time.
+    /// Unlike `Item::Long(_)` and `Item::Double(_)`, it always takes one spot,
+    /// even if its type is `long` or `double`.
+    Dynamic {
+        /// The index to an entry of the BootstrapMethods attribute of the class file.
+        bootstrap_method_attribute: u16,
+        /// The index to an `Item::NameAndType { .. }`,
+        /// whose descriptor is a field descriptor.
+        name_and_type: u16,
+    },
     /// Describes a invoke dynamic instruction,
     /// and specifies information regarding the bootstrap method.
     InvokeDynamic {
@@ -101,13 +118,19 @@ pub enum Item {
 }
 
 impl Item {
-    /// Returns true if this item takes up two spaces, false otherwise.
-    fn is_double(&self) -> bool {
-        match *self {
-            Item::Long(_) | Item::Double(_) => true,
-            _ => false,
+    /// Creates an `Item::UTF8(_)` from UTF-16 code units,
+    /// or an `Item::UTF16(_)`, if they contain unpaired surrogates.
+    pub fn from_utf16(units: Vec<u16>) -> Item {
+        match String::from_utf16(&units) {
+            Ok(s) => Item::UTF8(s),
+            Err(_) => Item::UTF16(units),
         }
     }
+
+    /// Returns true if this item takes up two spaces, false otherwise.
+    pub fn is_double(&self) -> bool {
+        matches!(*self, Item::Long(_) | Item::Double(_))
+    }
 }
 
 // Implementing `Hash` and `Eq` manually (sorry for this awful mess of code),
@@ -123,6 +146,10 @@ impl Hash for Item {
                 state.write_u8(1);
                 s.hash(state);
             }
+            Item::UTF16(ref units) => {
+                state.write_u8(1);
+                units.hash(state);
+            }
             Item::Integer(i) => {
                 state.write_u8(3);
                 i.hash(state);
@@ -185,6 +212,14 @@ impl Hash for Item {
                 state.write_u8(16);
                 ptr.hash(state);
             }
+            Item::Dynamic {
+                bootstrap_method_attribute,
+                name_and_type,
+            } => {
+                state.write_u8(17);
+                bootstrap_method_attribute.hash(state);
+                name_and_type.hash(state);
+            }
             Item::InvokeDynamic {
                 bootstrap_method_attribute,
                 name_and_type,
@@ -208,7 +243,8 @@ impl Hash for Item {
 impl PartialEq for Item {
     fn eq(&self, other: &Item) -> bool {
         match (self, other) {
-            (&Item::UTF8(ref str1), &Item::UTF8(ref str2)) => *str1 == *str2,
+            (Item::UTF8(str1), Item::UTF8(str2)) => str1 == str2,
+            (Item::UTF16(units1), Item::UTF16(units2)) => units1 == units2,
             (&Item::Integer(i1), &Item::Integer(i2)) => i1 == i2,
             (&Item::Float(f1), &Item::Float(f2)) => f1.to_bits() == f2.to_bits(),
             (&Item::Long(i1), &Item::Long(i2)) => i1 == i2,
@@ -267,6 +303,16 @@ impl PartialEq for Item {
                 },
             ) => kind1 == kind2 && index1 == index2,
             (
+                &Item::Dynamic {
+                    bootstrap_method_attribute: bma1,
+                    name_and_type: nat1,
+                },
+                &Item::Dynamic {
+                    bootstrap_method_attribute: bma2,
+                    name_and_type: nat2,
+                },
+            )
+            | (
                 &Item::InvokeDynamic {
                     bootstrap_method_attribute: bma1,
                     name_and_type: nat1,
@@ -314,6 +360,10 @@ pub struct Pool {
     length: u16,
     by_index: Vec<Option<Rc<Item>>>,
     by_entry: HashMap<Rc<Item>, u16>,
+    /// The name indices of parsed attributes by the first index of their name,
+    /// in the order they have been parsed.
+    /// Only names, which are referenced by an attribute through a duplicate, are kept.
+    attribute_names: HashMap<u16, Vec<u16>>,
 }
 
 impl Pool {
@@ -322,6 +372,7 @@ impl Pool {
             length: 1,
             by_index: Vec::new(),
             by_entry: HashMap::new(),
+            attribute_names: HashMap::new(),
         }
     }
 
@@ -330,6 +381,7 @@ impl Pool {
             length: 1,
             by_index: Vec::with_capacity(cap as usize),
             by_entry: HashMap::with_capacity(cap as usize),
+            attribute_names: HashMap::new(),
         }
     }
 
@@ -349,7 +401,7 @@ impl Pool {
     /// If the index is 0 or greater than the size of the pool, an error is returned.
     pub fn get(&self, index: u16) -> Result<&Item> {
         // bounds checking
-        if index != 0 && index <= self.len() {
+        if index != 0 && index < self.len() {
             if let Some(ref item) = self.by_index[index as usize - 1] {
                 return Ok(item);
             }
@@ -360,10 +412,10 @@ impl Pool {
 
     /// Returns a cloned String at a specified index.
     pub fn get_utf8(&self, index: u16) -> Result<String> {
-        if let Item::UTF8(ref s) = *self.get(index)? {
-            Ok(s.clone())
-        } else {
-            Err(Error::InvalidCPItem(index))
+        match *self.get(index)? {
+            Item::UTF8(ref s) => Ok(s.clone()),
+            Item::UTF16(_) => Err(Error::InvalidUTF8),
+            _ => Err(Error::InvalidCPItem(index)),
         }
     }
 
@@ -390,13 +442,39 @@ impl Pool {
         }
     }
 
+    /// Returns the bootstrap method and the index to the `Item::NameAndType { .. }`
+    /// of an `Item::Dynamic { .. }` or an `Item::InvokeDynamic { .. }` at a specified index.
+    /// The bootstrap methods are the entries of the `BootstrapMethods` attribute
+    /// (see `Class::bootstrap_methods`).
+    pub fn get_bootstrap_method<'a>(
+        &self,
+        index: u16,
+        bootstrap_methods: &'a [BootstrapMethod],
+    ) -> Result<(&'a BootstrapMethod, u16)> {
+        match *self.get(index)? {
+            Item::Dynamic {
+                bootstrap_method_attribute,
+                name_and_type,
+            }
+            | Item::InvokeDynamic {
+                bootstrap_method_attribute,
+                name_and_type,
+            } => match bootstrap_methods.get(bootstrap_method_attribute as usize) {
+                Some(bootstrap_method) => Ok((bootstrap_method, name_and_type)),
+                None => Err(Error::InvalidCPItem(index)),
+            },
+            _ => Err(Error::InvalidCPItem(index)),
+        }
+    }
+
     /// Pushes an item on the pool.
     pub fn push(&mut self, item: Item) -> Result<u16> {
-        if self.len() == u16::max_value() {
+        let double = item.is_double();
+        let size = if double { 2 } else { 1 };
+        if u32::from(self.len()) + size > u32::from(u16::MAX) {
             return Err(Error::CPTooLarge);
         }
 
-        let double = item.is_double();
         let length = &mut self.length;
 
         let rc_item = Rc::new(item);
@@ -420,7 +498,62 @@ impl Pool {
         }))
     }
 
-    pub fn iter(&self) -> PoolIter {
+    /// Pushes an item on the pool, even if an equal item is already in the pool.
+    /// Looking up the index of an item will still return the first index.
+    /// This is used to keep the indices of a parsed class file intact.
+    pub fn push_duplicate(&mut self, item: Item) -> Result<u16> {
+        let size = if item.is_double() { 2 } else { 1 };
+        if u32::from(self.len()) + size > u32::from(u16::MAX) {
+            return Err(Error::CPTooLarge);
+        }
+
+        let index = self.length;
+        let rc_item = Rc::new(item);
+        self.by_index.push(Some(Rc::clone(&rc_item)));
+        if size == 2 {
+            // long and double take an additional space
+            self.by_index.push(None);
+        }
+        self.length += size as u16;
+
+        self.by_entry.entry(rc_item).or_insert(index);
+        Ok(index)
+    }
+
+    /// Returns the index of an item, which is already in the pool.
+    /// If it has not been pushed yet, None is returned.
+    pub fn index_of(&self, item: &Item) -> Option<u16> {
+        self.by_entry.get(item).cloned()
+    }
+
+    /// Keeps the name indices of the attributes of a parsed class,
+    /// which are given in the order they have been parsed,
+    /// so attributes referencing a duplicate of their name keep referencing it when written.
+    pub(super) fn keep_attribute_names(&mut self, indices: &[u16]) -> Result<()> {
+        let mut names: HashMap<u16, Vec<u16>> = HashMap::new();
+        for &index in indices {
+            let first = self.index_of(self.get(index)?).unwrap_or(index);
+            names.entry(first).or_default().push(index);
+        }
+        names.retain(|first, indices| indices.iter().any(|index| index != first));
+        self.attribute_names = names;
+        Ok(())
+    }
+
+    /// Returns the index of the name of an attribute,
+    /// which is written after `occurrence` other attributes with the same name.
+    /// A parsed attribute gets the index it has been parsed with,
+    /// every other attribute the first index of its name.
+    pub(super) fn attribute_name(&self, name: &str, occurrence: usize) -> Option<u16> {
+        let first = self.index_of(&Item::UTF8(name.to_owned()))?;
+        let parsed = self
+            .attribute_names
+            .get(&first)
+            .and_then(|indices| indices.get(occurrence));
+        Some(parsed.cloned().unwrap_or(first))
+    }
+
+    pub fn iter(&self) -> PoolIter<'_> {
         PoolIter {
             iter: self.by_index.iter(),
             index: 0,
@@ -451,7 +584,7 @@ impl<'a> Iterator for PoolIter<'a> {
         self.index += 1;
         if let Some(rc_item) = self.iter.next() {
             if let Some(ref item) = *rc_item {
-                Some((self.index + 1, item))
+                Some((self.index, item))
             } else {
                 self.next()
             }
@@ -479,11 +612,27 @@ mod tests {
         assert_eq!(pool.get(4).unwrap(), &Item::Long(65535));
         assert_eq!(pool.get(6).unwrap(), &Item::Float(3.8));
 
-        //let mut iter = pool.iter();
-        //assert_eq!(iter.next(), Some((1, &Item::Integer(123))));
-        //assert_eq!(iter.next(), Some((2, &Item::Long(32767))));
-        //assert_eq!(iter.next(), Some((4, &Item::Long(65535))));
-        //assert_eq!(iter.next(), Some((6, &Item::Float(3.8))));
-        //assert_eq!(iter.next(), None);
+        assert_eq!(pool.push_duplicate(Item::Long(32767)).unwrap(), 7);
+        assert_eq!(pool.push_duplicate(Item::Integer(1)).unwrap(), 9);
+        assert_eq!(pool.push(Item::Long(32767)).unwrap(), 2);
+        assert_eq!(pool.get(7).unwrap(), &Item::Long(32767));
+        assert_eq!(pool.len(), 10);
+
+        assert!(pool.get(0).is_err());
+        assert!(pool.get(3).is_err());
+        assert!(pool.get(8).is_err());
+        assert!(pool.get(10).is_err());
+
+        assert_eq!(pool.index_of(&Item::Long(65535)), Some(4));
+        assert_eq!(pool.index_of(&Item::Long(0)), None);
+
+        let mut iter = pool.iter();
+        assert_eq!(iter.next(), Some((1, &Item::Integer(123))));
+        assert_eq!(iter.next(), Some((2, &Item::Long(32767))));
+        assert_eq!(iter.next(), Some((4, &Item::Long(65535))));
+        assert_eq!(iter.next(), Some((6, &Item::Float(3.8))));
+        assert_eq!(iter.next(), Some((7, &Item::Long(32767))));
+        assert_eq!(iter.next(), Some((9, &Item::Integer(1))));
+        assert_eq!(iter.next(), None);
     }
 }
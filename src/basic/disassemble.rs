@@ -0,0 +1,1598 @@
+//! The disassemble module renders a class as text, similar to `javap -c -v -p`.
+//!
+//! Unlike javap every reference into the constant pool is rendered as its index,
+//! followed by the resolved item in a comment, and flags are rendered with their bits,
+//! so no information of the class is lost and `assemble` can read the text again.
+//! Every member is marked as a field or a method,
+//! since its descriptor does not have to match its kind.
+
+use source::*;
+use types::*;
+use super::constpool::*;
+use super::tree::*;
+use super::validate::ItemKind;
+
+/// The column at which comments start, if the text before is short enough.
+const COMMENT_COLUMN: usize = 40;
+
+pub(super) const CLASS_FLAGS: &[(u16, &str)] = &[
+    (0x0001, "ACC_PUBLIC"),
+    (0x0010, "ACC_FINAL"),
+    (0x0020, "ACC_SUPER"),
+    (0x0200, "ACC_INTERFACE"),
+    (0x0400, "ACC_ABSTRACT"),
+    (0x1000, "ACC_SYNTHETIC"),
+    (0x2000, "ACC_ANNOTATION"),
+    (0x4000, "ACC_ENUM"),
+    (0x8000, "ACC_MODULE"),
+];
+
+pub(super) const FIELD_FLAGS: &[(u16, &str)] = &[
+    (0x0001, "ACC_PUBLIC"),
+    (0x0002, "ACC_PRIVATE"),
+    (0x0004, "ACC_PROTECTED"),
+    (0x0008, "ACC_STATIC"),
+    (0x0010, "ACC_FINAL"),
+    (0x0040, "ACC_VOLATILE"),
+    (0x0080, "ACC_TRANSIENT"),
+    (0x1000, "ACC_SYNTHETIC"),
+    (0x4000, "ACC_ENUM"),
+];
+
+pub(super) const METHOD_FLAGS: &[(u16, &str)] = &[
+    (0x0001, "ACC_PUBLIC"),
+    (0x0002, "ACC_PRIVATE"),
+    (0x0004, "ACC_PROTECTED"),
+    (0x0008, "ACC_STATIC"),
+    (0x0010, "ACC_FINAL"),
+    (0x0020, "ACC_SYNCHRONIZED"),
+    (0x0040, "ACC_BRIDGE"),
+    (0x0080, "ACC_VARARGS"),
+    (0x0100, "ACC_NATIVE"),
+    (0x0400, "ACC_ABSTRACT"),
+    (0x0800, "ACC_STRICT"),
+    (0x1000, "ACC_SYNTHETIC"),
+];
+
+pub(super) const INNER_CLASS_FLAGS: &[(u16, &str)] = &[
+    (0x0001, "ACC_PUBLIC"),
+    (0x0002, "ACC_PRIVATE"),
+    (0x0004, "ACC_PROTECTED"),
+    (0x0008, "ACC_STATIC"),
+    (0x0010, "ACC_FINAL"),
+    (0x0200, "ACC_INTERFACE"),
+    (0x0400, "ACC_ABSTRACT"),
+    (0x1000, "ACC_SYNTHETIC"),
+    (0x2000, "ACC_ANNOTATION"),
+    (0x4000, "ACC_ENUM"),
+];
+
+pub(super) const PARAMETER_FLAGS: &[(u16, &str)] = &[
+    (0x0010, "ACC_FINAL"),
+    (0x1000, "ACC_SYNTHETIC"),
+    (0x8000, "ACC_MANDATED"),
+];
+
+pub(super) const MODULE_FLAGS: &[(u16, &str)] = &[
+    (0x0020, "ACC_OPEN"),
+    (0x1000, "ACC_SYNTHETIC"),
+    (0x8000, "ACC_MANDATED"),
+];
+
+pub(super) const REQUIRES_FLAGS: &[(u16, &str)] = &[
+    (0x0020, "ACC_TRANSITIVE"),
+    (0x0040, "ACC_STATIC_PHASE"),
+    (0x1000, "ACC_SYNTHETIC"),
+    (0x8000, "ACC_MANDATED"),
+];
+
+pub(super) const EXPORTS_FLAGS: &[(u16, &str)] = &[(0x1000, "ACC_SYNTHETIC"), (0x8000, "ACC_MANDATED")];
+
+/// Renders a class as text, similar to `javap -c -v -p`:
+/// the constant pool, every field and method with its code
+/// and every attribute, including the attributes of the code.
+///
+/// Invalid indices into the constant pool do not cause an error,
+/// but are marked as invalid in the comments.
+///
+/// # Examples
+///
+/// ```
+/// use jbcrs::basic;
+///
+/// let (pool, class) = basic::parse(include_bytes!("../../examples/resources/Fibonacci.class"))
+///     .unwrap();
+/// let text = basic::disassemble(&pool, &class);
+///
+/// assert!(text.starts_with("public class Fibonacci\n"));
+/// assert!(text.contains("Constant pool:"));
+/// assert!(text.contains("invokespecial #"));
+/// ```
+pub fn disassemble(constant_pool: &Pool, class: &Class) -> String {
+    let mut printer = Printer {
+        constant_pool,
+        out: String::new(),
+        indent: 0,
+    };
+    printer.class(class);
+    printer.out
+}
+
+/// Writes indented lines.
+struct Printer<'a> {
+    constant_pool: &'a Pool,
+    out: String,
+    indent: usize,
+}
+
+impl<'a> Printer<'a> {
+    fn line(&mut self, text: &str) {
+        for _ in 0..self.indent {
+            self.out.push_str("  ");
+        }
+        self.out.push_str(text.trim_end());
+        self.out.push('\n');
+    }
+
+    /// Writes a line with a comment, which is aligned if possible.
+    fn commented(&mut self, text: &str, comment: &str) {
+        if comment.is_empty() {
+            return self.line(text);
+        }
+
+        let mut line = text.to_owned();
+        let column = COMMENT_COLUMN.saturating_sub(self.indent * 2);
+        while line.len() < column {
+            line.push(' ');
+        }
+        line.push_str(" // ");
+        line.push_str(&comment.replace('\n', "\\n").replace('\r', "\\r"));
+        self.line(&line);
+    }
+
+    /// Returns the string at an index or a marker, if there is none.
+    fn utf8(&self, index: u16) -> String {
+        self.constant_pool
+            .get_utf8(index)
+            .unwrap_or_else(|_| invalid(index))
+    }
+
+    /// Resolves an item, like javap does in the comments of the constant pool.
+    fn describe(&self, index: u16) -> String {
+        let item = match self.constant_pool.get(index) {
+            Ok(item) => item,
+            Err(_) => return invalid(index),
+        };
+
+        match *item {
+            Item::UTF8(ref s) => s.clone(),
+            Item::UTF16(ref units) => utf16(units),
+            Item::Integer(value) => value.to_string(),
+            Item::Float(value) => float(value),
+            Item::Long(value) => format!("{}l", value),
+            Item::Double(value) => double(value),
+            Item::Class(index) | Item::MethodType(index) | Item::Module(index)
+            | Item::Package(index) => self.utf8(index),
+            Item::String(index) => match self.constant_pool.get(index) {
+                Ok(Item::UTF16(units)) => utf16(units),
+                _ => format!("{:?}", self.utf8(index)),
+            },
+            Item::FieldRef {
+                class,
+                name_and_type,
+            }
+            | Item::MethodRef {
+                class,
+                name_and_type,
+            }
+            | Item::InterfaceMethodRef {
+                class,
+                name_and_type,
+            } => format!("{}.{}", self.describe(class), self.describe(name_and_type)),
+            Item::NameAndType { name, desc } => {
+                let name = self.utf8(name);
+                if name.starts_with('<') {
+                    format!("\"{}\":{}", name, self.utf8(desc))
+                } else {
+                    format!("{}:{}", name, self.utf8(desc))
+                }
+            }
+            Item::MethodHandle { ref kind, index } => {
+                format!("{} {}", reference_kind(kind).1, self.describe(index))
+            }
+            Item::Dynamic {
+                bootstrap_method_attribute,
+                name_and_type,
+            }
+            | Item::InvokeDynamic {
+                bootstrap_method_attribute,
+                name_and_type,
+            } => format!(
+                "#{}:{}",
+                bootstrap_method_attribute,
+                self.describe(name_and_type)
+            ),
+        }
+    }
+
+    /// Resolves an item and prefixes it with its kind, like javap does for instructions.
+    fn describe_kind(&self, index: u16) -> String {
+        let kind = match self.constant_pool.get(index) {
+            Ok(&Item::UTF8(_)) | Ok(&Item::UTF16(_)) => "Utf8",
+            Ok(&Item::Integer(_)) => "int",
+            Ok(&Item::Float(_)) => "float",
+            Ok(&Item::Long(_)) => "long",
+            Ok(&Item::Double(_)) => "double",
+            Ok(&Item::Class(_)) => "class",
+            Ok(&Item::String(_)) => "String",
+            Ok(&Item::FieldRef { .. }) => "Field",
+            Ok(&Item::MethodRef { .. }) => "Method",
+            Ok(&Item::InterfaceMethodRef { .. }) => "InterfaceMethod",
+            Ok(&Item::NameAndType { .. }) => "NameAndType",
+            Ok(&Item::MethodHandle { .. }) => "MethodHandle",
+            Ok(&Item::MethodType(_)) => "MethodType",
+            Ok(&Item::Dynamic { .. }) => "Dynamic",
+            Ok(&Item::InvokeDynamic { .. }) => "InvokeDynamic",
+            Ok(&Item::Module(_)) => "Module",
+            Ok(&Item::Package(_)) => "Package",
+            Err(_) => return invalid(index),
+        };
+        format!("{} {}", kind, self.describe(index))
+    }
+
+    /// Resolves a class, which is optional.
+    fn describe_opt(&self, index: u16) -> String {
+        if index == 0 {
+            String::new()
+        } else {
+            self.describe(index)
+        }
+    }
+
+    fn class(&mut self, class: &Class) {
+        let name = self.utf8_class_name(class.name);
+
+        let declaration = self.class_declaration(&name, class);
+        self.line(&declaration);
+        self.indent += 1;
+        self.line(&format!("minor version: {}", class.minor_version));
+        self.line(&format!("major version: {}", class.major_version));
+        self.line(&format!(
+            "flags: {}",
+            flags(class.access_flags.bits(), CLASS_FLAGS)
+        ));
+        let comment = self.describe(class.name);
+        self.commented(&format!("this_class: #{}", class.name), &comment);
+        let comment = self.describe_opt(class.super_name);
+        self.commented(&format!("super_class: #{}", class.super_name), &comment);
+        if !class.interfaces.is_empty() {
+            self.commented(
+                &format!("interfaces: {}", indices(&class.interfaces)),
+                &self.describe_all(&class.interfaces),
+            );
+        }
+        self.indent -= 1;
+
+        self.constant_pool();
+
+        self.line("{");
+        self.indent += 1;
+        let members = class.fields.len() + class.methods.len();
+        for (i, field) in class.fields.iter().enumerate() {
+            self.field(field);
+            if i + 1 != members {
+                self.out.push('\n');
+            }
+        }
+        for (i, method) in class.methods.iter().enumerate() {
+            self.method(&name, method);
+            if class.fields.len() + i + 1 != members {
+                self.out.push('\n');
+            }
+        }
+        self.indent -= 1;
+        self.line("}");
+
+        self.attributes(&class.attributes, None);
+    }
+
+    /// Returns the name of a class, or a marker if it is invalid.
+    fn utf8_class_name(&self, index: u16) -> String {
+        self.constant_pool
+            .get_class_name(index)
+            .unwrap_or_else(|_| invalid(index))
+    }
+
+    /// Renders the declaration of a class in java source code.
+    fn class_declaration(&self, name: &str, class: &Class) -> String {
+        let style = SourceStyle::default();
+        let flags = class.access_flags;
+
+        if flags.contains(ClassAccessFlags::MODULE) {
+            return format!("module {}", style.class_name(name));
+        }
+
+        let keyword = if flags.contains(ClassAccessFlags::ANNOTATION) {
+            "@interface"
+        } else if flags.contains(ClassAccessFlags::INTERFACE) {
+            "interface"
+        } else {
+            "class"
+        };
+
+        let signature = self.signature(&class.attributes)
+            .and_then(|signature| signature.parse::<ClassSignature>().ok())
+            .unwrap_or_else(|| ClassSignature {
+                type_parameters: Vec::new(),
+                super_class: ClassTypeSignature::new(if class.super_name == 0 {
+                    "java/lang/Object".to_owned()
+                } else {
+                    self.utf8_class_name(class.super_name)
+                }),
+                interfaces: class
+                    .interfaces
+                    .iter()
+                    .map(|interface| ClassTypeSignature::new(self.utf8_class_name(*interface)))
+                    .collect(),
+            });
+        let declaration = signature.source_declaration(
+            name,
+            flags.contains(ClassAccessFlags::INTERFACE),
+            &style,
+        );
+
+        modifiers(&flags.to_string(), &format!("{} {}", keyword, declaration))
+    }
+
+    /// Returns the content of the `Signature` attribute, if there is one.
+    fn signature(&self, attributes: &[Attribute]) -> Option<String> {
+        attributes.iter().find_map(|attribute| match *attribute {
+            Attribute::Signature(index) => self.constant_pool.get_utf8(index).ok(),
+            _ => None,
+        })
+    }
+
+    /// Renders the type of a field or a record component in java source code,
+    /// preferring the signature over the descriptor.
+    fn field_type(&self, desc: &str, attributes: &[Attribute]) -> String {
+        let style = SourceStyle::default();
+        if let Some(signature) = self.signature(attributes) {
+            if let Ok(signature) = signature.parse::<FieldSignature>() {
+                return signature.to_source(&style);
+            }
+        }
+        match desc.parse::<TypeDescriptor>() {
+            Ok(desc) => desc.to_source(&style),
+            Err(_) => desc.to_owned(),
+        }
+    }
+
+    fn constant_pool(&mut self) {
+        self.line("Constant pool:");
+        self.indent += 1;
+        for (index, item) in self.constant_pool.iter() {
+            let kind = ItemKind::of(item).to_string();
+            let operands = match *item {
+                Item::UTF8(ref s) => format!("{:?}", s),
+                Item::UTF16(ref units) => utf16(units),
+                Item::Integer(value) => value.to_string(),
+                Item::Float(value) => float(value),
+                Item::Long(value) => format!("{}l", value),
+                Item::Double(value) => double(value),
+                Item::Class(index) | Item::String(index) | Item::MethodType(index)
+                | Item::Module(index) | Item::Package(index) => format!("#{}", index),
+                Item::FieldRef {
+                    class,
+                    name_and_type,
+                }
+                | Item::MethodRef {
+                    class,
+                    name_and_type,
+                }
+                | Item::InterfaceMethodRef {
+                    class,
+                    name_and_type,
+                } => format!("#{}.#{}", class, name_and_type),
+                Item::NameAndType { name, desc } => format!("#{}:#{}", name, desc),
+                Item::MethodHandle { ref kind, index } => {
+                    format!("{}:#{}", reference_kind(kind).0, index)
+                }
+                Item::Dynamic {
+                    bootstrap_method_attribute,
+                    name_and_type,
+                }
+                | Item::InvokeDynamic {
+                    bootstrap_method_attribute,
+                    name_and_type,
+                } => format!("#{}:#{}", bootstrap_method_attribute, name_and_type),
+            };
+            let comment = match *item {
+                Item::UTF8(_) | Item::UTF16(_) | Item::Integer(_) | Item::Float(_)
+                | Item::Long(_) | Item::Double(_) => String::new(),
+                _ => self.describe(index),
+            };
+
+            let number = format!("#{}", index);
+            self.commented(
+                &format!(
+                    "{:>5} = {:<18} {}",
+                    number,
+                    kind.trim_start_matches("CONSTANT_"),
+                    operands
+                ),
+                &comment,
+            );
+        }
+        self.indent -= 1;
+    }
+
+    fn field(&mut self, field: &Field) {
+        let name = self.utf8(field.name);
+        let desc = self.utf8(field.desc);
+        let field_type = self.field_type(&desc, &field.attributes);
+        self.line(&format!(
+            "{};",
+            modifiers(
+                &field.access_flags.to_string(),
+                &format!("{} {}", field_type, name)
+            )
+        ));
+
+        self.indent += 1;
+        self.line("kind: field");
+        self.commented(&format!("name: #{}", field.name), &name);
+        self.commented(&format!("descriptor: #{}", field.desc), &desc);
+        self.line(&format!(
+            "flags: {}",
+            flags(field.access_flags.bits(), FIELD_FLAGS)
+        ));
+        self.attributes(&field.attributes, None);
+        self.indent -= 1;
+    }
+
+    fn method(&mut self, class_name: &str, method: &Method) {
+        let style = SourceStyle::default();
+        let name = self.utf8(method.name);
+        let desc = self.utf8(method.desc);
+        let parsed_desc = desc.parse::<MethodDescriptor>().ok();
+
+        let declaration = if name == "<clinit>" {
+            "{}".to_owned()
+        } else if let (true, Some(parsed_desc)) = (name == "<init>", parsed_desc.as_ref()) {
+            let params: Vec<String> = parsed_desc
+                .params
+                .iter()
+                .map(|param| param.to_source(&style))
+                .collect();
+            format!("{}({})", style.class_name(class_name), params.join(", "))
+        } else if let Some(signature) = self.signature(&method.attributes)
+            .and_then(|signature| signature.parse::<MethodSignature>().ok())
+        {
+            signature.source_declaration(&name, &style)
+        } else if let Some(ref parsed_desc) = parsed_desc {
+            parsed_desc.source_declaration(&name, &style)
+        } else {
+            format!("{}{}", name, desc)
+        };
+        self.line(&format!(
+            "{};",
+            modifiers(&method.access_flags.to_string(), &declaration)
+        ));
+
+        // the receiver takes one slot as well
+        let args_size = parsed_desc.map(|desc| {
+            let receiver = if method.access_flags.contains(MethodAccessFlags::STATIC) {
+                0
+            } else {
+                1
+            };
+            desc.params.iter().map(TypeDescriptor::size).sum::<u16>() + receiver
+        });
+
+        self.indent += 1;
+        self.line("kind: method");
+        self.commented(&format!("name: #{}", method.name), &name);
+        self.commented(&format!("descriptor: #{}", method.desc), &desc);
+        self.line(&format!(
+            "flags: {}",
+            flags(method.access_flags.bits(), METHOD_FLAGS)
+        ));
+        self.attributes(&method.attributes, args_size);
+        self.indent -= 1;
+    }
+
+    /// Renders all attributes, `args_size` is rendered in the header of the code.
+    fn attributes(&mut self, attributes: &[Attribute], args_size: Option<u16>) {
+        for attribute in attributes {
+            self.attribute(attribute, args_size);
+        }
+    }
+
+    fn attribute(&mut self, attribute: &Attribute, args_size: Option<u16>) {
+        match *attribute {
+            Attribute::AnnotationDefault(ref value) => {
+                let comment = self.element_value_source(value);
+                self.commented(
+                    &format!("AnnotationDefault: {}", element_value_indices(value)),
+                    &comment,
+                );
+            }
+            Attribute::BootstrapMethods(ref methods) => {
+                self.line("BootstrapMethods:");
+                self.indent += 1;
+                for (i, method) in methods.iter().enumerate() {
+                    let comment = self.describe(method.method_ref);
+                    self.commented(&format!("{}: #{}", i, method.method_ref), &comment);
+                    self.indent += 1;
+                    self.line("Method arguments:");
+                    self.indent += 1;
+                    for argument in &method.arguments {
+                        let comment = self.describe_kind(*argument);
+                        self.commented(&format!("#{}", argument), &comment);
+                    }
+                    self.indent -= 2;
+                }
+                self.indent -= 1;
+            }
+            Attribute::Code {
+                max_stack,
+                max_locals,
+                ref instructions,
+                ref exceptions,
+                ref attributes,
+            } => {
+                self.line("Code:");
+                self.indent += 1;
+                let mut header = format!("stack={}, locals={}", max_stack, max_locals);
+                if let Some(args_size) = args_size {
+                    header.push_str(&format!(", args_size={}", args_size));
+                }
+                self.line(&header);
+                for (offset, insn) in instructions {
+                    self.instruction(offset, insn);
+                }
+                if !exceptions.is_empty() {
+                    self.line("Exception table:");
+                    self.indent += 1;
+                    self.line(" from    to  target type");
+                    for exception in exceptions {
+                        let catch_type = if exception.catch_type == 0 {
+                            "any".to_owned()
+                        } else {
+                            format!("#{}", exception.catch_type)
+                        };
+                        let comment = self.describe_opt(exception.catch_type);
+                        self.commented(
+                            &format!(
+                                "{:>5} {:>5} {:>5}   {}",
+                                exception.start, exception.end, exception.handler, catch_type
+                            ),
+                            &comment,
+                        );
+                    }
+                    self.indent -= 1;
+                }
+                self.attributes(attributes, None);
+                self.indent -= 1;
+            }
+            Attribute::ConstantValue(index) => {
+                let comment = self.describe_kind(index);
+                self.commented(&format!("ConstantValue: #{}", index), &comment);
+            }
+            Attribute::Deprecated => self.line("Deprecated: true"),
+            Attribute::EnclosingMethod {
+                class_index,
+                method_index,
+            } => {
+                let mut comment = self.describe(class_index);
+                if method_index != 0 {
+                    comment.push('.');
+                    comment.push_str(&self.describe(method_index));
+                }
+                self.commented(
+                    &format!("EnclosingMethod: #{}.#{}", class_index, method_index),
+                    &comment,
+                );
+            }
+            Attribute::Exceptions(ref exceptions) => {
+                self.line("Exceptions:");
+                self.indent += 1;
+                for exception in exceptions {
+                    let comment = self.describe(*exception);
+                    self.commented(&format!("throws #{}", exception), &comment);
+                }
+                self.indent -= 1;
+            }
+            Attribute::InnerClasses(ref inner_classes) => {
+                self.line("InnerClasses:");
+                self.indent += 1;
+                for inner_class in inner_classes {
+                    let mut comment = String::new();
+                    if inner_class.inner_name != 0 {
+                        comment.push_str(&self.utf8(inner_class.inner_name));
+                        comment.push('=');
+                    }
+                    comment.push_str("class ");
+                    comment.push_str(&self.describe(inner_class.inner_class_info));
+                    if inner_class.outer_class_info != 0 {
+                        comment.push_str(" of class ");
+                        comment.push_str(&self.describe(inner_class.outer_class_info));
+                    }
+
+                    self.commented(
+                        &format!(
+                            "{} #{}= #{} of #{};",
+                            flags(
+                                inner_class.inner_class_access_flags.bits(),
+                                INNER_CLASS_FLAGS
+                            ),
+                            inner_class.inner_name,
+                            inner_class.inner_class_info,
+                            inner_class.outer_class_info
+                        ),
+                        &comment,
+                    );
+                }
+                self.indent -= 1;
+            }
+            Attribute::LineNumberTable(ref line_numbers) => {
+                self.line("LineNumberTable:");
+                self.indent += 1;
+                for line_number in line_numbers {
+                    self.line(&format!(
+                        "line {}: {}",
+                        line_number.line_number, line_number.start
+                    ));
+                }
+                self.indent -= 1;
+            }
+            Attribute::LocalVariableTable(ref variables) => {
+                let variables: Vec<_> = variables
+                    .iter()
+                    .map(|v| (v.start, v.length, v.index, v.name, v.descriptor))
+                    .collect();
+                self.local_variables("LocalVariableTable", &variables);
+            }
+            Attribute::LocalVariableTypeTable(ref variables) => {
+                let variables: Vec<_> = variables
+                    .iter()
+                    .map(|v| (v.start, v.length, v.index, v.name, v.signature))
+                    .collect();
+                self.local_variables("LocalVariableTypeTable", &variables);
+            }
+            Attribute::MethodParameters(ref parameters) => {
+                self.line("MethodParameters:");
+                self.indent += 1;
+                for parameter in parameters {
+                    let comment = self.describe_opt(parameter.name);
+                    self.commented(
+                        &format!(
+                            "{} #{}",
+                            flags(parameter.access_flags.bits(), PARAMETER_FLAGS),
+                            parameter.name
+                        ),
+                        &comment,
+                    );
+                }
+                self.indent -= 1;
+            }
+            Attribute::Module { .. } => self.module(attribute),
+            Attribute::ModuleMainClass(index) => {
+                let comment = self.describe(index);
+                self.commented(&format!("ModuleMainClass: #{}", index), &comment);
+            }
+            Attribute::ModulePackages(ref packages) => self.list("ModulePackages", packages),
+            Attribute::NestHost(index) => {
+                let comment = self.describe(index);
+                self.commented(&format!("NestHost: #{}", index), &comment);
+            }
+            Attribute::NestMembers(ref members) => self.list("NestMembers", members),
+            Attribute::PermittedSubclasses(ref subclasses) => {
+                self.list("PermittedSubclasses", subclasses)
+            }
+            Attribute::Record(ref components) => {
+                self.line("Record:");
+                self.indent += 1;
+                for component in components {
+                    let name = self.utf8(component.name);
+                    let desc = self.utf8(component.descriptor);
+                    let component_type = self.field_type(&desc, &component.attributes);
+                    self.line(&format!("{} {};", component_type, name));
+
+                    self.indent += 1;
+                    self.commented(&format!("name: #{}", component.name), &name);
+                    self.commented(&format!("descriptor: #{}", component.descriptor), &desc);
+                    self.attributes(&component.attributes, None);
+                    self.indent -= 1;
+                }
+                self.indent -= 1;
+            }
+            Attribute::RuntimeVisibleAnnotations(ref annotations) => {
+                self.annotations("RuntimeVisibleAnnotations", annotations)
+            }
+            Attribute::RuntimeInvisibleAnnotations(ref annotations) => {
+                self.annotations("RuntimeInvisibleAnnotations", annotations)
+            }
+            Attribute::RuntimeVisibleParameterAnnotations(ref parameters) => {
+                self.parameter_annotations("RuntimeVisibleParameterAnnotations", parameters)
+            }
+            Attribute::RuntimeInvisibleParameterAnnotations(ref parameters) => {
+                self.parameter_annotations("RuntimeInvisibleParameterAnnotations", parameters)
+            }
+            Attribute::RuntimeVisibleTypeAnnotations(ref annotations) => {
+                self.type_annotations("RuntimeVisibleTypeAnnotations", annotations)
+            }
+            Attribute::RuntimeInvisibleTypeAnnotations(ref annotations) => {
+                self.type_annotations("RuntimeInvisibleTypeAnnotations", annotations)
+            }
+            Attribute::Signature(index) => {
+                let comment = self.describe(index);
+                self.commented(&format!("Signature: #{}", index), &comment);
+            }
+            Attribute::Synthetic => self.line("Synthetic: true"),
+            Attribute::SourceFile(index) => {
+                let comment = self.describe(index);
+                self.commented(&format!("SourceFile: #{}", index), &comment);
+            }
+            Attribute::SourceDebugExtension(ref extension) => {
+                self.line(&format!("SourceDebugExtension: {:?}", extension))
+            }
+            Attribute::StackMapTable(ref frames) => self.stack_map_table(frames),
+            Attribute::Unknown(name, ref bytes) => {
+                let comment = self.describe(name);
+                self.commented(
+                    &format!("Unknown #{}: length = {}", name, bytes.len()),
+                    &comment,
+                );
+                self.indent += 1;
+                for chunk in bytes.chunks(16) {
+                    let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+                    self.line(&hex.join(" "));
+                }
+                self.indent -= 1;
+            }
+        }
+    }
+
+    /// Renders an attribute, which is a list of indices.
+    fn list(&mut self, name: &str, indices: &[u16]) {
+        self.line(&format!("{}:", name));
+        self.indent += 1;
+        for index in indices {
+            let comment = self.describe(*index);
+            self.commented(&format!("#{}", index), &comment);
+        }
+        self.indent -= 1;
+    }
+
+    /// Renders the entries of a `LocalVariableTable` or a `LocalVariableTypeTable`,
+    /// given by start, length, slot, name and descriptor or signature.
+    fn local_variables(&mut self, name: &str, variables: &[(u16, u16, u16, u16, u16)]) {
+        self.line(&format!("{}:", name));
+        self.indent += 1;
+        self.line("Start  Length  Slot  Name   Signature");
+        for &(start, length, slot, name, desc) in variables {
+            let comment = format!("{} {}", self.utf8(name), self.utf8(desc));
+            self.commented(
+                &format!(
+                    "{:>5} {:>7} {:>5}  #{:<5} #{}",
+                    start, length, slot, name, desc
+                ),
+                &comment,
+            );
+        }
+        self.indent -= 1;
+    }
+
+    fn module(&mut self, module: &Attribute) {
+        let (name, module_flags, version, requires, exports, opens, uses, provides) = match *module {
+            Attribute::Module {
+                name,
+                flags,
+                version,
+                ref requires,
+                ref exports,
+                ref opens,
+                ref uses,
+                ref provides,
+            } => (name, flags, version, requires, exports, opens, uses, provides),
+            _ => return,
+        };
+
+        self.line("Module:");
+        self.indent += 1;
+        let comment = self.describe(name);
+        self.commented(&format!("name: #{}", name), &comment);
+        self.line(&format!(
+            "flags: {}",
+            flags(module_flags.bits(), MODULE_FLAGS)
+        ));
+        let comment = self.describe_opt(version);
+        self.commented(&format!("version: #{}", version), &comment);
+
+        self.line("requires:");
+        self.indent += 1;
+        for requirement in requires {
+            let mut comment = self.describe(requirement.index);
+            if requirement.version != 0 {
+                comment.push('@');
+                comment.push_str(&self.describe(requirement.version));
+            }
+            self.commented(
+                &format!(
+                    "{} #{} version #{}",
+                    flags(requirement.flags.bits(), REQUIRES_FLAGS),
+                    requirement.index,
+                    requirement.version
+                ),
+                &comment,
+            );
+        }
+        self.indent -= 1;
+
+        let exports: Vec<_> = exports
+            .iter()
+            .map(|export| (export.index, export.flags, &export.to[..]))
+            .collect();
+        self.module_packages("exports", &exports);
+        let opens: Vec<_> = opens
+            .iter()
+            .map(|opening| (opening.index, opening.flags, &opening.to[..]))
+            .collect();
+        self.module_packages("opens", &opens);
+
+        self.line("uses:");
+        self.indent += 1;
+        for class in uses {
+            let comment = self.describe(*class);
+            self.commented(&format!("#{}", class), &comment);
+        }
+        self.indent -= 1;
+
+        self.line("provides:");
+        self.indent += 1;
+        for provider in provides {
+            let comment = format!(
+                "{} with {}",
+                self.describe(provider.index),
+                self.describe_all(&provider.with)
+            );
+            self.commented(
+                &format!("#{} with {}", provider.index, indices(&provider.with)),
+                &comment,
+            );
+        }
+        self.indent -= 1;
+        self.indent -= 1;
+    }
+
+    /// Renders the packages exported or opened by a module.
+    fn module_packages(&mut self, name: &str, packages: &[(u16, ExportsFlags, &[u16])]) {
+        self.line(&format!("{}:", name));
+        self.indent += 1;
+        for &(index, package_flags, to) in packages {
+            let mut text = format!("{} #{}", flags(package_flags.bits(), EXPORTS_FLAGS), index);
+            let mut comment = self.describe(index);
+            if !to.is_empty() {
+                text.push_str(" to ");
+                text.push_str(&indices(to));
+                comment.push_str(" to ");
+                comment.push_str(&self.describe_all(to));
+            }
+            self.commented(&text, &comment);
+        }
+        self.indent -= 1;
+    }
+
+    /// Resolves every index and separates them by `, `.
+    fn describe_all(&self, indices: &[u16]) -> String {
+        let described: Vec<String> = indices.iter().map(|index| self.describe(*index)).collect();
+        described.join(", ")
+    }
+
+    fn instruction(&mut self, offset: u32, insn: &Instruction) {
+        match *insn {
+            Instruction::TableSwitch {
+                default,
+                low,
+                high,
+                ref offsets,
+                ref padding,
+            } => {
+                self.commented(
+                    &format!("{:>5}: tableswitch {}{{", offset, switch_padding(offset, padding)),
+                    &format!("{} to {}", low, high),
+                );
+                for (i, relative) in offsets.iter().enumerate() {
+                    self.line(&format!(
+                        "{:>19}: {}",
+                        i64::from(low) + i as i64,
+                        target(offset, *relative)
+                    ));
+                }
+                self.line(&format!("{:>19}: {}", "default", target(offset, default)));
+                self.line("       }");
+            }
+            Instruction::LookupSwitch {
+                default,
+                ref offsets,
+                ref padding,
+            } => {
+                self.commented(
+                    &format!("{:>5}: lookupswitch {}{{", offset, switch_padding(offset, padding)),
+                    &offsets.len().to_string(),
+                );
+                for (key, relative) in offsets {
+                    self.line(&format!("{:>19}: {}", key, target(offset, *relative)));
+                }
+                self.line(&format!("{:>19}: {}", "default", target(offset, default)));
+                self.line("       }");
+            }
+            _ => {
+                let (text, comment) = self.instruction_text(offset, insn);
+                self.commented(&format!("{:>5}: {}", offset, text), &comment);
+            }
+        }
+    }
+
+    /// Renders an instruction, which is not a switch, and the comment describing its operand.
+    fn instruction_text(&self, offset: u32, insn: &Instruction) -> (String, String) {
+        use self::Instruction::*;
+
+        let mnemonic = insn.mnemonic();
+        match *insn {
+            BIPush(value) => (format!("{} {}", mnemonic, value), String::new()),
+            SIPush(value) => (format!("{} {}", mnemonic, value), String::new()),
+            LDC(index) | LDCW(index) | LDC2W(index) | GetStatic(index) | PutStatic(index)
+            | GetField(index) | PutField(index) | InvokeVirtual(index)
+            | InvokeSpecial(index) | InvokeStatic(index) | InvokeDynamic(index, 0)
+            | New(index) | ANewArray(index) | CheckCast(index) | InstanceOf(index) => (
+                format!("{} #{}", mnemonic, index),
+                self.describe_kind(index),
+            ),
+            ILoad(index) | LLoad(index) | FLoad(index) | DLoad(index) | ALoad(index)
+            | IStore(index) | LStore(index) | FStore(index) | DStore(index)
+            | AStore(index) | Ret(index) => (format!("{} {}", mnemonic, index), String::new()),
+            IInc(index, value) => (format!("{} {}, {}", mnemonic, index, value), String::new()),
+            IfEq(relative) | IfNE(relative) | IfLT(relative) | IfGE(relative)
+            | IfGT(relative) | IfLE(relative) | IfICmpEq(relative) | IfICmpNE(relative)
+            | IfICmpLT(relative) | IfICmpGE(relative) | IfICmpGT(relative)
+            | IfICmpLE(relative) | IfACmpEq(relative) | IfACmpNE(relative)
+            | IfNull(relative) | IfNonNull(relative) => (
+                format!("{} {}", mnemonic, target(offset, i32::from(relative))),
+                String::new(),
+            ),
+            GoTo(relative) | JSR(relative) | GoToW(relative) | JSRW(relative) => (
+                format!("{} {}", mnemonic, target(offset, relative)),
+                String::new(),
+            ),
+            InvokeInterface(index, count, 0) => (
+                format!("{} #{}, {}", mnemonic, index, count),
+                self.describe_kind(index),
+            ),
+            // the reserved operands are only rendered, if they have been set
+            InvokeInterface(index, count, reserved) => (
+                format!("{} #{}, {}, {}", mnemonic, index, count, reserved),
+                self.describe_kind(index),
+            ),
+            InvokeDynamic(index, reserved) => (
+                format!("{} #{}, {}", mnemonic, index, reserved),
+                self.describe_kind(index),
+            ),
+            MultiANewArray(index, dimensions) => (
+                format!("{} #{}, {}", mnemonic, index, dimensions),
+                self.describe_kind(index),
+            ),
+            NewArray(ref array_type) => (
+                format!("{} {}", mnemonic, array_type_name(array_type)),
+                String::new(),
+            ),
+            Wide(ref insn) => {
+                let (text, comment) = self.instruction_text(offset, insn);
+                (format!("{} {}", mnemonic, text), comment)
+            }
+            _ => (mnemonic.to_owned(), String::new()),
+        }
+    }
+
+    fn stack_map_table(&mut self, frames: &[StackMapFrame]) {
+        self.line(&format!(
+            "StackMapTable: number_of_entries = {}",
+            frames.len()
+        ));
+        self.indent += 1;
+        for frame in frames {
+            match *frame {
+                StackMapFrame::Same {
+                    offset_delta,
+                    extended,
+                } => {
+                    self.line(if extended {
+                        "frame_type = same_extended"
+                    } else {
+                        "frame_type = same"
+                    });
+                    self.indent += 1;
+                    self.line(&format!("offset_delta = {}", offset_delta));
+                }
+                StackMapFrame::Same1 {
+                    offset_delta,
+                    ref stack,
+                    extended,
+                } => {
+                    self.line(if extended {
+                        "frame_type = same_locals_1_stack_item_extended"
+                    } else {
+                        "frame_type = same_locals_1_stack_item"
+                    });
+                    self.indent += 1;
+                    self.line(&format!("offset_delta = {}", offset_delta));
+                    self.verification_types("stack", ::std::slice::from_ref(stack));
+                }
+                StackMapFrame::Chop {
+                    offset_delta,
+                    count,
+                } => {
+                    self.line("frame_type = chop");
+                    self.indent += 1;
+                    self.line(&format!("offset_delta = {}", offset_delta));
+                    self.line(&format!("count = {}", count));
+                }
+                StackMapFrame::Append {
+                    offset_delta,
+                    ref locals,
+                } => {
+                    self.line("frame_type = append");
+                    self.indent += 1;
+                    self.line(&format!("offset_delta = {}", offset_delta));
+                    self.verification_types("locals", locals);
+                }
+                StackMapFrame::Full {
+                    offset_delta,
+                    ref locals,
+                    ref stack,
+                } => {
+                    self.line("frame_type = full_frame");
+                    self.indent += 1;
+                    self.line(&format!("offset_delta = {}", offset_delta));
+                    self.verification_types("locals", locals);
+                    self.verification_types("stack", stack);
+                }
+            }
+            self.indent -= 1;
+        }
+        self.indent -= 1;
+    }
+
+    /// Renders the locals or the stack of a frame,
+    /// the classes are resolved in a comment.
+    fn verification_types(&mut self, name: &str, types: &[VerificationType]) {
+        let mut text = Vec::with_capacity(types.len());
+        let mut comment = Vec::with_capacity(types.len());
+        let mut resolved = false;
+        for verification_type in types {
+            let (t, c) = match *verification_type {
+                VerificationType::Top => ("top".to_owned(), "top".to_owned()),
+                VerificationType::Integer => ("int".to_owned(), "int".to_owned()),
+                VerificationType::Float => ("float".to_owned(), "float".to_owned()),
+                VerificationType::Double => ("double".to_owned(), "double".to_owned()),
+                VerificationType::Long => ("long".to_owned(), "long".to_owned()),
+                VerificationType::Null => ("null".to_owned(), "null".to_owned()),
+                VerificationType::UninitializedThis => (
+                    "uninitialized_this".to_owned(),
+                    "uninitialized_this".to_owned(),
+                ),
+                VerificationType::Object(index) => {
+                    resolved = true;
+                    (
+                        format!("class #{}", index),
+                        format!("class {}", self.describe(index)),
+                    )
+                }
+                VerificationType::Uninitialized(offset) => (
+                    format!("uninitialized {}", offset),
+                    format!("uninitialized {}", offset),
+                ),
+            };
+            text.push(t);
+            comment.push(c);
+        }
+
+        let text = format!("{} = {}", name, bracketed(&text));
+        if resolved {
+            self.commented(&text, &bracketed(&comment));
+        } else {
+            self.line(&text);
+        }
+    }
+
+    fn annotations(&mut self, name: &str, annotations: &[Annotation]) {
+        self.line(&format!("{}:", name));
+        self.indent += 1;
+        for (i, annotation) in annotations.iter().enumerate() {
+            let comment = self.annotation_source(annotation);
+            self.commented(
+                &format!("{}: {}", i, annotation_indices(annotation)),
+                &comment,
+            );
+        }
+        self.indent -= 1;
+    }
+
+    fn parameter_annotations(&mut self, name: &str, parameters: &[Vec<Annotation>]) {
+        self.line(&format!("{}:", name));
+        self.indent += 1;
+        for (i, annotations) in parameters.iter().enumerate() {
+            self.line(&format!("parameter {}:", i));
+            self.indent += 1;
+            for (j, annotation) in annotations.iter().enumerate() {
+                let comment = self.annotation_source(annotation);
+                self.commented(
+                    &format!("{}: {}", j, annotation_indices(annotation)),
+                    &comment,
+                );
+            }
+            self.indent -= 1;
+        }
+        self.indent -= 1;
+    }
+
+    fn type_annotations(&mut self, name: &str, annotations: &[TypeAnnotation]) {
+        self.line(&format!("{}:", name));
+        self.indent += 1;
+        for (i, annotation) in annotations.iter().enumerate() {
+            let mut text = format!(
+                "{}: {}: {}",
+                i,
+                annotation_indices(&annotation.annotation),
+                target_type(&annotation.target_type)
+            );
+            if !annotation.target_path.is_empty() {
+                let path: Vec<String> = annotation
+                    .target_path
+                    .iter()
+                    .map(type_path_element)
+                    .collect();
+                text.push_str(&format!(", location=[{}]", path.join(", ")));
+            }
+            let comment = self.annotation_source(&annotation.annotation);
+            self.commented(&text, &comment);
+        }
+        self.indent -= 1;
+    }
+
+    /// Renders an annotation like in java source code.
+    fn annotation_source(&self, annotation: &Annotation) -> String {
+        let mut source = self.descriptor_source(annotation.type_index);
+        if !annotation.element_value_pairs.is_empty() {
+            let pairs: Vec<String> = annotation
+                .element_value_pairs
+                .iter()
+                .map(|(name, value)| {
+                    format!("{}={}", self.utf8(*name), self.element_value_source(value))
+                })
+                .collect();
+            source.push('(');
+            source.push_str(&pairs.join(", "));
+            source.push(')');
+        }
+        source
+    }
+
+    /// Renders the field descriptor at an index in java source code.
+    fn descriptor_source(&self, index: u16) -> String {
+        let desc = self.utf8(index);
+        match desc.parse::<TypeDescriptor>() {
+            Ok(desc) => desc.to_source(&SourceStyle::default()),
+            Err(_) => desc,
+        }
+    }
+
+    /// Renders an element value like in java source code.
+    fn element_value_source(&self, value: &ElementValue) -> String {
+        match *value {
+            ElementValue::Byte(index)
+            | ElementValue::Short(index)
+            | ElementValue::Int(index)
+            | ElementValue::Long(index)
+            | ElementValue::Float(index)
+            | ElementValue::Double(index) => self.describe(index),
+            ElementValue::Char(index) => match self.constant_pool.get(index) {
+                Ok(&Item::Integer(value)) => match ::std::char::from_u32(value as u32) {
+                    Some(ch) => format!("{:?}", ch),
+                    None => value.to_string(),
+                },
+                _ => invalid(index),
+            },
+            ElementValue::Boolean(index) => match self.constant_pool.get(index) {
+                Ok(&Item::Integer(value)) => (value != 0).to_string(),
+                _ => invalid(index),
+            },
+            ElementValue::String(index) => format!("{:?}", self.utf8(index)),
+            ElementValue::Enum {
+                type_name,
+                const_name,
+            } => format!(
+                "{}.{}",
+                self.descriptor_source(type_name),
+                self.utf8(const_name)
+            ),
+            ElementValue::Class(index) => {
+                if self.utf8(index) == "V" {
+                    "void.class".to_owned()
+                } else {
+                    format!("{}.class", self.descriptor_source(index))
+                }
+            }
+            ElementValue::Annotation(ref annotation) => {
+                format!("@{}", self.annotation_source(annotation))
+            }
+            ElementValue::Array(ref values) => {
+                let values: Vec<String> = values
+                    .iter()
+                    .map(|value| self.element_value_source(value))
+                    .collect();
+                format!("[{}]", values.join(", "))
+            }
+        }
+    }
+}
+
+/// Returns a marker for an invalid index.
+fn invalid(index: u16) -> String {
+    format!("<invalid #{}>", index)
+}
+
+/// Renders flags with their bits and their names.
+fn flags(bits: u16, names: &[(u16, &str)]) -> String {
+    let mut text = format!("(0x{:04x})", bits);
+    let mut first = true;
+    for &(flag, name) in names {
+        if bits & flag != 0 {
+            text.push_str(if first { " " } else { ", " });
+            text.push_str(name);
+            first = false;
+        }
+    }
+    text
+}
+
+/// Prefixes a declaration with modifiers, if there are any.
+fn modifiers(modifiers: &str, declaration: &str) -> String {
+    if modifiers.is_empty() {
+        declaration.to_owned()
+    } else {
+        format!("{} {}", modifiers, declaration)
+    }
+}
+
+/// Renders indices separated by `, `.
+fn indices(indices: &[u16]) -> String {
+    let indices: Vec<String> = indices.iter().map(|index| format!("#{}", index)).collect();
+    indices.join(", ")
+}
+
+/// Renders a list like javap does in stack map frames.
+fn bracketed(items: &[String]) -> String {
+    if items.is_empty() {
+        "[]".to_owned()
+    } else {
+        format!("[ {} ]", items.join(", "))
+    }
+}
+
+/// Renders a float, so it can be read again without losing any information.
+/// Formats UTF-16 code units like a quoted string,
+/// unpaired surrogates are escaped like `\u{d800}`.
+fn utf16(units: &[u16]) -> String {
+    let mut out = String::from("\"");
+    let mut chars = String::new();
+    for unit in ::std::char::decode_utf16(units.iter().cloned()) {
+        match unit {
+            Ok(c) => chars.push(c),
+            Err(error) => {
+                let quoted = format!("{:?}", chars);
+                out.push_str(&quoted[1..quoted.len() - 1]);
+                out.push_str(&format!("\\u{{{:x}}}", error.unpaired_surrogate()));
+                chars.clear();
+            }
+        }
+    }
+    let quoted = format!("{:?}", chars);
+    out.push_str(&quoted[1..]);
+    out
+}
+
+fn float(value: f32) -> String {
+    if value.is_nan() {
+        format!("NaN(0x{:08x})", value.to_bits())
+    } else if value.is_infinite() {
+        if value > 0.0 { "Infinity" } else { "-Infinity" }.to_owned()
+    } else {
+        format!("{:?}f", value)
+    }
+}
+
+/// Renders a double, so it can be read again without losing any information.
+fn double(value: f64) -> String {
+    if value.is_nan() {
+        format!("NaN(0x{:016x})", value.to_bits())
+    } else if value.is_infinite() {
+        if value > 0.0 { "Infinity" } else { "-Infinity" }.to_owned()
+    } else {
+        format!("{:?}d", value)
+    }
+}
+
+/// Computes the absolute target of a jump.
+fn target(offset: u32, relative: i32) -> i64 {
+    i64::from(offset) + i64::from(relative)
+}
+
+/// Renders the padding of a switch located at `offset`, if it is not zero.
+fn switch_padding(offset: u32, padding: &[u8; 3]) -> String {
+    let padding = &padding[..(3 - (offset & 3)) as usize];
+    if padding.iter().all(|byte| *byte == 0) {
+        return String::new();
+    }
+
+    let bytes: Vec<String> = padding.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("padding {} ", bytes.join(" "))
+}
+
+/// Returns the number and the name of a reference kind.
+fn reference_kind(kind: &ReferenceKind) -> (u8, &'static str) {
+    match *kind {
+        ReferenceKind::GetField => (1, "REF_getField"),
+        ReferenceKind::GetStatic => (2, "REF_getStatic"),
+        ReferenceKind::PutField => (3, "REF_putField"),
+        ReferenceKind::PutStatic => (4, "REF_putStatic"),
+        ReferenceKind::InvokeVirtual => (5, "REF_invokeVirtual"),
+        ReferenceKind::InvokeStatic => (6, "REF_invokeStatic"),
+        ReferenceKind::InvokeSpecial => (7, "REF_invokeSpecial"),
+        ReferenceKind::NewInvokeSpecial => (8, "REF_newInvokeSpecial"),
+        ReferenceKind::InvokeInterface => (9, "REF_invokeInterface"),
+    }
+}
+
+fn array_type_name(array_type: &ArrayType) -> &'static str {
+    match *array_type {
+        ArrayType::Boolean => "boolean",
+        ArrayType::Char => "char",
+        ArrayType::Float => "float",
+        ArrayType::Double => "double",
+        ArrayType::Byte => "byte",
+        ArrayType::Short => "short",
+        ArrayType::Int => "int",
+        ArrayType::Long => "long",
+    }
+}
+
+/// Renders an annotation with indices into the constant pool, like javap does.
+fn annotation_indices(annotation: &Annotation) -> String {
+    let pairs: Vec<String> = annotation
+        .element_value_pairs
+        .iter()
+        .map(|(name, value)| format!("#{}={}", name, element_value_indices(value)))
+        .collect();
+    format!("#{}({})", annotation.type_index, pairs.join(","))
+}
+
+/// Renders an element value with its tag and indices into the constant pool.
+fn element_value_indices(value: &ElementValue) -> String {
+    match *value {
+        ElementValue::Byte(index) => format!("B#{}", index),
+        ElementValue::Short(index) => format!("S#{}", index),
+        ElementValue::Char(index) => format!("C#{}", index),
+        ElementValue::Int(index) => format!("I#{}", index),
+        ElementValue::Long(index) => format!("J#{}", index),
+        ElementValue::Float(index) => format!("F#{}", index),
+        ElementValue::Double(index) => format!("D#{}", index),
+        ElementValue::Boolean(index) => format!("Z#{}", index),
+        ElementValue::String(index) => format!("s#{}", index),
+        ElementValue::Enum {
+            type_name,
+            const_name,
+        } => format!("e#{}.#{}", type_name, const_name),
+        ElementValue::Class(index) => format!("c#{}", index),
+        ElementValue::Annotation(ref annotation) => format!("@{}", annotation_indices(annotation)),
+        ElementValue::Array(ref values) => {
+            let values: Vec<String> = values.iter().map(element_value_indices).collect();
+            format!("[{}]", values.join(","))
+        }
+    }
+}
+
+/// Renders the target of a type annotation, like javap does.
+fn target_type(target_type: &TargetType) -> String {
+    fn local_variables(name: &str, targets: &[LocalVariableTarget]) -> String {
+        let targets: Vec<String> = targets
+            .iter()
+            .map(|target| {
+                format!(
+                    "{{start_pc={}, length={}, index={}}}",
+                    target.start, target.length, target.index
+                )
+            })
+            .collect();
+        format!("{}, {}", name, targets.join(", "))
+    }
+
+    match *target_type {
+        TargetType::TypeParameterClass(index) => {
+            format!("CLASS_TYPE_PARAMETER, param_index={}", index)
+        }
+        TargetType::TypeParameterMethod(index) => {
+            format!("METHOD_TYPE_PARAMETER, param_index={}", index)
+        }
+        TargetType::SuperType(index) => format!("CLASS_EXTENDS, type_index={}", index),
+        TargetType::TypeParameterBoundClass {
+            type_parameter,
+            bound_index,
+        } => format!(
+            "CLASS_TYPE_PARAMETER_BOUND, param_index={}, bound_index={}",
+            type_parameter, bound_index
+        ),
+        TargetType::TypeParameterBoundMethod {
+            type_parameter,
+            bound_index,
+        } => format!(
+            "METHOD_TYPE_PARAMETER_BOUND, param_index={}, bound_index={}",
+            type_parameter, bound_index
+        ),
+        TargetType::EmptyField => "FIELD".to_owned(),
+        TargetType::EmptyReturn => "METHOD_RETURN".to_owned(),
+        TargetType::EmptyReceiver => "METHOD_RECEIVER".to_owned(),
+        TargetType::FormalParameter(index) => {
+            format!("METHOD_FORMAL_PARAMETER, param_index={}", index)
+        }
+        TargetType::Throws(index) => format!("THROWS, type_index={}", index),
+        TargetType::LocalVariable(ref targets) => local_variables("LOCAL_VARIABLE", targets),
+        TargetType::ResourceVariable(ref targets) => {
+            local_variables("RESOURCE_VARIABLE", targets)
+        }
+        TargetType::Catch(index) => format!("EXCEPTION_PARAMETER, exception_index={}", index),
+        TargetType::OffsetInstanceOf(offset) => format!("INSTANCEOF, offset={}", offset),
+        TargetType::OffsetNew(offset) => format!("NEW, offset={}", offset),
+        TargetType::OffsetNewRef(offset) => format!("CONSTRUCTOR_REFERENCE, offset={}", offset),
+        TargetType::OffsetRef(offset) => format!("METHOD_REFERENCE, offset={}", offset),
+        TargetType::TypeArgumentCast {
+            offset,
+            type_argument,
+        } => format!("CAST, offset={}, type_index={}", offset, type_argument),
+        TargetType::TypeArgumentMethod {
+            offset,
+            type_argument,
+        } => format!(
+            "METHOD_INVOCATION_TYPE_ARGUMENT, offset={}, type_index={}",
+            offset, type_argument
+        ),
+        TargetType::TypeArgumentConstructor {
+            offset,
+            type_argument,
+        } => format!(
+            "CONSTRUCTOR_INVOCATION_TYPE_ARGUMENT, offset={}, type_index={}",
+            offset, type_argument
+        ),
+        TargetType::TypeArgumentNewRef {
+            offset,
+            type_argument,
+        } => format!(
+            "CONSTRUCTOR_REFERENCE_TYPE_ARGUMENT, offset={}, type_index={}",
+            offset, type_argument
+        ),
+        TargetType::TypeArgumentRef {
+            offset,
+            type_argument,
+        } => format!(
+            "METHOD_REFERENCE_TYPE_ARGUMENT, offset={}, type_index={}",
+            offset, type_argument
+        ),
+    }
+}
+
+/// Renders an element of the path of a type annotation, like javap does.
+/// The argument index is only rendered, if it is not 0 or the element is a type argument.
+fn type_path_element(element: &TypePathElement) -> String {
+    let name = match element.path_kind {
+        TypePathKind::ArrayType => "ARRAY",
+        TypePathKind::NestedType => "INNER_TYPE",
+        TypePathKind::WildcardType => "WILDCARD",
+        TypePathKind::Type => "TYPE_ARGUMENT",
+    };
+    match element.path_kind {
+        TypePathKind::Type => format!("{}({})", name, element.argument_index),
+        _ if element.argument_index != 0 => format!("{}({})", name, element.argument_index),
+        _ => name.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::parse;
+    use super::*;
+
+    #[test]
+    fn fibonacci() {
+        let (pool, class) = parse(include_bytes!("../../examples/resources/Fibonacci.class")).unwrap();
+        let text = disassemble(&pool, &class);
+
+        for expected in &[
+            "  flags: (0x0021) ACC_PUBLIC, ACC_SUPER\n",
+            "     #1 = Methodref          #9.#21      // java/lang/Object.\"<init>\":()V\n",
+            "     #3 = String             #24         // \"Not enough arguments provided\"\n",
+            "    #10 = Utf8               \"<init>\"\n",
+            "  public static void main(java.lang.String[]);\n    kind: method\n    name: #14",
+            "          2: ifne 14\n",
+            "          8: ldc #3                      // String \"Not enough arguments provided\"\n",
+            "         21: iinc 3, 1\n         24: goto 6\n",
+            "      StackMapTable: number_of_entries = 2\n\
+             \x20       frame_type = append\n\
+             \x20         offset_delta = 6\n\
+             \x20         locals = [ int, int, int ]\n\
+             \x20       frame_type = chop\n\
+             \x20         offset_delta = 20\n\
+             \x20         count = 1\n\
+             }\n\
+             SourceFile: #20                          // Fibonacci.java\n",
+        ] {
+            assert!(text.contains(expected), "{:?} not found in\n{}", expected, text);
+        }
+    }
+
+    #[test]
+    fn inner_class() {
+        let bytes = include_bytes!("../../examples/resources/CustomLinkedList$Element.class");
+        let (pool, class) = parse(bytes).unwrap();
+        let text = disassemble(&pool, &class);
+
+        assert!(text.contains("  CustomLinkedList<E>.Element<E> next;\n"), "{}", text);
+    }
+
+    #[test]
+    fn invalid_indices() {
+        let mut pool = Pool::new();
+        let name = pool.push(Item::UTF8("Broken".to_owned())).unwrap();
+        let name = pool.push(Item::Class(name)).unwrap();
+        let class = Class {
+            minor_version: 0,
+            major_version: 52,
+            access_flags: ClassAccessFlags::PUBLIC,
+            name,
+            super_name: 0,
+            interfaces: vec![42],
+            fields: Vec::new(),
+            methods: Vec::new(),
+            attributes: vec![Attribute::Unknown(name, (0..20).collect())],
+        };
+
+        let text = disassemble(&pool, &class);
+        assert!(text.contains("  interfaces: #42                        // <invalid #42>\n"));
+        assert!(text.contains(
+            "Unknown #2: length = 20                  // Broken\n\
+             \x20 00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e 0f\n\
+             \x20 10 11 12 13\n"
+        ));
+    }
+
+    #[test]
+    fn resources() {
+        for bytes in &[
+            &include_bytes!("../../examples/resources/AnyAnnotation.class")[..],
+            &include_bytes!("../../examples/resources/CustomLinkedList.class")[..],
+            &include_bytes!("../../examples/resources/CustomLinkedList$Element.class")[..],
+            &include_bytes!("../../examples/resources/CustomLinkedList$Iter.class")[..],
+            &include_bytes!("../../examples/resources/FizzBuzzStream.class")[..],
+            &include_bytes!("../../examples/resources/RoundTrip.class")[..],
+            &include_bytes!("../../examples/resources/Nestmates.class")[..],
+            &include_bytes!("../../examples/resources/Records.class")[..],
+            &include_bytes!("../../examples/resources/Sealed.class")[..],
+            &include_bytes!("../../examples/resources/Surrogates.class")[..],
+        ] {
+            let (pool, class) = parse(bytes).unwrap();
+            let text = disassemble(&pool, &class);
+            assert!(!text.contains("<invalid"), "{}", text);
+            assert_eq!(text.lines().filter(|line| *line == "{").count(), 1);
+        }
+    }
+}
@@ -0,0 +1,41 @@
+//! The fixture module creates classes for the tests of analyses of multiple classes.
+
+use super::constpool::*;
+use super::tree::*;
+
+/// Pushes the name of a class and returns the index of its `Item::Class(_)`
+pub fn push_class(pool: &mut Pool, name: &str) -> u16 {
+    let name = pool.push(Item::UTF8(name.to_owned())).unwrap();
+    pool.push(Item::Class(name)).unwrap()
+}
+
+/// Creates a class without members and attributes,
+/// extending a super class (if any) and implementing interfaces
+pub fn class(
+    major_version: u16,
+    access_flags: ClassAccessFlags,
+    name: &str,
+    super_name: Option<&str>,
+    interfaces: &[&str],
+) -> (Pool, Class) {
+    let mut pool = Pool::new();
+    let name = push_class(&mut pool, name);
+    let super_name = super_name.map_or(0, |super_name| push_class(&mut pool, super_name));
+    let interfaces = interfaces
+        .iter()
+        .map(|interface| push_class(&mut pool, interface))
+        .collect();
+
+    let class = Class {
+        minor_version: 0,
+        major_version,
+        access_flags,
+        name,
+        super_name,
+        interfaces,
+        fields: Vec::new(),
+        methods: Vec::new(),
+        attributes: Vec::new(),
+    };
+    (pool, class)
+}
@@ -0,0 +1,445 @@
+//! The hierarchy module links a set of classes to their super classes,
+//! interfaces and subtypes, to answer subtype queries without reading any class again.
+
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+use super::analysis::ClassResolver;
+use super::constpool::*;
+use super::tree::*;
+use super::OBJECT;
+use result::*;
+use types::{Type, TypeDescriptor};
+
+const CLONEABLE: &str = "java/lang/Cloneable";
+const SERIALIZABLE: &str = "java/io/Serializable";
+
+/// The direct supertypes of a single class.
+#[derive(Debug)]
+struct Node {
+    super_name: Option<String>,
+    interfaces: Vec<String>,
+    is_interface: bool,
+}
+
+/// The super classes, interfaces and subtypes of every class in a set of classes.
+///
+/// Classes are allowed to extend or implement classes, which are not part of the set.
+/// Such a missing class is treated as a leaf of the hierarchy, whose supertypes are unknown:
+/// the lists of supertypes stop at it and `missing` returns it.
+/// A query, whose answer depends on a missing class, returns `Error::ClassNotFound`.
+/// `java/lang/Object` is never missing, it is the root of every hierarchy.
+///
+/// The most specific common super class of two classes is computed
+/// by `ClassResolver::common_super_class`.
+///
+/// # Examples
+///
+/// ```
+/// use jbcrs::TypeDescriptor;
+/// use jbcrs::basic::{self, ClassHierarchy, ClassResolver};
+///
+/// let sealed = basic::parse(include_bytes!("../../examples/resources/Sealed.class")).unwrap();
+/// let leaf = basic::parse(include_bytes!("../../examples/resources/Sealed$Leaf.class")).unwrap();
+/// let node = basic::parse(include_bytes!("../../examples/resources/Sealed$Node.class")).unwrap();
+///
+/// let hierarchy = ClassHierarchy::new(&[sealed, leaf, node]).unwrap();
+/// assert_eq!(hierarchy.supertypes("Sealed$Leaf"), vec!["Sealed", "java/lang/Object"]);
+/// assert_eq!(hierarchy.subtypes("Sealed"), vec!["Sealed$Leaf", "Sealed$Node"]);
+/// assert!(hierarchy.is_subtype("Sealed$Node", "Sealed").unwrap());
+/// assert_eq!(hierarchy.common_super_class("Sealed$Leaf", "Sealed$Node").unwrap(), "Sealed");
+///
+/// let leaves: TypeDescriptor = "[LSealed$Leaf;".parse().unwrap();
+/// let objects: TypeDescriptor = "[Ljava/lang/Object;".parse().unwrap();
+/// assert!(hierarchy.is_assignable(&leaves, &objects).unwrap());
+/// assert!(!hierarchy.is_assignable(&objects, &leaves).unwrap());
+/// ```
+#[derive(Debug, Default)]
+pub struct ClassHierarchy {
+    nodes: HashMap<String, Node>,
+    /// The direct subtypes of every class, which is extended or implemented
+    subtypes: HashMap<String, Vec<String>>,
+}
+
+impl ClassHierarchy {
+    /// Creates the hierarchy of a set of classes.
+    /// An error is returned, if a class name cannot be found in the constant pool.
+    pub fn new(classes: &[(Pool, Class)]) -> Result<ClassHierarchy> {
+        let mut hierarchy = ClassHierarchy::default();
+        for (constant_pool, class) in classes {
+            hierarchy.push(constant_pool, class)?;
+        }
+        Ok(hierarchy)
+    }
+
+    /// Adds a class to the hierarchy.
+    /// If a class of the same name has been added before, the class is ignored,
+    /// just like a class path would hide it.
+    pub fn push(&mut self, constant_pool: &Pool, class: &Class) -> Result<()> {
+        let name = constant_pool.get_class_name(class.name)?;
+        if self.nodes.contains_key(&name) {
+            return Ok(());
+        }
+
+        let super_name = if class.super_name == 0 {
+            None
+        } else {
+            Some(constant_pool.get_class_name(class.super_name)?)
+        };
+        let mut interfaces = Vec::with_capacity(class.interfaces.len());
+        for interface in &class.interfaces {
+            interfaces.push(constant_pool.get_class_name(*interface)?);
+        }
+
+        for supertype in super_name.iter().chain(&interfaces) {
+            self.subtypes
+                .entry(supertype.clone())
+                .or_default()
+                .push(name.clone());
+        }
+        self.nodes.insert(
+            name,
+            Node {
+                super_name,
+                interfaces,
+                is_interface: class.access_flags.contains(ClassAccessFlags::INTERFACE),
+            },
+        );
+        Ok(())
+    }
+
+    /// Returns true if a class is part of the hierarchy
+    pub fn contains(&self, name: &str) -> bool {
+        self.nodes.contains_key(name)
+    }
+
+    /// Returns every class, which is extended or implemented by a class of the hierarchy,
+    /// but is not part of it itself, in ascending order
+    pub fn missing(&self) -> Vec<&str> {
+        let mut missing: Vec<&str> = self.subtypes
+            .keys()
+            .map(String::as_str)
+            .filter(|name| *name != OBJECT && !self.nodes.contains_key(*name))
+            .collect();
+        missing.sort_unstable();
+        missing
+    }
+
+    /// Returns the direct super class and the direct interfaces of a class, in this order.
+    /// Nothing is returned for a missing class.
+    pub fn direct_supertypes(&self, name: &str) -> Vec<&str> {
+        match self.nodes.get(name) {
+            Some(node) => node.super_name
+                .iter()
+                .chain(&node.interfaces)
+                .map(String::as_str)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns the direct super class of a class.
+    /// Nothing is returned for `java/lang/Object` and a missing class.
+    pub fn direct_super_class(&self, name: &str) -> Option<&str> {
+        self.nodes
+            .get(name)
+            .and_then(|node| node.super_name.as_ref())
+            .map(String::as_str)
+    }
+
+    /// Returns the direct interfaces of a class, in the order they are declared.
+    /// Nothing is returned for a missing class.
+    pub fn direct_interfaces(&self, name: &str) -> Vec<&str> {
+        match self.nodes.get(name) {
+            Some(node) => node.interfaces.iter().map(String::as_str).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns every direct and indirect supertype of a class
+    /// (without the class itself), the nearest ones first.
+    /// Missing classes are listed, but their supertypes are unknown.
+    pub fn supertypes(&self, name: &str) -> Vec<&str> {
+        self.walk_supertypes(name).0
+    }
+
+    /// Returns the super class of a class, its super class and so on,
+    /// up to `java/lang/Object` or the first missing class
+    pub fn super_classes(&self, name: &str) -> Vec<&str> {
+        let mut supers = Vec::new();
+        let mut current = self.nodes.get(name).and_then(|node| node.super_name.as_ref());
+        while let Some(super_name) = current {
+            // protects against circular hierarchies
+            if super_name == name || supers.contains(&super_name.as_str()) {
+                break;
+            }
+            supers.push(super_name.as_str());
+            current = self.nodes
+                .get(super_name)
+                .and_then(|node| node.super_name.as_ref());
+        }
+        supers
+    }
+
+    /// Returns every interface implemented by a class directly,
+    /// by one of its super classes or extended by one of these interfaces,
+    /// the nearest ones first.
+    pub fn interfaces(&self, name: &str) -> Vec<&str> {
+        let supertypes = self.supertypes(name);
+        let mut interfaces: HashSet<&str> = HashSet::new();
+        for supertype in Some(name).into_iter().chain(supertypes.iter().cloned()) {
+            if let Some(node) = self.nodes.get(supertype) {
+                interfaces.extend(node.interfaces.iter().map(String::as_str));
+            }
+        }
+        supertypes
+            .into_iter()
+            .filter(|supertype| interfaces.contains(supertype))
+            .collect()
+    }
+
+    /// Returns the classes directly extending or implementing a class, in ascending order
+    pub fn direct_subtypes(&self, name: &str) -> Vec<&str> {
+        let mut subtypes: Vec<&str> = self.subtypes
+            .get(name)
+            .map(|subtypes| subtypes.iter().map(String::as_str).collect())
+            .unwrap_or_default();
+        subtypes.sort_unstable();
+        subtypes
+    }
+
+    /// Returns every class of the hierarchy, which directly or indirectly
+    /// extends or implements a class (without the class itself), in ascending order
+    pub fn subtypes(&self, name: &str) -> Vec<&str> {
+        let mut subtypes = BTreeSet::new();
+        let mut queue = vec![name];
+        while let Some(current) = queue.pop() {
+            for subtype in self.subtypes.get(current).into_iter().flatten() {
+                if subtype != name && subtypes.insert(subtype.as_str()) {
+                    queue.push(subtype);
+                }
+            }
+        }
+        subtypes.into_iter().collect()
+    }
+
+    /// Returns true if a class is the same class as `to`, extends or implements it,
+    /// directly or indirectly.
+    /// Every class is a subtype of `java/lang/Object`.
+    ///
+    /// `Error::ClassNotFound` is returned, if `to` is not found among the known supertypes
+    /// and a missing class could extend or implement it.
+    pub fn is_subtype(&self, from: &str, to: &str) -> Result<bool> {
+        if from == to || to == OBJECT {
+            return Ok(true);
+        }
+
+        let (supertypes, missing) = self.walk_supertypes(from);
+        if supertypes.contains(&to) {
+            Ok(true)
+        } else if let Some(missing) = missing {
+            Err(Error::ClassNotFound(missing))
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Returns true if a value of the type `from` may be assigned to a variable of type `to`,
+    /// following the rules of `checkcast` (JVMS §6.5):
+    ///
+    /// - Primitive types are only assignable to themselves.
+    /// - A class or an interface is assignable to its supertypes (see `is_subtype`).
+    /// - An array is assignable to `java/lang/Object`, `java/lang/Cloneable`
+    ///   and `java/io/Serializable`,
+    ///   and to another array, if its components are primitive types of the same kind
+    ///   or references assignable to the components of the other one.
+    pub fn is_assignable(&self, from: &TypeDescriptor, to: &TypeDescriptor) -> Result<bool> {
+        if from == to {
+            return Ok(true);
+        }
+
+        match (from.dimensions, to.dimensions) {
+            (0, 0) => match (&from.base_type, &to.base_type) {
+                (Type::Reference(from), Type::Reference(to)) => self.is_subtype(from, to),
+                _ => Ok(false),
+            },
+            (0, _) => Ok(false),
+            (_, 0) => Ok(match to.base_type {
+                Type::Reference(ref to) => to == OBJECT || to == CLONEABLE || to == SERIALIZABLE,
+                _ => false,
+            }),
+            (_, _) => self.is_assignable(
+                &TypeDescriptor::new(from.dimensions - 1, from.base_type.clone()),
+                &TypeDescriptor::new(to.dimensions - 1, to.base_type.clone()),
+            ),
+        }
+    }
+
+    /// Walks all supertypes breadth first,
+    /// returning them and the first missing class found, if any
+    fn walk_supertypes<'a>(&'a self, name: &str) -> (Vec<&'a str>, Option<String>) {
+        let mut supertypes = Vec::new();
+        let mut missing = None;
+        let mut visited = HashSet::new();
+        visited.insert(name);
+        let mut queue = VecDeque::new();
+        queue.push_back(name);
+        while let Some(current) = queue.pop_front() {
+            let node = match self.nodes.get(current) {
+                Some(node) => node,
+                None => {
+                    if current != OBJECT && missing.is_none() {
+                        missing = Some(current.to_owned());
+                    }
+                    continue;
+                }
+            };
+            for supertype in node.super_name.iter().chain(&node.interfaces) {
+                if visited.insert(supertype.as_str()) {
+                    supertypes.push(supertype.as_str());
+                    queue.push_back(supertype);
+                }
+            }
+        }
+        (supertypes, missing)
+    }
+}
+
+/// Resolves classes for `compute_frames`,
+/// `Error::ClassNotFound` is returned for missing classes.
+impl ClassResolver for ClassHierarchy {
+    fn super_class(&self, name: &str) -> Result<Option<String>> {
+        match self.nodes.get(name) {
+            Some(node) => Ok(node.super_name.clone()),
+            None if name == OBJECT => Ok(None),
+            None => Err(Error::ClassNotFound(name.to_owned())),
+        }
+    }
+
+    fn is_interface(&self, name: &str) -> Result<bool> {
+        match self.nodes.get(name) {
+            Some(node) => Ok(node.is_interface),
+            None if name == OBJECT => Ok(false),
+            None => Err(Error::ClassNotFound(name.to_owned())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::fixture;
+    use super::*;
+
+    /// Creates a class extending a super class and implementing interfaces
+    fn class(
+        name: &str,
+        super_name: Option<&str>,
+        interfaces: &[&str],
+        interface: bool,
+    ) -> (Pool, Class) {
+        let access_flags = if interface {
+            ClassAccessFlags::INTERFACE | ClassAccessFlags::ABSTRACT
+        } else {
+            ClassAccessFlags::PUBLIC
+        };
+        fixture::class(52, access_flags, name, super_name, interfaces)
+    }
+
+    fn hierarchy() -> ClassHierarchy {
+        ClassHierarchy::new(&[
+            class("A", Some(OBJECT), &["I"], false),
+            class("B", Some("A"), &["J", SERIALIZABLE], false),
+            class("C", Some("B"), &[], false),
+            class("D", Some("A"), &[], false),
+            class("E", Some("x/Missing"), &[], false),
+            class("I", Some(OBJECT), &[], true),
+            class("J", Some(OBJECT), &["I", "K"], true),
+            class("K", Some(OBJECT), &[], true),
+            class("Cycle", Some("Cycle"), &[], false),
+            class(OBJECT, None, &[], false),
+            // hidden by the first class of the same name
+            class("C", Some(OBJECT), &[], false),
+        ]).unwrap()
+    }
+
+    fn desc(desc: &str) -> TypeDescriptor {
+        desc.parse().unwrap()
+    }
+
+    #[test]
+    fn supertypes() {
+        let hierarchy = hierarchy();
+        assert!(hierarchy.contains("C"));
+        assert!(hierarchy.contains(OBJECT));
+        assert_eq!(hierarchy.missing(), vec![SERIALIZABLE, "x/Missing"]);
+
+        assert_eq!(hierarchy.direct_supertypes("B"), vec!["A", "J", SERIALIZABLE]);
+        assert_eq!(hierarchy.direct_super_class("B"), Some("A"));
+        assert_eq!(hierarchy.direct_super_class(OBJECT), None);
+        assert_eq!(hierarchy.direct_interfaces("B"), vec!["J", SERIALIZABLE]);
+        assert!(hierarchy.direct_interfaces("x/Missing").is_empty());
+        assert_eq!(
+            hierarchy.supertypes("C"),
+            vec!["B", "A", "J", SERIALIZABLE, OBJECT, "I", "K"]
+        );
+        assert_eq!(hierarchy.super_classes("C"), vec!["B", "A", OBJECT]);
+        assert_eq!(hierarchy.super_classes("E"), vec!["x/Missing"]);
+        assert_eq!(hierarchy.super_classes("Cycle"), Vec::<&str>::new());
+        assert_eq!(hierarchy.interfaces("C"), vec!["J", SERIALIZABLE, "I", "K"]);
+        assert_eq!(hierarchy.interfaces("J"), vec!["I", "K"]);
+        assert!(hierarchy.supertypes("x/Missing").is_empty());
+
+        assert_eq!(hierarchy.direct_subtypes("A"), vec!["B", "D"]);
+        assert_eq!(hierarchy.subtypes("A"), vec!["B", "C", "D"]);
+        assert_eq!(hierarchy.subtypes("I"), vec!["A", "B", "C", "D", "J"]);
+        assert_eq!(hierarchy.subtypes("x/Missing"), vec!["E"]);
+        assert_eq!(hierarchy.subtypes("Cycle"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn subtypes() {
+        let hierarchy = hierarchy();
+        assert!(hierarchy.is_subtype("C", "A").unwrap());
+        assert!(hierarchy.is_subtype("C", "K").unwrap());
+        assert!(hierarchy.is_subtype("J", OBJECT).unwrap());
+        assert!(!hierarchy.is_subtype("D", "J").unwrap());
+        assert!(!hierarchy.is_subtype("A", "C").unwrap());
+        assert!(!hierarchy.is_subtype("Cycle", "A").unwrap());
+        assert!(hierarchy.is_subtype("E", "x/Missing").unwrap());
+        match hierarchy.is_subtype("E", "A") {
+            Err(Error::ClassNotFound(ref name)) if name == "x/Missing" => {}
+            _ => panic!("the super class of E is missing"),
+        }
+
+        assert_eq!(hierarchy.common_super_class("C", "D").unwrap(), "A");
+        assert_eq!(hierarchy.common_super_class("C", "J").unwrap(), OBJECT);
+        assert!(hierarchy.common_super_class("C", "E").is_err());
+    }
+
+    #[test]
+    fn assignable() {
+        let hierarchy = hierarchy();
+        let assignable = |from: &str, to: &str| hierarchy.is_assignable(&desc(from), &desc(to));
+
+        assert!(assignable("I", "I").unwrap());
+        assert!(!assignable("I", "J").unwrap());
+        assert!(!assignable("I", "LA;").unwrap());
+        assert!(assignable("LC;", "LI;").unwrap());
+        assert!(!assignable("LI;", "LA;").unwrap());
+        assert!(assignable("LI;", "Ljava/lang/Object;").unwrap());
+
+        assert!(assignable("[I", "Ljava/lang/Object;").unwrap());
+        assert!(assignable("[[LE;", "Ljava/lang/Cloneable;").unwrap());
+        assert!(assignable("[LA;", "Ljava/io/Serializable;").unwrap());
+        assert!(!assignable("[LA;", "LA;").unwrap());
+        assert!(!assignable("LA;", "[LA;").unwrap());
+        assert!(!assignable("[I", "[J").unwrap());
+        assert!(!assignable("[I", "[Ljava/lang/Object;").unwrap());
+        assert!(assignable("[[I", "[Ljava/lang/Object;").unwrap());
+        assert!(assignable("[[LC;", "[[LJ;").unwrap());
+        assert!(!assignable("[[LA;", "[[LJ;").unwrap());
+        assert!(assignable("[[LC;", "[Ljava/lang/Cloneable;").unwrap());
+        assert!(assignable("[LE;", "[LE;").unwrap());
+        assert!(assignable("[LE;", "[Lx/Missing;").unwrap());
+        assert!(assignable("[LE;", "[LA;").is_err());
+    }
+}
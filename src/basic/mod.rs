@@ -1,9 +1,46 @@
 //! The basic module provides basic read and write capabilities.
 
+mod analysis;
+mod assemble;
 mod constpool;
+mod disassemble;
+mod hierarchy;
+mod nest;
 mod parser;
+mod record;
+mod resolve;
+mod sealed;
 mod tree;
+mod validate;
+mod writer;
 
+#[cfg(test)]
+mod fixture;
+
+pub use self::analysis::*;
+pub use self::assemble::*;
 pub use self::constpool::*;
+pub use self::disassemble::*;
+pub use self::hierarchy::*;
+pub use self::nest::*;
 pub use self::parser::*;
+pub use self::record::*;
+pub use self::resolve::*;
+pub use self::sealed::*;
 pub use self::tree::*;
+pub use self::validate::*;
+pub use self::writer::*;
+
+/// The first 4 bytes of every java class file
+const MAGIC: &[u8] = &[0xCA, 0xFE, 0xBA, 0xBE];
+
+/// The root of every class hierarchy
+const OBJECT: &str = "java/lang/Object";
+
+/// Returns the package of a class in internal form, which is empty for the unnamed package.
+fn package(class: &str) -> &str {
+    match class.rfind('/') {
+        Some(i) => &class[..i],
+        None => "",
+    }
+}
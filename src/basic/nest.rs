@@ -0,0 +1,284 @@
+//! The nest module determines the nest host of every class in a set of classes,
+//! just like the JVM does, when checking access to private members.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use super::constpool::*;
+use super::package;
+use super::tree::*;
+use result::*;
+
+/// A reason, why a class is not a member of the nest it claims to be a member of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NestError {
+    /// The class has both a `NestHost` and a `NestMembers` attribute,
+    /// so it is not a member of any nest.
+    Conflicting { class: String },
+    /// Another class of the same name has been given before, so the class is ignored.
+    Duplicate { class: String },
+    /// The nest host is not part of the classes given.
+    HostNotFound { class: String, host: String },
+    /// The nest host is declared in a different package.
+    DifferentPackage { class: String, host: String },
+    /// The `NestMembers` attribute of the nest host does not list the class.
+    NotAMember { class: String, host: String },
+}
+
+impl fmt::Display for NestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            NestError::Conflicting { ref class } => {
+                write!(f, "{} has both a NestHost and a NestMembers attribute", class)
+            }
+            NestError::Duplicate { ref class } => write!(f, "{} is given more than once", class),
+            NestError::HostNotFound {
+                ref class,
+                ref host,
+            } => write!(f, "nest host {} of {} could not be found", host, class),
+            NestError::DifferentPackage {
+                ref class,
+                ref host,
+            } => write!(f, "nest host {} of {} is in a different package", host, class),
+            NestError::NotAMember {
+                ref class,
+                ref host,
+            } => write!(f, "nest host {} does not list {} as a member", host, class),
+        }
+    }
+}
+
+/// The nest host of every class in a set of classes.
+///
+/// A class without a `NestHost` attribute is the host of its own nest.
+/// Otherwise the host named must be among the classes, in the same package
+/// and its `NestMembers` attribute must list the class.
+/// If it is not, the class is the host of its own nest as well (like on Java 15 and later)
+/// and the reason is recorded.
+/// Members listed by a host, which do not name it as their host, are not considered.
+///
+/// A class with both a `NestHost` and a `NestMembers` attribute is rejected by the JVM,
+/// so it is not a member of any nest and cannot be the host of other classes.
+/// If a class name is given more than once, only the first class is considered.
+/// Both cases are recorded as errors as well.
+///
+/// # Examples
+///
+/// ```
+/// use jbcrs::basic::{self, Nests};
+///
+/// let outer = basic::parse(include_bytes!("../../examples/resources/Nestmates.class")).unwrap();
+/// let inner = basic::parse(include_bytes!("../../examples/resources/Nestmates$Inner.class"))
+///     .unwrap();
+///
+/// let nests = Nests::new(&[outer, inner]).unwrap();
+/// assert!(nests.errors().is_empty());
+/// assert_eq!(nests.host("Nestmates$Inner"), Some("Nestmates"));
+/// assert!(nests.are_nestmates("Nestmates", "Nestmates$Inner"));
+/// ```
+#[derive(Debug)]
+pub struct Nests {
+    hosts: HashMap<String, String>,
+    errors: Vec<NestError>,
+}
+
+/// The nest attributes of a single class.
+struct NestInfo {
+    host: Option<String>,
+    members: Vec<String>,
+}
+
+impl Nests {
+    /// Determines the nest hosts of all classes.
+    /// An error is returned, if a class name cannot be found in the constant pool.
+    pub fn new(classes: &[(Pool, Class)]) -> Result<Nests> {
+        let mut names = Vec::with_capacity(classes.len());
+        let mut infos = HashMap::with_capacity(classes.len());
+        let mut seen = HashSet::with_capacity(classes.len());
+        let mut errors = Vec::new();
+        for (constant_pool, class) in classes {
+            let name = constant_pool.get_class_name(class.name)?;
+            if !seen.insert(name.clone()) {
+                errors.push(NestError::Duplicate { class: name });
+                continue;
+            }
+
+            let mut host = None;
+            let mut members = Vec::new();
+            let mut has_members = false;
+            for attribute in &class.attributes {
+                match *attribute {
+                    Attribute::NestHost(index) => {
+                        host = Some(constant_pool.get_class_name(index)?);
+                    }
+                    Attribute::NestMembers(ref indices) => {
+                        has_members = true;
+                        for index in indices {
+                            members.push(constant_pool.get_class_name(*index)?);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            if host.is_some() && has_members {
+                errors.push(NestError::Conflicting { class: name });
+                continue;
+            }
+            names.push(name.clone());
+            infos.insert(name, NestInfo { host, members });
+        }
+
+        let mut hosts = HashMap::with_capacity(infos.len());
+        for name in names {
+            let host = match infos[&name].host {
+                Some(ref host) => match validate_host(&infos, &name, host) {
+                    Ok(()) => host.clone(),
+                    Err(error) => {
+                        errors.push(error);
+                        name.clone()
+                    }
+                },
+                None => name.clone(),
+            };
+            hosts.insert(name, host);
+        }
+
+        Ok(Nests { hosts, errors })
+    }
+
+    /// Returns the nest host of a class
+    /// or `None` if the class is unknown or not a member of any nest.
+    pub fn host(&self, class: &str) -> Option<&str> {
+        self.hosts.get(class).map(String::as_str)
+    }
+
+    /// Returns true if both classes are known and have the same nest host,
+    /// so they may access each other's private members.
+    pub fn are_nestmates(&self, a: &str, b: &str) -> bool {
+        match (self.host(a), self.host(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Returns every class, which is not a member of the nest it claims to be a member of.
+    pub fn errors(&self) -> &[NestError] {
+        &self.errors
+    }
+}
+
+/// Checks whether a class is a valid member of the nest of `host`.
+fn validate_host(
+    infos: &HashMap<String, NestInfo>,
+    class: &str,
+    host: &str,
+) -> ::std::result::Result<(), NestError> {
+    let info = match infos.get(host) {
+        Some(info) => info,
+        None => {
+            return Err(NestError::HostNotFound {
+                class: class.to_owned(),
+                host: host.to_owned(),
+            })
+        }
+    };
+
+    if package(class) != package(host) {
+        return Err(NestError::DifferentPackage {
+            class: class.to_owned(),
+            host: host.to_owned(),
+        });
+    }
+
+    if !info.members.iter().any(|member| member == class) {
+        return Err(NestError::NotAMember {
+            class: class.to_owned(),
+            host: host.to_owned(),
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::fixture::{self, push_class};
+    use super::*;
+
+    /// Creates a class with a `NestHost` attribute, if a host is given,
+    /// and a `NestMembers` attribute, if members are given.
+    fn class(name: &str, host: Option<&str>, members: &[&str]) -> (Pool, Class) {
+        let (mut pool, mut class) = fixture::class(55, ClassAccessFlags::PUBLIC, name, None, &[]);
+        if let Some(host) = host {
+            let host = push_class(&mut pool, host);
+            class.attributes.push(Attribute::NestHost(host));
+        }
+        if !members.is_empty() {
+            let members = members
+                .iter()
+                .map(|member| push_class(&mut pool, member))
+                .collect();
+            class.attributes.push(Attribute::NestMembers(members));
+        }
+        (pool, class)
+    }
+
+    #[test]
+    fn nests() {
+        let nests = Nests::new(&[
+            class("a/Outer", None, &["a/Outer$Inner", "a/Outer$Liar", "b/Foreign"]),
+            class("a/Outer$Inner", Some("a/Outer"), &[]),
+            class("a/Outer$Liar", Some("a/Outer"), &["a/Outer$Inner"]),
+            class("a/Stranger", Some("a/Outer"), &[]),
+            class("a/Orphan", Some("a/Missing"), &[]),
+            class("b/Foreign", Some("a/Outer"), &[]),
+            class("a/Outer$Inner", None, &[]),
+            class("a/Liar$Inner", Some("a/Outer$Liar"), &[]),
+        ]).unwrap();
+
+        assert_eq!(
+            nests.errors(),
+            &[
+                NestError::Conflicting {
+                    class: "a/Outer$Liar".to_owned(),
+                },
+                NestError::Duplicate {
+                    class: "a/Outer$Inner".to_owned(),
+                },
+                NestError::NotAMember {
+                    class: "a/Stranger".to_owned(),
+                    host: "a/Outer".to_owned(),
+                },
+                NestError::HostNotFound {
+                    class: "a/Orphan".to_owned(),
+                    host: "a/Missing".to_owned(),
+                },
+                NestError::DifferentPackage {
+                    class: "b/Foreign".to_owned(),
+                    host: "a/Outer".to_owned(),
+                },
+                NestError::HostNotFound {
+                    class: "a/Liar$Inner".to_owned(),
+                    host: "a/Outer$Liar".to_owned(),
+                },
+            ]
+        );
+
+        assert_eq!(nests.host("a/Outer"), Some("a/Outer"));
+        assert_eq!(nests.host("a/Outer$Inner"), Some("a/Outer"));
+        assert_eq!(nests.host("a/Stranger"), Some("a/Stranger"));
+        assert_eq!(nests.host("a/Orphan"), Some("a/Orphan"));
+        assert_eq!(nests.host("b/Foreign"), Some("b/Foreign"));
+        assert_eq!(nests.host("a/Missing"), None);
+
+        assert_eq!(nests.host("a/Outer$Liar"), None);
+        assert_eq!(nests.host("a/Liar$Inner"), Some("a/Liar$Inner"));
+
+        assert!(nests.are_nestmates("a/Outer", "a/Outer$Inner"));
+        assert!(!nests.are_nestmates("a/Outer$Inner", "a/Outer$Liar"));
+        assert!(!nests.are_nestmates("a/Outer$Liar", "a/Outer$Liar"));
+        assert!(!nests.are_nestmates("a/Outer", "a/Stranger"));
+        assert!(!nests.are_nestmates("a/Missing", "a/Missing"));
+    }
+}
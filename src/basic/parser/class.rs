@@ -36,7 +36,7 @@ pub fn parse_inner_classes(decoder: &mut Decoder) -> Result<Attribute> {
         let inner_class_info = decoder.read_u16()?;
         let outer_class_info = decoder.read_u16()?;
         let inner_name = decoder.read_u16()?;
-        let inner_class_access_flags = AccessFlags::from_bits_truncate(decoder.read_u16()?);
+        let inner_class_access_flags = InnerClassAccessFlags::from_bits_retain(decoder.read_u16()?);
         inner_classes.push(InnerClass {
             inner_class_info,
             outer_class_info,
@@ -57,10 +57,51 @@ pub fn parse_module_packages(decoder: &mut Decoder) -> Result<Attribute> {
     Ok(Attribute::ModulePackages(packages))
 }
 
+/// Parses the `NestMembers` attribute.
+pub fn parse_nest_members(decoder: &mut Decoder) -> Result<Attribute> {
+    let count = decoder.read_u16()?;
+    let mut members = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        members.push(decoder.read_u16()?);
+    }
+    Ok(Attribute::NestMembers(members))
+}
+
+/// Parses the `PermittedSubclasses` attribute.
+pub fn parse_permitted_subclasses(decoder: &mut Decoder) -> Result<Attribute> {
+    let count = decoder.read_u16()?;
+    let mut subclasses = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        subclasses.push(decoder.read_u16()?);
+    }
+    Ok(Attribute::PermittedSubclasses(subclasses))
+}
+
+/// Parses the `Record` attribute and the attributes of its components.
+pub fn parse_record(decoder: &mut Decoder, context: &mut Context) -> Result<Attribute> {
+    let count = decoder.read_u16()?;
+    let mut components = Vec::with_capacity(count as usize);
+    for i in 0..count {
+        let name = decoder.read_u16()?;
+        let descriptor = decoder.read_u16()?;
+
+        context.path.push(PathElement::Entry(i as usize));
+        let attributes = parse_attributes(decoder, context)?;
+        context.path.pop();
+
+        components.push(RecordComponent {
+            name,
+            descriptor,
+            attributes,
+        });
+    }
+    Ok(Attribute::Record(components))
+}
+
 /// Parses the `Module` attribute.
 pub fn parse_module(decoder: &mut Decoder) -> Result<Attribute> {
     let name = decoder.read_u16()?;
-    let flags = AccessFlags::from_bits_truncate(decoder.read_u16()?);
+    let flags = ModuleFlags::from_bits_retain(decoder.read_u16()?);
     let version = decoder.read_u16()?;
 
     // read requires
@@ -68,7 +109,7 @@ pub fn parse_module(decoder: &mut Decoder) -> Result<Attribute> {
     let mut requires = Vec::with_capacity(requires_count as usize);
     for _ in 0..requires_count {
         let index = decoder.read_u16()?;
-        let flags = AccessFlags::from_bits_truncate(decoder.read_u16()?);
+        let flags = RequiresFlags::from_bits_retain(decoder.read_u16()?);
         let version = decoder.read_u16()?;
         requires.push(Requirement {
             index,
@@ -82,7 +123,7 @@ pub fn parse_module(decoder: &mut Decoder) -> Result<Attribute> {
     let mut exports = Vec::with_capacity(exports_count as usize);
     for _ in 0..exports_count {
         let index = decoder.read_u16()?;
-        let flags = AccessFlags::from_bits_truncate(decoder.read_u16()?);
+        let flags = ExportsFlags::from_bits_retain(decoder.read_u16()?);
 
         let to_count = decoder.read_u16()?;
         let mut to = Vec::with_capacity(to_count as usize);
@@ -98,7 +139,7 @@ pub fn parse_module(decoder: &mut Decoder) -> Result<Attribute> {
     let mut opens = Vec::with_capacity(opens_count as usize);
     for _ in 0..opens_count {
         let index = decoder.read_u16()?;
-        let flags = AccessFlags::from_bits_truncate(decoder.read_u16()?);
+        let flags = ExportsFlags::from_bits_retain(decoder.read_u16()?);
 
         let to_count = decoder.read_u16()?;
         let mut to = Vec::with_capacity(to_count as usize);
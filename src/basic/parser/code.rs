@@ -1,15 +1,15 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::BTreeMap;
 
 use super::*;
 use self::decode::Decoder;
 
 /// Parses the code attribute
-pub fn parse_code(decoder: &mut Decoder, constant_pool: &Pool) -> Result<Attribute> {
+pub fn parse_code(decoder: &mut Decoder, context: &mut Context) -> Result<Attribute> {
     let max_stack = decoder.read_u16()?;
     let max_locals = decoder.read_u16()?;
 
     let code_length = decoder.read_u32()?;
-    let mut instructions = HashMap::new();
+    let mut instructions = Instructions::new();
 
     // Read the instructions
     // Using an extra block so we don't have to enable NLL on nightly
@@ -49,7 +49,7 @@ pub fn parse_code(decoder: &mut Decoder, constant_pool: &Pool) -> Result<Attribu
         });
     }
 
-    let attributes = parse_attributes(decoder, constant_pool)?;
+    let attributes = parse_attributes(decoder, context)?;
 
     Ok(Attribute::Code {
         max_stack,
@@ -86,8 +86,9 @@ fn parse_instruction(decoder: &mut Decoder, at: u32) -> Result<(u32, Instruction
         0x0F => DConst1,
         0x10 => BIPush(decoder.read_i8()?),
         0x11 => SIPush(decoder.read_i16()?),
-        0x12 => LDC(u16::from(decoder.read_u8()?)), // ldc
-        0x13 | 0x14 => LDC(decoder.read_u16()?),    // ldc_w
+        0x12 => LDC(u16::from(decoder.read_u8()?)),
+        0x13 => LDCW(decoder.read_u16()?),
+        0x14 => LDC2W(decoder.read_u16()?),
         0x15 => ILoad(u16::from(decoder.read_u8()?)),
         0x16 => LLoad(u16::from(decoder.read_u8()?)),
         0x17 => FLoad(u16::from(decoder.read_u8()?)),
@@ -242,8 +243,7 @@ fn parse_instruction(decoder: &mut Decoder, at: u32) -> Result<(u32, Instruction
         0xA8 => JSR(i32::from(decoder.read_i16()?)),
         0xA9 => Ret(u16::from(decoder.read_u8()?)),
         0xAA => {
-            // skip padding
-            decoder.skip(3 - (at & 3) as usize)?;
+            let padding = read_padding(decoder, at)?;
             let default = decoder.read_i32()?;
             let low = decoder.read_i32()?;
             let high = decoder.read_i32()?;
@@ -258,11 +258,11 @@ fn parse_instruction(decoder: &mut Decoder, at: u32) -> Result<(u32, Instruction
                 low,
                 high,
                 offsets,
+                padding,
             }
         }
         0xAB => {
-            // skip padding
-            decoder.skip(3 - (at & 3) as usize)?;
+            let padding = read_padding(decoder, at)?;
             let default = decoder.read_i32()?;
 
             let count = decoder.read_u32()?;
@@ -273,7 +273,11 @@ fn parse_instruction(decoder: &mut Decoder, at: u32) -> Result<(u32, Instruction
                 offsets.insert(key, offset);
             }
 
-            LookupSwitch { default, offsets }
+            LookupSwitch {
+                default,
+                offsets,
+                padding,
+            }
         }
         0xAC => IReturn,
         0xAD => LReturn,
@@ -291,13 +295,13 @@ fn parse_instruction(decoder: &mut Decoder, at: u32) -> Result<(u32, Instruction
         0xB9 => {
             let index = decoder.read_u16()?;
             let count = decoder.read_u8()?;
-            decoder.skip(1)?;
-            InvokeInterface(index, count)
+            let reserved = decoder.read_u8()?;
+            InvokeInterface(index, count, reserved)
         }
         0xBA => {
             let index = decoder.read_u16()?;
-            decoder.skip(2)?;
-            InvokeDynamic(index)
+            let reserved = decoder.read_u16()?;
+            InvokeDynamic(index, reserved)
         }
         0xBB => New(decoder.read_u16()?),
         0xBC => NewArray(match decoder.read_u8()? {
@@ -319,7 +323,7 @@ fn parse_instruction(decoder: &mut Decoder, at: u32) -> Result<(u32, Instruction
         0xC1 => InstanceOf(decoder.read_u16()?),
         0xC2 => MonitorEnter,
         0xC3 => MonitorExit,
-        0xC4 => match decoder.read_u8()? {
+        0xC4 => wide(match decoder.read_u8()? {
             0x15 => ILoad(decoder.read_u16()?),
             0x16 => LLoad(decoder.read_u16()?),
             0x17 => FLoad(decoder.read_u16()?),
@@ -329,7 +333,7 @@ fn parse_instruction(decoder: &mut Decoder, at: u32) -> Result<(u32, Instruction
             0x37 => LStore(decoder.read_u16()?),
             0x38 => FStore(decoder.read_u16()?),
             0x39 => DStore(decoder.read_u16()?),
-            0x40 => AStore(decoder.read_u16()?),
+            0x3A => AStore(decoder.read_u16()?),
             0x84 => {
                 let index = decoder.read_u16()?;
                 let value = decoder.read_i16()?;
@@ -338,7 +342,7 @@ fn parse_instruction(decoder: &mut Decoder, at: u32) -> Result<(u32, Instruction
             0xA9 => Ret(decoder.read_u16()?),
 
             _ => return Err(Error::InvalidInstruction { op_code: 0xC4, at }),
-        },
+        }),
         0xC5 => {
             let array_type = decoder.read_u16()?;
             let dimensions = decoder.read_u8()?;
@@ -346,8 +350,8 @@ fn parse_instruction(decoder: &mut Decoder, at: u32) -> Result<(u32, Instruction
         }
         0xC6 => IfNull(decoder.read_i16()?),
         0xC7 => IfNonNull(decoder.read_i16()?),
-        0xC8 => GoTo(decoder.read_i32()?),
-        0xC9 => JSR(decoder.read_i32()?),
+        0xC8 => GoToW(decoder.read_i32()?),
+        0xC9 => JSRW(decoder.read_i32()?),
         0xCA => BreakPoint,
         0xFE => ImpDep1,
         0xFF => ImpDep2,
@@ -357,3 +361,35 @@ fn parse_instruction(decoder: &mut Decoder, at: u32) -> Result<(u32, Instruction
 
     Ok(((at as usize + decoder.cursor() - prev_cursor) as u32, insn))
 }
+
+/// Reads the padding of a switch located at `at`,
+/// which is kept, since it does not have to be zero in older class files.
+fn read_padding(decoder: &mut Decoder, at: u32) -> Result<[u8; 3]> {
+    let mut padding = [0; 3];
+    for byte in &mut padding[..(3 - (at & 3)) as usize] {
+        *byte = decoder.read_u8()?;
+    }
+    Ok(padding)
+}
+
+/// Wraps an instruction read with the `wide` prefix in `Instruction::Wide`,
+/// if the short form would have been sufficient.
+fn wide(insn: Instruction) -> Instruction {
+    use self::Instruction::*;
+
+    let required = match insn {
+        IInc(index, value) => {
+            index > u16::from(u8::MAX) || value < i16::from(i8::MIN) || value > i16::from(i8::MAX)
+        }
+        ILoad(index) | LLoad(index) | FLoad(index) | DLoad(index) | ALoad(index)
+        | IStore(index) | LStore(index) | FStore(index) | DStore(index) | AStore(index)
+        | Ret(index) => index > u16::from(u8::MAX),
+        _ => true,
+    };
+
+    if required {
+        insn
+    } else {
+        Wide(Box::new(insn))
+    }
+}
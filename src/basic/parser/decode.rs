@@ -3,7 +3,6 @@
 
 use result::*;
 use byteorder::{BigEndian, ByteOrder};
-use std::char;
 
 pub struct Decoder<'a> {
     bytes: &'a [u8],
@@ -23,7 +22,7 @@ impl<'a> Decoder<'a> {
     }
 
     /// Limits the decoder to `to` after the cursor
-    pub fn limit(&mut self, to: usize) -> Result<Decoder> {
+    pub fn limit(&mut self, to: usize) -> Result<Decoder<'_>> {
         let end = *self.cursor + to;
         self.check(end)?;
         Ok(Decoder {
@@ -42,14 +41,6 @@ impl<'a> Decoder<'a> {
         }
     }
 
-    /// Skips a certain amount of bytes and returns an error if it exceeded the limit
-    pub fn skip(&mut self, to: usize) -> Result<()> {
-        let end = *self.cursor + to;
-        self.check(end)?;
-        *self.cursor = end;
-        Ok(())
-    }
-
     /// Returns the current cursor
     pub fn cursor(&self) -> usize {
         *self.cursor
@@ -110,49 +101,39 @@ impl<'a> Decoder<'a> {
     /// Length is the amount of bytes the String was encoded in.
     /// The length used here may differ from the count of all chars.
     pub fn read_str(&mut self, length: usize) -> Result<String> {
-        let mut out = String::with_capacity(length);
+        String::from_utf16(&self.read_utf16(length)?).map_err(|_| Error::InvalidUTF8)
+    }
+
+    /// Decodes a modified UTF-8 string into UTF-16 code units,
+    /// which may contain unpaired surrogates.
+    /// Length is the amount of bytes the String was encoded in.
+    pub fn read_utf16(&mut self, length: usize) -> Result<Vec<u16>> {
+        let mut out = Vec::with_capacity(length);
 
         let mut i = length;
         while i > 0 {
             // read first byte
-            let r1 = u32::from(self.read_u8()?);
-            let ch = if r1 != 0 && r1 < 0x80 {
+            let r1 = u16::from(self.read_u8()?);
+            let unit = if r1 != 0 && r1 < 0x80 {
                 // single byte
                 i -= 1;
                 r1
-            } else if r1 >= 0xC0 && r1 < 0xE0 && i >= 2 {
+            } else if (0xC0..0xE0).contains(&r1) && i >= 2 {
                 // 2 bytes
                 i -= 2;
-                let r2 = u32::from(self.read_u8()?);
+                let r2 = u16::from(self.read_u8()?);
                 (r1 & 0x1F) << 6 | (r2 & 0x3F)
-            } else if r1 >= 0xE0 && r1 < 0xF0 && i >= 3 {
+            } else if (0xE0..0xF0).contains(&r1) && i >= 3 {
+                // 3 bytes, supplementary characters are encoded as surrogate pairs
                 i -= 3;
-                let r2 = u32::from(self.read_u8()?);
-                let r3 = u32::from(self.read_u8()?);
-                if r1 == 0xED && r2 >= 0xA0 && r2 <= 0xAF {
-                    if i >= 3 {
-                        i -= 3;
-
-                        self.read_u8()?;
-                        let r5 = u32::from(self.read_u8()?);
-                        let r6 = u32::from(self.read_u8()?);
-                        // r1 and r4 can be ignored
-                        0x1_0000 + ((r2 & 0x0F) << 16) + ((r3 & 0x3F) << 10) + ((r5 & 0x0F) << 6)
-                            + (r6 & 0x3F)
-                    } else {
-                        return Err(Error::InvalidUTF8);
-                    }
-                } else {
-                    ((r1 & 0x0F) << 12) + ((r2 & 0x3F) << 6) + (r3 & 0x3F)
-                }
+                let r2 = u16::from(self.read_u8()?);
+                let r3 = u16::from(self.read_u8()?);
+                (r1 & 0x0F) << 12 | (r2 & 0x3F) << 6 | (r3 & 0x3F)
             } else {
                 // this is not a valid utf8 scalar value
                 return Err(Error::InvalidUTF8);
             };
-
-            // convert the u32 to a char and push it to the output string
-            let ch = char::from_u32(ch).ok_or(Error::InvalidUTF8)?;
-            out.push(ch);
+            out.push(unit);
         }
 
         Ok(out)
@@ -248,7 +229,33 @@ mod test {
         let mut cursor = 0;
         let mut decoder = super::Decoder::new("Hello, world!".as_bytes(), &mut cursor);
         assert_eq!(decoder.read_str(13).unwrap(), "Hello, world!".to_owned());
-        // not complete, add more later.
+
+        // the same bytes the encoder writes
+        let bytes = [
+            0x61, 0xC0, 0x80, 0xC3, 0xA4, 0xE2, 0x82, 0xAC, 0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80
+        ];
+        let mut cursor = 0;
+        let mut decoder = super::Decoder::new(&bytes, &mut cursor);
+        assert_eq!(
+            decoder.read_str(bytes.len()).unwrap(),
+            "a\0\u{E4}\u{20AC}\u{1F600}".to_owned()
+        );
+    }
+
+    #[test]
+    fn read_utf16() {
+        // an unpaired high surrogate, followed by an unpaired low surrogate
+        let bytes = [0xED, 0xA0, 0xBD, 0x61, 0xED, 0xB8, 0x80];
+        let mut cursor = 0;
+        let mut decoder = super::Decoder::new(&bytes, &mut cursor);
+        assert_eq!(
+            decoder.read_utf16(bytes.len()).unwrap(),
+            vec![0xD83D, 0x61, 0xDE00]
+        );
+
+        let mut cursor = 0;
+        let mut decoder = super::Decoder::new(&bytes, &mut cursor);
+        assert!(decoder.read_str(bytes.len()).is_err());
     }
 
 }
@@ -73,25 +73,35 @@ pub fn parse_stack_map_table(decoder: &mut Decoder) -> Result<Attribute> {
     for _ in 0..count {
         let frame_type = decoder.read_u8()?;
         let frame = match frame_type {
-            0...63 => StackMapFrame::Same {
+            0..=63 => StackMapFrame::Same {
                 offset_delta: u16::from(frame_type),
+                extended: false,
             },
-            64...127 => StackMapFrame::Same1 {
+            64..=127 => StackMapFrame::Same1 {
                 offset_delta: u16::from(frame_type) - 64,
                 stack: parse_verification_type(decoder)?,
+                extended: false,
             },
-            247 => StackMapFrame::Same1 {
-                offset_delta: decoder.read_u16()?,
-                stack: parse_verification_type(decoder)?,
-            },
-            248...250 => StackMapFrame::Chop {
+            247 => {
+                let offset_delta = decoder.read_u16()?;
+                StackMapFrame::Same1 {
+                    offset_delta,
+                    stack: parse_verification_type(decoder)?,
+                    extended: offset_delta < 64,
+                }
+            }
+            248..=250 => StackMapFrame::Chop {
                 offset_delta: decoder.read_u16()?,
                 count: 251 - frame_type,
             },
-            251 => StackMapFrame::Same {
-                offset_delta: decoder.read_u16()?,
-            },
-            252...254 => {
+            251 => {
+                let offset_delta = decoder.read_u16()?;
+                StackMapFrame::Same {
+                    offset_delta,
+                    extended: offset_delta < 64,
+                }
+            }
+            252..=254 => {
                 let offset_delta = decoder.read_u16()?;
                 let dif = frame_type as usize - 251;
                 let mut locals = Vec::with_capacity(dif);
@@ -155,11 +165,11 @@ fn parse_verification_type(decoder: &mut Decoder) -> Result<VerificationType> {
 
 /// Parses the `MethodParameters` attribute.
 pub fn parse_method_parameters(decoder: &mut Decoder) -> Result<Attribute> {
-    let count = decoder.read_u16()?;
+    let count = decoder.read_u8()?;
     let mut params = Vec::with_capacity(count as usize);
     for _ in 0..count {
         let name = decoder.read_u16()?;
-        let access_flags = AccessFlags::from_bits_truncate(decoder.read_u16()?);
+        let access_flags = ParameterFlags::from_bits_retain(decoder.read_u16()?);
         params.push(MethodParameter { name, access_flags });
     }
     Ok(Attribute::MethodParameters(params))
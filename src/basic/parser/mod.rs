@@ -4,8 +4,11 @@ mod method;
 mod code;
 mod annotation;
 
-pub use super::constpool::*;
-pub use super::tree::*;
+use std::collections::HashSet;
+
+use super::constpool::*;
+use super::tree::*;
+use super::validate::PathElement;
 pub use result::*;
 
 use self::class::*;
@@ -13,13 +16,67 @@ use self::method::*;
 use self::code::*;
 use self::annotation::*;
 use self::decode::Decoder;
+use super::MAGIC;
+
+/// Options controlling how attributes are parsed.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashSet;
+/// use jbcrs::basic::ParseOptions;
+///
+/// // keep broken attributes instead of failing
+/// let lenient = ParseOptions {
+///     lenient: true,
+///     ..Default::default()
+/// };
+///
+/// // decode signatures only, the code is kept as is
+/// let mut attributes = HashSet::new();
+/// attributes.insert("Signature".to_owned());
+/// let lazy = ParseOptions {
+///     attributes: Some(attributes),
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Default)]
+pub struct ParseOptions {
+    /// If an attribute cannot be parsed, it is kept as `Attribute::Unknown`
+    /// with its raw content and the error is recorded,
+    /// instead of failing to parse the entire class.
+    pub lenient: bool,
+    /// The names of all attributes to decode, every other attribute
+    /// is kept as `Attribute::Unknown` with its raw content.
+    /// If `None`, every attribute is decoded.
+    ///
+    /// Attributes kept as `Attribute::Unknown` may still reference the constant pool,
+    /// so they must be written with the same pool again.
+    pub attributes: Option<HashSet<String>>,
+}
 
-/// The first 4 bytes of every java class file
-const MAGIC: &[u8] = &[0xCA, 0xFE, 0xBA, 0xBE];
+/// An attribute, which could not be parsed in lenient mode
+/// and has been kept as `Attribute::Unknown`.
+#[derive(Debug)]
+pub struct AttributeError {
+    /// The path from the class to the attribute.
+    pub path: Vec<PathElement>,
+    pub error: Error,
+}
 
 /// Parses the class file, which is represented as a byte array.
 /// The constant pool and the class is returned, if no error occurred.
 pub fn parse(input: &[u8]) -> Result<(Pool, Class)> {
+    let (constant_pool, class, _) = parse_with(input, &ParseOptions::default())?;
+    Ok((constant_pool, class))
+}
+
+/// Parses the class file like `parse`, but attributes are parsed as specified by the options.
+/// The errors of all attributes, which could not be parsed in lenient mode, are returned as well.
+pub fn parse_with(
+    input: &[u8],
+    options: &ParseOptions,
+) -> Result<(Pool, Class, Vec<AttributeError>)> {
     // create a new decoder from the byte array
     let mut cursor = 0;
     let mut decoder = Decoder::new(input, &mut cursor);
@@ -32,9 +89,9 @@ pub fn parse(input: &[u8]) -> Result<(Pool, Class)> {
     let minor_version = decoder.read_u16()?;
     let major_version = decoder.read_u16()?;
 
-    let constant_pool = read_constant_pool(&mut decoder)?;
+    let mut constant_pool = read_constant_pool(&mut decoder)?;
 
-    let access_flags = AccessFlags::from_bits_truncate(decoder.read_u16()?);
+    let access_flags = ClassAccessFlags::from_bits_retain(decoder.read_u16()?);
 
     let name = decoder.read_u16()?;
     let super_name = decoder.read_u16()?;
@@ -46,9 +103,19 @@ pub fn parse(input: &[u8]) -> Result<(Pool, Class)> {
         interfaces.push(decoder.read_u16()?);
     }
 
-    let fields = parse_fields(&mut decoder, &constant_pool)?;
-    let methods = parse_methods(&mut decoder, &constant_pool)?;
-    let attributes = parse_attributes(&mut decoder, &constant_pool)?;
+    let mut context = Context {
+        constant_pool: &constant_pool,
+        options,
+        path: Vec::new(),
+        errors: Vec::new(),
+        attribute_names: Vec::new(),
+    };
+    let fields = parse_fields(&mut decoder, &mut context)?;
+    let methods = parse_methods(&mut decoder, &mut context)?;
+    let attributes = parse_attributes(&mut decoder, &mut context)?;
+    let errors = context.errors;
+    let attribute_names = context.attribute_names;
+    constant_pool.keep_attribute_names(&attribute_names)?;
 
     let class = Class {
         minor_version,
@@ -65,7 +132,18 @@ pub fn parse(input: &[u8]) -> Result<(Pool, Class)> {
         attributes,
     };
 
-    Ok((constant_pool, class))
+    Ok((constant_pool, class, errors))
+}
+
+/// The state required to parse attributes.
+struct Context<'a> {
+    constant_pool: &'a Pool,
+    options: &'a ParseOptions,
+    /// The path to the element, whose attributes are parsed
+    path: Vec<PathElement>,
+    errors: Vec<AttributeError>,
+    /// The name indices of all attributes parsed, except for `Attribute::Unknown`
+    attribute_names: Vec<u16>,
 }
 
 /// Reads the entire constant pool
@@ -81,7 +159,7 @@ fn read_constant_pool(decoder: &mut Decoder) -> Result<Pool> {
         let item = match tag {
             1 => {
                 let length = decoder.read_u16()?;
-                Item::UTF8(decoder.read_str(length as usize)?)
+                Item::from_utf16(decoder.read_utf16(length as usize)?)
             }
             3 => Item::Integer(decoder.read_i32()?),
             4 => Item::Float(decoder.read_f32()?),
@@ -141,6 +219,15 @@ fn read_constant_pool(decoder: &mut Decoder) -> Result<Pool> {
                 Item::MethodHandle { kind, index }
             }
             16 => Item::MethodType(decoder.read_u16()?),
+            17 => {
+                let bootstrap_method_attribute = decoder.read_u16()?;
+                let name_and_type = decoder.read_u16()?;
+
+                Item::Dynamic {
+                    bootstrap_method_attribute,
+                    name_and_type,
+                }
+            }
             18 => {
                 let bootstrap_method_attribute = decoder.read_u16()?;
                 let name_and_type = decoder.read_u16()?;
@@ -156,22 +243,27 @@ fn read_constant_pool(decoder: &mut Decoder) -> Result<Pool> {
             _ => return Err(Error::InvalidCPItem(index)),
         };
 
-        pool.push(item)?;
-        index += 1;
+        // Duplicates must be kept,
+        // otherwise all following indices would point to the wrong items.
+        pool.push_duplicate(item)?;
+        index = pool.len();
     }
 
     Ok(pool)
 }
 
 /// Parses all fields and their attributes
-fn parse_fields(decoder: &mut Decoder, constant_pool: &Pool) -> Result<Vec<Field>> {
+fn parse_fields(decoder: &mut Decoder, context: &mut Context) -> Result<Vec<Field>> {
     let count = decoder.read_u16()?;
     let mut fields = Vec::with_capacity(count as usize);
-    for _ in 0..count {
-        let access_flags = AccessFlags::from_bits_truncate(decoder.read_u16()?);
+    for i in 0..count {
+        let access_flags = FieldAccessFlags::from_bits_retain(decoder.read_u16()?);
         let name = decoder.read_u16()?;
         let desc = decoder.read_u16()?;
-        let attributes = parse_attributes(decoder, constant_pool)?;
+
+        context.path.push(PathElement::Field(i as usize));
+        let attributes = parse_attributes(decoder, context)?;
+        context.path.pop();
 
         fields.push(Field {
             access_flags,
@@ -185,14 +277,17 @@ fn parse_fields(decoder: &mut Decoder, constant_pool: &Pool) -> Result<Vec<Field
 }
 
 /// Parses all methods and their attributes
-fn parse_methods(decoder: &mut Decoder, constant_pool: &Pool) -> Result<Vec<Method>> {
+fn parse_methods(decoder: &mut Decoder, context: &mut Context) -> Result<Vec<Method>> {
     let count = decoder.read_u16()?;
     let mut fields = Vec::with_capacity(count as usize);
-    for _ in 0..count {
-        let access_flags = AccessFlags::from_bits_truncate(decoder.read_u16()?);
+    for i in 0..count {
+        let access_flags = MethodAccessFlags::from_bits_retain(decoder.read_u16()?);
         let name = decoder.read_u16()?;
         let desc = decoder.read_u16()?;
-        let attributes = parse_attributes(decoder, constant_pool)?;
+
+        context.path.push(PathElement::Method(i as usize));
+        let attributes = parse_attributes(decoder, context)?;
+        context.path.pop();
 
         fields.push(Method {
             access_flags,
@@ -206,89 +301,359 @@ fn parse_methods(decoder: &mut Decoder, constant_pool: &Pool) -> Result<Vec<Meth
 }
 
 /// Parses all attributes
-fn parse_attributes(decoder: &mut Decoder, constant_pool: &Pool) -> Result<Vec<Attribute>> {
+fn parse_attributes(decoder: &mut Decoder, context: &mut Context) -> Result<Vec<Attribute>> {
     let count = decoder.read_u16()?;
     let mut attributes = Vec::with_capacity(count as usize);
-    for _ in 0..count {
+    for i in 0..count {
         let name_index = decoder.read_u16()?;
-        let name = constant_pool.get_utf8(name_index)?;
         let length = decoder.read_u32()?;
+        let bytes = decoder.read_bytes(length as usize)?;
+
+        context.path.push(PathElement::Attribute(i as usize));
+        // errors and names of nested attributes are discarded, if the attribute itself is broken
+        let error_count = context.errors.len();
+        let name_count = context.attribute_names.len();
+        let attribute = match parse_attribute(name_index, bytes, context) {
+            Ok(attribute) => attribute,
+            Err(error) => {
+                if !context.options.lenient {
+                    return Err(error);
+                }
 
-        // limit attribute length
-        let mut attr_decoder = decoder.limit(length as usize)?;
-
-        let attribute = match name.as_ref() {
-            "AnnotationDefault" => {
-                Attribute::AnnotationDefault(parse_element_value(&mut attr_decoder)?)
-            }
-            "BootstrapMethods" => parse_bootstrap_methods(&mut attr_decoder)?,
-            "Code" => parse_code(&mut attr_decoder, constant_pool)?,
-            "ConstantValue" => {
-                let index = attr_decoder.read_u16()?;
-                Attribute::ConstantValue(index)
-            }
-            "Deprecated" => Attribute::Deprecated,
-            "EnclosingMethods" => parse_enclosing_method(&mut attr_decoder)?,
-            "Exceptions" => parse_exceptions(&mut attr_decoder)?,
-            "InnerClasses" => parse_inner_classes(&mut attr_decoder)?,
-            "LineNumberTable" => parse_line_number_table(&mut attr_decoder)?,
-            "LocalVariableTable" => parse_local_variable_table(&mut attr_decoder)?,
-            "LocalVariableTypeTable" => parse_local_variable_type_table(&mut attr_decoder)?,
-            "MethodParameters" => parse_method_parameters(&mut attr_decoder)?,
-            "Module" => parse_module(&mut attr_decoder)?,
-            "ModuleMainClass" => {
-                let index = attr_decoder.read_u16()?;
-                Attribute::ModuleMainClass(index)
-            }
-            "ModulePackages" => parse_module_packages(&mut attr_decoder)?,
-            "RuntimeVisibleAnnotations" => {
-                let annotations = parse_annotations(&mut attr_decoder)?;
-                Attribute::RuntimeVisibleAnnotations(annotations)
-            }
-            "RuntimeInvisibleAnnotations" => {
-                let annotations = parse_annotations(&mut attr_decoder)?;
-                Attribute::RuntimeInvisibleAnnotations(annotations)
-            }
-            "RuntimeVisibleParameterAnnotations" => {
-                let annotations = parse_parameter_annotations(&mut attr_decoder)?;
-                Attribute::RuntimeVisibleParameterAnnotations(annotations)
-            }
-            "RuntimeInvisibleParameterAnnotations" => {
-                let annotations = parse_parameter_annotations(&mut attr_decoder)?;
-                Attribute::RuntimeInvisibleParameterAnnotations(annotations)
-            }
-            "RuntimeVisibleTypeAnnotations" => {
-                let annotations = parse_type_annotations(&mut attr_decoder)?;
-                Attribute::RuntimeVisibleTypeAnnotations(annotations)
-            }
-            "RuntimeInvisibleTypeAnnotations" => {
-                let annotations = parse_type_annotations(&mut attr_decoder)?;
-                Attribute::RuntimeInvisibleTypeAnnotations(annotations)
+                context.errors.truncate(error_count);
+                context.attribute_names.truncate(name_count);
+                context.errors.push(AttributeError {
+                    path: context.path.clone(),
+                    error,
+                });
+                Attribute::Unknown(name_index, bytes.to_vec())
             }
-            "SourceFile" => {
-                let index = attr_decoder.read_u16()?;
-                Attribute::SourceFile(index)
+        };
+        // a known attribute may reference a duplicate of its name in the constant pool
+        if !matches!(attribute, Attribute::Unknown(..)) {
+            context.attribute_names.push(name_index);
+        }
+        context.path.pop();
+
+        attributes.push(attribute);
+    }
+
+    Ok(attributes)
+}
+
+/// Parses a single attribute given by its name and its content
+fn parse_attribute(name_index: u16, bytes: &[u8], context: &mut Context) -> Result<Attribute> {
+    let name = context.constant_pool.get_utf8(name_index)?;
+    if let Some(ref selected) = context.options.attributes {
+        if !selected.contains(&name) {
+            return Ok(Attribute::Unknown(name_index, bytes.to_vec()));
+        }
+    }
+
+    let length = bytes.len() as u32;
+    let mut cursor = 0;
+    let mut attr_decoder = Decoder::new(bytes, &mut cursor);
+
+    let attribute = match name.as_ref() {
+        "AnnotationDefault" => {
+            Attribute::AnnotationDefault(parse_element_value(&mut attr_decoder)?)
+        }
+        "BootstrapMethods" => parse_bootstrap_methods(&mut attr_decoder)?,
+        "Code" => parse_code(&mut attr_decoder, context)?,
+        "ConstantValue" => {
+            let index = attr_decoder.read_u16()?;
+            Attribute::ConstantValue(index)
+        }
+        "Deprecated" => Attribute::Deprecated,
+        "EnclosingMethod" => parse_enclosing_method(&mut attr_decoder)?,
+        "Exceptions" => parse_exceptions(&mut attr_decoder)?,
+        "InnerClasses" => parse_inner_classes(&mut attr_decoder)?,
+        "LineNumberTable" => parse_line_number_table(&mut attr_decoder)?,
+        "LocalVariableTable" => parse_local_variable_table(&mut attr_decoder)?,
+        "LocalVariableTypeTable" => parse_local_variable_type_table(&mut attr_decoder)?,
+        "MethodParameters" => parse_method_parameters(&mut attr_decoder)?,
+        "Module" => parse_module(&mut attr_decoder)?,
+        "ModuleMainClass" => {
+            let index = attr_decoder.read_u16()?;
+            Attribute::ModuleMainClass(index)
+        }
+        "ModulePackages" => parse_module_packages(&mut attr_decoder)?,
+        "NestHost" => {
+            let index = attr_decoder.read_u16()?;
+            Attribute::NestHost(index)
+        }
+        "NestMembers" => parse_nest_members(&mut attr_decoder)?,
+        "PermittedSubclasses" => parse_permitted_subclasses(&mut attr_decoder)?,
+        "Record" => parse_record(&mut attr_decoder, context)?,
+        "RuntimeVisibleAnnotations" => {
+            let annotations = parse_annotations(&mut attr_decoder)?;
+            Attribute::RuntimeVisibleAnnotations(annotations)
+        }
+        "RuntimeInvisibleAnnotations" => {
+            let annotations = parse_annotations(&mut attr_decoder)?;
+            Attribute::RuntimeInvisibleAnnotations(annotations)
+        }
+        "RuntimeVisibleParameterAnnotations" => {
+            let annotations = parse_parameter_annotations(&mut attr_decoder)?;
+            Attribute::RuntimeVisibleParameterAnnotations(annotations)
+        }
+        "RuntimeInvisibleParameterAnnotations" => {
+            let annotations = parse_parameter_annotations(&mut attr_decoder)?;
+            Attribute::RuntimeInvisibleParameterAnnotations(annotations)
+        }
+        "RuntimeVisibleTypeAnnotations" => {
+            let annotations = parse_type_annotations(&mut attr_decoder)?;
+            Attribute::RuntimeVisibleTypeAnnotations(annotations)
+        }
+        "RuntimeInvisibleTypeAnnotations" => {
+            let annotations = parse_type_annotations(&mut attr_decoder)?;
+            Attribute::RuntimeInvisibleTypeAnnotations(annotations)
+        }
+        "SourceFile" => {
+            let index = attr_decoder.read_u16()?;
+            Attribute::SourceFile(index)
+        }
+        "Signature" => {
+            let index = attr_decoder.read_u16()?;
+            Attribute::Signature(index)
+        }
+        "StackMapTable" => parse_stack_map_table(&mut attr_decoder)?,
+        "Synthetic" => Attribute::Synthetic,
+        "SourceDebugExtension" => {
+            Attribute::SourceDebugExtension(attr_decoder.read_str(length as usize)?)
+        }
+
+        _ => {
+            let bytes = attr_decoder.read_bytes(length as usize)?;
+            Attribute::Unknown(name_index, bytes.to_vec())
+        }
+    };
+
+    // the entire content must have been read
+    attr_decoder.remove_limit()?;
+
+    Ok(attribute)
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::write;
+    use super::*;
+
+    /// Builds a class with a broken `SourceFile` attribute in its only method.
+    fn broken_class() -> Vec<u8> {
+        let mut pool = Pool::new();
+        let name = pool.push(Item::UTF8("Broken".to_owned())).unwrap();
+        let name = pool.push(Item::Class(name)).unwrap();
+        let method_name = pool.push(Item::UTF8("test".to_owned())).unwrap();
+        let method_desc = pool.push(Item::UTF8("()V".to_owned())).unwrap();
+        let source_file = pool.push(Item::UTF8("SourceFile".to_owned())).unwrap();
+
+        let class = Class {
+            minor_version: 0,
+            major_version: 52,
+            access_flags: ClassAccessFlags::PUBLIC,
+            name,
+            super_name: 0,
+            interfaces: Vec::new(),
+            fields: Vec::new(),
+            methods: vec![
+                Method {
+                    access_flags: MethodAccessFlags::ABSTRACT,
+                    name: method_name,
+                    desc: method_desc,
+                    attributes: vec![
+                        Attribute::Deprecated,
+                        // the index has only 2 bytes
+                        Attribute::Unknown(source_file, vec![0, 3, 0]),
+                    ],
+                },
+            ],
+            attributes: Vec::new(),
+        };
+        pool.push(Item::UTF8("Deprecated".to_owned())).unwrap();
+        write(&pool, &class).unwrap()
+    }
+
+    #[test]
+    fn lenient() {
+        let bytes = broken_class();
+        assert!(parse(&bytes).is_err());
+
+        let options = ParseOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        let (pool, class, errors) = parse_with(&bytes, &options).unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].path,
+            vec![PathElement::Method(0), PathElement::Attribute(1)]
+        );
+        match class.methods[0].attributes[..] {
+            [Attribute::Deprecated, Attribute::Unknown(_, ref content)] => {
+                assert_eq!(content, &[0, 3, 0]);
             }
-            "Signature" => {
-                let index = attr_decoder.read_u16()?;
-                Attribute::Signature(index)
+            ref other => panic!("unexpected attributes: {:?}", other),
+        }
+
+        // the broken attribute is written as it was
+        assert!(write(&pool, &class).unwrap() == bytes);
+    }
+
+    #[test]
+    fn lazy() {
+        let bytes = include_bytes!("../../../examples/resources/Fibonacci.class");
+        let mut attributes = HashSet::new();
+        attributes.insert("SourceFile".to_owned());
+        let options = ParseOptions {
+            attributes: Some(attributes),
+            ..Default::default()
+        };
+
+        let (pool, class, errors) = parse_with(bytes, &options).unwrap();
+        assert!(errors.is_empty());
+        assert!(class
+            .attributes
+            .iter()
+            .any(|attribute| matches!(*attribute, Attribute::SourceFile(_))));
+        for method in &class.methods {
+            for attribute in &method.attributes {
+                match *attribute {
+                    Attribute::Unknown(name, _) => {
+                        assert_eq!(pool.get_utf8(name).unwrap(), "Code");
+                    }
+                    ref other => panic!("unexpected attribute: {:?}", other),
+                }
             }
-            "StackMapTable" => parse_stack_map_table(&mut attr_decoder)?,
-            "Synthetic" => Attribute::Synthetic,
-            "SourceDebugExtension" => {
-                Attribute::SourceDebugExtension(attr_decoder.read_str(length as usize)?)
+        }
+
+        // skipped attributes are written as they were
+        assert!(write(&pool, &class).unwrap()[..] == bytes[..]);
+    }
+
+    #[test]
+    fn default_options() {
+        let bytes = include_bytes!("../../../examples/resources/FizzBuzzStream.class");
+        let (pool, class) = parse(bytes).unwrap();
+        let (options_pool, options_class, errors) =
+            parse_with(bytes, &ParseOptions::default()).unwrap();
+        assert!(errors.is_empty());
+        assert_eq!(pool.len(), options_pool.len());
+        assert_eq!(format!("{:?}", class), format!("{:?}", options_class));
+    }
+
+    /// Parses a single attribute with the given name and content
+    fn attribute(name: &str, content: &[u8]) -> Attribute {
+        let mut pool = Pool::new();
+        let name = pool.push(Item::UTF8(name.to_owned())).unwrap();
+
+        let mut bytes = vec![0, 1, (name >> 8) as u8, name as u8];
+        bytes.extend_from_slice(&[0, 0, 0, content.len() as u8]);
+        bytes.extend_from_slice(content);
+        let options = ParseOptions::default();
+        let mut context = Context {
+            constant_pool: &pool,
+            options: &options,
+            path: Vec::new(),
+            errors: Vec::new(),
+            attribute_names: Vec::new(),
+        };
+        let mut cursor = 0;
+        let mut decoder = Decoder::new(&bytes, &mut cursor);
+        let mut attributes = parse_attributes(&mut decoder, &mut context).unwrap();
+        assert_eq!(cursor, bytes.len());
+        attributes.pop().unwrap()
+    }
+
+    #[test]
+    fn constant_pool() {
+        let bytes = [
+            0, 4, // count
+            5, 0, 0, 0, 0, 0, 0, 0, 1, // long 1
+            3, 0, 0, 0, 2, // integer 2
+        ];
+        let mut cursor = 0;
+        let pool = read_constant_pool(&mut Decoder::new(&bytes, &mut cursor)).unwrap();
+        assert_eq!(cursor, bytes.len());
+        assert_eq!(pool.get(1).unwrap(), &Item::Long(1));
+        assert_eq!(pool.get(3).unwrap(), &Item::Integer(2));
+    }
+
+    #[test]
+    fn enclosing_method() {
+        match attribute("EnclosingMethod", &[0, 2, 0, 3]) {
+            Attribute::EnclosingMethod {
+                class_index: 2,
+                method_index: 3,
+            } => {}
+            other => panic!("unexpected attribute: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn method_parameters() {
+        match attribute("MethodParameters", &[1, 0, 2, 0, 0x10]) {
+            Attribute::MethodParameters(ref parameters) => {
+                assert_eq!(parameters.len(), 1);
+                assert_eq!(parameters[0].name, 2);
+                assert_eq!(parameters[0].access_flags, ParameterFlags::FINAL);
             }
+            other => panic!("unexpected attribute: {:?}", other),
+        }
+    }
 
-            _ => {
-                let bytes = attr_decoder.read_bytes(length as usize)?;
-                Attribute::Unknown(name_index, bytes.to_vec())
+    #[test]
+    fn local_variable_target() {
+        let content = [
+            0, 1, // annotations
+            0x40, 0, 1, 0, 0, 0, 5, 0, 1, // local variable table
+            0, // type path
+            0, 2, 0, 0, // annotation
+        ];
+        match attribute("RuntimeVisibleTypeAnnotations", &content) {
+            Attribute::RuntimeVisibleTypeAnnotations(ref annotations) => {
+                match annotations[0].target_type {
+                    TargetType::LocalVariable(ref table) => {
+                        assert_eq!(table.len(), 1);
+                        assert_eq!(table[0].length, 5);
+                        assert_eq!(table[0].index, 1);
+                    }
+                    ref other => panic!("unexpected target type: {:?}", other),
+                }
             }
-        };
-        attributes.push(attribute);
+            other => panic!("unexpected attribute: {:?}", other),
+        }
+    }
 
-        // go on
-        attr_decoder.remove_limit()?;
+    #[test]
+    fn wide() {
+        let content = [
+            0, 1, 1, 1, // max stack and locals
+            0, 0, 0, 5, 0xC4, 0x3A, 1, 0, 0xB1, // wide astore 256; return
+            0, 0, 0, 0, // exceptions and attributes
+        ];
+        match attribute("Code", &content) {
+            Attribute::Code {
+                ref instructions, ..
+            } => {
+                assert_eq!(instructions.len(), 2);
+                match instructions.get(0) {
+                    Some(&Instruction::AStore(256)) => {}
+                    ref other => panic!("unexpected instruction: {:?}", other),
+                }
+            }
+            other => panic!("unexpected attribute: {:?}", other),
+        }
     }
 
-    Ok(attributes)
+    #[test]
+    fn surrogates() {
+        let bytes = include_bytes!("../../../examples/resources/Surrogates.class");
+        let (pool, _) = parse(bytes).unwrap();
+        let index = pool.index_of(&Item::UTF16(vec![0x61, 0xDE00, 0x62])).unwrap();
+        assert!(pool.get_utf8(index).is_err());
+        assert!(pool.index_of(&Item::UTF16(vec![0xD83D])).is_some());
+        assert!(pool.index_of(&Item::UTF8("\u{1F600}".to_owned())).is_some());
+    }
 }
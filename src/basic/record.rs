@@ -0,0 +1,147 @@
+//! The record module finds the members of a record class,
+//! which are implicitly declared for its components.
+
+use super::constpool::*;
+use super::tree::*;
+use result::*;
+
+/// The canonical constructor and the accessor methods of a record class,
+/// given by their index into `Class::methods`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordMembers {
+    /// The constructor, whose parameters equal the components in order,
+    /// `None` if it is missing.
+    pub canonical_constructor: Option<usize>,
+    /// The accessor of every component, in the order of the components,
+    /// `None` if it is missing.
+    pub accessors: Vec<Option<usize>>,
+}
+
+/// Finds the canonical constructor and the accessor methods of a record class.
+/// An accessor is an instance method named like its component,
+/// which takes no parameters and returns the type of the component.
+///
+/// `None` is returned, if the class has no `Record` attribute.
+///
+/// # Examples
+///
+/// ```
+/// use jbcrs::basic;
+///
+/// let (pool, class) = basic::parse(include_bytes!("../../examples/resources/Records.class"))
+///     .unwrap();
+/// let members = basic::record_members(&pool, &class).unwrap().unwrap();
+///
+/// let constructor = &class.methods[members.canonical_constructor.unwrap()];
+/// assert_eq!(
+///     pool.get_utf8(constructor.desc).unwrap(),
+///     "(Ljava/lang/String;Ljava/util/List;I)V"
+/// );
+///
+/// let accessor = &class.methods[members.accessors[2].unwrap()];
+/// assert_eq!(pool.get_utf8(accessor.name).unwrap(), "count");
+/// ```
+pub fn record_members(constant_pool: &Pool, class: &Class) -> Result<Option<RecordMembers>> {
+    let components = match class.attributes.iter().find_map(|attribute| match *attribute {
+        Attribute::Record(ref components) => Some(components),
+        _ => None,
+    }) {
+        Some(components) => components,
+        None => return Ok(None),
+    };
+
+    let mut constructor_desc = "(".to_owned();
+    let mut accessor_descs = Vec::with_capacity(components.len());
+    for component in components {
+        let name = constant_pool.get_utf8(component.name)?;
+        let desc = constant_pool.get_utf8(component.descriptor)?;
+        constructor_desc.push_str(&desc);
+        accessor_descs.push((name, format!("(){}", desc)));
+    }
+    constructor_desc.push_str(")V");
+
+    let mut members = RecordMembers {
+        canonical_constructor: None,
+        accessors: vec![None; components.len()],
+    };
+    for (i, method) in class.methods.iter().enumerate() {
+        if method.access_flags.contains(MethodAccessFlags::STATIC) {
+            continue;
+        }
+
+        let name = constant_pool.get_utf8(method.name)?;
+        let desc = constant_pool.get_utf8(method.desc)?;
+        if name == "<init>" && desc == constructor_desc {
+            members.canonical_constructor = Some(i);
+            continue;
+        }
+
+        let position = accessor_descs
+            .iter()
+            .position(|(accessor_name, accessor_desc)| {
+                *accessor_name == name && *accessor_desc == desc
+            });
+        if let Some(position) = position {
+            members.accessors[position] = Some(i);
+        }
+    }
+
+    Ok(Some(members))
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::parse;
+    use super::*;
+
+    #[test]
+    fn records() {
+        let (pool, class) = parse(include_bytes!("../../examples/resources/Records.class")).unwrap();
+        let members = record_members(&pool, &class).unwrap().unwrap();
+
+        let name = |index: Option<usize>| {
+            let method = &class.methods[index.unwrap()];
+            (
+                pool.get_utf8(method.name).unwrap(),
+                pool.get_utf8(method.desc).unwrap(),
+            )
+        };
+        assert_eq!(
+            name(members.canonical_constructor),
+            (
+                "<init>".to_owned(),
+                "(Ljava/lang/String;Ljava/util/List;I)V".to_owned()
+            )
+        );
+        assert_eq!(
+            members.accessors.iter().map(|index| name(*index)).collect::<Vec<_>>(),
+            vec![
+                ("name".to_owned(), "()Ljava/lang/String;".to_owned()),
+                ("values".to_owned(), "()Ljava/util/List;".to_owned()),
+                ("count".to_owned(), "()I".to_owned()),
+            ]
+        );
+
+        // the components carry their own attributes
+        match class.attributes.iter().find_map(|attribute| match *attribute {
+            Attribute::Record(ref components) => Some(components),
+            _ => None,
+        }) {
+            Some(components) => {
+                assert_eq!(components.len(), 3);
+                assert!(components[0].attributes.is_empty());
+                match components[1].attributes[..] {
+                    [Attribute::Signature(signature), Attribute::RuntimeVisibleAnnotations(_)] => {
+                        assert_eq!(pool.get_utf8(signature).unwrap(), "Ljava/util/List<TT;>;");
+                    }
+                    ref other => panic!("unexpected attributes: {:?}", other),
+                }
+            }
+            None => panic!("no record attribute"),
+        }
+
+        // not a record
+        let (pool, class) = parse(include_bytes!("../../examples/resources/Fibonacci.class")).unwrap();
+        assert_eq!(record_members(&pool, &class).unwrap(), None);
+    }
+}
@@ -0,0 +1,1027 @@
+//! The resolve module resolves references to fields and methods in a set of classes
+//! and selects the method invoked on a receiver,
+//! just like the JVM does when linking (JVMS §5.4.3 and §5.4.6).
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use super::constpool::*;
+use super::hierarchy::ClassHierarchy;
+use super::tree::*;
+use super::{package, OBJECT};
+use result::*;
+
+/// The classes, which may declare signature polymorphic methods (JVMS §2.9.3)
+const SIGNATURE_POLYMORPHIC_CLASSES: &[&str] =
+    &["java/lang/invoke/MethodHandle", "java/lang/invoke/VarHandle"];
+
+/// The parameters of every signature polymorphic method
+const SIGNATURE_POLYMORPHIC_PARAMETERS: &str = "([Ljava/lang/Object;)";
+
+/// A reason, why a reference cannot be resolved or no method can be selected.
+/// The java error thrown by the JVM is given for every reason.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolutionError {
+    /// A method reference names an interface (`IncompatibleClassChangeError`).
+    ExpectedClass { class: String },
+    /// An interface method reference names a class (`IncompatibleClassChangeError`).
+    ExpectedInterface { class: String },
+    /// Neither the class nor its supertypes declare the field (`NoSuchFieldError`).
+    NoSuchField {
+        class: String,
+        name: String,
+        desc: String,
+    },
+    /// Neither the class nor its supertypes declare the method (`NoSuchMethodError`).
+    NoSuchMethod {
+        class: String,
+        name: String,
+        desc: String,
+    },
+    /// The method selected for the receiver is abstract
+    /// or there is no method to select (`AbstractMethodError`).
+    AbstractMethod {
+        class: String,
+        name: String,
+        desc: String,
+    },
+    /// More than one maximally-specific superinterface method of the receiver
+    /// is not abstract, the interfaces declaring them are given
+    /// (`IncompatibleClassChangeError`).
+    AmbiguousMethod {
+        class: String,
+        name: String,
+        desc: String,
+        interfaces: Vec<String>,
+    },
+}
+
+impl fmt::Display for ResolutionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ResolutionError::ExpectedClass { ref class } => {
+                write!(f, "method reference to interface {}", class)
+            }
+            ResolutionError::ExpectedInterface { ref class } => {
+                write!(f, "interface method reference to class {}", class)
+            }
+            ResolutionError::NoSuchField {
+                ref class,
+                ref name,
+                ref desc,
+            } => write!(f, "no field {}:{} in {}", name, desc, class),
+            ResolutionError::NoSuchMethod {
+                ref class,
+                ref name,
+                ref desc,
+            } => write!(f, "no method {}{} in {}", name, desc, class),
+            ResolutionError::AbstractMethod {
+                ref class,
+                ref name,
+                ref desc,
+            } => write!(f, "no implementation of {}{} for {}", name, desc, class),
+            ResolutionError::AmbiguousMethod {
+                ref class,
+                ref name,
+                ref desc,
+                ref interfaces,
+            } => write!(
+                f,
+                "conflicting default methods {}{} of {} in {}",
+                name,
+                desc,
+                class,
+                interfaces.join(", ")
+            ),
+        }
+    }
+}
+
+/// A field found by `MemberResolver`.
+#[derive(Clone, Copy)]
+pub struct ResolvedField<'a> {
+    /// The name of the class declaring the field
+    pub class_name: &'a str,
+    /// The constant pool of the class declaring the field
+    pub constant_pool: &'a Pool,
+    /// The class declaring the field
+    pub class: &'a Class,
+    /// The field itself
+    pub field: &'a Field,
+}
+
+/// A method found by `MemberResolver`.
+///
+/// The descriptor of a signature polymorphic method
+/// differs from the descriptor of the reference resolved to it.
+#[derive(Clone, Copy)]
+pub struct ResolvedMethod<'a> {
+    /// The name of the class declaring the method
+    pub class_name: &'a str,
+    /// The constant pool of the class declaring the method
+    pub constant_pool: &'a Pool,
+    /// The class declaring the method
+    pub class: &'a Class,
+    /// The method itself
+    pub method: &'a Method,
+}
+
+/// A class of a `MemberResolver`, whose member names have been read from its constant pool.
+struct Entry<'a> {
+    constant_pool: &'a Pool,
+    class: &'a Class,
+    name: String,
+    /// The name and the descriptor of every field, in the order of the class
+    fields: Vec<(String, String)>,
+    /// The name and the descriptor of every method, in the order of the class
+    methods: Vec<(String, String)>,
+}
+
+impl<'a> Entry<'a> {
+    fn is_interface(&self) -> bool {
+        self.class.access_flags.contains(ClassAccessFlags::INTERFACE)
+    }
+
+    /// Returns the first method matching a name, a descriptor and a condition
+    fn method<F>(&self, name: &str, desc: &str, condition: F) -> Option<&'a Method>
+    where
+        F: Fn(MethodAccessFlags) -> bool,
+    {
+        let methods: &'a [Method] = &self.class.methods;
+        self.methods
+            .iter()
+            .zip(methods)
+            .find(|&((method_name, method_desc), method)| {
+                method_name == name && method_desc == desc && condition(method.access_flags)
+            })
+            .map(|(_, method)| method)
+    }
+
+    /// Returns a method, which is neither private nor static
+    fn instance_method(&self, name: &str, desc: &str) -> Option<&'a Method> {
+        self.method(name, desc, |flags| {
+            !flags.intersects(MethodAccessFlags::PRIVATE | MethodAccessFlags::STATIC)
+        })
+    }
+}
+
+/// Resolves symbolic references to fields and methods in a set of classes
+/// and selects the method invoked by `invokevirtual` and `invokeinterface`
+/// for the class of the receiver, as specified by JVMS §5.4.3.2 - §5.4.3.4 and §5.4.6.
+///
+/// Classes are identified by their names only, so there are no class loaders.
+/// Access control (JVMS §5.4.4) is not checked,
+/// except for the accessibility required to override a method.
+/// Arrays are treated like `java/lang/Object`.
+///
+/// If the result depends on a class, which is not part of the set,
+/// `Error::ClassNotFound` is returned.
+/// If the reference cannot be resolved or no method can be selected,
+/// `Error::Resolution` is returned.
+///
+/// # Examples
+///
+/// ```
+/// use jbcrs::basic::{self, MemberResolver};
+///
+/// let sealed = basic::parse(include_bytes!("../../examples/resources/Sealed.class")).unwrap();
+/// let leaf = basic::parse(include_bytes!("../../examples/resources/Sealed$Leaf.class")).unwrap();
+/// let node = basic::parse(include_bytes!("../../examples/resources/Sealed$Node.class")).unwrap();
+/// let classes = [sealed, leaf, node];
+/// let resolver = MemberResolver::new(&classes).unwrap();
+///
+/// // `Sealed$Node.size` calls `left.size()`, where `left` is a `Sealed`
+/// let size = resolver.resolve_method("Sealed", "size", "()I").unwrap();
+/// assert_eq!(size.class_name, "Sealed");
+///
+/// let selected = resolver.select(&size, "Sealed$Leaf").unwrap();
+/// assert_eq!(selected.class_name, "Sealed$Leaf");
+/// ```
+pub struct MemberResolver<'a> {
+    /// The super classes and interfaces of the classes
+    hierarchy: ClassHierarchy,
+    classes: HashMap<String, Entry<'a>>,
+}
+
+impl<'a> MemberResolver<'a> {
+    /// Creates a resolver for a set of classes.
+    /// An error is returned, if a name cannot be found in a constant pool.
+    pub fn new(classes: &'a [(Pool, Class)]) -> Result<MemberResolver<'a>> {
+        let mut resolver = MemberResolver {
+            hierarchy: ClassHierarchy::default(),
+            classes: HashMap::with_capacity(classes.len()),
+        };
+        for (constant_pool, class) in classes {
+            resolver.push(constant_pool, class)?;
+        }
+        Ok(resolver)
+    }
+
+    /// Adds a class to the resolver.
+    /// If a class of the same name has been added before, the class is ignored,
+    /// just like a class path would hide it.
+    pub fn push(&mut self, constant_pool: &'a Pool, class: &'a Class) -> Result<()> {
+        let name = constant_pool.get_class_name(class.name)?;
+        if self.classes.contains_key(&name) {
+            return Ok(());
+        }
+
+        self.hierarchy.push(constant_pool, class)?;
+        let mut fields = Vec::with_capacity(class.fields.len());
+        for field in &class.fields {
+            fields.push((
+                constant_pool.get_utf8(field.name)?,
+                constant_pool.get_utf8(field.desc)?,
+            ));
+        }
+        let mut methods = Vec::with_capacity(class.methods.len());
+        for method in &class.methods {
+            methods.push((
+                constant_pool.get_utf8(method.name)?,
+                constant_pool.get_utf8(method.desc)?,
+            ));
+        }
+
+        let entry = Entry {
+            constant_pool,
+            class,
+            name: name.clone(),
+            fields,
+            methods,
+        };
+        self.classes.insert(name, entry);
+        Ok(())
+    }
+
+    /// Resolves a `FieldRef` of a constant pool
+    pub fn resolve_field_ref(&self, constant_pool: &Pool, index: u16) -> Result<ResolvedField<'_>> {
+        match *constant_pool.get(index)? {
+            Item::FieldRef {
+                class,
+                name_and_type,
+            } => {
+                let (name, desc) = name_and_type_of(constant_pool, name_and_type)?;
+                self.resolve_field(&constant_pool.get_class_name(class)?, &name, &desc)
+            }
+            _ => Err(Error::InvalidCPItem(index)),
+        }
+    }
+
+    /// Resolves a `MethodRef` or an `InterfaceMethodRef` of a constant pool
+    pub fn resolve_method_ref(
+        &self,
+        constant_pool: &Pool,
+        index: u16,
+    ) -> Result<ResolvedMethod<'_>> {
+        let (class, name_and_type, interface) = match *constant_pool.get(index)? {
+            Item::MethodRef {
+                class,
+                name_and_type,
+            } => (class, name_and_type, false),
+            Item::InterfaceMethodRef {
+                class,
+                name_and_type,
+            } => (class, name_and_type, true),
+            _ => return Err(Error::InvalidCPItem(index)),
+        };
+
+        let class = constant_pool.get_class_name(class)?;
+        let (name, desc) = name_and_type_of(constant_pool, name_and_type)?;
+        if interface {
+            self.resolve_interface_method(&class, &name, &desc)
+        } else {
+            self.resolve_method(&class, &name, &desc)
+        }
+    }
+
+    /// Resolves a field of a class or an interface (JVMS §5.4.3.2):
+    /// The field is looked up in the class, then in its direct superinterfaces
+    /// (recursively and in the order they are declared)
+    /// and at last in its super class (recursively).
+    pub fn resolve_field(&self, class: &str, name: &str, desc: &str) -> Result<ResolvedField<'_>> {
+        let entry = self.entry(class)?;
+        let mut visited = HashSet::new();
+        match self.lookup_field(entry, name, desc, &mut visited)? {
+            Some(field) => Ok(field),
+            None => Err(Error::Resolution(ResolutionError::NoSuchField {
+                class: class.to_owned(),
+                name: name.to_owned(),
+                desc: desc.to_owned(),
+            })),
+        }
+    }
+
+    /// Resolves a method of a class referenced by a `MethodRef` (JVMS §5.4.3.3):
+    ///
+    /// 1. The class and its super classes are searched, the nearest declaration wins.
+    ///    If `java/lang/invoke/MethodHandle` or `java/lang/invoke/VarHandle`
+    ///    declare exactly one method of the name, which is signature polymorphic,
+    ///    the descriptor is not compared.
+    /// 2. Otherwise the only maximally-specific superinterface method, which is not abstract,
+    ///    is chosen.
+    /// 3. Otherwise any maximally-specific superinterface method is chosen.
+    pub fn resolve_method(
+        &self,
+        class: &str,
+        name: &str,
+        desc: &str,
+    ) -> Result<ResolvedMethod<'_>> {
+        let entry = self.entry(class)?;
+        if entry.is_interface() {
+            return Err(Error::Resolution(ResolutionError::ExpectedClass {
+                class: class.to_owned(),
+            }));
+        }
+
+        if let Some(method) = self.lookup_in_classes(entry, name, desc)? {
+            return Ok(method);
+        }
+        self.lookup_in_interfaces(entry, name, desc)?
+            .ok_or_else(|| no_such_method(class, name, desc))
+    }
+
+    /// Resolves a method of an interface referenced by an `InterfaceMethodRef`
+    /// (JVMS §5.4.3.4):
+    ///
+    /// 1. The method declared by the interface itself is chosen.
+    /// 2. Otherwise a public instance method of `java/lang/Object` is chosen.
+    /// 3. Otherwise the only maximally-specific superinterface method, which is not abstract,
+    ///    is chosen.
+    /// 4. Otherwise any maximally-specific superinterface method is chosen.
+    pub fn resolve_interface_method(
+        &self,
+        class: &str,
+        name: &str,
+        desc: &str,
+    ) -> Result<ResolvedMethod<'_>> {
+        let entry = self.entry(class)?;
+        if !entry.is_interface() {
+            return Err(Error::Resolution(ResolutionError::ExpectedInterface {
+                class: class.to_owned(),
+            }));
+        }
+
+        if let Some(method) = entry.method(name, desc, |_| true) {
+            return Ok(resolved_method(entry, method));
+        }
+
+        let object = self.entry(OBJECT)?;
+        let public = object.method(name, desc, |flags| {
+            flags.contains(MethodAccessFlags::PUBLIC) && !flags.contains(MethodAccessFlags::STATIC)
+        });
+        if let Some(method) = public {
+            return Ok(resolved_method(object, method));
+        }
+
+        self.lookup_in_interfaces(entry, name, desc)?
+            .ok_or_else(|| no_such_method(class, name, desc))
+    }
+
+    /// Selects the method invoked by `invokevirtual` or `invokeinterface` (JVMS §5.4.6),
+    /// given the resolved method and the class of the receiver:
+    ///
+    /// 1. A private method is selected itself.
+    /// 2. Otherwise the class of the receiver and its super classes are searched
+    ///    for an instance method, which can override the resolved method (JVMS §5.4.5).
+    /// 3. Otherwise the only maximally-specific superinterface method of the receiver,
+    ///    which is not abstract, is selected.
+    ///
+    /// `ResolutionError::AbstractMethod` is returned, if the method selected is abstract
+    /// or no method is found, and `ResolutionError::AmbiguousMethod`,
+    /// if more than one maximally-specific superinterface method is not abstract.
+    pub fn select<'r>(
+        &'r self,
+        resolved: &ResolvedMethod<'r>,
+        receiver: &str,
+    ) -> Result<ResolvedMethod<'r>> {
+        if resolved.method.access_flags.contains(MethodAccessFlags::PRIVATE) {
+            return Ok(*resolved);
+        }
+        let name = resolved.constant_pool.get_utf8(resolved.method.name)?;
+        let desc = resolved.constant_pool.get_utf8(resolved.method.desc)?;
+        let (name, desc) = (name.as_str(), desc.as_str());
+
+        let receiver_entry = self.entry(receiver)?;
+        for entry in self.super_classes(receiver_entry) {
+            let entry = entry?;
+            let method = entry.method(name, desc, |flags| {
+                !flags.contains(MethodAccessFlags::STATIC)
+            });
+            if let Some(method) = method {
+                if self.can_override(entry, method, resolved.class_name, resolved.method)? {
+                    if method.access_flags.contains(MethodAccessFlags::ABSTRACT) {
+                        return Err(abstract_method(receiver, name, desc));
+                    }
+                    return Ok(resolved_method(entry, method));
+                }
+            }
+        }
+
+        let mut candidates: Vec<(&Entry, &Method)> = self
+            .maximally_specific(receiver_entry, name, desc)?
+            .into_iter()
+            .filter(|&(_, method)| !method.access_flags.contains(MethodAccessFlags::ABSTRACT))
+            .collect();
+        match candidates.len() {
+            0 => Err(abstract_method(receiver, name, desc)),
+            1 => {
+                let (entry, method) = candidates.remove(0);
+                Ok(resolved_method(entry, method))
+            }
+            _ => Err(Error::Resolution(ResolutionError::AmbiguousMethod {
+                class: receiver.to_owned(),
+                name: name.to_owned(),
+                desc: desc.to_owned(),
+                interfaces: candidates
+                    .into_iter()
+                    .map(|(entry, _)| entry.name.clone())
+                    .collect(),
+            })),
+        }
+    }
+
+    /// Returns a class by its name, arrays are treated like `java/lang/Object`
+    fn entry(&self, name: &str) -> Result<&Entry<'a>> {
+        let name = if name.starts_with('[') { OBJECT } else { name };
+        self.classes
+            .get(name)
+            .ok_or_else(|| Error::ClassNotFound(name.to_owned()))
+    }
+
+    /// Returns a class followed by its super classes, the nearest ones first.
+    /// The classes are looked up one after another, so a missing class is an error
+    /// only if the classes in front of it do not suffice.
+    fn super_classes<'r>(
+        &'r self,
+        entry: &'r Entry<'a>,
+    ) -> impl Iterator<Item = Result<&'r Entry<'a>>> + 'r {
+        let super_classes = self.hierarchy.super_classes(&entry.name);
+        Some(Ok(entry))
+            .into_iter()
+            .chain(super_classes.into_iter().map(move |name| self.entry(name)))
+    }
+
+    /// Looks up a field in a class, its superinterfaces and its super class
+    fn lookup_field<'r>(
+        &'r self,
+        entry: &'r Entry<'a>,
+        name: &str,
+        desc: &str,
+        visited: &mut HashSet<&'r str>,
+    ) -> Result<Option<ResolvedField<'r>>> {
+        // protects against circular hierarchies
+        if !visited.insert(&entry.name) {
+            return Ok(None);
+        }
+
+        let field = entry
+            .fields
+            .iter()
+            .zip(&entry.class.fields)
+            .find(|&((field_name, field_desc), _)| field_name == name && field_desc == desc);
+        if let Some((_, field)) = field {
+            return Ok(Some(ResolvedField {
+                class_name: &entry.name,
+                constant_pool: entry.constant_pool,
+                class: entry.class,
+                field,
+            }));
+        }
+
+        for interface in self.hierarchy.direct_interfaces(&entry.name) {
+            let interface = self.entry(interface)?;
+            if let Some(field) = self.lookup_field(interface, name, desc, visited)? {
+                return Ok(Some(field));
+            }
+        }
+
+        match self.hierarchy.direct_super_class(&entry.name) {
+            Some(super_name) => self.lookup_field(self.entry(super_name)?, name, desc, visited),
+            None => Ok(None),
+        }
+    }
+
+    /// Looks up a method in a class and its super classes (step 2 of JVMS §5.4.3.3)
+    fn lookup_in_classes<'r>(
+        &'r self,
+        entry: &'r Entry<'a>,
+        name: &str,
+        desc: &str,
+    ) -> Result<Option<ResolvedMethod<'r>>> {
+        for entry in self.super_classes(entry) {
+            let entry = entry?;
+            if SIGNATURE_POLYMORPHIC_CLASSES.contains(&entry.name.as_str()) {
+                let mut named = entry
+                    .methods
+                    .iter()
+                    .zip(&entry.class.methods)
+                    .filter(|&((method_name, _), _)| method_name == name);
+                if let (Some(((_, method_desc), method)), None) = (named.next(), named.next()) {
+                    if is_signature_polymorphic(method_desc, method) {
+                        return Ok(Some(resolved_method(entry, method)));
+                    }
+                }
+            }
+
+            if let Some(method) = entry.method(name, desc, |_| true) {
+                return Ok(Some(resolved_method(entry, method)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Looks up a method in the superinterfaces of a class or an interface
+    /// (step 3 of JVMS §5.4.3.3 and steps 4 and 5 of JVMS §5.4.3.4)
+    fn lookup_in_interfaces<'r>(
+        &'r self,
+        entry: &'r Entry<'a>,
+        name: &str,
+        desc: &str,
+    ) -> Result<Option<ResolvedMethod<'r>>> {
+        let candidates = self.maximally_specific(entry, name, desc)?;
+        let mut non_abstract = candidates
+            .iter()
+            .filter(|&&(_, method)| !method.access_flags.contains(MethodAccessFlags::ABSTRACT));
+        if let (Some(&(entry, method)), None) = (non_abstract.next(), non_abstract.next()) {
+            return Ok(Some(resolved_method(entry, method)));
+        }
+
+        // every superinterface method is a maximally-specific one,
+        // or is overridden by one, so the first one is chosen
+        Ok(candidates
+            .first()
+            .map(|&(entry, method)| resolved_method(entry, method)))
+    }
+
+    /// Returns every interface, which is implemented by a class directly,
+    /// by one of its super classes or is extended by one of these interfaces,
+    /// the nearest ones first.
+    fn superinterfaces<'r>(&'r self, entry: &'r Entry<'a>) -> Result<Vec<&'r Entry<'a>>> {
+        let mut interfaces = Vec::new();
+        for supertype in self.hierarchy.supertypes(&entry.name) {
+            let supertype = self.entry(supertype)?;
+            if supertype.is_interface() {
+                interfaces.push(supertype);
+            }
+        }
+        Ok(interfaces)
+    }
+
+    /// Returns the maximally-specific superinterface methods of a class or an interface
+    /// (JVMS §5.4.3.3): all instance methods of its superinterfaces,
+    /// which are not declared by an interface extended by another one declaring the method.
+    fn maximally_specific<'r>(
+        &'r self,
+        entry: &'r Entry<'a>,
+        name: &str,
+        desc: &str,
+    ) -> Result<Vec<(&'r Entry<'a>, &'a Method)>> {
+        let mut candidates = Vec::new();
+        for interface in self.superinterfaces(entry)? {
+            if let Some(method) = interface.instance_method(name, desc) {
+                candidates.push((interface, method));
+            }
+        }
+
+        let mut maximal = Vec::with_capacity(candidates.len());
+        for &(interface, method) in &candidates {
+            let mut overridden = false;
+            for &(other, _) in &candidates {
+                if other.name != interface.name
+                    && self.superinterfaces(other)?
+                        .iter()
+                        .any(|superinterface| superinterface.name == interface.name)
+                {
+                    overridden = true;
+                    break;
+                }
+            }
+            if !overridden {
+                maximal.push((interface, method));
+            }
+        }
+        Ok(maximal)
+    }
+
+    /// Returns true if the instance method `mc` of the class `c`
+    /// can override the method `ma` of the class `a` (JVMS §5.4.5).
+    /// Both methods must have the same name and descriptor.
+    fn can_override(&self, c: &Entry<'a>, mc: &Method, a: &str, ma: &Method) -> Result<bool> {
+        if mc.access_flags.contains(MethodAccessFlags::PRIVATE) {
+            return Ok(false);
+        }
+        if ma.access_flags
+            .intersects(MethodAccessFlags::PUBLIC | MethodAccessFlags::PROTECTED)
+        {
+            return Ok(true);
+        }
+        if ma.access_flags.contains(MethodAccessFlags::PRIVATE) {
+            return Ok(false);
+        }
+        if package(&c.name) == package(a) {
+            return Ok(true);
+        }
+
+        // a package private method may still be overridden transitively
+        // by overriding a method of a class in between, which overrides `ma`
+        let name = c.constant_pool.get_utf8(mc.name)?;
+        let desc = c.constant_pool.get_utf8(mc.desc)?;
+        let mut between: Vec<&Entry<'a>> = Vec::new();
+        for super_name in self.hierarchy.super_classes(&c.name) {
+            if super_name == a {
+                for b in between {
+                    let mb = b.method(&name, &desc, |flags| {
+                        !flags.contains(MethodAccessFlags::STATIC)
+                    });
+                    if let Some(mb) = mb {
+                        if self.can_override(b, mb, a, ma)?
+                            && self.can_override(c, mc, &b.name, mb)?
+                        {
+                            return Ok(true);
+                        }
+                    }
+                }
+                return Ok(false);
+            }
+            between.push(self.entry(super_name)?);
+        }
+        Ok(false)
+    }
+}
+
+/// Creates the result of a resolution
+fn resolved_method<'r>(entry: &'r Entry, method: &'r Method) -> ResolvedMethod<'r> {
+    ResolvedMethod {
+        class_name: &entry.name,
+        constant_pool: entry.constant_pool,
+        class: entry.class,
+        method,
+    }
+}
+
+/// Returns true if a method is signature polymorphic (JVMS §2.9.3),
+/// the class declaring it must be checked as well
+fn is_signature_polymorphic(desc: &str, method: &Method) -> bool {
+    desc.starts_with(SIGNATURE_POLYMORPHIC_PARAMETERS)
+        && method
+            .access_flags
+            .contains(MethodAccessFlags::VARARGS | MethodAccessFlags::NATIVE)
+}
+
+/// Returns the name and the descriptor of a `NameAndType` item
+fn name_and_type_of(constant_pool: &Pool, index: u16) -> Result<(String, String)> {
+    match *constant_pool.get(index)? {
+        Item::NameAndType { name, desc } => {
+            Ok((constant_pool.get_utf8(name)?, constant_pool.get_utf8(desc)?))
+        }
+        _ => Err(Error::InvalidCPItem(index)),
+    }
+}
+
+fn no_such_method(class: &str, name: &str, desc: &str) -> Error {
+    Error::Resolution(ResolutionError::NoSuchMethod {
+        class: class.to_owned(),
+        name: name.to_owned(),
+        desc: desc.to_owned(),
+    })
+}
+
+fn abstract_method(class: &str, name: &str, desc: &str) -> Error {
+    Error::Resolution(ResolutionError::AbstractMethod {
+        class: class.to_owned(),
+        name: name.to_owned(),
+        desc: desc.to_owned(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::fixture;
+    use super::*;
+
+    const PUBLIC: MethodAccessFlags = MethodAccessFlags::PUBLIC;
+    const PACKAGE: MethodAccessFlags = MethodAccessFlags::empty();
+
+    fn abstract_() -> MethodAccessFlags {
+        MethodAccessFlags::PUBLIC | MethodAccessFlags::ABSTRACT
+    }
+
+    /// The kind of a class created by `class`
+    enum Kind {
+        Class,
+        Interface,
+    }
+
+    /// Creates a class declaring public static fields of the descriptor `I`
+    /// and methods of the descriptor `()V`
+    fn class(
+        kind: Kind,
+        name: &str,
+        super_name: Option<&str>,
+        interfaces: &[&str],
+        fields: &[&str],
+        methods: &[(&str, MethodAccessFlags)],
+    ) -> (Pool, Class) {
+        let access_flags = match kind {
+            Kind::Class => ClassAccessFlags::PUBLIC,
+            Kind::Interface => {
+                ClassAccessFlags::PUBLIC | ClassAccessFlags::INTERFACE | ClassAccessFlags::ABSTRACT
+            }
+        };
+        let (mut pool, mut class) =
+            fixture::class(52, access_flags, name, super_name, interfaces);
+
+        let int = pool.push(Item::UTF8("I".to_owned())).unwrap();
+        class.fields = fields
+            .iter()
+            .map(|field| Field {
+                access_flags: FieldAccessFlags::PUBLIC | FieldAccessFlags::STATIC,
+                name: pool.push(Item::UTF8((*field).to_owned())).unwrap(),
+                desc: int,
+                attributes: Vec::new(),
+            })
+            .collect();
+        let void = pool.push(Item::UTF8("()V".to_owned())).unwrap();
+        class.methods = methods
+            .iter()
+            .map(|&(method, access_flags)| Method {
+                access_flags,
+                name: pool.push(Item::UTF8(method.to_owned())).unwrap(),
+                desc: void,
+                attributes: Vec::new(),
+            })
+            .collect();
+        (pool, class)
+    }
+
+    fn classes() -> Vec<(Pool, Class)> {
+        use self::Kind::*;
+
+        vec![
+            class(
+                Class,
+                OBJECT,
+                None,
+                &[],
+                &[],
+                &[("hashCode", PUBLIC), ("clone", MethodAccessFlags::PROTECTED)],
+            ),
+            class(Interface, "I", Some(OBJECT), &[], &["x"], &[("d", PUBLIC), ("a", abstract_())]),
+            class(Interface, "J", Some(OBJECT), &["I"], &["y"], &[("d", PUBLIC)]),
+            class(Interface, "K", Some(OBJECT), &[], &[], &[("d", PUBLIC)]),
+            class(Class, "A", Some(OBJECT), &["I"], &["y"], &[("m", PUBLIC)]),
+            class(Class, "B", Some("A"), &["J"], &[], &[]),
+            class(Class, "C", Some(OBJECT), &["J"], &[], &[]),
+            class(Class, "D", Some("C"), &["K"], &[], &[]),
+            class(
+                Class,
+                "E",
+                Some("C"),
+                &[],
+                &[],
+                &[("d", PUBLIC), ("a", MethodAccessFlags::PUBLIC | MethodAccessFlags::STATIC)],
+            ),
+            class(Class, "F", Some("x/Missing"), &[], &[], &[]),
+            class(Class, "G", Some("A"), &[], &[], &[("m", abstract_())]),
+        ]
+    }
+
+    /// Returns the name of the class declaring a method and its access flags
+    fn found(method: Result<ResolvedMethod<'_>>) -> (&str, MethodAccessFlags) {
+        let method = method.unwrap();
+        (method.class_name, method.method.access_flags)
+    }
+
+    fn error<T>(result: Result<T>) -> ResolutionError {
+        match result {
+            Err(Error::Resolution(error)) => error,
+            Err(error) => panic!("unexpected error {:?}", error),
+            Ok(_) => panic!("resolution should fail"),
+        }
+    }
+
+    fn no_such_method(class: &str, name: &str) -> ResolutionError {
+        ResolutionError::NoSuchMethod {
+            class: class.to_owned(),
+            name: name.to_owned(),
+            desc: "()V".to_owned(),
+        }
+    }
+
+    #[test]
+    fn fields() {
+        let classes = classes();
+        let resolver = MemberResolver::new(&classes).unwrap();
+
+        assert_eq!(resolver.resolve_field("B", "x", "I").unwrap().class_name, "I");
+        // superinterfaces are searched before the super class
+        assert_eq!(resolver.resolve_field("B", "y", "I").unwrap().class_name, "J");
+        assert_eq!(resolver.resolve_field("A", "y", "I").unwrap().class_name, "A");
+        assert_eq!(
+            error(resolver.resolve_field("B", "y", "J")),
+            ResolutionError::NoSuchField {
+                class: "B".to_owned(),
+                name: "y".to_owned(),
+                desc: "J".to_owned(),
+            }
+        );
+        match resolver.resolve_field("F", "z", "I") {
+            Err(Error::ClassNotFound(ref name)) if name == "x/Missing" => {}
+            _ => panic!("the super class of F is missing"),
+        }
+    }
+
+    #[test]
+    fn methods() {
+        let classes = classes();
+        let resolver = MemberResolver::new(&classes).unwrap();
+
+        assert_eq!(found(resolver.resolve_method("B", "m", "()V")), ("A", PUBLIC));
+        assert_eq!(found(resolver.resolve_method("B", "d", "()V")), ("J", PUBLIC));
+        assert_eq!(found(resolver.resolve_method("B", "a", "()V")), ("I", abstract_()));
+        // both J.d and K.d are maximally-specific, the nearest one is chosen
+        assert_eq!(found(resolver.resolve_method("D", "d", "()V")), ("K", PUBLIC));
+        assert_eq!(
+            found(resolver.resolve_method("[I", "clone", "()V")),
+            (OBJECT, MethodAccessFlags::PROTECTED)
+        );
+        assert_eq!(
+            error(resolver.resolve_method("I", "d", "()V")),
+            ResolutionError::ExpectedClass {
+                class: "I".to_owned(),
+            }
+        );
+        assert_eq!(error(resolver.resolve_method("A", "n", "()V")), no_such_method("A", "n"));
+
+        assert_eq!(found(resolver.resolve_interface_method("J", "d", "()V")), ("J", PUBLIC));
+        assert_eq!(
+            found(resolver.resolve_interface_method("J", "a", "()V")),
+            ("I", abstract_())
+        );
+        assert_eq!(
+            found(resolver.resolve_interface_method("K", "hashCode", "()V")),
+            (OBJECT, PUBLIC)
+        );
+        // only public methods of java/lang/Object are found
+        assert_eq!(
+            error(resolver.resolve_interface_method("K", "clone", "()V")),
+            no_such_method("K", "clone")
+        );
+        assert_eq!(
+            error(resolver.resolve_interface_method("A", "m", "()V")),
+            ResolutionError::ExpectedInterface {
+                class: "A".to_owned(),
+            }
+        );
+    }
+
+    #[test]
+    fn references() {
+        let classes = classes();
+        let resolver = MemberResolver::new(&classes).unwrap();
+
+        let mut pool = Pool::new();
+        let class = pool.push(Item::UTF8("B".to_owned())).unwrap();
+        let class = pool.push(Item::Class(class)).unwrap();
+        let name = pool.push(Item::UTF8("d".to_owned())).unwrap();
+        let desc = pool.push(Item::UTF8("()V".to_owned())).unwrap();
+        let name_and_type = pool.push(Item::NameAndType { name, desc }).unwrap();
+        let method = pool.push(Item::MethodRef {
+            class,
+            name_and_type,
+        }).unwrap();
+        let interface_method = pool.push(Item::InterfaceMethodRef {
+            class,
+            name_and_type,
+        }).unwrap();
+        let name = pool.push(Item::UTF8("x".to_owned())).unwrap();
+        let desc = pool.push(Item::UTF8("I".to_owned())).unwrap();
+        let name_and_type = pool.push(Item::NameAndType { name, desc }).unwrap();
+        let field = pool.push(Item::FieldRef {
+            class,
+            name_and_type,
+        }).unwrap();
+
+        assert_eq!(found(resolver.resolve_method_ref(&pool, method)), ("J", PUBLIC));
+        assert_eq!(
+            error(resolver.resolve_method_ref(&pool, interface_method)),
+            ResolutionError::ExpectedInterface {
+                class: "B".to_owned(),
+            }
+        );
+        assert_eq!(resolver.resolve_field_ref(&pool, field).unwrap().class_name, "I");
+        assert!(resolver.resolve_field_ref(&pool, method).is_err());
+        assert!(resolver.resolve_method_ref(&pool, field).is_err());
+    }
+
+    #[test]
+    fn selection() {
+        let classes = classes();
+        let resolver = MemberResolver::new(&classes).unwrap();
+
+        let d = resolver.resolve_method("C", "d", "()V").unwrap();
+        assert_eq!(found(resolver.select(&d, "C")), ("J", PUBLIC));
+        assert_eq!(found(resolver.select(&d, "E")), ("E", PUBLIC));
+        assert_eq!(
+            error(resolver.select(&d, "D")),
+            ResolutionError::AmbiguousMethod {
+                class: "D".to_owned(),
+                name: "d".to_owned(),
+                desc: "()V".to_owned(),
+                interfaces: vec!["K".to_owned(), "J".to_owned()],
+            }
+        );
+
+        // the static method of E does not override the abstract method
+        let a = resolver.resolve_interface_method("I", "a", "()V").unwrap();
+        assert_eq!(
+            error(resolver.select(&a, "E")),
+            ResolutionError::AbstractMethod {
+                class: "E".to_owned(),
+                name: "a".to_owned(),
+                desc: "()V".to_owned(),
+            }
+        );
+
+        let m = resolver.resolve_method("A", "m", "()V").unwrap();
+        assert_eq!(found(resolver.select(&m, "B")), ("A", PUBLIC));
+        assert!(error(resolver.select(&m, "G")) == ResolutionError::AbstractMethod {
+            class: "G".to_owned(),
+            name: "m".to_owned(),
+            desc: "()V".to_owned(),
+        });
+        match resolver.select(&m, "F") {
+            Err(Error::ClassNotFound(ref name)) if name == "x/Missing" => {}
+            _ => panic!("the super class of F is missing"),
+        }
+    }
+
+    #[test]
+    fn package_private() {
+        use self::Kind::*;
+
+        let private = MethodAccessFlags::PRIVATE;
+        let classes = vec![
+            class(Class, OBJECT, None, &[], &[], &[]),
+            class(Class, "p/A", Some(OBJECT), &[], &[], &[("m", PACKAGE), ("n", private)]),
+            // cannot override p/A.m, since it is in another package
+            class(Class, "q/B", Some("p/A"), &[], &[], &[("m", PUBLIC), ("n", PUBLIC)]),
+            class(Class, "p/C", Some("q/B"), &[], &[], &[("m", PACKAGE)]),
+            class(Class, "p/D", Some("p/A"), &[], &[], &[("m", PUBLIC)]),
+            // overrides p/A.m through p/D.m
+            class(Class, "q/E", Some("p/D"), &[], &[], &[("m", PUBLIC)]),
+        ];
+        let resolver = MemberResolver::new(&classes).unwrap();
+
+        let m = resolver.resolve_method("p/A", "m", "()V").unwrap();
+        assert_eq!(found(resolver.select(&m, "q/B")), ("p/A", PACKAGE));
+        assert_eq!(found(resolver.select(&m, "p/C")), ("p/C", PACKAGE));
+        assert_eq!(found(resolver.select(&m, "q/E")), ("q/E", PUBLIC));
+
+        let n = resolver.resolve_method("p/A", "n", "()V").unwrap();
+        assert_eq!(found(resolver.select(&n, "q/B")), ("p/A", private));
+    }
+
+    #[test]
+    fn signature_polymorphic() {
+        use self::Kind::*;
+
+        let native = MethodAccessFlags::PUBLIC
+            | MethodAccessFlags::FINAL
+            | MethodAccessFlags::NATIVE
+            | MethodAccessFlags::VARARGS;
+        let mut handle = class(Class, "java/lang/invoke/MethodHandle", Some(OBJECT), &[], &[], &[
+            ("invokeExact", native),
+            ("invoke", native),
+            ("invoke", native),
+        ]);
+        let polymorphic = handle
+            .0
+            .push(Item::UTF8("([Ljava/lang/Object;)Ljava/lang/Object;".to_owned()))
+            .unwrap();
+        handle.1.methods[0].desc = polymorphic;
+        handle.1.methods[1].desc = polymorphic;
+
+        let classes = vec![
+            class(Class, OBJECT, None, &[], &[], &[]),
+            handle,
+            class(Class, "x/Direct", Some("java/lang/invoke/MethodHandle"), &[], &[], &[]),
+        ];
+        let resolver = MemberResolver::new(&classes).unwrap();
+
+        let found = resolver
+            .resolve_method("x/Direct", "invokeExact", "(ILjava/lang/String;)J")
+            .unwrap();
+        assert_eq!(found.class_name, "java/lang/invoke/MethodHandle");
+        // more than one method of the name is declared, so the descriptor must match
+        assert_eq!(
+            error(resolver.resolve_method("x/Direct", "invoke", "(I)V")),
+            ResolutionError::NoSuchMethod {
+                class: "x/Direct".to_owned(),
+                name: "invoke".to_owned(),
+                desc: "(I)V".to_owned(),
+            }
+        );
+        assert!(resolver.resolve_method("x/Direct", "invoke", "()V").is_ok());
+    }
+}
@@ -0,0 +1,204 @@
+//! The sealed module checks the direct subclasses of all sealed classes
+//! in a set of classes against their `PermittedSubclasses` attribute.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use super::constpool::*;
+use super::tree::*;
+use result::*;
+
+/// A violation of a sealed hierarchy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SealedError {
+    /// Another class of the same name has been given before, so the class is ignored.
+    Duplicate { class: String },
+    /// The class extends or implements a sealed class, which does not permit it.
+    NotPermitted { class: String, sealed: String },
+    /// The class is permitted by a sealed class,
+    /// but it neither extends nor implements it.
+    NotASubclass { class: String, sealed: String },
+}
+
+impl fmt::Display for SealedError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SealedError::Duplicate { ref class } => write!(f, "{} is given more than once", class),
+            SealedError::NotPermitted {
+                ref class,
+                ref sealed,
+            } => write!(f, "{} extends sealed {}, but is not permitted", class, sealed),
+            SealedError::NotASubclass {
+                ref class,
+                ref sealed,
+            } => write!(f, "{} is permitted by {}, but does not extend it", class, sealed),
+        }
+    }
+}
+
+/// The super class, the interfaces and the permitted subclasses of a single class.
+struct Hierarchy {
+    supers: Vec<String>,
+    /// `None` if the class is not sealed
+    permitted: Option<Vec<String>>,
+}
+
+/// Checks every sealed class against its direct subclasses.
+/// A class extending or implementing a sealed class must be listed in its
+/// `PermittedSubclasses` attribute and every class listed must
+/// extend or implement it directly.
+/// Classes not part of the classes given are not checked.
+/// If a class name is given more than once, only the first class is considered
+/// and the others are recorded as errors.
+///
+/// An error is returned, if a class name cannot be found in the constant pool.
+///
+/// # Examples
+///
+/// ```
+/// use jbcrs::basic;
+///
+/// let sealed = basic::parse(include_bytes!("../../examples/resources/Sealed.class")).unwrap();
+/// let leaf = basic::parse(include_bytes!("../../examples/resources/Sealed$Leaf.class"))
+///     .unwrap();
+/// let node = basic::parse(include_bytes!("../../examples/resources/Sealed$Node.class"))
+///     .unwrap();
+///
+/// assert!(basic::check_sealed(&[sealed, leaf, node]).unwrap().is_empty());
+/// ```
+pub fn check_sealed(classes: &[(Pool, Class)]) -> Result<Vec<SealedError>> {
+    let mut names = Vec::with_capacity(classes.len());
+    let mut hierarchies = HashMap::with_capacity(classes.len());
+    let mut seen = HashSet::with_capacity(classes.len());
+    let mut errors = Vec::new();
+    for (constant_pool, class) in classes {
+        let name = constant_pool.get_class_name(class.name)?;
+        if !seen.insert(name.clone()) {
+            errors.push(SealedError::Duplicate { class: name });
+            continue;
+        }
+
+        let mut supers = Vec::with_capacity(class.interfaces.len() + 1);
+        if let Some(super_name) = constant_pool.get_class_name_opt(class.super_name)? {
+            supers.push(super_name);
+        }
+        for interface in &class.interfaces {
+            supers.push(constant_pool.get_class_name(*interface)?);
+        }
+
+        let mut permitted = None;
+        for attribute in &class.attributes {
+            if let Attribute::PermittedSubclasses(ref subclasses) = *attribute {
+                let mut subclass_names = Vec::with_capacity(subclasses.len());
+                for subclass in subclasses {
+                    subclass_names.push(constant_pool.get_class_name(*subclass)?);
+                }
+                permitted = Some(subclass_names);
+            }
+        }
+
+        names.push(name.clone());
+        hierarchies.insert(name, Hierarchy { supers, permitted });
+    }
+
+    for name in &names {
+        let hierarchy = &hierarchies[name];
+
+        // every sealed super class must permit this class
+        for super_name in &hierarchy.supers {
+            let permitted = match hierarchies.get(super_name) {
+                Some(&Hierarchy {
+                    permitted: Some(ref permitted),
+                    ..
+                }) => permitted,
+                _ => continue,
+            };
+            if !permitted.contains(name) {
+                errors.push(SealedError::NotPermitted {
+                    class: name.clone(),
+                    sealed: super_name.clone(),
+                });
+            }
+        }
+
+        // every subclass permitted must extend this class
+        if let Some(ref permitted) = hierarchy.permitted {
+            for subclass in permitted {
+                let extends = match hierarchies.get(subclass) {
+                    Some(subclass) => subclass.supers.contains(name),
+                    None => continue,
+                };
+                if !extends {
+                    errors.push(SealedError::NotASubclass {
+                        class: subclass.clone(),
+                        sealed: name.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(errors)
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::fixture::{self, push_class};
+    use super::super::OBJECT;
+    use super::*;
+
+    /// Creates a class with a `PermittedSubclasses` attribute, if subclasses are given.
+    fn class(
+        name: &str,
+        super_name: &str,
+        interfaces: &[&str],
+        permitted: &[&str],
+    ) -> (Pool, Class) {
+        let access_flags = ClassAccessFlags::PUBLIC;
+        let (mut pool, mut class) =
+            fixture::class(61, access_flags, name, Some(super_name), interfaces);
+        if !permitted.is_empty() {
+            let permitted = permitted
+                .iter()
+                .map(|subclass| push_class(&mut pool, subclass))
+                .collect();
+            class.attributes.push(Attribute::PermittedSubclasses(permitted));
+        }
+        (pool, class)
+    }
+
+    #[test]
+    fn sealed() {
+        let errors = check_sealed(&[
+            class("Shape", OBJECT, &[], &["Circle", "Square", "Unrelated", "Missing"]),
+            class("Named", OBJECT, &[], &["Circle"]),
+            class("Circle", "Shape", &["Named"], &[]),
+            class("Square", "Shape", &[], &[]),
+            class("Triangle", "Shape", &["Named"], &[]),
+            class("Unrelated", OBJECT, &[], &[]),
+            // ignored, the first class of this name is permitted
+            class("Square", OBJECT, &[], &[]),
+        ]).unwrap();
+
+        assert_eq!(
+            errors,
+            vec![
+                SealedError::Duplicate {
+                    class: "Square".to_owned(),
+                },
+                SealedError::NotASubclass {
+                    class: "Unrelated".to_owned(),
+                    sealed: "Shape".to_owned(),
+                },
+                SealedError::NotPermitted {
+                    class: "Triangle".to_owned(),
+                    sealed: "Shape".to_owned(),
+                },
+                SealedError::NotPermitted {
+                    class: "Triangle".to_owned(),
+                    sealed: "Named".to_owned(),
+                },
+            ]
+        );
+    }
+}
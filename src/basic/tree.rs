@@ -1,6 +1,10 @@
 //! The tree package provides the basic structure of a basic class file
 
-use std::collections::{BTreeMap, HashMap};
+use std::collections::BTreeMap;
+use std::collections::btree_map;
+use std::fmt;
+use std::ops::Bound::{Excluded, Unbounded};
+use std::ops::RangeBounds;
 
 /// A java class file.
 #[derive(Debug)]
@@ -8,7 +12,7 @@ pub struct Class {
     pub minor_version: u16,
     pub major_version: u16,
 
-    pub access_flags: AccessFlags,
+    pub access_flags: ClassAccessFlags,
     pub name: u16,
     pub super_name: u16,
     pub interfaces: Vec<u16>,
@@ -19,10 +23,25 @@ pub struct Class {
     pub attributes: Vec<Attribute>,
 }
 
+impl Class {
+    /// Returns the entries of the `BootstrapMethods` attribute,
+    /// which are referenced by `Item::Dynamic { .. }` and `Item::InvokeDynamic { .. }`.
+    /// If there is no such attribute, no entries are returned.
+    pub fn bootstrap_methods(&self) -> &[BootstrapMethod] {
+        self.attributes
+            .iter()
+            .find_map(|attribute| match *attribute {
+                Attribute::BootstrapMethods(ref methods) => Some(&methods[..]),
+                _ => None,
+            })
+            .unwrap_or(&[])
+    }
+}
+
 /// A field.
 #[derive(Debug)]
 pub struct Field {
-    pub access_flags: AccessFlags,
+    pub access_flags: FieldAccessFlags,
     pub name: u16,
     pub desc: u16,
     pub attributes: Vec<Attribute>,
@@ -31,7 +50,7 @@ pub struct Field {
 /// A method.
 #[derive(Debug)]
 pub struct Method {
-    pub access_flags: AccessFlags,
+    pub access_flags: MethodAccessFlags,
     pub name: u16,
     pub desc: u16,
     pub attributes: Vec<Attribute>,
@@ -45,7 +64,7 @@ pub enum Attribute {
     Code {
         max_stack: u16,
         max_locals: u16,
-        instructions: HashMap<u32, Instruction>,
+        instructions: Instructions,
         exceptions: Vec<Exception>,
         attributes: Vec<Attribute>,
     },
@@ -63,7 +82,7 @@ pub enum Attribute {
     MethodParameters(Vec<MethodParameter>),
     Module {
         name: u16,
-        flags: AccessFlags,
+        flags: ModuleFlags,
         version: u16,
 
         requires: Vec<Requirement>,
@@ -74,6 +93,10 @@ pub enum Attribute {
     },
     ModuleMainClass(u16),
     ModulePackages(Vec<u16>),
+    NestHost(u16),
+    NestMembers(Vec<u16>),
+    PermittedSubclasses(Vec<u16>),
+    Record(Vec<RecordComponent>),
     RuntimeVisibleAnnotations(Vec<Annotation>),
     RuntimeInvisibleAnnotations(Vec<Annotation>),
     RuntimeVisibleParameterAnnotations(Vec<Vec<Annotation>>),
@@ -89,29 +112,277 @@ pub enum Attribute {
 }
 
 bitflags! {
-    /// The access flags of a part of the class
-    pub struct AccessFlags: u16 {
+    /// The access flags of a class.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct ClassAccessFlags: u16 {
+        const PUBLIC     = 0b0000_0000_0000_0001;
+        const FINAL      = 0b0000_0000_0001_0000;
+        const SUPER      = 0b0000_0000_0010_0000;
+        const INTERFACE  = 0b0000_0010_0000_0000;
+        const ABSTRACT   = 0b0000_0100_0000_0000;
+        const SYNTHETIC  = 0b0001_0000_0000_0000;
+        const ANNOTATION = 0b0010_0000_0000_0000;
+        const ENUM       = 0b0100_0000_0000_0000;
+        const MODULE     = 0b1000_0000_0000_0000;
+    }
+}
+
+bitflags! {
+    /// The access flags of a field.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct FieldAccessFlags: u16 {
+        const PUBLIC    = 0b0000_0000_0000_0001;
+        const PRIVATE   = 0b0000_0000_0000_0010;
+        const PROTECTED = 0b0000_0000_0000_0100;
+        const STATIC    = 0b0000_0000_0000_1000;
+        const FINAL     = 0b0000_0000_0001_0000;
+        const VOLATILE  = 0b0000_0000_0100_0000;
+        const TRANSIENT = 0b0000_0000_1000_0000;
+        const SYNTHETIC = 0b0001_0000_0000_0000;
+        const ENUM      = 0b0100_0000_0000_0000;
+    }
+}
+
+bitflags! {
+    /// The access flags of a method.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct MethodAccessFlags: u16 {
         const PUBLIC       = 0b0000_0000_0000_0001;
         const PRIVATE      = 0b0000_0000_0000_0010;
         const PROTECTED    = 0b0000_0000_0000_0100;
         const STATIC       = 0b0000_0000_0000_1000;
         const FINAL        = 0b0000_0000_0001_0000;
-        const SUPER        = 0b0000_0000_0010_0000;
         const SYNCHRONIZED = 0b0000_0000_0010_0000;
-        const VOLATILE     = 0b0000_0000_0100_0000;
         const BRIDGE       = 0b0000_0000_0100_0000;
-        const STATIC_PHASE = 0b0000_0000_0100_0000;
-        const TRANSIENT    = 0b0000_0000_1000_0000;
         const VARARGS      = 0b0000_0000_1000_0000;
         const NATIVE       = 0b0000_0001_0000_0000;
-        const INTERFACE    = 0b0000_0010_0000_0000;
         const ABSTRACT     = 0b0000_0100_0000_0000;
         const STRICT       = 0b0000_1000_0000_0000;
         const SYNTHETIC    = 0b0001_0000_0000_0000;
-        const ANNOTATION   = 0b0010_0000_0000_0000;
-        const ENUM         = 0b0100_0000_0000_0000;
-        const MODULE       = 0b1000_0000_0000_0001;
-        const MANDATED     = 0b1000_0000_0000_0001;
+    }
+}
+
+bitflags! {
+    /// The access flags of an inner class, as declared in the source code.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct InnerClassAccessFlags: u16 {
+        const PUBLIC     = 0b0000_0000_0000_0001;
+        const PRIVATE    = 0b0000_0000_0000_0010;
+        const PROTECTED  = 0b0000_0000_0000_0100;
+        const STATIC     = 0b0000_0000_0000_1000;
+        const FINAL      = 0b0000_0000_0001_0000;
+        const INTERFACE  = 0b0000_0010_0000_0000;
+        const ABSTRACT   = 0b0000_0100_0000_0000;
+        const SYNTHETIC  = 0b0001_0000_0000_0000;
+        const ANNOTATION = 0b0010_0000_0000_0000;
+        const ENUM       = 0b0100_0000_0000_0000;
+    }
+}
+
+bitflags! {
+    /// The flags of a method parameter.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct ParameterFlags: u16 {
+        const FINAL     = 0b0000_0000_0001_0000;
+        const SYNTHETIC = 0b0001_0000_0000_0000;
+        const MANDATED  = 0b1000_0000_0000_0000;
+    }
+}
+
+bitflags! {
+    /// The flags of a module.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct ModuleFlags: u16 {
+        const OPEN      = 0b0000_0000_0010_0000;
+        const SYNTHETIC = 0b0001_0000_0000_0000;
+        const MANDATED  = 0b1000_0000_0000_0000;
+    }
+}
+
+bitflags! {
+    /// The flags of a module dependence.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct RequiresFlags: u16 {
+        const TRANSITIVE   = 0b0000_0000_0010_0000;
+        const STATIC_PHASE = 0b0000_0000_0100_0000;
+        const SYNTHETIC    = 0b0001_0000_0000_0000;
+        const MANDATED     = 0b1000_0000_0000_0000;
+    }
+}
+
+bitflags! {
+    /// The flags of a package exported or opened by a module.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub struct ExportsFlags: u16 {
+        const SYNTHETIC = 0b0001_0000_0000_0000;
+        const MANDATED  = 0b1000_0000_0000_0000;
+    }
+}
+
+/// Writes the modifiers of all flags set, separated by spaces.
+fn write_modifiers(f: &mut fmt::Formatter, modifiers: &[(bool, &str)]) -> fmt::Result {
+    let mut first = true;
+    for &(set, modifier) in modifiers {
+        if set {
+            if !first {
+                f.write_str(" ")?;
+            }
+            f.write_str(modifier)?;
+            first = false;
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Display for ClassAccessFlags {
+    /// Formats the modifiers in the order used in java source code.
+    /// Flags without a modifier (like `SUPER` or `INTERFACE`) are omitted,
+    /// just like `abstract` for interfaces.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jbcrs::basic::ClassAccessFlags;
+    ///
+    /// let flags = ClassAccessFlags::PUBLIC | ClassAccessFlags::FINAL | ClassAccessFlags::SUPER;
+    /// assert_eq!(flags.to_string(), "public final");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let interface = self.contains(ClassAccessFlags::INTERFACE);
+        write_modifiers(
+            f,
+            &[
+                (self.contains(ClassAccessFlags::PUBLIC), "public"),
+                (self.contains(ClassAccessFlags::ABSTRACT) && !interface, "abstract"),
+                (self.contains(ClassAccessFlags::FINAL), "final"),
+            ],
+        )
+    }
+}
+
+impl fmt::Display for FieldAccessFlags {
+    /// Formats the modifiers in the order used in java source code.
+    /// Flags without a modifier (like `SYNTHETIC`) are omitted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jbcrs::basic::FieldAccessFlags;
+    ///
+    /// let flags = FieldAccessFlags::FINAL | FieldAccessFlags::STATIC | FieldAccessFlags::PUBLIC;
+    /// assert_eq!(flags.to_string(), "public static final");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_modifiers(
+            f,
+            &[
+                (self.contains(FieldAccessFlags::PUBLIC), "public"),
+                (self.contains(FieldAccessFlags::PROTECTED), "protected"),
+                (self.contains(FieldAccessFlags::PRIVATE), "private"),
+                (self.contains(FieldAccessFlags::STATIC), "static"),
+                (self.contains(FieldAccessFlags::FINAL), "final"),
+                (self.contains(FieldAccessFlags::TRANSIENT), "transient"),
+                (self.contains(FieldAccessFlags::VOLATILE), "volatile"),
+            ],
+        )
+    }
+}
+
+impl fmt::Display for MethodAccessFlags {
+    /// Formats the modifiers in the order used in java source code.
+    /// Flags without a modifier (like `BRIDGE` or `VARARGS`) are omitted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jbcrs::basic::MethodAccessFlags;
+    ///
+    /// let flags = MethodAccessFlags::SYNCHRONIZED | MethodAccessFlags::BRIDGE
+    ///     | MethodAccessFlags::STATIC | MethodAccessFlags::PRIVATE;
+    /// assert_eq!(flags.to_string(), "private static synchronized");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_modifiers(
+            f,
+            &[
+                (self.contains(MethodAccessFlags::PUBLIC), "public"),
+                (self.contains(MethodAccessFlags::PROTECTED), "protected"),
+                (self.contains(MethodAccessFlags::PRIVATE), "private"),
+                (self.contains(MethodAccessFlags::ABSTRACT), "abstract"),
+                (self.contains(MethodAccessFlags::STATIC), "static"),
+                (self.contains(MethodAccessFlags::FINAL), "final"),
+                (self.contains(MethodAccessFlags::SYNCHRONIZED), "synchronized"),
+                (self.contains(MethodAccessFlags::NATIVE), "native"),
+                (self.contains(MethodAccessFlags::STRICT), "strictfp"),
+            ],
+        )
+    }
+}
+
+impl fmt::Display for InnerClassAccessFlags {
+    /// Formats the modifiers in the order used in java source code.
+    /// Flags without a modifier (like `INTERFACE`) are omitted,
+    /// just like `abstract` for interfaces.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let interface = self.contains(InnerClassAccessFlags::INTERFACE);
+        write_modifiers(
+            f,
+            &[
+                (self.contains(InnerClassAccessFlags::PUBLIC), "public"),
+                (self.contains(InnerClassAccessFlags::PROTECTED), "protected"),
+                (self.contains(InnerClassAccessFlags::PRIVATE), "private"),
+                (self.contains(InnerClassAccessFlags::ABSTRACT) && !interface, "abstract"),
+                (self.contains(InnerClassAccessFlags::STATIC), "static"),
+                (self.contains(InnerClassAccessFlags::FINAL), "final"),
+            ],
+        )
+    }
+}
+
+impl fmt::Display for ParameterFlags {
+    /// Formats the modifiers in the order used in java source code.
+    /// Flags without a modifier (like `MANDATED`) are omitted.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_modifiers(f, &[(self.contains(ParameterFlags::FINAL), "final")])
+    }
+}
+
+impl fmt::Display for ModuleFlags {
+    /// Formats the modifiers in the order used in java source code.
+    /// Flags without a modifier (like `MANDATED`) are omitted.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_modifiers(f, &[(self.contains(ModuleFlags::OPEN), "open")])
+    }
+}
+
+impl fmt::Display for RequiresFlags {
+    /// Formats the modifiers in the order used in java source code.
+    /// Flags without a modifier (like `MANDATED`) are omitted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jbcrs::basic::RequiresFlags;
+    ///
+    /// let flags = RequiresFlags::STATIC_PHASE | RequiresFlags::TRANSITIVE;
+    /// assert_eq!(flags.to_string(), "transitive static");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_modifiers(
+            f,
+            &[
+                (self.contains(RequiresFlags::TRANSITIVE), "transitive"),
+                (self.contains(RequiresFlags::STATIC_PHASE), "static"),
+            ],
+        )
+    }
+}
+
+impl fmt::Display for ExportsFlags {
+    /// Formats the modifiers in the order used in java source code,
+    /// which is always empty, since no flag has a modifier.
+    fn fmt(&self, _: &mut fmt::Formatter) -> fmt::Result {
+        Ok(())
     }
 }
 
@@ -135,6 +406,216 @@ pub struct LineNumber {
     pub line_number: u16,
 }
 
+/// The instructions of a `Code` attribute, ordered by their offset in the code.
+/// Instructions are always returned together with their offset as `(u32, &Instruction)`.
+///
+/// # Examples
+///
+/// ```
+/// use jbcrs::basic::{Instruction, Instructions};
+///
+/// let mut instructions = Instructions::new();
+/// instructions.insert(2, Instruction::IAdd);
+/// instructions.insert(0, Instruction::ILoad0);
+/// instructions.insert(1, Instruction::ILoad1);
+/// instructions.insert(3, Instruction::IReturn);
+///
+/// let offsets: Vec<u32> = instructions.iter().map(|(offset, _)| offset).collect();
+/// assert_eq!(offsets, vec![0, 1, 2, 3]);
+/// ```
+#[derive(Debug, Default)]
+pub struct Instructions {
+    by_offset: BTreeMap<u32, Instruction>,
+}
+
+impl Instructions {
+    pub fn new() -> Instructions {
+        Instructions {
+            by_offset: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the amount of instructions.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.by_offset.len()
+    }
+
+    /// Returns true if there are no instructions, false otherwise.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.by_offset.is_empty()
+    }
+
+    /// Inserts an instruction at an offset.
+    /// If there already was an instruction at that offset, it is returned.
+    pub fn insert(&mut self, offset: u32, insn: Instruction) -> Option<Instruction> {
+        self.by_offset.insert(offset, insn)
+    }
+
+    /// Removes the instruction at an offset and returns it.
+    pub fn remove(&mut self, offset: u32) -> Option<Instruction> {
+        self.by_offset.remove(&offset)
+    }
+
+    /// Returns the instruction at an offset.
+    pub fn get(&self, offset: u32) -> Option<&Instruction> {
+        self.by_offset.get(&offset)
+    }
+
+    /// Returns the instruction at an offset mutably.
+    pub fn get_mut(&mut self, offset: u32) -> Option<&mut Instruction> {
+        self.by_offset.get_mut(&offset)
+    }
+
+    /// Returns the first instruction and its offset.
+    pub fn first(&self) -> Option<(u32, &Instruction)> {
+        self.iter().next()
+    }
+
+    /// Returns the last instruction and its offset.
+    pub fn last(&self) -> Option<(u32, &Instruction)> {
+        self.iter().next_back()
+    }
+
+    /// Returns the instruction following the instruction at an offset.
+    /// There does not have to be an instruction at that offset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jbcrs::basic::{Instruction, Instructions};
+    ///
+    /// let mut instructions = Instructions::new();
+    /// instructions.insert(0, Instruction::BIPush(5));
+    /// instructions.insert(2, Instruction::IReturn);
+    ///
+    /// assert_eq!(instructions.next(0).map(|(offset, _)| offset), Some(2));
+    /// assert_eq!(instructions.next(1).map(|(offset, _)| offset), Some(2));
+    /// assert!(instructions.next(2).is_none());
+    /// ```
+    pub fn next(&self, offset: u32) -> Option<(u32, &Instruction)> {
+        self.range((Excluded(offset), Unbounded)).next()
+    }
+
+    /// Returns the instruction preceding the instruction at an offset.
+    /// There does not have to be an instruction at that offset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jbcrs::basic::{Instruction, Instructions};
+    ///
+    /// let mut instructions = Instructions::new();
+    /// instructions.insert(0, Instruction::BIPush(5));
+    /// instructions.insert(2, Instruction::IReturn);
+    ///
+    /// assert_eq!(instructions.prev(2).map(|(offset, _)| offset), Some(0));
+    /// assert!(instructions.prev(0).is_none());
+    /// ```
+    pub fn prev(&self, offset: u32) -> Option<(u32, &Instruction)> {
+        self.range(..offset).next_back()
+    }
+
+    /// Returns all instructions, whose offsets are in a range, ordered by their offset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jbcrs::basic::{Instruction, Instructions};
+    ///
+    /// let mut instructions = Instructions::new();
+    /// instructions.insert(0, Instruction::ILoad0);
+    /// instructions.insert(1, Instruction::ILoad1);
+    /// instructions.insert(2, Instruction::IAdd);
+    /// instructions.insert(3, Instruction::IReturn);
+    ///
+    /// let offsets: Vec<u32> = instructions.range(1..3).map(|(offset, _)| offset).collect();
+    /// assert_eq!(offsets, vec![1, 2]);
+    /// ```
+    pub fn range<R: RangeBounds<u32>>(&self, range: R) -> InstructionIter<'_> {
+        InstructionIter {
+            iter: self.by_offset.range(range),
+        }
+    }
+
+    /// Iterates over all instructions ordered by their offset.
+    pub fn iter(&self) -> InstructionIter<'_> {
+        self.range(..)
+    }
+
+    /// Iterates mutably over all instructions ordered by their offset.
+    pub fn iter_mut(&mut self) -> InstructionIterMut<'_> {
+        InstructionIterMut {
+            iter: self.by_offset.iter_mut(),
+        }
+    }
+}
+
+/// Iterates over instructions and their offsets, ordered by their offset
+pub struct InstructionIter<'a> {
+    iter: btree_map::Range<'a, u32, Instruction>,
+}
+
+impl<'a> Iterator for InstructionIter<'a> {
+    type Item = (u32, &'a Instruction);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(offset, insn)| (*offset, insn))
+    }
+}
+
+impl<'a> DoubleEndedIterator for InstructionIter<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(offset, insn)| (*offset, insn))
+    }
+}
+
+/// Iterates mutably over instructions and their offsets, ordered by their offset
+pub struct InstructionIterMut<'a> {
+    iter: btree_map::IterMut<'a, u32, Instruction>,
+}
+
+impl<'a> Iterator for InstructionIterMut<'a> {
+    type Item = (u32, &'a mut Instruction);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|(offset, insn)| (*offset, insn))
+    }
+}
+
+impl<'a> DoubleEndedIterator for InstructionIterMut<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|(offset, insn)| (*offset, insn))
+    }
+}
+
+impl<'a> IntoIterator for &'a Instructions {
+    type Item = (u32, &'a Instruction);
+    type IntoIter = InstructionIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for Instructions {
+    type Item = (u32, Instruction);
+    type IntoIter = btree_map::IntoIter<u32, Instruction>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.by_offset.into_iter()
+    }
+}
+
+impl ::std::iter::FromIterator<(u32, Instruction)> for Instructions {
+    fn from_iter<I: IntoIterator<Item = (u32, Instruction)>>(iter: I) -> Instructions {
+        Instructions {
+            by_offset: iter.into_iter().collect(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum Instruction {
     /// No operation
@@ -181,8 +662,14 @@ pub enum Instruction {
     /// Pushes a short on the stack
     SIPush(i16),
 
-    /// Pushes a constant from the constant pool on the stack
+    /// Pushes a constant from the constant pool on the stack.
+    /// If the index does not fit in a byte, `ldc_w` is written instead.
     LDC(u16),
+    /// Pushes a constant from the constant pool on the stack,
+    /// always encoded as `ldc_w`.
+    LDCW(u16),
+    /// Pushes a long or double from the constant pool on the stack
+    LDC2W(u16),
 
     /// Pushes the int at a specific local variable index on the stack
     ILoad(u16),
@@ -413,19 +900,34 @@ pub enum Instruction {
     IfACmpEq(i16),
     IfACmpNE(i16),
 
+    /// Jumps to an offset.
+    /// If the offset does not fit in two bytes, `goto_w` is written instead.
     GoTo(i32),
+    /// Jumps to a subroutine.
+    /// If the offset does not fit in two bytes, `jsr_w` is written instead.
     JSR(i32),
     Ret(u16),
 
+    /// Jumps to an offset, always encoded as `goto_w`.
+    GoToW(i32),
+    /// Jumps to a subroutine, always encoded as `jsr_w`.
+    JSRW(i32),
+
+    /// A jump table, `padding` holds the bytes aligning `default` to 4 bytes.
+    /// They are zero, unless a class file has set them,
+    /// only as many of them as required at the location of the switch are written.
     TableSwitch {
         default: i32,
         low: i32,
         high: i32,
         offsets: Vec<i32>,
+        padding: [u8; 3],
     },
+    /// A switch looking up the offset by its key, `padding` is the same as in `TableSwitch`.
     LookupSwitch {
         default: i32,
         offsets: BTreeMap<i32, i32>,
+        padding: [u8; 3],
     },
 
     IReturn,
@@ -443,8 +945,12 @@ pub enum Instruction {
     InvokeVirtual(u16),
     InvokeSpecial(u16),
     InvokeStatic(u16),
-    InvokeInterface(u16, u8),
-    InvokeDynamic(u16),
+    /// Invokes an interface method with the index, the count of argument slots
+    /// and the reserved byte following it, which is zero, unless a class file has set it.
+    InvokeInterface(u16, u8, u8),
+    /// Invokes a call site with the index and the two reserved bytes following it,
+    /// which are zero, unless a class file has set them.
+    InvokeDynamic(u16, u16),
 
     New(u16),
     NewArray(ArrayType),
@@ -467,6 +973,237 @@ pub enum Instruction {
     BreakPoint,
     ImpDep1,
     ImpDep2,
+
+    /// A load, store, `iinc` or `ret` instruction with the `wide` prefix,
+    /// although its operands would fit in the short form.
+    /// Instructions requiring the `wide` prefix are not wrapped.
+    Wide(Box<Instruction>),
+}
+
+impl Instruction {
+    /// Returns the mnemonic of the op code, as used in the JVM specification.
+    /// `Instruction::Wide(_)` returns `wide`, not the mnemonic of the instruction it wraps.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jbcrs::basic::Instruction;
+    ///
+    /// assert_eq!(Instruction::ALoad0.mnemonic(), "aload_0");
+    /// assert_eq!(Instruction::IfICmpGE(3).mnemonic(), "if_icmpge");
+    /// assert_eq!(Instruction::LDC2W(1).mnemonic(), "ldc2_w");
+    /// ```
+    pub fn mnemonic(&self) -> &'static str {
+        use self::Instruction::*;
+
+        match *self {
+            NOP => "nop",
+            AConstNull => "aconst_null",
+            IConstM1 => "iconst_m1",
+            IConst0 => "iconst_0",
+            IConst1 => "iconst_1",
+            IConst2 => "iconst_2",
+            IConst3 => "iconst_3",
+            IConst4 => "iconst_4",
+            IConst5 => "iconst_5",
+            LConst0 => "lconst_0",
+            LConst1 => "lconst_1",
+            FConst0 => "fconst_0",
+            FConst1 => "fconst_1",
+            FConst2 => "fconst_2",
+            DConst0 => "dconst_0",
+            DConst1 => "dconst_1",
+            BIPush(..) => "bipush",
+            SIPush(..) => "sipush",
+            LDC(..) => "ldc",
+            LDCW(..) => "ldc_w",
+            LDC2W(..) => "ldc2_w",
+            ILoad(..) => "iload",
+            LLoad(..) => "lload",
+            FLoad(..) => "fload",
+            DLoad(..) => "dload",
+            ALoad(..) => "aload",
+            ILoad0 => "iload_0",
+            ILoad1 => "iload_1",
+            ILoad2 => "iload_2",
+            ILoad3 => "iload_3",
+            LLoad0 => "lload_0",
+            LLoad1 => "lload_1",
+            LLoad2 => "lload_2",
+            LLoad3 => "lload_3",
+            FLoad0 => "fload_0",
+            FLoad1 => "fload_1",
+            FLoad2 => "fload_2",
+            FLoad3 => "fload_3",
+            DLoad0 => "dload_0",
+            DLoad1 => "dload_1",
+            DLoad2 => "dload_2",
+            DLoad3 => "dload_3",
+            ALoad0 => "aload_0",
+            ALoad1 => "aload_1",
+            ALoad2 => "aload_2",
+            ALoad3 => "aload_3",
+            IALoad => "iaload",
+            LALoad => "laload",
+            FALoad => "faload",
+            DALoad => "daload",
+            AALoad => "aaload",
+            BALoad => "baload",
+            CALoad => "caload",
+            SALoad => "saload",
+            IStore(..) => "istore",
+            LStore(..) => "lstore",
+            FStore(..) => "fstore",
+            DStore(..) => "dstore",
+            AStore(..) => "astore",
+            IStore0 => "istore_0",
+            IStore1 => "istore_1",
+            IStore2 => "istore_2",
+            IStore3 => "istore_3",
+            LStore0 => "lstore_0",
+            LStore1 => "lstore_1",
+            LStore2 => "lstore_2",
+            LStore3 => "lstore_3",
+            FStore0 => "fstore_0",
+            FStore1 => "fstore_1",
+            FStore2 => "fstore_2",
+            FStore3 => "fstore_3",
+            DStore0 => "dstore_0",
+            DStore1 => "dstore_1",
+            DStore2 => "dstore_2",
+            DStore3 => "dstore_3",
+            AStore0 => "astore_0",
+            AStore1 => "astore_1",
+            AStore2 => "astore_2",
+            AStore3 => "astore_3",
+            IAStore => "iastore",
+            LAStore => "lastore",
+            FAStore => "fastore",
+            DAStore => "dastore",
+            AAStore => "aastore",
+            BAStore => "bastore",
+            CAStore => "castore",
+            SAStore => "sastore",
+            Pop => "pop",
+            Pop2 => "pop2",
+            Dup => "dup",
+            DupX1 => "dup_x1",
+            DupX2 => "dup_x2",
+            Dup2 => "dup2",
+            Dup2X1 => "dup2_x1",
+            Dup2X2 => "dup2_x2",
+            Swap => "swap",
+            IAdd => "iadd",
+            LAdd => "ladd",
+            FAdd => "fadd",
+            DAdd => "dadd",
+            ISub => "isub",
+            LSub => "lsub",
+            FSub => "fsub",
+            DSub => "dsub",
+            IMul => "imul",
+            LMul => "lmul",
+            FMul => "fmul",
+            DMul => "dmul",
+            IDiv => "idiv",
+            LDiv => "ldiv",
+            FDiv => "fdiv",
+            DDiv => "ddiv",
+            IRem => "irem",
+            LRem => "lrem",
+            FRem => "frem",
+            DRem => "drem",
+            INeg => "ineg",
+            LNeg => "lneg",
+            FNeg => "fneg",
+            DNeg => "dneg",
+            IShL => "ishl",
+            LShL => "lshl",
+            IShR => "ishr",
+            LShR => "lshr",
+            IUShR => "iushr",
+            LUShR => "lushr",
+            IAnd => "iand",
+            LAnd => "land",
+            IOr => "ior",
+            LOr => "lor",
+            IXOr => "ixor",
+            LXOr => "lxor",
+            IInc(..) => "iinc",
+            I2L => "i2l",
+            I2F => "i2f",
+            I2D => "i2d",
+            L2I => "l2i",
+            L2F => "l2f",
+            L2D => "l2d",
+            F2I => "f2i",
+            F2L => "f2l",
+            F2D => "f2d",
+            D2I => "d2i",
+            D2L => "d2l",
+            D2F => "d2f",
+            I2B => "i2b",
+            I2C => "i2c",
+            I2S => "i2s",
+            LCmp => "lcmp",
+            FCmpL => "fcmpl",
+            FCmpG => "fcmpg",
+            DCmpL => "dcmpl",
+            DCmpG => "dcmpg",
+            IfEq(..) => "ifeq",
+            IfNE(..) => "ifne",
+            IfLT(..) => "iflt",
+            IfGE(..) => "ifge",
+            IfGT(..) => "ifgt",
+            IfLE(..) => "ifle",
+            IfICmpEq(..) => "if_icmpeq",
+            IfICmpNE(..) => "if_icmpne",
+            IfICmpLT(..) => "if_icmplt",
+            IfICmpGE(..) => "if_icmpge",
+            IfICmpGT(..) => "if_icmpgt",
+            IfICmpLE(..) => "if_icmple",
+            IfACmpEq(..) => "if_acmpeq",
+            IfACmpNE(..) => "if_acmpne",
+            GoTo(..) => "goto",
+            JSR(..) => "jsr",
+            Ret(..) => "ret",
+            GoToW(..) => "goto_w",
+            JSRW(..) => "jsr_w",
+            TableSwitch { .. } => "tableswitch",
+            LookupSwitch { .. } => "lookupswitch",
+            IReturn => "ireturn",
+            LReturn => "lreturn",
+            FReturn => "freturn",
+            DReturn => "dreturn",
+            AReturn => "areturn",
+            Return => "return",
+            GetStatic(..) => "getstatic",
+            PutStatic(..) => "putstatic",
+            GetField(..) => "getfield",
+            PutField(..) => "putfield",
+            InvokeVirtual(..) => "invokevirtual",
+            InvokeSpecial(..) => "invokespecial",
+            InvokeStatic(..) => "invokestatic",
+            InvokeInterface(..) => "invokeinterface",
+            InvokeDynamic(..) => "invokedynamic",
+            New(..) => "new",
+            NewArray(..) => "newarray",
+            ANewArray(..) => "anewarray",
+            ArrayLength => "arraylength",
+            AThrow => "athrow",
+            CheckCast(..) => "checkcast",
+            InstanceOf(..) => "instanceof",
+            MonitorEnter => "monitorenter",
+            MonitorExit => "monitorexit",
+            MultiANewArray(..) => "multianewarray",
+            IfNull(..) => "ifnull",
+            IfNonNull(..) => "ifnonnull",
+            BreakPoint => "breakpoint",
+            ImpDep1 => "impdep1",
+            ImpDep2 => "impdep2",
+            Wide(..) => "wide",
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -481,22 +1218,38 @@ pub enum ArrayType {
     Long,
 }
 
+/// A component of a record, declared by the `Record` attribute.
+#[derive(Debug)]
+pub struct RecordComponent {
+    pub name: u16,
+    pub descriptor: u16,
+    pub attributes: Vec<Attribute>,
+}
+
 #[derive(Debug)]
 pub struct InnerClass {
     pub inner_class_info: u16,
     pub outer_class_info: u16,
     pub inner_name: u16,
-    pub inner_class_access_flags: AccessFlags,
+    pub inner_class_access_flags: InnerClassAccessFlags,
 }
 
 #[derive(Debug)]
 pub enum StackMapFrame {
     Same {
         offset_delta: u16,
+        /// The frame is encoded in the extended form,
+        /// although its offset delta would fit in the compact form.
+        /// Frames requiring the extended form do not set it.
+        extended: bool,
     },
     Same1 {
         offset_delta: u16,
         stack: VerificationType,
+        /// The frame is encoded in the extended form,
+        /// although its offset delta would fit in the compact form.
+        /// Frames requiring the extended form do not set it.
+        extended: bool,
     },
     Chop {
         offset_delta: u16,
@@ -752,27 +1505,27 @@ pub struct LocalVariableType {
 #[derive(Debug)]
 pub struct MethodParameter {
     pub name: u16,
-    pub access_flags: AccessFlags,
+    pub access_flags: ParameterFlags,
 }
 
 #[derive(Debug)]
 pub struct Requirement {
     pub index: u16,
-    pub flags: AccessFlags,
+    pub flags: RequiresFlags,
     pub version: u16,
 }
 
 #[derive(Debug)]
 pub struct Export {
     pub index: u16,
-    pub flags: AccessFlags,
+    pub flags: ExportsFlags,
     pub to: Vec<u16>,
 }
 
 #[derive(Debug)]
 pub struct Opening {
     pub index: u16,
-    pub flags: AccessFlags,
+    pub flags: ExportsFlags,
     pub to: Vec<u16>,
 }
 
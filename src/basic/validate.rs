@@ -0,0 +1,815 @@
+//! The validate module checks whether every index into the constant pool
+//! points to an item of the kind required by the JVM specification.
+
+use std::fmt;
+
+use types::{Type, TypeDescriptor};
+use super::constpool::*;
+use super::tree::*;
+
+/// The kind of a constant pool item, without its content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ItemKind {
+    UTF8,
+    Integer,
+    Float,
+    Long,
+    Double,
+    Class,
+    String,
+    FieldRef,
+    MethodRef,
+    InterfaceMethodRef,
+    NameAndType,
+    MethodHandle,
+    MethodType,
+    Dynamic,
+    InvokeDynamic,
+    Module,
+    Package,
+}
+
+impl ItemKind {
+    /// Returns the kind of an item.
+    pub fn of(item: &Item) -> ItemKind {
+        match *item {
+            Item::UTF8(_) | Item::UTF16(_) => ItemKind::UTF8,
+            Item::Integer(_) => ItemKind::Integer,
+            Item::Float(_) => ItemKind::Float,
+            Item::Long(_) => ItemKind::Long,
+            Item::Double(_) => ItemKind::Double,
+            Item::Class(_) => ItemKind::Class,
+            Item::String(_) => ItemKind::String,
+            Item::FieldRef { .. } => ItemKind::FieldRef,
+            Item::MethodRef { .. } => ItemKind::MethodRef,
+            Item::InterfaceMethodRef { .. } => ItemKind::InterfaceMethodRef,
+            Item::NameAndType { .. } => ItemKind::NameAndType,
+            Item::MethodHandle { .. } => ItemKind::MethodHandle,
+            Item::MethodType(_) => ItemKind::MethodType,
+            Item::Dynamic { .. } => ItemKind::Dynamic,
+            Item::InvokeDynamic { .. } => ItemKind::InvokeDynamic,
+            Item::Module(_) => ItemKind::Module,
+            Item::Package(_) => ItemKind::Package,
+        }
+    }
+}
+
+impl fmt::Display for ItemKind {
+    /// Formats the kind using the name of its tag in the JVM specification.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jbcrs::basic::ItemKind;
+    ///
+    /// assert_eq!(ItemKind::InterfaceMethodRef.to_string(), "CONSTANT_InterfaceMethodref");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            ItemKind::UTF8 => "CONSTANT_Utf8",
+            ItemKind::Integer => "CONSTANT_Integer",
+            ItemKind::Float => "CONSTANT_Float",
+            ItemKind::Long => "CONSTANT_Long",
+            ItemKind::Double => "CONSTANT_Double",
+            ItemKind::Class => "CONSTANT_Class",
+            ItemKind::String => "CONSTANT_String",
+            ItemKind::FieldRef => "CONSTANT_Fieldref",
+            ItemKind::MethodRef => "CONSTANT_Methodref",
+            ItemKind::InterfaceMethodRef => "CONSTANT_InterfaceMethodref",
+            ItemKind::NameAndType => "CONSTANT_NameAndType",
+            ItemKind::MethodHandle => "CONSTANT_MethodHandle",
+            ItemKind::MethodType => "CONSTANT_MethodType",
+            ItemKind::Dynamic => "CONSTANT_Dynamic",
+            ItemKind::InvokeDynamic => "CONSTANT_InvokeDynamic",
+            ItemKind::Module => "CONSTANT_Module",
+            ItemKind::Package => "CONSTANT_Package",
+        })
+    }
+}
+
+/// An element of the path to an index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathElement {
+    /// The item of the constant pool at an index.
+    Item(u16),
+    /// The field at a position in `Class::fields`.
+    Field(usize),
+    /// The method at a position in `Class::methods`.
+    Method(usize),
+    /// The attribute at a position in the attributes of its parent.
+    Attribute(usize),
+    /// The instruction at an offset in the code.
+    Instruction(u32),
+    /// The entry at a position in a table, a list or an annotation.
+    Entry(usize),
+    /// A named part of its parent, like `name` or `descriptor`.
+    Member(&'static str),
+}
+
+/// An index, which does not point to an item of the kind required.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The path from the class (or the constant pool) to the index.
+    pub path: Vec<PathElement>,
+    pub index: u16,
+    /// The kinds of items allowed at this index.
+    pub expected: &'static [ItemKind],
+    /// The kind of the item found, `None` if no item is located at this index.
+    pub found: Option<ItemKind>,
+}
+
+impl fmt::Display for Diagnostic {
+    /// Formats the path, the index, the kinds expected and the kind found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jbcrs::basic::{Diagnostic, ItemKind, PathElement};
+    ///
+    /// let diagnostic = Diagnostic {
+    ///     path: vec![
+    ///         PathElement::Method(1),
+    ///         PathElement::Attribute(0),
+    ///         PathElement::Instruction(4),
+    ///     ],
+    ///     index: 12,
+    ///     expected: &[ItemKind::MethodRef, ItemKind::InterfaceMethodRef],
+    ///     found: Some(ItemKind::FieldRef),
+    /// };
+    /// assert_eq!(
+    ///     diagnostic.to_string(),
+    ///     "methods[1].attributes[0].code@4: #12 must be \
+    ///      CONSTANT_Methodref or CONSTANT_InterfaceMethodref, found CONSTANT_Fieldref"
+    /// );
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, element) in self.path.iter().enumerate() {
+            if i != 0 {
+                match *element {
+                    PathElement::Entry(_) => {}
+                    _ => f.write_str(".")?,
+                }
+            }
+
+            match *element {
+                PathElement::Item(index) => write!(f, "#{}", index)?,
+                PathElement::Field(i) => write!(f, "fields[{}]", i)?,
+                PathElement::Method(i) => write!(f, "methods[{}]", i)?,
+                PathElement::Attribute(i) => write!(f, "attributes[{}]", i)?,
+                PathElement::Instruction(at) => write!(f, "code@{}", at)?,
+                PathElement::Entry(i) => write!(f, "[{}]", i)?,
+                PathElement::Member(name) => f.write_str(name)?,
+            }
+        }
+
+        write!(f, ": #{} must be ", self.index)?;
+        for (i, kind) in self.expected.iter().enumerate() {
+            if i != 0 {
+                f.write_str(" or ")?;
+            }
+            write!(f, "{}", kind)?;
+        }
+
+        match self.found {
+            Some(kind) => write!(f, ", found {}", kind),
+            None => f.write_str(", found nothing"),
+        }
+    }
+}
+
+const UTF8: &[ItemKind] = &[ItemKind::UTF8];
+const INTEGER: &[ItemKind] = &[ItemKind::Integer];
+const FLOAT: &[ItemKind] = &[ItemKind::Float];
+const LONG: &[ItemKind] = &[ItemKind::Long];
+const DOUBLE: &[ItemKind] = &[ItemKind::Double];
+const CLASS: &[ItemKind] = &[ItemKind::Class];
+const STRING: &[ItemKind] = &[ItemKind::String];
+const FIELD_REF: &[ItemKind] = &[ItemKind::FieldRef];
+const METHOD_REF: &[ItemKind] = &[ItemKind::MethodRef];
+const INTERFACE_METHOD_REF: &[ItemKind] = &[ItemKind::InterfaceMethodRef];
+const ANY_METHOD_REF: &[ItemKind] = &[ItemKind::MethodRef, ItemKind::InterfaceMethodRef];
+const NAME_AND_TYPE: &[ItemKind] = &[ItemKind::NameAndType];
+const METHOD_HANDLE: &[ItemKind] = &[ItemKind::MethodHandle];
+const INVOKE_DYNAMIC: &[ItemKind] = &[ItemKind::InvokeDynamic];
+const MODULE: &[ItemKind] = &[ItemKind::Module];
+const PACKAGE: &[ItemKind] = &[ItemKind::Package];
+
+/// Items loadable by `ldc` and `ldc_w`.
+const LOADABLE: &[ItemKind] = &[
+    ItemKind::Integer,
+    ItemKind::Float,
+    ItemKind::String,
+    ItemKind::Class,
+    ItemKind::MethodType,
+    ItemKind::MethodHandle,
+    ItemKind::Dynamic,
+];
+/// Items loadable by `ldc2_w`.
+const LOADABLE_WIDE: &[ItemKind] = &[ItemKind::Long, ItemKind::Double, ItemKind::Dynamic];
+/// Items allowed as arguments of bootstrap methods.
+const BOOTSTRAP_ARGUMENT: &[ItemKind] = &[
+    ItemKind::Integer,
+    ItemKind::Float,
+    ItemKind::Long,
+    ItemKind::Double,
+    ItemKind::String,
+    ItemKind::Class,
+    ItemKind::MethodType,
+    ItemKind::MethodHandle,
+    ItemKind::Dynamic,
+];
+/// Items allowed as `ConstantValue`, if the type of the field is unknown.
+const CONSTANT_VALUE: &[ItemKind] = &[
+    ItemKind::Integer,
+    ItemKind::Float,
+    ItemKind::Long,
+    ItemKind::Double,
+    ItemKind::String,
+];
+
+/// Checks whether every index in a class and in its constant pool
+/// points to an item of the kind required by the JVM specification.
+/// Optional indices may be 0.
+///
+/// In contrast to the parser, this does not stop at the first invalid index,
+/// a diagnostic is returned for every one of them.
+/// Descriptors, names and indices into other tables are not checked.
+///
+/// # Examples
+///
+/// ```
+/// use jbcrs::basic::{self, ItemKind, Pool};
+///
+/// let mut pool = Pool::new();
+/// let name = pool.push(basic::Item::UTF8("Empty".to_owned())).unwrap();
+/// let class = basic::Class {
+///     minor_version: 0,
+///     major_version: 52,
+///     access_flags: basic::ClassAccessFlags::PUBLIC,
+///     // must be an `Item::Class(_)`
+///     name,
+///     super_name: 0,
+///     interfaces: Vec::new(),
+///     fields: Vec::new(),
+///     methods: Vec::new(),
+///     attributes: Vec::new(),
+/// };
+///
+/// let diagnostics = basic::validate(&pool, &class);
+/// assert_eq!(diagnostics.len(), 1);
+/// assert_eq!(diagnostics[0].expected, &[ItemKind::Class]);
+/// assert_eq!(diagnostics[0].found, Some(ItemKind::UTF8));
+/// ```
+pub fn validate(constant_pool: &Pool, class: &Class) -> Vec<Diagnostic> {
+    let mut validator = Validator {
+        constant_pool,
+        path: Vec::new(),
+        diagnostics: Vec::new(),
+    };
+
+    for (index, item) in constant_pool.iter() {
+        validator.path.push(PathElement::Item(index));
+        validator.item(item);
+        validator.path.pop();
+    }
+
+    validator.check_member("this_class", class.name, CLASS);
+    validator.check_optional_member("super_class", class.super_name, CLASS);
+    validator.path.push(PathElement::Member("interfaces"));
+    for (i, interface) in class.interfaces.iter().enumerate() {
+        validator.check_entry(i, *interface, CLASS);
+    }
+    validator.path.pop();
+
+    for (i, field) in class.fields.iter().enumerate() {
+        validator.path.push(PathElement::Field(i));
+        validator.check_member("name", field.name, UTF8);
+        validator.check_member("descriptor", field.desc, UTF8);
+        validator.attributes(&field.attributes, Some(field.desc));
+        validator.path.pop();
+    }
+
+    for (i, method) in class.methods.iter().enumerate() {
+        validator.path.push(PathElement::Method(i));
+        validator.check_member("name", method.name, UTF8);
+        validator.check_member("descriptor", method.desc, UTF8);
+        validator.attributes(&method.attributes, None);
+        validator.path.pop();
+    }
+
+    validator.attributes(&class.attributes, None);
+
+    validator.diagnostics
+}
+
+struct Validator<'a> {
+    constant_pool: &'a Pool,
+    /// The path to the element currently validated
+    path: Vec<PathElement>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> Validator<'a> {
+    /// Checks an index located at the current path.
+    fn check(&mut self, index: u16, expected: &'static [ItemKind]) {
+        let found = self.constant_pool.get(index).ok().map(ItemKind::of);
+        match found {
+            Some(kind) if expected.contains(&kind) => {}
+            _ => self.diagnostics.push(Diagnostic {
+                path: self.path.clone(),
+                index,
+                expected,
+                found,
+            }),
+        }
+    }
+
+    fn check_member(&mut self, name: &'static str, index: u16, expected: &'static [ItemKind]) {
+        self.path.push(PathElement::Member(name));
+        self.check(index, expected);
+        self.path.pop();
+    }
+
+    fn check_optional_member(
+        &mut self,
+        name: &'static str,
+        index: u16,
+        expected: &'static [ItemKind],
+    ) {
+        if index != 0 {
+            self.check_member(name, index, expected);
+        }
+    }
+
+    fn check_entry(&mut self, i: usize, index: u16, expected: &'static [ItemKind]) {
+        self.path.push(PathElement::Entry(i));
+        self.check(index, expected);
+        self.path.pop();
+    }
+
+    fn item(&mut self, item: &Item) {
+        match *item {
+            Item::Class(name) | Item::Module(name) | Item::Package(name) => {
+                self.check_member("name", name, UTF8)
+            }
+            Item::String(string) => self.check_member("string", string, UTF8),
+            Item::FieldRef {
+                class,
+                name_and_type,
+            }
+            | Item::MethodRef {
+                class,
+                name_and_type,
+            }
+            | Item::InterfaceMethodRef {
+                class,
+                name_and_type,
+            } => {
+                self.check_member("class", class, CLASS);
+                self.check_member("name_and_type", name_and_type, NAME_AND_TYPE);
+            }
+            Item::NameAndType { name, desc } => {
+                self.check_member("name", name, UTF8);
+                self.check_member("descriptor", desc, UTF8);
+            }
+            Item::MethodHandle { ref kind, index } => {
+                let expected = match *kind {
+                    ReferenceKind::GetField
+                    | ReferenceKind::GetStatic
+                    | ReferenceKind::PutField
+                    | ReferenceKind::PutStatic => FIELD_REF,
+                    ReferenceKind::InvokeVirtual | ReferenceKind::NewInvokeSpecial => METHOD_REF,
+                    // interface methods are allowed since version 52.0
+                    ReferenceKind::InvokeStatic | ReferenceKind::InvokeSpecial => ANY_METHOD_REF,
+                    ReferenceKind::InvokeInterface => INTERFACE_METHOD_REF,
+                };
+                self.check_member("reference", index, expected);
+            }
+            Item::MethodType(desc) => self.check_member("descriptor", desc, UTF8),
+            Item::Dynamic { name_and_type, .. } | Item::InvokeDynamic { name_and_type, .. } => {
+                self.check_member("name_and_type", name_and_type, NAME_AND_TYPE)
+            }
+            Item::UTF8(_)
+            | Item::UTF16(_)
+            | Item::Integer(_)
+            | Item::Float(_)
+            | Item::Long(_)
+            | Item::Double(_) => {}
+        }
+    }
+
+    /// Validates attributes, `field_desc` is the descriptor of the field they belong to.
+    fn attributes(&mut self, attributes: &[Attribute], field_desc: Option<u16>) {
+        for (i, attribute) in attributes.iter().enumerate() {
+            self.path.push(PathElement::Attribute(i));
+            self.attribute(attribute, field_desc);
+            self.path.pop();
+        }
+    }
+
+    fn attribute(&mut self, attribute: &Attribute, field_desc: Option<u16>) {
+        match *attribute {
+            Attribute::AnnotationDefault(ref value) => self.element_value(value),
+            Attribute::BootstrapMethods(ref methods) => {
+                for (i, method) in methods.iter().enumerate() {
+                    self.path.push(PathElement::Entry(i));
+                    self.check_member("bootstrap_method_ref", method.method_ref, METHOD_HANDLE);
+                    self.path.push(PathElement::Member("bootstrap_arguments"));
+                    for (i, argument) in method.arguments.iter().enumerate() {
+                        self.check_entry(i, *argument, BOOTSTRAP_ARGUMENT);
+                    }
+                    self.path.pop();
+                    self.path.pop();
+                }
+            }
+            Attribute::Code {
+                ref instructions,
+                ref exceptions,
+                ref attributes,
+                ..
+            } => {
+                for (at, insn) in instructions {
+                    self.path.push(PathElement::Instruction(at));
+                    self.instruction(insn);
+                    self.path.pop();
+                }
+
+                self.path.push(PathElement::Member("exception_table"));
+                for (i, exception) in exceptions.iter().enumerate() {
+                    self.path.push(PathElement::Entry(i));
+                    self.check_optional_member("catch_type", exception.catch_type, CLASS);
+                    self.path.pop();
+                }
+                self.path.pop();
+
+                self.attributes(attributes, None);
+            }
+            Attribute::ConstantValue(index) => {
+                let expected = field_desc.map_or(CONSTANT_VALUE, |desc| self.constant_value(desc));
+                self.check(index, expected);
+            }
+            Attribute::EnclosingMethod {
+                class_index,
+                method_index,
+            } => {
+                self.check_member("class", class_index, CLASS);
+                self.check_optional_member("method", method_index, NAME_AND_TYPE);
+            }
+            Attribute::Exceptions(ref exceptions) => {
+                for (i, exception) in exceptions.iter().enumerate() {
+                    self.check_entry(i, *exception, CLASS);
+                }
+            }
+            Attribute::InnerClasses(ref classes) => for (i, class) in classes.iter().enumerate() {
+                self.path.push(PathElement::Entry(i));
+                self.check_member("inner_class_info", class.inner_class_info, CLASS);
+                self.check_optional_member("outer_class_info", class.outer_class_info, CLASS);
+                self.check_optional_member("inner_name", class.inner_name, UTF8);
+                self.path.pop();
+            },
+            Attribute::LocalVariableTable(ref table) => {
+                for (i, variable) in table.iter().enumerate() {
+                    self.path.push(PathElement::Entry(i));
+                    self.check_member("name", variable.name, UTF8);
+                    self.check_member("descriptor", variable.descriptor, UTF8);
+                    self.path.pop();
+                }
+            }
+            Attribute::LocalVariableTypeTable(ref table) => {
+                for (i, variable) in table.iter().enumerate() {
+                    self.path.push(PathElement::Entry(i));
+                    self.check_member("name", variable.name, UTF8);
+                    self.check_member("signature", variable.signature, UTF8);
+                    self.path.pop();
+                }
+            }
+            Attribute::MethodParameters(ref parameters) => {
+                for (i, parameter) in parameters.iter().enumerate() {
+                    self.path.push(PathElement::Entry(i));
+                    self.check_optional_member("name", parameter.name, UTF8);
+                    self.path.pop();
+                }
+            }
+            Attribute::Module {
+                name,
+                version,
+                ref requires,
+                ref exports,
+                ref opens,
+                ref uses,
+                ref provides,
+                ..
+            } => {
+                self.check_member("name", name, MODULE);
+                self.check_optional_member("version", version, UTF8);
+
+                self.path.push(PathElement::Member("requires"));
+                for (i, requirement) in requires.iter().enumerate() {
+                    self.path.push(PathElement::Entry(i));
+                    self.check_member("requires", requirement.index, MODULE);
+                    self.check_optional_member("version", requirement.version, UTF8);
+                    self.path.pop();
+                }
+                self.path.pop();
+
+                self.path.push(PathElement::Member("exports"));
+                for (i, export) in exports.iter().enumerate() {
+                    self.path.push(PathElement::Entry(i));
+                    self.check_member("exports", export.index, PACKAGE);
+                    self.modules_to(&export.to);
+                    self.path.pop();
+                }
+                self.path.pop();
+
+                self.path.push(PathElement::Member("opens"));
+                for (i, opening) in opens.iter().enumerate() {
+                    self.path.push(PathElement::Entry(i));
+                    self.check_member("opens", opening.index, PACKAGE);
+                    self.modules_to(&opening.to);
+                    self.path.pop();
+                }
+                self.path.pop();
+
+                self.path.push(PathElement::Member("uses"));
+                for (i, service) in uses.iter().enumerate() {
+                    self.check_entry(i, *service, CLASS);
+                }
+                self.path.pop();
+
+                self.path.push(PathElement::Member("provides"));
+                for (i, provider) in provides.iter().enumerate() {
+                    self.path.push(PathElement::Entry(i));
+                    self.check_member("provides", provider.index, CLASS);
+                    self.path.push(PathElement::Member("with"));
+                    for (i, implementation) in provider.with.iter().enumerate() {
+                        self.check_entry(i, *implementation, CLASS);
+                    }
+                    self.path.pop();
+                    self.path.pop();
+                }
+                self.path.pop();
+            }
+            Attribute::ModuleMainClass(index) => self.check(index, CLASS),
+            Attribute::ModulePackages(ref packages) => {
+                for (i, package) in packages.iter().enumerate() {
+                    self.check_entry(i, *package, PACKAGE);
+                }
+            }
+            Attribute::NestHost(index) => self.check(index, CLASS),
+            Attribute::NestMembers(ref members) => {
+                for (i, member) in members.iter().enumerate() {
+                    self.check_entry(i, *member, CLASS);
+                }
+            }
+            Attribute::PermittedSubclasses(ref subclasses) => {
+                for (i, subclass) in subclasses.iter().enumerate() {
+                    self.check_entry(i, *subclass, CLASS);
+                }
+            }
+            Attribute::Record(ref components) => {
+                for (i, component) in components.iter().enumerate() {
+                    self.path.push(PathElement::Entry(i));
+                    self.check_member("name", component.name, UTF8);
+                    self.check_member("descriptor", component.descriptor, UTF8);
+                    self.attributes(&component.attributes, None);
+                    self.path.pop();
+                }
+            }
+            Attribute::RuntimeVisibleAnnotations(ref annotations)
+            | Attribute::RuntimeInvisibleAnnotations(ref annotations) => {
+                self.annotations(annotations)
+            }
+            Attribute::RuntimeVisibleParameterAnnotations(ref parameters)
+            | Attribute::RuntimeInvisibleParameterAnnotations(ref parameters) => {
+                for (i, annotations) in parameters.iter().enumerate() {
+                    self.path.push(PathElement::Entry(i));
+                    self.annotations(annotations);
+                    self.path.pop();
+                }
+            }
+            Attribute::RuntimeVisibleTypeAnnotations(ref annotations)
+            | Attribute::RuntimeInvisibleTypeAnnotations(ref annotations) => {
+                for (i, annotation) in annotations.iter().enumerate() {
+                    self.path.push(PathElement::Entry(i));
+                    self.annotation(&annotation.annotation);
+                    self.path.pop();
+                }
+            }
+            Attribute::Signature(index) | Attribute::SourceFile(index) => self.check(index, UTF8),
+            Attribute::StackMapTable(ref frames) => for (i, frame) in frames.iter().enumerate() {
+                self.path.push(PathElement::Entry(i));
+                match *frame {
+                    StackMapFrame::Same1 { ref stack, .. } => {
+                        self.verification_types(::std::slice::from_ref(stack))
+                    }
+                    StackMapFrame::Append { ref locals, .. } => self.verification_types(locals),
+                    StackMapFrame::Full {
+                        ref locals,
+                        ref stack,
+                        ..
+                    } => {
+                        self.verification_types(locals);
+                        self.verification_types(stack);
+                    }
+                    StackMapFrame::Same { .. } | StackMapFrame::Chop { .. } => {}
+                }
+                self.path.pop();
+            },
+            Attribute::Unknown(name, _) => self.check_member("name", name, UTF8),
+            Attribute::Deprecated
+            | Attribute::LineNumberTable(_)
+            | Attribute::Synthetic
+            | Attribute::SourceDebugExtension(_) => {}
+        }
+    }
+
+    /// Returns the kind of item required by the `ConstantValue` of a field.
+    fn constant_value(&self, field_desc: u16) -> &'static [ItemKind] {
+        let desc: TypeDescriptor = match self.constant_pool
+            .get_utf8(field_desc)
+            .ok()
+            .and_then(|desc| desc.parse().ok())
+        {
+            Some(desc) => desc,
+            None => return CONSTANT_VALUE,
+        };
+        if desc.dimensions != 0 {
+            return CONSTANT_VALUE;
+        }
+
+        match desc.base_type {
+            Type::Boolean | Type::Byte | Type::Short | Type::Int | Type::Char => INTEGER,
+            Type::Long => LONG,
+            Type::Float => FLOAT,
+            Type::Double => DOUBLE,
+            Type::Reference(ref name) if name == "java/lang/String" => STRING,
+            Type::Reference(_) => CONSTANT_VALUE,
+        }
+    }
+
+    fn modules_to(&mut self, to: &[u16]) {
+        self.path.push(PathElement::Member("to"));
+        for (i, module) in to.iter().enumerate() {
+            self.check_entry(i, *module, MODULE);
+        }
+        self.path.pop();
+    }
+
+    fn verification_types(&mut self, types: &[VerificationType]) {
+        for verification_type in types {
+            if let VerificationType::Object(index) = *verification_type {
+                self.check(index, CLASS);
+            }
+        }
+    }
+
+    fn instruction(&mut self, insn: &Instruction) {
+        use self::Instruction::*;
+
+        match *insn {
+            LDC(index) | LDCW(index) => self.check(index, LOADABLE),
+            LDC2W(index) => self.check(index, LOADABLE_WIDE),
+            GetStatic(index) | PutStatic(index) | GetField(index) | PutField(index) => {
+                self.check(index, FIELD_REF)
+            }
+            InvokeVirtual(index) => self.check(index, METHOD_REF),
+            // interface methods are allowed since version 52.0
+            InvokeSpecial(index) | InvokeStatic(index) => self.check(index, ANY_METHOD_REF),
+            InvokeInterface(index, ..) => self.check(index, INTERFACE_METHOD_REF),
+            InvokeDynamic(index, _) => self.check(index, INVOKE_DYNAMIC),
+            New(index) | ANewArray(index) | CheckCast(index) | InstanceOf(index)
+            | MultiANewArray(index, _) => self.check(index, CLASS),
+            _ => {}
+        }
+    }
+
+    fn annotations(&mut self, annotations: &[Annotation]) {
+        for (i, annotation) in annotations.iter().enumerate() {
+            self.path.push(PathElement::Entry(i));
+            self.annotation(annotation);
+            self.path.pop();
+        }
+    }
+
+    fn annotation(&mut self, annotation: &Annotation) {
+        self.check_member("type", annotation.type_index, UTF8);
+        for (i, &(name, ref value)) in annotation.element_value_pairs.iter().enumerate() {
+            self.path.push(PathElement::Entry(i));
+            self.check_member("name", name, UTF8);
+            self.path.push(PathElement::Member("value"));
+            self.element_value(value);
+            self.path.pop();
+            self.path.pop();
+        }
+    }
+
+    fn element_value(&mut self, value: &ElementValue) {
+        match *value {
+            ElementValue::Byte(index)
+            | ElementValue::Short(index)
+            | ElementValue::Char(index)
+            | ElementValue::Int(index)
+            | ElementValue::Boolean(index) => self.check(index, INTEGER),
+            ElementValue::Long(index) => self.check(index, LONG),
+            ElementValue::Float(index) => self.check(index, FLOAT),
+            ElementValue::Double(index) => self.check(index, DOUBLE),
+            ElementValue::String(index) | ElementValue::Class(index) => self.check(index, UTF8),
+            ElementValue::Enum {
+                type_name,
+                const_name,
+            } => {
+                self.check_member("type_name", type_name, UTF8);
+                self.check_member("const_name", const_name, UTF8);
+            }
+            ElementValue::Annotation(ref annotation) => self.annotation(annotation),
+            ElementValue::Array(ref values) => for (i, value) in values.iter().enumerate() {
+                self.path.push(PathElement::Entry(i));
+                self.element_value(value);
+                self.path.pop();
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use basic::parse;
+
+    #[test]
+    fn validate_resources() {
+        for bytes in &[
+            &include_bytes!("../../examples/resources/AnyAnnotation.class")[..],
+            &include_bytes!("../../examples/resources/CustomLinkedList.class")[..],
+            &include_bytes!("../../examples/resources/CustomLinkedList$Element.class")[..],
+            &include_bytes!("../../examples/resources/CustomLinkedList$Iter.class")[..],
+            &include_bytes!("../../examples/resources/Fibonacci.class")[..],
+            &include_bytes!("../../examples/resources/FizzBuzzStream.class")[..],
+            &include_bytes!("../../examples/resources/Nestmates.class")[..],
+            &include_bytes!("../../examples/resources/Nestmates$Inner.class")[..],
+            &include_bytes!("../../examples/resources/Records.class")[..],
+            &include_bytes!("../../examples/resources/Records$Component.class")[..],
+            &include_bytes!("../../examples/resources/Sealed.class")[..],
+            &include_bytes!("../../examples/resources/Sealed$Leaf.class")[..],
+            &include_bytes!("../../examples/resources/Sealed$Node.class")[..],
+            &include_bytes!("../../examples/resources/RoundTrip.class")[..],
+            &include_bytes!("../../examples/resources/Surrogates.class")[..],
+        ] {
+            let (pool, class) = parse(bytes).unwrap();
+            assert_eq!(validate(&pool, &class), Vec::new());
+        }
+    }
+
+    #[test]
+    fn invalid_indices() {
+        let mut pool = Pool::new();
+        let object = pool.push(Item::UTF8("java/lang/Object".to_owned())).unwrap();
+        let object = pool.push(Item::Class(object)).unwrap();
+        let name = pool.push(Item::UTF8("value".to_owned())).unwrap();
+        let desc = pool.push(Item::UTF8("I".to_owned())).unwrap();
+        let name_and_type = pool.push(Item::NameAndType { name, desc }).unwrap();
+        let method_ref = pool.push(Item::MethodRef {
+            class: object,
+            name_and_type,
+        }).unwrap();
+        // a field reference with a utf8 item as class
+        pool.push(Item::FieldRef {
+            class: name,
+            name_and_type,
+        }).unwrap();
+
+        let class = Class {
+            minor_version: 0,
+            major_version: 52,
+            access_flags: ClassAccessFlags::PUBLIC,
+            name: 9999,
+            super_name: object,
+            interfaces: Vec::new(),
+            fields: vec![
+                Field {
+                    access_flags: FieldAccessFlags::STATIC | FieldAccessFlags::FINAL,
+                    name,
+                    desc,
+                    attributes: vec![Attribute::Deprecated, Attribute::ConstantValue(method_ref)],
+                },
+            ],
+            methods: Vec::new(),
+            attributes: Vec::new(),
+        };
+
+        let diagnostics = validate(&pool, &class);
+        let messages: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "#7.class: #3 must be CONSTANT_Class, found CONSTANT_Utf8",
+                "this_class: #9999 must be CONSTANT_Class, found nothing",
+                "fields[0].attributes[1]: #6 must be CONSTANT_Integer, found CONSTANT_Methodref",
+            ]
+        );
+        assert_eq!(
+            diagnostics[2].path,
+            vec![PathElement::Field(0), PathElement::Attribute(1)]
+        );
+    }
+}
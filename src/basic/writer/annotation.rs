@@ -0,0 +1,269 @@
+use super::*;
+
+/// Writes the parameter annotations.
+pub fn write_parameter_annotations(
+    encoder: &mut Encoder,
+    annotations: &[Vec<Annotation>],
+) -> Result<()> {
+    if annotations.len() > u8::MAX as usize {
+        return Err(Error::TooLarge);
+    }
+
+    encoder.write_u8(annotations.len() as u8);
+    for parameter in annotations {
+        write_annotations(encoder, parameter)?;
+    }
+    Ok(())
+}
+
+/// Writes the annotations.
+pub fn write_annotations(encoder: &mut Encoder, annotations: &[Annotation]) -> Result<()> {
+    encoder.write_len(annotations.len())?;
+    for annotation in annotations {
+        write_annotation(encoder, annotation)?;
+    }
+    Ok(())
+}
+
+/// Writes the type annotations.
+pub fn write_type_annotations(encoder: &mut Encoder, annotations: &[TypeAnnotation]) -> Result<()> {
+    encoder.write_len(annotations.len())?;
+    for annotation in annotations {
+        write_type_annotation(encoder, annotation)?;
+    }
+    Ok(())
+}
+
+/// Writes a single annotation.
+fn write_annotation(encoder: &mut Encoder, annotation: &Annotation) -> Result<()> {
+    encoder.write_u16(annotation.type_index);
+    encoder.write_len(annotation.element_value_pairs.len())?;
+
+    for &(name_index, ref value) in &annotation.element_value_pairs {
+        encoder.write_u16(name_index);
+        write_element_value(encoder, value)?;
+    }
+
+    Ok(())
+}
+
+/// Writes an element value.
+pub fn write_element_value(encoder: &mut Encoder, value: &ElementValue) -> Result<()> {
+    match *value {
+        ElementValue::Byte(index) => {
+            encoder.write_u8(b'B');
+            encoder.write_u16(index);
+        }
+        ElementValue::Short(index) => {
+            encoder.write_u8(b'S');
+            encoder.write_u16(index);
+        }
+        ElementValue::Char(index) => {
+            encoder.write_u8(b'C');
+            encoder.write_u16(index);
+        }
+        ElementValue::Int(index) => {
+            encoder.write_u8(b'I');
+            encoder.write_u16(index);
+        }
+        ElementValue::Long(index) => {
+            encoder.write_u8(b'J');
+            encoder.write_u16(index);
+        }
+        ElementValue::Float(index) => {
+            encoder.write_u8(b'F');
+            encoder.write_u16(index);
+        }
+        ElementValue::Double(index) => {
+            encoder.write_u8(b'D');
+            encoder.write_u16(index);
+        }
+        ElementValue::Boolean(index) => {
+            encoder.write_u8(b'Z');
+            encoder.write_u16(index);
+        }
+        ElementValue::String(index) => {
+            encoder.write_u8(b's');
+            encoder.write_u16(index);
+        }
+        ElementValue::Class(index) => {
+            encoder.write_u8(b'c');
+            encoder.write_u16(index);
+        }
+        ElementValue::Enum {
+            type_name,
+            const_name,
+        } => {
+            encoder.write_u8(b'e');
+            encoder.write_u16(type_name);
+            encoder.write_u16(const_name);
+        }
+        ElementValue::Annotation(ref annotation) => {
+            encoder.write_u8(b'@');
+            write_annotation(encoder, annotation)?;
+        }
+        ElementValue::Array(ref values) => {
+            encoder.write_u8(b'[');
+            encoder.write_len(values.len())?;
+            for value in values {
+                write_element_value(encoder, value)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes a type annotation
+fn write_type_annotation(encoder: &mut Encoder, annotation: &TypeAnnotation) -> Result<()> {
+    write_target_type(encoder, &annotation.target_type)?;
+    write_type_path(encoder, &annotation.target_path)?;
+    write_annotation(encoder, &annotation.annotation)
+}
+
+/// Writes the target type of a type annotation
+fn write_target_type(encoder: &mut Encoder, target_type: &TargetType) -> Result<()> {
+    use self::TargetType::*;
+
+    match *target_type {
+        TypeParameterClass(index) => {
+            encoder.write_u8(0x00);
+            encoder.write_u8(index);
+        }
+        TypeParameterMethod(index) => {
+            encoder.write_u8(0x01);
+            encoder.write_u8(index);
+        }
+        SuperType(index) => {
+            encoder.write_u8(0x10);
+            encoder.write_u16(index);
+        }
+        TypeParameterBoundClass {
+            type_parameter,
+            bound_index,
+        } => {
+            encoder.write_u8(0x11);
+            encoder.write_u8(type_parameter);
+            encoder.write_u8(bound_index);
+        }
+        TypeParameterBoundMethod {
+            type_parameter,
+            bound_index,
+        } => {
+            encoder.write_u8(0x12);
+            encoder.write_u8(type_parameter);
+            encoder.write_u8(bound_index);
+        }
+        EmptyField => encoder.write_u8(0x13),
+        EmptyReturn => encoder.write_u8(0x14),
+        EmptyReceiver => encoder.write_u8(0x15),
+        FormalParameter(index) => {
+            encoder.write_u8(0x16);
+            encoder.write_u8(index);
+        }
+        Throws(index) => {
+            encoder.write_u8(0x17);
+            encoder.write_u16(index);
+        }
+        LocalVariable(ref table) => {
+            encoder.write_u8(0x40);
+            write_local_variable(encoder, table)?;
+        }
+        ResourceVariable(ref table) => {
+            encoder.write_u8(0x41);
+            write_local_variable(encoder, table)?;
+        }
+        Catch(index) => {
+            encoder.write_u8(0x42);
+            encoder.write_u16(index);
+        }
+        OffsetInstanceOf(offset) => {
+            encoder.write_u8(0x43);
+            encoder.write_u16(offset);
+        }
+        OffsetNew(offset) => {
+            encoder.write_u8(0x44);
+            encoder.write_u16(offset);
+        }
+        OffsetNewRef(offset) => {
+            encoder.write_u8(0x45);
+            encoder.write_u16(offset);
+        }
+        OffsetRef(offset) => {
+            encoder.write_u8(0x46);
+            encoder.write_u16(offset);
+        }
+        TypeArgumentCast {
+            offset,
+            type_argument,
+        } => {
+            encoder.write_u8(0x47);
+            encoder.write_u16(offset);
+            encoder.write_u8(type_argument);
+        }
+        TypeArgumentConstructor {
+            offset,
+            type_argument,
+        } => {
+            encoder.write_u8(0x48);
+            encoder.write_u16(offset);
+            encoder.write_u8(type_argument);
+        }
+        TypeArgumentMethod {
+            offset,
+            type_argument,
+        } => {
+            encoder.write_u8(0x49);
+            encoder.write_u16(offset);
+            encoder.write_u8(type_argument);
+        }
+        TypeArgumentNewRef {
+            offset,
+            type_argument,
+        } => {
+            encoder.write_u8(0x4A);
+            encoder.write_u16(offset);
+            encoder.write_u8(type_argument);
+        }
+        TypeArgumentRef {
+            offset,
+            type_argument,
+        } => {
+            encoder.write_u8(0x4B);
+            encoder.write_u16(offset);
+            encoder.write_u8(type_argument);
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the local variables of a local variable target type
+fn write_local_variable(encoder: &mut Encoder, table: &[LocalVariableTarget]) -> Result<()> {
+    encoder.write_len(table.len())?;
+    for target in table {
+        encoder.write_u16(target.start);
+        encoder.write_u16(target.length);
+        encoder.write_u16(target.index);
+    }
+    Ok(())
+}
+
+/// Writes the type path of a type annotation
+fn write_type_path(encoder: &mut Encoder, type_path: &[TypePathElement]) -> Result<()> {
+    if type_path.len() > u8::MAX as usize {
+        return Err(Error::TooLarge);
+    }
+
+    encoder.write_u8(type_path.len() as u8);
+    for element in type_path {
+        encoder.write_u8(match element.path_kind {
+            TypePathKind::ArrayType => 0x00,
+            TypePathKind::NestedType => 0x01,
+            TypePathKind::WildcardType => 0x02,
+            TypePathKind::Type => 0x03,
+        });
+        encoder.write_u8(element.argument_index);
+    }
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use super::*;
+
+/// Writes the `BootstrapMethods` attribute
+pub fn write_bootstrap_methods(
+    encoder: &mut Encoder,
+    bootstrap_methods: &[BootstrapMethod],
+) -> Result<()> {
+    encoder.write_len(bootstrap_methods.len())?;
+    for bootstrap_method in bootstrap_methods {
+        encoder.write_u16(bootstrap_method.method_ref);
+        encoder.write_len(bootstrap_method.arguments.len())?;
+        for argument in &bootstrap_method.arguments {
+            encoder.write_u16(*argument);
+        }
+    }
+    Ok(())
+}
+
+/// Writes the `InnerClasses` attribute
+pub fn write_inner_classes(encoder: &mut Encoder, inner_classes: &[InnerClass]) -> Result<()> {
+    encoder.write_len(inner_classes.len())?;
+    for inner_class in inner_classes {
+        encoder.write_u16(inner_class.inner_class_info);
+        encoder.write_u16(inner_class.outer_class_info);
+        encoder.write_u16(inner_class.inner_name);
+        encoder.write_u16(inner_class.inner_class_access_flags.bits());
+    }
+    Ok(())
+}
+
+/// Writes the `ModulePackages` attribute.
+pub fn write_module_packages(encoder: &mut Encoder, packages: &[u16]) -> Result<()> {
+    encoder.write_len(packages.len())?;
+    for package in packages {
+        encoder.write_u16(*package);
+    }
+    Ok(())
+}
+
+/// Writes the `NestMembers` attribute.
+pub fn write_nest_members(encoder: &mut Encoder, members: &[u16]) -> Result<()> {
+    encoder.write_len(members.len())?;
+    for member in members {
+        encoder.write_u16(*member);
+    }
+    Ok(())
+}
+
+/// Writes the `PermittedSubclasses` attribute.
+pub fn write_permitted_subclasses(encoder: &mut Encoder, subclasses: &[u16]) -> Result<()> {
+    encoder.write_len(subclasses.len())?;
+    for subclass in subclasses {
+        encoder.write_u16(*subclass);
+    }
+    Ok(())
+}
+
+/// Writes the `Record` attribute and the attributes of its components.
+pub fn write_record(
+    encoder: &mut Encoder,
+    context: &mut Context,
+    components: &[RecordComponent],
+) -> Result<()> {
+    encoder.write_len(components.len())?;
+    for component in components {
+        encoder.write_u16(component.name);
+        encoder.write_u16(component.descriptor);
+        write_attributes(encoder, context, &component.attributes)?;
+    }
+    Ok(())
+}
+
+/// Writes the tables of the `Module` attribute.
+pub fn write_module(
+    encoder: &mut Encoder,
+    requires: &[Requirement],
+    exports: &[Export],
+    opens: &[Opening],
+    uses: &[u16],
+    provides: &[Provider],
+) -> Result<()> {
+    // write requires
+    encoder.write_len(requires.len())?;
+    for requirement in requires {
+        encoder.write_u16(requirement.index);
+        encoder.write_u16(requirement.flags.bits());
+        encoder.write_u16(requirement.version);
+    }
+
+    // write exports
+    encoder.write_len(exports.len())?;
+    for export in exports {
+        encoder.write_u16(export.index);
+        encoder.write_u16(export.flags.bits());
+
+        encoder.write_len(export.to.len())?;
+        for to in &export.to {
+            encoder.write_u16(*to);
+        }
+    }
+
+    // write opens
+    encoder.write_len(opens.len())?;
+    for opening in opens {
+        encoder.write_u16(opening.index);
+        encoder.write_u16(opening.flags.bits());
+
+        encoder.write_len(opening.to.len())?;
+        for to in &opening.to {
+            encoder.write_u16(*to);
+        }
+    }
+
+    // write uses
+    encoder.write_len(uses.len())?;
+    for index in uses {
+        encoder.write_u16(*index);
+    }
+
+    // write provides
+    encoder.write_len(provides.len())?;
+    for provider in provides {
+        encoder.write_u16(provider.index);
+
+        encoder.write_len(provider.with.len())?;
+        for with in &provider.with {
+            encoder.write_u16(*with);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,413 @@
+use super::*;
+
+/// Writes the code attribute
+pub fn write_code(
+    encoder: &mut Encoder,
+    context: &mut Context,
+    max_stack: u16,
+    max_locals: u16,
+    instructions: &Instructions,
+    exceptions: &[Exception],
+    attributes: &[Attribute],
+) -> Result<()> {
+    encoder.write_u16(max_stack);
+    encoder.write_u16(max_locals);
+
+    let mut code_encoder = Encoder::new();
+    for (location, insn) in instructions {
+        // the location of every instruction must match the encoded location,
+        // otherwise all branches would point to the wrong location
+        if location as usize != code_encoder.len() {
+            return Err(Error::InvalidOffset(location));
+        }
+
+        write_instruction(&mut code_encoder, location, insn)?;
+    }
+
+    let code = code_encoder.bytes();
+    if code.len() > u16::MAX as usize {
+        return Err(Error::TooLarge);
+    }
+    encoder.write_u32(code.len() as u32);
+    encoder.write_bytes(&code);
+
+    // Write all exceptions
+    encoder.write_len(exceptions.len())?;
+    for exception in exceptions {
+        encoder.write_u16(exception.start);
+        encoder.write_u16(exception.end);
+        encoder.write_u16(exception.handler);
+        encoder.write_u16(exception.catch_type);
+    }
+
+    write_attributes(encoder, context, attributes)
+}
+
+/// Returns the amount of bytes an instruction takes up, if it is located at `at`.
+/// The location is required, since the padding of switches depends on it.
+pub fn instruction_len(at: u32, insn: &Instruction) -> Result<u32> {
+    let mut encoder = Encoder::new();
+    write_instruction(&mut encoder, at, insn)?;
+    Ok(encoder.len() as u32)
+}
+
+/// Writes an instruction taking a local variable index.
+/// If the index is too large, the wide form is used.
+fn write_local(encoder: &mut Encoder, op_code: u8, index: u16) {
+    if index > u16::from(u8::MAX) {
+        encoder.write_u8(0xC4);
+        encoder.write_u8(op_code);
+        encoder.write_u16(index);
+    } else {
+        encoder.write_u8(op_code);
+        encoder.write_u8(index as u8);
+    }
+}
+
+/// Writes an instruction taking a branch offset.
+/// If the offset is too large, the wide form (`goto_w` or `jsr_w`) is used.
+fn write_jump(encoder: &mut Encoder, op_code: u8, wide_op_code: u8, offset: i32) {
+    if offset < i32::from(i16::MIN) || offset > i32::from(i16::MAX) {
+        encoder.write_u8(wide_op_code);
+        encoder.write_i32(offset);
+    } else {
+        encoder.write_u8(op_code);
+        encoder.write_i16(offset as i16);
+    }
+}
+
+/// Writes an instruction taking a branch offset of 2 bytes.
+fn write_branch(encoder: &mut Encoder, op_code: u8, offset: i16) {
+    encoder.write_u8(op_code);
+    encoder.write_i16(offset);
+}
+
+/// Writes an instruction taking an index to the constant pool.
+fn write_indexed(encoder: &mut Encoder, op_code: u8, index: u16) {
+    encoder.write_u8(op_code);
+    encoder.write_u16(index);
+}
+
+/// Writes the padding of a switch, so the next byte is aligned to 4 bytes
+fn write_padding(encoder: &mut Encoder, at: u32, padding: &[u8; 3]) {
+    encoder.write_bytes(&padding[..(3 - (at & 3)) as usize]);
+}
+
+/// Writes a single instruction, which is located at `at`
+fn write_instruction(encoder: &mut Encoder, at: u32, insn: &Instruction) -> Result<()> {
+    use self::Instruction::*;
+
+    match *insn {
+        NOP => encoder.write_u8(0x00),
+        AConstNull => encoder.write_u8(0x01),
+        IConstM1 => encoder.write_u8(0x02),
+        IConst0 => encoder.write_u8(0x03),
+        IConst1 => encoder.write_u8(0x04),
+        IConst2 => encoder.write_u8(0x05),
+        IConst3 => encoder.write_u8(0x06),
+        IConst4 => encoder.write_u8(0x07),
+        IConst5 => encoder.write_u8(0x08),
+        LConst0 => encoder.write_u8(0x09),
+        LConst1 => encoder.write_u8(0x0A),
+        FConst0 => encoder.write_u8(0x0B),
+        FConst1 => encoder.write_u8(0x0C),
+        FConst2 => encoder.write_u8(0x0D),
+        DConst0 => encoder.write_u8(0x0E),
+        DConst1 => encoder.write_u8(0x0F),
+        ILoad0 => encoder.write_u8(0x1A),
+        ILoad1 => encoder.write_u8(0x1B),
+        ILoad2 => encoder.write_u8(0x1C),
+        ILoad3 => encoder.write_u8(0x1D),
+        LLoad0 => encoder.write_u8(0x1E),
+        LLoad1 => encoder.write_u8(0x1F),
+        LLoad2 => encoder.write_u8(0x20),
+        LLoad3 => encoder.write_u8(0x21),
+        FLoad0 => encoder.write_u8(0x22),
+        FLoad1 => encoder.write_u8(0x23),
+        FLoad2 => encoder.write_u8(0x24),
+        FLoad3 => encoder.write_u8(0x25),
+        DLoad0 => encoder.write_u8(0x26),
+        DLoad1 => encoder.write_u8(0x27),
+        DLoad2 => encoder.write_u8(0x28),
+        DLoad3 => encoder.write_u8(0x29),
+        ALoad0 => encoder.write_u8(0x2A),
+        ALoad1 => encoder.write_u8(0x2B),
+        ALoad2 => encoder.write_u8(0x2C),
+        ALoad3 => encoder.write_u8(0x2D),
+        IALoad => encoder.write_u8(0x2E),
+        LALoad => encoder.write_u8(0x2F),
+        FALoad => encoder.write_u8(0x30),
+        DALoad => encoder.write_u8(0x31),
+        AALoad => encoder.write_u8(0x32),
+        BALoad => encoder.write_u8(0x33),
+        CALoad => encoder.write_u8(0x34),
+        SALoad => encoder.write_u8(0x35),
+        IStore0 => encoder.write_u8(0x3B),
+        IStore1 => encoder.write_u8(0x3C),
+        IStore2 => encoder.write_u8(0x3D),
+        IStore3 => encoder.write_u8(0x3E),
+        LStore0 => encoder.write_u8(0x3F),
+        LStore1 => encoder.write_u8(0x40),
+        LStore2 => encoder.write_u8(0x41),
+        LStore3 => encoder.write_u8(0x42),
+        FStore0 => encoder.write_u8(0x43),
+        FStore1 => encoder.write_u8(0x44),
+        FStore2 => encoder.write_u8(0x45),
+        FStore3 => encoder.write_u8(0x46),
+        DStore0 => encoder.write_u8(0x47),
+        DStore1 => encoder.write_u8(0x48),
+        DStore2 => encoder.write_u8(0x49),
+        DStore3 => encoder.write_u8(0x4A),
+        AStore0 => encoder.write_u8(0x4B),
+        AStore1 => encoder.write_u8(0x4C),
+        AStore2 => encoder.write_u8(0x4D),
+        AStore3 => encoder.write_u8(0x4E),
+        IAStore => encoder.write_u8(0x4F),
+        LAStore => encoder.write_u8(0x50),
+        FAStore => encoder.write_u8(0x51),
+        DAStore => encoder.write_u8(0x52),
+        AAStore => encoder.write_u8(0x53),
+        BAStore => encoder.write_u8(0x54),
+        CAStore => encoder.write_u8(0x55),
+        SAStore => encoder.write_u8(0x56),
+        Pop => encoder.write_u8(0x57),
+        Pop2 => encoder.write_u8(0x58),
+        Dup => encoder.write_u8(0x59),
+        DupX1 => encoder.write_u8(0x5A),
+        DupX2 => encoder.write_u8(0x5B),
+        Dup2 => encoder.write_u8(0x5C),
+        Dup2X1 => encoder.write_u8(0x5D),
+        Dup2X2 => encoder.write_u8(0x5E),
+        Swap => encoder.write_u8(0x5F),
+        IAdd => encoder.write_u8(0x60),
+        LAdd => encoder.write_u8(0x61),
+        FAdd => encoder.write_u8(0x62),
+        DAdd => encoder.write_u8(0x63),
+        ISub => encoder.write_u8(0x64),
+        LSub => encoder.write_u8(0x65),
+        FSub => encoder.write_u8(0x66),
+        DSub => encoder.write_u8(0x67),
+        IMul => encoder.write_u8(0x68),
+        LMul => encoder.write_u8(0x69),
+        FMul => encoder.write_u8(0x6A),
+        DMul => encoder.write_u8(0x6B),
+        IDiv => encoder.write_u8(0x6C),
+        LDiv => encoder.write_u8(0x6D),
+        FDiv => encoder.write_u8(0x6E),
+        DDiv => encoder.write_u8(0x6F),
+        IRem => encoder.write_u8(0x70),
+        LRem => encoder.write_u8(0x71),
+        FRem => encoder.write_u8(0x72),
+        DRem => encoder.write_u8(0x73),
+        INeg => encoder.write_u8(0x74),
+        LNeg => encoder.write_u8(0x75),
+        FNeg => encoder.write_u8(0x76),
+        DNeg => encoder.write_u8(0x77),
+        IShL => encoder.write_u8(0x78),
+        LShL => encoder.write_u8(0x79),
+        IShR => encoder.write_u8(0x7A),
+        LShR => encoder.write_u8(0x7B),
+        IUShR => encoder.write_u8(0x7C),
+        LUShR => encoder.write_u8(0x7D),
+        IAnd => encoder.write_u8(0x7E),
+        LAnd => encoder.write_u8(0x7F),
+        IOr => encoder.write_u8(0x80),
+        LOr => encoder.write_u8(0x81),
+        IXOr => encoder.write_u8(0x82),
+        LXOr => encoder.write_u8(0x83),
+        I2L => encoder.write_u8(0x85),
+        I2F => encoder.write_u8(0x86),
+        I2D => encoder.write_u8(0x87),
+        L2I => encoder.write_u8(0x88),
+        L2F => encoder.write_u8(0x89),
+        L2D => encoder.write_u8(0x8A),
+        F2I => encoder.write_u8(0x8B),
+        F2L => encoder.write_u8(0x8C),
+        F2D => encoder.write_u8(0x8D),
+        D2I => encoder.write_u8(0x8E),
+        D2L => encoder.write_u8(0x8F),
+        D2F => encoder.write_u8(0x90),
+        I2B => encoder.write_u8(0x91),
+        I2C => encoder.write_u8(0x92),
+        I2S => encoder.write_u8(0x93),
+        LCmp => encoder.write_u8(0x94),
+        FCmpL => encoder.write_u8(0x95),
+        FCmpG => encoder.write_u8(0x96),
+        DCmpL => encoder.write_u8(0x97),
+        DCmpG => encoder.write_u8(0x98),
+        IReturn => encoder.write_u8(0xAC),
+        LReturn => encoder.write_u8(0xAD),
+        FReturn => encoder.write_u8(0xAE),
+        DReturn => encoder.write_u8(0xAF),
+        AReturn => encoder.write_u8(0xB0),
+        Return => encoder.write_u8(0xB1),
+        ArrayLength => encoder.write_u8(0xBE),
+        AThrow => encoder.write_u8(0xBF),
+        MonitorEnter => encoder.write_u8(0xC2),
+        MonitorExit => encoder.write_u8(0xC3),
+        BreakPoint => encoder.write_u8(0xCA),
+        ImpDep1 => encoder.write_u8(0xFE),
+        ImpDep2 => encoder.write_u8(0xFF),
+        BIPush(value) => {
+            encoder.write_u8(0x10);
+            encoder.write_i8(value);
+        }
+        SIPush(value) => {
+            encoder.write_u8(0x11);
+            encoder.write_i16(value);
+        }
+        LDC(index) => {
+            if index > u16::from(u8::MAX) {
+                write_indexed(encoder, 0x13, index); // ldc_w
+            } else {
+                encoder.write_u8(0x12);
+                encoder.write_u8(index as u8);
+            }
+        }
+        LDCW(index) => write_indexed(encoder, 0x13, index),
+        LDC2W(index) => write_indexed(encoder, 0x14, index),
+        ILoad(index) => write_local(encoder, 0x15, index),
+        LLoad(index) => write_local(encoder, 0x16, index),
+        FLoad(index) => write_local(encoder, 0x17, index),
+        DLoad(index) => write_local(encoder, 0x18, index),
+        ALoad(index) => write_local(encoder, 0x19, index),
+        IStore(index) => write_local(encoder, 0x36, index),
+        LStore(index) => write_local(encoder, 0x37, index),
+        FStore(index) => write_local(encoder, 0x38, index),
+        DStore(index) => write_local(encoder, 0x39, index),
+        AStore(index) => write_local(encoder, 0x3A, index),
+        IInc(index, value) => {
+            if index > u16::from(u8::MAX)
+                || value < i16::from(i8::MIN)
+                || value > i16::from(i8::MAX)
+            {
+                encoder.write_u8(0xC4);
+                encoder.write_u8(0x84);
+                encoder.write_u16(index);
+                encoder.write_i16(value);
+            } else {
+                encoder.write_u8(0x84);
+                encoder.write_u8(index as u8);
+                encoder.write_i8(value as i8);
+            }
+        }
+        IfEq(offset) => write_branch(encoder, 0x99, offset),
+        IfNE(offset) => write_branch(encoder, 0x9A, offset),
+        IfLT(offset) => write_branch(encoder, 0x9B, offset),
+        IfGE(offset) => write_branch(encoder, 0x9C, offset),
+        IfGT(offset) => write_branch(encoder, 0x9D, offset),
+        IfLE(offset) => write_branch(encoder, 0x9E, offset),
+        IfICmpEq(offset) => write_branch(encoder, 0x9F, offset),
+        IfICmpNE(offset) => write_branch(encoder, 0xA0, offset),
+        IfICmpLT(offset) => write_branch(encoder, 0xA1, offset),
+        IfICmpGE(offset) => write_branch(encoder, 0xA2, offset),
+        IfICmpGT(offset) => write_branch(encoder, 0xA3, offset),
+        IfICmpLE(offset) => write_branch(encoder, 0xA4, offset),
+        IfACmpEq(offset) => write_branch(encoder, 0xA5, offset),
+        IfACmpNE(offset) => write_branch(encoder, 0xA6, offset),
+        GoTo(offset) => write_jump(encoder, 0xA7, 0xC8, offset),
+        JSR(offset) => write_jump(encoder, 0xA8, 0xC9, offset),
+        Ret(index) => write_local(encoder, 0xA9, index),
+        GoToW(offset) => {
+            encoder.write_u8(0xC8);
+            encoder.write_i32(offset);
+        }
+        JSRW(offset) => {
+            encoder.write_u8(0xC9);
+            encoder.write_i32(offset);
+        }
+        TableSwitch {
+            default,
+            low,
+            high,
+            ref offsets,
+            ref padding,
+        } => {
+            encoder.write_u8(0xAA);
+            write_padding(encoder, at, padding);
+            encoder.write_i32(default);
+            encoder.write_i32(low);
+            encoder.write_i32(high);
+            for offset in offsets {
+                encoder.write_i32(*offset);
+            }
+        }
+        LookupSwitch {
+            default,
+            ref offsets,
+            ref padding,
+        } => {
+            encoder.write_u8(0xAB);
+            write_padding(encoder, at, padding);
+            encoder.write_i32(default);
+            encoder.write_u32(offsets.len() as u32);
+            for (key, offset) in offsets {
+                encoder.write_i32(*key);
+                encoder.write_i32(*offset);
+            }
+        }
+        GetStatic(index) => write_indexed(encoder, 0xB2, index),
+        PutStatic(index) => write_indexed(encoder, 0xB3, index),
+        GetField(index) => write_indexed(encoder, 0xB4, index),
+        PutField(index) => write_indexed(encoder, 0xB5, index),
+        InvokeVirtual(index) => write_indexed(encoder, 0xB6, index),
+        InvokeSpecial(index) => write_indexed(encoder, 0xB7, index),
+        InvokeStatic(index) => write_indexed(encoder, 0xB8, index),
+        InvokeInterface(index, count, reserved) => {
+            write_indexed(encoder, 0xB9, index);
+            encoder.write_u8(count);
+            encoder.write_u8(reserved);
+        }
+        InvokeDynamic(index, reserved) => {
+            write_indexed(encoder, 0xBA, index);
+            encoder.write_u16(reserved);
+        }
+        New(index) => write_indexed(encoder, 0xBB, index),
+        NewArray(ref array_type) => {
+            encoder.write_u8(0xBC);
+            encoder.write_u8(match *array_type {
+                ArrayType::Boolean => 0x04,
+                ArrayType::Char => 0x05,
+                ArrayType::Float => 0x06,
+                ArrayType::Double => 0x07,
+                ArrayType::Byte => 0x08,
+                ArrayType::Short => 0x09,
+                ArrayType::Int => 0x0A,
+                ArrayType::Long => 0x0B,
+            });
+        }
+        ANewArray(index) => write_indexed(encoder, 0xBD, index),
+        CheckCast(index) => write_indexed(encoder, 0xC0, index),
+        InstanceOf(index) => write_indexed(encoder, 0xC1, index),
+        MultiANewArray(index, dimensions) => {
+            write_indexed(encoder, 0xC5, index);
+            encoder.write_u8(dimensions);
+        }
+        IfNull(offset) => write_branch(encoder, 0xC6, offset),
+        IfNonNull(offset) => write_branch(encoder, 0xC7, offset),
+        Wide(ref insn) => {
+            encoder.write_u8(0xC4);
+            match **insn {
+                ILoad(index) => write_indexed(encoder, 0x15, index),
+                LLoad(index) => write_indexed(encoder, 0x16, index),
+                FLoad(index) => write_indexed(encoder, 0x17, index),
+                DLoad(index) => write_indexed(encoder, 0x18, index),
+                ALoad(index) => write_indexed(encoder, 0x19, index),
+                IStore(index) => write_indexed(encoder, 0x36, index),
+                LStore(index) => write_indexed(encoder, 0x37, index),
+                FStore(index) => write_indexed(encoder, 0x38, index),
+                DStore(index) => write_indexed(encoder, 0x39, index),
+                AStore(index) => write_indexed(encoder, 0x3A, index),
+                IInc(index, value) => {
+                    write_indexed(encoder, 0x84, index);
+                    encoder.write_i16(value);
+                }
+                Ret(index) => write_indexed(encoder, 0xA9, index),
+
+                _ => return Err(Error::InvalidInstruction { op_code: 0xC4, at }),
+            }
+        }
+    }
+
+    Ok(())
+}
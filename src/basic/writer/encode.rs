@@ -0,0 +1,285 @@
+//! The `encode` crate is used for encoding simple data,
+//! like integers, floats and Strings.
+
+use result::*;
+use byteorder::{BigEndian, ByteOrder};
+
+pub struct Encoder {
+    bytes: Vec<u8>,
+}
+
+impl Encoder {
+    /// Creates a new, empty encoder
+    pub fn new() -> Encoder {
+        Encoder { bytes: Vec::new() }
+    }
+
+    /// Returns the amount of bytes written so far
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Consumes the encoder and returns all bytes written
+    pub fn bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub fn write_u16(&mut self, value: u16) {
+        let mut buf = [0; 2];
+        BigEndian::write_u16(&mut buf, value);
+        self.write_bytes(&buf);
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        let mut buf = [0; 4];
+        BigEndian::write_u32(&mut buf, value);
+        self.write_bytes(&buf);
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        let mut buf = [0; 8];
+        BigEndian::write_u64(&mut buf, value);
+        self.write_bytes(&buf);
+    }
+
+    pub fn write_i8(&mut self, value: i8) {
+        self.write_u8(value as u8);
+    }
+
+    pub fn write_i16(&mut self, value: i16) {
+        self.write_u16(value as u16);
+    }
+
+    pub fn write_i32(&mut self, value: i32) {
+        self.write_u32(value as u32);
+    }
+
+    pub fn write_i64(&mut self, value: i64) {
+        self.write_u64(value as u64);
+    }
+
+    pub fn write_f32(&mut self, value: f32) {
+        self.write_u32(value.to_bits());
+    }
+
+    pub fn write_f64(&mut self, value: f64) {
+        self.write_u64(value.to_bits());
+    }
+
+    /// Writes the length of a table (or anything else) as an u16.
+    /// If the length is too large, an error is returned.
+    pub fn write_len(&mut self, len: usize) -> Result<()> {
+        if len > u16::MAX as usize {
+            return Err(Error::TooLarge);
+        }
+
+        self.write_u16(len as u16);
+        Ok(())
+    }
+
+    /// Encodes a string as modified UTF-8, prefixed by its length in bytes,
+    /// like it is done for an `Item::UTF8(_)`.
+    /// If the encoded string takes up more than 65535 bytes, an error is returned.
+    pub fn write_utf8(&mut self, s: &str) -> Result<()> {
+        let length = str_len(s);
+        if length > u16::MAX as usize {
+            return Err(Error::StringTooLong(length));
+        }
+
+        self.write_u16(length as u16);
+        self.write_str(s);
+        Ok(())
+    }
+
+    /// Encodes UTF-16 code units as modified UTF-8, prefixed by its length in bytes,
+    /// like it is done for an `Item::UTF16(_)`.
+    /// If the encoded units take up more than 65535 bytes, an error is returned.
+    pub fn write_utf16(&mut self, units: &[u16]) -> Result<()> {
+        let length = units_len(units.iter().cloned());
+        if length > u16::MAX as usize {
+            return Err(Error::StringTooLong(length));
+        }
+
+        self.write_u16(length as u16);
+        self.write_units(units.iter().cloned());
+        Ok(())
+    }
+
+    /// Encodes a string as modified UTF-8.
+    /// The length is not written, use `str_len` to compute it.
+    pub fn write_str(&mut self, s: &str) {
+        // Supplementary characters are encoded as surrogate pairs,
+        // so we can just iterate over the UTF-16 representation.
+        self.write_units(s.encode_utf16());
+    }
+
+    /// Encodes UTF-16 code units as modified UTF-8, surrogates are encoded one by one.
+    fn write_units<I: Iterator<Item = u16>>(&mut self, units: I) {
+        for ch in units {
+            if ch != 0 && ch < 0x80 {
+                // single byte
+                self.write_u8(ch as u8);
+            } else if ch < 0x800 {
+                // 2 bytes, includes the null character
+                self.write_u8((0xC0 | (ch >> 6)) as u8);
+                self.write_u8((0x80 | (ch & 0x3F)) as u8);
+            } else {
+                // 3 bytes, includes surrogates
+                self.write_u8((0xE0 | (ch >> 12)) as u8);
+                self.write_u8((0x80 | ((ch >> 6) & 0x3F)) as u8);
+                self.write_u8((0x80 | (ch & 0x3F)) as u8);
+            }
+        }
+    }
+}
+
+/// Returns the amount of bytes a string takes up, if encoded as modified UTF-8.
+/// This may differ from the length of the string encoded as standard UTF-8,
+/// since the null character takes up 2 bytes
+/// and supplementary characters take up 6 bytes instead of 4.
+pub fn str_len(s: &str) -> usize {
+    units_len(s.encode_utf16())
+}
+
+/// Returns the amount of bytes UTF-16 code units take up, if encoded as modified UTF-8.
+fn units_len<I: Iterator<Item = u16>>(units: I) -> usize {
+    units
+        .map(|ch| {
+            if ch != 0 && ch < 0x80 {
+                1
+            } else if ch < 0x800 {
+                2
+            } else {
+                3
+            }
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod test {
+    macro_rules! test_values {
+        ( $func:ident { $( $input:expr => $expected:expr ),* } ) => {
+            #[test]
+            fn $func() {
+                $(
+                    let mut encoder = super::Encoder::new();
+                    for v in &$input {
+                        encoder.$func(*v);
+                    }
+                    assert_eq!(encoder.bytes(), $expected.to_vec());
+                )*
+            }
+        };
+        ( $func:ident { $( $input:expr => $expected:expr,) + } ) => {
+            test_values!{$func { $($input => $expected),+ }}
+        };
+    }
+
+    test_values!{write_u8 {
+        [0] => [0],
+        [0xFF] => [0xFF],
+        [0xAA, 0xBB, 0xCC] => [0xAA, 0xBB, 0xCC],
+    }}
+
+    test_values!{write_u16 {
+        [0x00FE] => [0x00, 0xFE],
+        [0xFFFF, 0xCAFE] => [0xFF, 0xFF, 0xCA, 0xFE],
+    }}
+
+    test_values!{write_u32 {
+        [0xCAFEBABE] => [0xCA, 0xFE, 0xBA, 0xBE],
+        [0x00FE00AB] => [0x00, 0xFE, 0x00, 0xAB],
+    }}
+
+    test_values!{write_u64 {
+        [0xCAFEBABE_CAFED00D] => [0xCA, 0xFE, 0xBA, 0xBE, 0xCA, 0xFE, 0xD0, 0x0D],
+    }}
+
+    test_values!{write_i8 {
+        [-1, -128] => [0xFF, 0x80],
+    }}
+
+    test_values!{write_i16 {
+        [-0x0ABB] => [0xF5, 0x45],
+    }}
+
+    test_values!{write_i32 {
+        [-0x35014542] => [0xCA, 0xFE, 0xBA, 0xBE],
+    }}
+
+    test_values!{write_i64 {
+        [-0x5AFEBABE_CAFED00D] => [0xA5, 0x01, 0x45, 0x41, 0x35, 0x01, 0x2F, 0xF3],
+    }}
+
+    test_values!{write_f32 {
+        [1.0] => [0x3F, 0x80, 0x00, 0x00],
+    }}
+
+    test_values!{write_f64 {
+        [1.0] => [0x3F, 0xF0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
+    }}
+
+    #[test]
+    fn write_str() {
+        fn encode(s: &str) -> Vec<u8> {
+            let mut encoder = super::Encoder::new();
+            encoder.write_str(s);
+            let bytes = encoder.bytes();
+            assert_eq!(bytes.len(), super::str_len(s));
+            bytes
+        }
+
+        assert_eq!(encode("Hello, world!"), b"Hello, world!".to_vec());
+        assert_eq!(encode(""), Vec::<u8>::new());
+        // the null character is encoded using 2 bytes
+        assert_eq!(encode("a\0b"), vec![0x61, 0xC0, 0x80, 0x62]);
+        // 2 and 3 byte characters
+        assert_eq!(encode("\u{E4}"), vec![0xC3, 0xA4]);
+        assert_eq!(encode("\u{7FF}"), vec![0xDF, 0xBF]);
+        assert_eq!(encode("\u{800}"), vec![0xE0, 0xA0, 0x80]);
+        assert_eq!(encode("\u{20AC}"), vec![0xE2, 0x82, 0xAC]);
+        // supplementary characters are encoded as surrogate pairs
+        assert_eq!(
+            encode("\u{1F600}"),
+            vec![0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80]
+        );
+        assert_eq!(
+            encode("\u{10FFFF}"),
+            vec![0xED, 0xAF, 0xBF, 0xED, 0xBF, 0xBF]
+        );
+    }
+
+    #[test]
+    fn write_utf8() {
+        use result::Error;
+
+        let mut encoder = super::Encoder::new();
+        encoder.write_utf8("\0\u{1F600}").unwrap();
+        assert_eq!(
+            encoder.bytes(),
+            vec![0x00, 0x08, 0xC0, 0x80, 0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80]
+        );
+
+        // the limit is reached exactly
+        let mut encoder = super::Encoder::new();
+        encoder.write_utf8(&"a".repeat(65535)).unwrap();
+        assert_eq!(encoder.len(), 65537);
+
+        // 32768 null characters take up 65536 bytes
+        let mut encoder = super::Encoder::new();
+        match encoder.write_utf8(&"\0".repeat(32768)) {
+            Err(Error::StringTooLong(65536)) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(encoder.len(), 0);
+    }
+}
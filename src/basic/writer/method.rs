@@ -0,0 +1,158 @@
+use super::*;
+
+/// Writes the `Exceptions` attribute.
+pub fn write_exceptions(encoder: &mut Encoder, exceptions: &[u16]) -> Result<()> {
+    encoder.write_len(exceptions.len())?;
+    for exception in exceptions {
+        encoder.write_u16(*exception);
+    }
+    Ok(())
+}
+
+/// Writes the `LineNumberTable` attribute.
+pub fn write_line_number_table(encoder: &mut Encoder, table: &[LineNumber]) -> Result<()> {
+    encoder.write_len(table.len())?;
+    for line_number in table {
+        encoder.write_u16(line_number.start);
+        encoder.write_u16(line_number.line_number);
+    }
+    Ok(())
+}
+
+/// Writes the `LocalVariableTable` attribute.
+pub fn write_local_variable_table(encoder: &mut Encoder, table: &[LocalVariable]) -> Result<()> {
+    encoder.write_len(table.len())?;
+    for variable in table {
+        encoder.write_u16(variable.start);
+        encoder.write_u16(variable.length);
+        encoder.write_u16(variable.name);
+        encoder.write_u16(variable.descriptor);
+        encoder.write_u16(variable.index);
+    }
+    Ok(())
+}
+
+/// Writes the `LocalVariableTypeTable` attribute.
+pub fn write_local_variable_type_table(
+    encoder: &mut Encoder,
+    table: &[LocalVariableType],
+) -> Result<()> {
+    encoder.write_len(table.len())?;
+    for variable in table {
+        encoder.write_u16(variable.start);
+        encoder.write_u16(variable.length);
+        encoder.write_u16(variable.name);
+        encoder.write_u16(variable.signature);
+        encoder.write_u16(variable.index);
+    }
+    Ok(())
+}
+
+/// Writes the `StackMapTable` attribute.
+/// The most compact form of every frame is chosen, unless it is marked as extended.
+pub fn write_stack_map_table(encoder: &mut Encoder, table: &[StackMapFrame]) -> Result<()> {
+    encoder.write_len(table.len())?;
+
+    for frame in table {
+        match *frame {
+            StackMapFrame::Same {
+                offset_delta,
+                extended,
+            } => {
+                if offset_delta < 64 && !extended {
+                    encoder.write_u8(offset_delta as u8);
+                } else {
+                    encoder.write_u8(251);
+                    encoder.write_u16(offset_delta);
+                }
+            }
+            StackMapFrame::Same1 {
+                offset_delta,
+                ref stack,
+                extended,
+            } => {
+                if offset_delta < 64 && !extended {
+                    encoder.write_u8(offset_delta as u8 + 64);
+                } else {
+                    encoder.write_u8(247);
+                    encoder.write_u16(offset_delta);
+                }
+                write_verification_type(encoder, stack);
+            }
+            StackMapFrame::Chop {
+                offset_delta,
+                count,
+            } => {
+                encoder.write_u8(251 - count);
+                encoder.write_u16(offset_delta);
+            }
+            StackMapFrame::Append {
+                offset_delta,
+                ref locals,
+            } => {
+                encoder.write_u8(251 + locals.len() as u8);
+                encoder.write_u16(offset_delta);
+                for local in locals {
+                    write_verification_type(encoder, local);
+                }
+            }
+            StackMapFrame::Full {
+                offset_delta,
+                ref locals,
+                ref stack,
+            } => {
+                encoder.write_u8(255);
+                encoder.write_u16(offset_delta);
+
+                encoder.write_len(locals.len())?;
+                for local in locals {
+                    write_verification_type(encoder, local);
+                }
+
+                encoder.write_len(stack.len())?;
+                for value in stack {
+                    write_verification_type(encoder, value);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes a verification type.
+fn write_verification_type(encoder: &mut Encoder, verification_type: &VerificationType) {
+    use self::VerificationType::*;
+
+    match *verification_type {
+        Top => encoder.write_u8(0),
+        Integer => encoder.write_u8(1),
+        Float => encoder.write_u8(2),
+        Double => encoder.write_u8(3),
+        Long => encoder.write_u8(4),
+        Null => encoder.write_u8(5),
+        UninitializedThis => encoder.write_u8(6),
+        Object(index) => {
+            encoder.write_u8(7);
+            encoder.write_u16(index);
+        }
+        Uninitialized(offset) => {
+            encoder.write_u8(8);
+            encoder.write_u16(offset);
+        }
+    }
+}
+
+/// Writes the `MethodParameters` attribute.
+pub fn write_method_parameters(encoder: &mut Encoder, params: &[MethodParameter]) -> Result<()> {
+    if params.len() > u8::MAX as usize {
+        return Err(Error::TooLarge);
+    }
+
+    encoder.write_u8(params.len() as u8);
+    for param in params {
+        encoder.write_u16(param.name);
+        encoder.write_u16(param.access_flags.bits());
+    }
+    Ok(())
+}
@@ -0,0 +1,694 @@
+mod encode;
+mod class;
+mod method;
+mod code;
+mod annotation;
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use super::constpool::*;
+use super::tree::*;
+use result::*;
+
+use self::class::*;
+use self::method::*;
+use self::code::*;
+use self::annotation::*;
+use self::encode::Encoder;
+use super::MAGIC;
+
+pub use self::code::instruction_len;
+
+/// Writes the constant pool and the class to a byte array.
+/// Every index is written as is, no validation will be done.
+/// Names of attributes (except for `Attribute::Unknown`) must already be in the pool,
+/// parsed attributes keep the index of their name, even if it is a duplicate.
+///
+/// If a parsed class has not been modified, the output equals the parsed bytes,
+/// this includes the padding of switches and the reserved operands
+/// of `invokeinterface` and `invokedynamic`, even if they are not zero.
+pub fn write(constant_pool: &Pool, class: &Class) -> Result<Vec<u8>> {
+    let mut encoder = Encoder::new();
+
+    encoder.write_bytes(MAGIC);
+
+    encoder.write_u16(class.minor_version);
+    encoder.write_u16(class.major_version);
+
+    write_constant_pool(&mut encoder, constant_pool)?;
+
+    encoder.write_u16(class.access_flags.bits());
+
+    encoder.write_u16(class.name);
+    encoder.write_u16(class.super_name);
+
+    // Write interfaces
+    encoder.write_len(class.interfaces.len())?;
+    for interface in &class.interfaces {
+        encoder.write_u16(*interface);
+    }
+
+    let mut context = Context {
+        constant_pool,
+        attribute_names: HashMap::new(),
+    };
+    write_fields(&mut encoder, &mut context, &class.fields)?;
+    write_methods(&mut encoder, &mut context, &class.methods)?;
+    write_attributes(&mut encoder, &mut context, &class.attributes)?;
+
+    Ok(encoder.bytes())
+}
+
+/// Writes the constant pool and the class to a `Write`.
+pub fn write_to<W: Write>(constant_pool: &Pool, class: &Class, out: &mut W) -> Result<()> {
+    let bytes = write(constant_pool, class)?;
+    out.write_all(&bytes).map_err(Error::IO)
+}
+
+/// The state required to write attributes.
+struct Context<'a> {
+    constant_pool: &'a Pool,
+    /// The number of attributes written by their name
+    attribute_names: HashMap<&'static str, usize>,
+}
+
+/// Writes the entire constant pool
+fn write_constant_pool(encoder: &mut Encoder, constant_pool: &Pool) -> Result<()> {
+    encoder.write_u16(constant_pool.len());
+
+    for (_, item) in constant_pool.iter() {
+        match *item {
+            Item::UTF8(ref s) => {
+                encoder.write_u8(1);
+                encoder.write_utf8(s)?;
+            }
+            Item::UTF16(ref units) => {
+                encoder.write_u8(1);
+                encoder.write_utf16(units)?;
+            }
+            Item::Integer(value) => {
+                encoder.write_u8(3);
+                encoder.write_i32(value);
+            }
+            Item::Float(value) => {
+                encoder.write_u8(4);
+                encoder.write_f32(value);
+            }
+            Item::Long(value) => {
+                encoder.write_u8(5);
+                encoder.write_i64(value);
+            }
+            Item::Double(value) => {
+                encoder.write_u8(6);
+                encoder.write_f64(value);
+            }
+            Item::Class(index) => {
+                encoder.write_u8(7);
+                encoder.write_u16(index);
+            }
+            Item::String(index) => {
+                encoder.write_u8(8);
+                encoder.write_u16(index);
+            }
+            Item::FieldRef {
+                class,
+                name_and_type,
+            } => {
+                encoder.write_u8(9);
+                encoder.write_u16(class);
+                encoder.write_u16(name_and_type);
+            }
+            Item::MethodRef {
+                class,
+                name_and_type,
+            } => {
+                encoder.write_u8(10);
+                encoder.write_u16(class);
+                encoder.write_u16(name_and_type);
+            }
+            Item::InterfaceMethodRef {
+                class,
+                name_and_type,
+            } => {
+                encoder.write_u8(11);
+                encoder.write_u16(class);
+                encoder.write_u16(name_and_type);
+            }
+            Item::NameAndType { name, desc } => {
+                encoder.write_u8(12);
+                encoder.write_u16(name);
+                encoder.write_u16(desc);
+            }
+            Item::MethodHandle { ref kind, index } => {
+                encoder.write_u8(15);
+                encoder.write_u8(match *kind {
+                    ReferenceKind::GetField => 1,
+                    ReferenceKind::GetStatic => 2,
+                    ReferenceKind::PutField => 3,
+                    ReferenceKind::PutStatic => 4,
+                    ReferenceKind::InvokeVirtual => 5,
+                    ReferenceKind::InvokeStatic => 6,
+                    ReferenceKind::InvokeSpecial => 7,
+                    ReferenceKind::NewInvokeSpecial => 8,
+                    ReferenceKind::InvokeInterface => 9,
+                });
+                encoder.write_u16(index);
+            }
+            Item::MethodType(index) => {
+                encoder.write_u8(16);
+                encoder.write_u16(index);
+            }
+            Item::Dynamic {
+                bootstrap_method_attribute,
+                name_and_type,
+            } => {
+                encoder.write_u8(17);
+                encoder.write_u16(bootstrap_method_attribute);
+                encoder.write_u16(name_and_type);
+            }
+            Item::InvokeDynamic {
+                bootstrap_method_attribute,
+                name_and_type,
+            } => {
+                encoder.write_u8(18);
+                encoder.write_u16(bootstrap_method_attribute);
+                encoder.write_u16(name_and_type);
+            }
+            Item::Module(index) => {
+                encoder.write_u8(19);
+                encoder.write_u16(index);
+            }
+            Item::Package(index) => {
+                encoder.write_u8(20);
+                encoder.write_u16(index);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes all fields and their attributes
+fn write_fields(encoder: &mut Encoder, context: &mut Context, fields: &[Field]) -> Result<()> {
+    encoder.write_len(fields.len())?;
+    for field in fields {
+        encoder.write_u16(field.access_flags.bits());
+        encoder.write_u16(field.name);
+        encoder.write_u16(field.desc);
+        write_attributes(encoder, context, &field.attributes)?;
+    }
+
+    Ok(())
+}
+
+/// Writes all methods and their attributes
+fn write_methods(encoder: &mut Encoder, context: &mut Context, methods: &[Method]) -> Result<()> {
+    encoder.write_len(methods.len())?;
+    for method in methods {
+        encoder.write_u16(method.access_flags.bits());
+        encoder.write_u16(method.name);
+        encoder.write_u16(method.desc);
+        write_attributes(encoder, context, &method.attributes)?;
+    }
+
+    Ok(())
+}
+
+/// Writes all attributes
+fn write_attributes(
+    encoder: &mut Encoder,
+    context: &mut Context,
+    attributes: &[Attribute],
+) -> Result<()> {
+    encoder.write_len(attributes.len())?;
+    for attribute in attributes {
+        // the content is written first, since we need to know its length
+        let mut attr_encoder = Encoder::new();
+
+        let name = match *attribute {
+            Attribute::AnnotationDefault(ref value) => {
+                write_element_value(&mut attr_encoder, value)?;
+                "AnnotationDefault"
+            }
+            Attribute::BootstrapMethods(ref methods) => {
+                write_bootstrap_methods(&mut attr_encoder, methods)?;
+                "BootstrapMethods"
+            }
+            Attribute::Code {
+                max_stack,
+                max_locals,
+                ref instructions,
+                ref exceptions,
+                ref attributes,
+            } => {
+                write_code(
+                    &mut attr_encoder,
+                    context,
+                    max_stack,
+                    max_locals,
+                    instructions,
+                    exceptions,
+                    attributes,
+                )?;
+                "Code"
+            }
+            Attribute::ConstantValue(index) => {
+                attr_encoder.write_u16(index);
+                "ConstantValue"
+            }
+            Attribute::Deprecated => "Deprecated",
+            Attribute::EnclosingMethod {
+                class_index,
+                method_index,
+            } => {
+                attr_encoder.write_u16(class_index);
+                attr_encoder.write_u16(method_index);
+                "EnclosingMethod"
+            }
+            Attribute::Exceptions(ref exceptions) => {
+                write_exceptions(&mut attr_encoder, exceptions)?;
+                "Exceptions"
+            }
+            Attribute::InnerClasses(ref inner_classes) => {
+                write_inner_classes(&mut attr_encoder, inner_classes)?;
+                "InnerClasses"
+            }
+            Attribute::LineNumberTable(ref table) => {
+                write_line_number_table(&mut attr_encoder, table)?;
+                "LineNumberTable"
+            }
+            Attribute::LocalVariableTable(ref table) => {
+                write_local_variable_table(&mut attr_encoder, table)?;
+                "LocalVariableTable"
+            }
+            Attribute::LocalVariableTypeTable(ref table) => {
+                write_local_variable_type_table(&mut attr_encoder, table)?;
+                "LocalVariableTypeTable"
+            }
+            Attribute::MethodParameters(ref params) => {
+                write_method_parameters(&mut attr_encoder, params)?;
+                "MethodParameters"
+            }
+            Attribute::Module {
+                name,
+                flags,
+                version,
+                ref requires,
+                ref exports,
+                ref opens,
+                ref uses,
+                ref provides,
+            } => {
+                attr_encoder.write_u16(name);
+                attr_encoder.write_u16(flags.bits());
+                attr_encoder.write_u16(version);
+                write_module(&mut attr_encoder, requires, exports, opens, uses, provides)?;
+                "Module"
+            }
+            Attribute::ModuleMainClass(index) => {
+                attr_encoder.write_u16(index);
+                "ModuleMainClass"
+            }
+            Attribute::ModulePackages(ref packages) => {
+                write_module_packages(&mut attr_encoder, packages)?;
+                "ModulePackages"
+            }
+            Attribute::NestHost(index) => {
+                attr_encoder.write_u16(index);
+                "NestHost"
+            }
+            Attribute::NestMembers(ref members) => {
+                write_nest_members(&mut attr_encoder, members)?;
+                "NestMembers"
+            }
+            Attribute::PermittedSubclasses(ref subclasses) => {
+                write_permitted_subclasses(&mut attr_encoder, subclasses)?;
+                "PermittedSubclasses"
+            }
+            Attribute::Record(ref components) => {
+                write_record(&mut attr_encoder, context, components)?;
+                "Record"
+            }
+            Attribute::RuntimeVisibleAnnotations(ref annotations) => {
+                write_annotations(&mut attr_encoder, annotations)?;
+                "RuntimeVisibleAnnotations"
+            }
+            Attribute::RuntimeInvisibleAnnotations(ref annotations) => {
+                write_annotations(&mut attr_encoder, annotations)?;
+                "RuntimeInvisibleAnnotations"
+            }
+            Attribute::RuntimeVisibleParameterAnnotations(ref annotations) => {
+                write_parameter_annotations(&mut attr_encoder, annotations)?;
+                "RuntimeVisibleParameterAnnotations"
+            }
+            Attribute::RuntimeInvisibleParameterAnnotations(ref annotations) => {
+                write_parameter_annotations(&mut attr_encoder, annotations)?;
+                "RuntimeInvisibleParameterAnnotations"
+            }
+            Attribute::RuntimeVisibleTypeAnnotations(ref annotations) => {
+                write_type_annotations(&mut attr_encoder, annotations)?;
+                "RuntimeVisibleTypeAnnotations"
+            }
+            Attribute::RuntimeInvisibleTypeAnnotations(ref annotations) => {
+                write_type_annotations(&mut attr_encoder, annotations)?;
+                "RuntimeInvisibleTypeAnnotations"
+            }
+            Attribute::Signature(index) => {
+                attr_encoder.write_u16(index);
+                "Signature"
+            }
+            Attribute::Synthetic => "Synthetic",
+            Attribute::SourceFile(index) => {
+                attr_encoder.write_u16(index);
+                "SourceFile"
+            }
+            Attribute::SourceDebugExtension(ref s) => {
+                attr_encoder.write_str(s);
+                "SourceDebugExtension"
+            }
+            Attribute::StackMapTable(ref frames) => {
+                write_stack_map_table(&mut attr_encoder, frames)?;
+                "StackMapTable"
+            }
+            Attribute::Unknown(name_index, ref bytes) => {
+                // the name index is known already, no lookup required
+                encoder.write_u16(name_index);
+                encoder.write_u32(bytes.len() as u32);
+                encoder.write_bytes(bytes);
+                continue;
+            }
+        };
+
+        let occurrence = context.attribute_names.entry(name).or_insert(0);
+        let name_index = context
+            .constant_pool
+            .attribute_name(name, *occurrence)
+            .ok_or(Error::MissingAttributeName(name))?;
+        *occurrence += 1;
+        let bytes = attr_encoder.bytes();
+
+        encoder.write_u16(name_index);
+        encoder.write_u32(bytes.len() as u32);
+        encoder.write_bytes(&bytes);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::parse;
+    use super::*;
+
+    /// Parses a class file and writes it back again,
+    /// the output must equal the input.
+    fn round_trip(bytes: &[u8]) {
+        let (pool, class) = parse(bytes).unwrap();
+        let written = write(&pool, &class).unwrap();
+        assert!(written == bytes);
+
+        let mut out = Vec::new();
+        write_to(&pool, &class, &mut out).unwrap();
+        assert!(out == bytes);
+    }
+
+    #[test]
+    fn write_resources() {
+        round_trip(include_bytes!("../../../examples/resources/AnyAnnotation.class"));
+        round_trip(include_bytes!("../../../examples/resources/CustomLinkedList.class"));
+        round_trip(include_bytes!("../../../examples/resources/CustomLinkedList$Element.class"));
+        round_trip(include_bytes!("../../../examples/resources/CustomLinkedList$Iter.class"));
+        round_trip(include_bytes!("../../../examples/resources/Fibonacci.class"));
+        round_trip(include_bytes!("../../../examples/resources/Frames.class"));
+        round_trip(include_bytes!("../../../examples/resources/FizzBuzzStream.class"));
+        round_trip(include_bytes!("../../../examples/resources/Nestmates.class"));
+        round_trip(include_bytes!("../../../examples/resources/Nestmates$Inner.class"));
+        round_trip(include_bytes!("../../../examples/resources/Records.class"));
+        round_trip(include_bytes!("../../../examples/resources/Records$Component.class"));
+        round_trip(include_bytes!("../../../examples/resources/Sealed.class"));
+        round_trip(include_bytes!("../../../examples/resources/Sealed$Leaf.class"));
+        round_trip(include_bytes!("../../../examples/resources/Sealed$Node.class"));
+        round_trip(include_bytes!("../../../examples/resources/RoundTrip.class"));
+        round_trip(include_bytes!("../../../examples/resources/Surrogates.class"));
+    }
+
+    #[test]
+    fn write_non_canonical() {
+        use std::collections::BTreeMap;
+
+        let mut pool = Pool::new();
+        let name = pool.push(Item::UTF8("Test".to_owned())).unwrap();
+        let class_name = pool.push(Item::Class(name)).unwrap();
+        let duplicate = pool.push_duplicate(Item::Class(name)).unwrap();
+        let int = pool.push(Item::Integer(5)).unwrap();
+        let long = pool.push(Item::Long(5)).unwrap();
+        let method_name = pool.push(Item::UTF8("test".to_owned())).unwrap();
+        let method_desc = pool.push(Item::UTF8("()V".to_owned())).unwrap();
+        pool.push(Item::UTF8("Code".to_owned())).unwrap();
+
+        let mut lookup_offsets = BTreeMap::new();
+        lookup_offsets.insert(1, -37);
+
+        let mut instructions = Instructions::new();
+        instructions.insert(0, Instruction::LDCW(int));
+        instructions.insert(3, Instruction::LDC2W(long));
+        instructions.insert(6, Instruction::Wide(Box::new(Instruction::ILoad(1))));
+        instructions.insert(10, Instruction::Wide(Box::new(Instruction::IInc(1, -1))));
+        instructions.insert(
+            16,
+            Instruction::TableSwitch {
+                default: 45,
+                low: 0,
+                high: 0,
+                offsets: vec![45],
+                padding: [0x01, 0x02, 0x03],
+            },
+        );
+        instructions.insert(36, Instruction::NOP);
+        instructions.insert(
+            37,
+            Instruction::LookupSwitch {
+                default: 24,
+                offsets: lookup_offsets,
+                padding: [0; 3],
+            },
+        );
+        instructions.insert(56, Instruction::GoToW(5));
+        instructions.insert(61, Instruction::InvokeInterface(int, 1, 7));
+        instructions.insert(66, Instruction::InvokeDynamic(int, 0x0102));
+        instructions.insert(71, Instruction::Return);
+
+        let class = Class {
+            minor_version: 0,
+            major_version: 49,
+            access_flags: ClassAccessFlags::PUBLIC,
+            name: duplicate,
+            super_name: class_name,
+            interfaces: Vec::new(),
+            fields: Vec::new(),
+            methods: vec![Method {
+                access_flags: MethodAccessFlags::STATIC,
+                name: method_name,
+                desc: method_desc,
+                attributes: vec![Attribute::Code {
+                    max_stack: 4,
+                    max_locals: 2,
+                    instructions,
+                    exceptions: Vec::new(),
+                    attributes: Vec::new(),
+                }],
+            }],
+            attributes: Vec::new(),
+        };
+
+        let bytes = write(&pool, &class).unwrap();
+        #[rustfmt::skip]
+        let code = [
+            0x13, 0x00, int as u8,                  // ldc_w
+            0x14, 0x00, long as u8,                 // ldc2_w
+            0xC4, 0x15, 0x00, 0x01,                 // wide iload
+            0xC4, 0x84, 0x00, 0x01, 0xFF, 0xFF,     // wide iinc
+            0xAA, 0x01, 0x02, 0x03,                 // tableswitch + padding
+            0x00, 0x00, 0x00, 0x2D,                 // default
+            0x00, 0x00, 0x00, 0x00,                 // low
+            0x00, 0x00, 0x00, 0x00,                 // high
+            0x00, 0x00, 0x00, 0x2D,                 // offsets
+            0x00,                                   // nop
+            0xAB, 0x00, 0x00,                       // lookupswitch + padding
+            0x00, 0x00, 0x00, 0x18,                 // default
+            0x00, 0x00, 0x00, 0x01,                 // count
+            0x00, 0x00, 0x00, 0x01,                 // key
+            0xFF, 0xFF, 0xFF, 0xDB,                 // offset
+            0xC8, 0x00, 0x00, 0x00, 0x05,           // goto_w
+            0xB9, 0x00, int as u8, 0x01, 0x07,      // invokeinterface
+            0xBA, 0x00, int as u8, 0x01, 0x02,      // invokedynamic
+            0xB1,                                   // return
+        ];
+        assert!(bytes.windows(code.len()).any(|window| window == &code[..]));
+
+        // parsing and writing again must result in the same bytes
+        let (parsed_pool, parsed_class) = parse(&bytes).unwrap();
+        assert_eq!(parsed_pool.len(), pool.len());
+        assert_eq!(parsed_class.name, duplicate);
+        assert_eq!(parsed_pool.get(duplicate).unwrap(), &Item::Class(name));
+        round_trip(&bytes);
+    }
+
+    #[test]
+    fn write_ldc_as_given() {
+        let mut pool = Pool::new();
+        let name = pool.push(Item::UTF8("Test".to_owned())).unwrap();
+        let name = pool.push(Item::Class(name)).unwrap();
+        let long = pool.push(Item::Long(5)).unwrap();
+        let method_name = pool.push(Item::UTF8("test".to_owned())).unwrap();
+        let method_desc = pool.push(Item::UTF8("()V".to_owned())).unwrap();
+        pool.push(Item::UTF8("Code".to_owned())).unwrap();
+
+        // an invalid ldc of a long and one of a missing entry are written as they are
+        let mut instructions = Instructions::new();
+        instructions.insert(0, Instruction::LDC(long));
+        instructions.insert(2, Instruction::Pop2);
+        instructions.insert(3, Instruction::LDC(200));
+        instructions.insert(5, Instruction::Return);
+
+        let class = Class {
+            minor_version: 0,
+            major_version: 49,
+            access_flags: ClassAccessFlags::PUBLIC,
+            name,
+            super_name: 0,
+            interfaces: Vec::new(),
+            fields: Vec::new(),
+            methods: vec![Method {
+                access_flags: MethodAccessFlags::STATIC,
+                name: method_name,
+                desc: method_desc,
+                attributes: vec![Attribute::Code {
+                    max_stack: 2,
+                    max_locals: 0,
+                    instructions,
+                    exceptions: Vec::new(),
+                    attributes: Vec::new(),
+                }],
+            }],
+            attributes: Vec::new(),
+        };
+
+        let bytes = write(&pool, &class).unwrap();
+        let code = [0x12, long as u8, 0x58, 0x12, 200, 0xB1];
+        assert!(bytes.windows(code.len()).any(|window| window == &code[..]));
+        round_trip(&bytes);
+    }
+
+    #[test]
+    fn unknown_flags() {
+        let bytes = include_bytes!("../../../examples/resources/Fibonacci.class");
+        let (pool, mut class) = parse(bytes).unwrap();
+        // neither bit is defined for classes and methods
+        class.access_flags = ClassAccessFlags::from_bits_retain(class.access_flags.bits() | 0x0800);
+        let method = &mut class.methods[0];
+        method.access_flags =
+            MethodAccessFlags::from_bits_retain(method.access_flags.bits() | 0x0200);
+
+        let bytes = write(&pool, &class).unwrap();
+        let (_, parsed) = parse(&bytes).unwrap();
+        assert_eq!(parsed.access_flags.bits(), class.access_flags.bits());
+        assert_eq!(parsed.methods[0].access_flags.bits() & 0x0200, 0x0200);
+        round_trip(&bytes);
+    }
+
+    #[test]
+    fn duplicate_attribute_names() {
+        let mut pool = Pool::new();
+        let name = pool.push(Item::UTF8("Test".to_owned())).unwrap();
+        let name = pool.push(Item::Class(name)).unwrap();
+        let method_name = pool.push(Item::UTF8("test".to_owned())).unwrap();
+        let method_desc = pool.push(Item::UTF8("()V".to_owned())).unwrap();
+        pool.push(Item::UTF8("Code".to_owned())).unwrap();
+        pool.push(Item::UTF8("Deprecated".to_owned())).unwrap();
+        let duplicate_code = pool.push_duplicate(Item::UTF8("Code".to_owned())).unwrap();
+        let duplicate_deprecated = pool
+            .push_duplicate(Item::UTF8("Deprecated".to_owned()))
+            .unwrap();
+
+        // max stack, max locals, code length, return, no exceptions and no attributes
+        let code = vec![0, 0, 0, 0, 0, 0, 0, 1, 0xB1, 0, 0, 0, 0];
+        let class = Class {
+            minor_version: 0,
+            major_version: 49,
+            access_flags: ClassAccessFlags::PUBLIC,
+            name,
+            super_name: 0,
+            interfaces: Vec::new(),
+            fields: Vec::new(),
+            methods: vec![Method {
+                access_flags: MethodAccessFlags::STATIC,
+                name: method_name,
+                desc: method_desc,
+                attributes: vec![Attribute::Unknown(duplicate_code, code)],
+            }],
+            attributes: vec![
+                Attribute::Deprecated,
+                Attribute::Unknown(duplicate_deprecated, Vec::new()),
+            ],
+        };
+
+        // the attributes are parsed and keep the duplicates they reference
+        let bytes = write(&pool, &class).unwrap();
+        let (pool, mut parsed) = parse(&bytes).unwrap();
+        match parsed.methods[0].attributes[..] {
+            [Attribute::Code { .. }] => {}
+            ref other => panic!("unexpected attributes: {:?}", other),
+        }
+        match parsed.attributes[..] {
+            [Attribute::Deprecated, Attribute::Deprecated] => {}
+            ref other => panic!("unexpected attributes: {:?}", other),
+        }
+        round_trip(&bytes);
+
+        // a modified class still names every attribute
+        parsed.attributes[0] = Attribute::Synthetic;
+        parsed.attributes.push(Attribute::Deprecated);
+        let mut pool = pool;
+        pool.push(Item::UTF8("Synthetic".to_owned())).unwrap();
+        let bytes = write(&pool, &parsed).unwrap();
+        let (_, parsed) = parse(&bytes).unwrap();
+        match parsed.attributes[..] {
+            [Attribute::Synthetic, Attribute::Deprecated, Attribute::Deprecated] => {}
+            ref other => panic!("unexpected attributes: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn missing_attribute_name() {
+        let mut pool = Pool::new();
+        let name = pool.push(Item::UTF8("Test".to_owned())).unwrap();
+        let name = pool.push(Item::Class(name)).unwrap();
+        let class = Class {
+            minor_version: 0,
+            major_version: 52,
+            access_flags: ClassAccessFlags::PUBLIC,
+            name,
+            super_name: 0,
+            interfaces: Vec::new(),
+            fields: Vec::new(),
+            methods: Vec::new(),
+            attributes: vec![Attribute::Deprecated],
+        };
+
+        match write(&pool, &class) {
+            Err(Error::MissingAttributeName("Deprecated")) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        pool.push(Item::UTF8("Deprecated".to_owned())).unwrap();
+        let bytes = write(&pool, &class).unwrap();
+        let (_, parsed) = parse(&bytes).unwrap();
+        assert_eq!(parsed.name, name);
+        match parsed.attributes[..] {
+            [Attribute::Deprecated] => {}
+            ref other => panic!("unexpected attributes: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! Class paths reading classes from jars and jmod files.
+
+use super::{class_name, ClassPath};
+use archive::{JarReader, JmodReader, VersionedJar, CLASSES_SECTION};
+use result::*;
+
+/// Classes are never found in this directory of a jar
+const META_INF: &str = "META-INF/";
+
+impl ClassPath for JarReader {
+    fn find_class(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        if name.starts_with(META_INF) {
+            return Ok(None);
+        }
+        self.archive().read_by_name(&format!("{}.class", name))
+    }
+
+    fn class_names(&self) -> Result<Vec<String>> {
+        let names = self.archive()
+            .entries()
+            .iter()
+            .map(|entry| entry.name.as_str());
+        Ok(jar_class_names(names))
+    }
+}
+
+/// The classes of a multi-release jar as seen by the release of the view.
+impl<'a> ClassPath for VersionedJar<'a> {
+    fn find_class(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        if name.starts_with(META_INF) {
+            return Ok(None);
+        }
+        self.read(&format!("{}.class", name))
+    }
+
+    fn class_names(&self) -> Result<Vec<String>> {
+        Ok(jar_class_names(self.names().into_iter()))
+    }
+}
+
+impl ClassPath for JmodReader {
+    fn find_class(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        self.archive()
+            .read_by_name(&format!("{}{}.class", CLASSES_SECTION, name))
+    }
+
+    fn class_names(&self) -> Result<Vec<String>> {
+        let mut names: Vec<String> = self.archive()
+            .entries()
+            .iter()
+            .filter_map(|entry| entry.name.strip_prefix(CLASSES_SECTION))
+            .filter_map(class_name)
+            .map(str::to_owned)
+            .collect();
+        names.sort();
+        names.dedup();
+        Ok(names)
+    }
+}
+
+/// Returns the names of the classes of a jar given the names of its entries
+fn jar_class_names<'a, I: Iterator<Item = &'a str>>(entries: I) -> Vec<String> {
+    let mut names: Vec<String> = entries
+        .filter(|entry| !entry.starts_with(META_INF))
+        .filter_map(class_name)
+        .map(str::to_owned)
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use archive::stored_zip;
+
+    #[test]
+    fn jar() {
+        let jar = JarReader::new(stored_zip(&[
+            ("META-INF/MANIFEST.MF", b"Multi-Release: true\r\n\r\n"),
+            ("META-INF/versions/11/a/B.class", b"11"),
+            ("a/B.class", b"base"),
+            ("a/", b""),
+            ("a/data.txt", b"data"),
+        ])).unwrap();
+        assert_eq!(jar.class_names().unwrap(), vec!["a/B"]);
+        assert_eq!(jar.find_class("a/B").unwrap().unwrap(), b"base");
+        assert!(jar.find_class("a/data").unwrap().is_none());
+        assert!(
+            jar.find_class("META-INF/versions/11/a/B")
+                .unwrap()
+                .is_none()
+        );
+
+        let view = jar.versioned(11).unwrap();
+        assert_eq!(view.class_names().unwrap(), vec!["a/B"]);
+        assert_eq!(view.find_class("a/B").unwrap().unwrap(), b"11");
+    }
+}
@@ -0,0 +1,107 @@
+//! Class paths reading classes from a directory tree, like `target/classes`.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use super::{class_name, ClassPath};
+use result::*;
+
+/// A directory containing classes, where the class `a/b/C`
+/// is stored in the file `a/b/C.class` below the root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryClassPath {
+    root: PathBuf,
+}
+
+impl DirectoryClassPath {
+    /// Creates a class path reading classes below a root directory
+    pub fn new<P: Into<PathBuf>>(root: P) -> DirectoryClassPath {
+        DirectoryClassPath { root: root.into() }
+    }
+
+    /// Returns the root directory
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+impl ClassPath for DirectoryClassPath {
+    fn find_class(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        // a name must not leave the root directory
+        if name
+            .split('/')
+            .any(|part| part.is_empty() || part == "." || part == "..")
+        {
+            return Ok(None);
+        }
+
+        // the name may contain dots, so the extension is appended instead of set
+        let path = self.root.join(format!("{}.class", name));
+        match fs::read(&path) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(ref err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(Error::IO(err)),
+        }
+    }
+
+    fn class_names(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        walk(&self.root, "", &mut names)?;
+        names.sort();
+        Ok(names)
+    }
+}
+
+/// Collects the name of every class below a directory,
+/// whose path relative to the root is `prefix`
+fn walk(dir: &Path, prefix: &str, names: &mut Vec<String>) -> Result<()> {
+    for entry in fs::read_dir(dir).map_err(Error::IO)? {
+        let entry = entry.map_err(Error::IO)?;
+        // files, which cannot be named in a class path, are skipped
+        let file_name = match entry.file_name().into_string() {
+            Ok(file_name) => file_name,
+            Err(_) => continue,
+        };
+        let path = format!("{}{}", prefix, file_name);
+
+        if entry.file_type().map_err(Error::IO)?.is_dir() {
+            walk(&entry.path(), &format!("{}/", path), names)?;
+        } else if let Some(name) = class_name(&path) {
+            names.push(name.to_owned());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::env;
+    use std::process;
+
+    #[test]
+    fn classes() {
+        let root = env::temp_dir().join(format!("jbcrs-classpath-{}", process::id()));
+        fs::create_dir_all(root.join("a/b")).unwrap();
+        fs::write(root.join("Fibonacci.class"), b"fibonacci").unwrap();
+        fs::write(root.join("a/b/C.class"), b"c").unwrap();
+        fs::write(root.join("a/b/C.d.class"), b"c.d").unwrap();
+        fs::write(root.join("a/b/data.txt"), b"data").unwrap();
+        fs::write(root.join("a/.class"), b"").unwrap();
+
+        let class_path = DirectoryClassPath::new(&root);
+        let names = class_path.class_names();
+        let found = class_path.find_class("a/b/C");
+        let dotted = class_path.find_class("a/b/C.d");
+        let missing = class_path.find_class("a/b/D");
+        let outside = class_path.find_class("a/../Fibonacci");
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(names.unwrap(), vec!["Fibonacci", "a/b/C", "a/b/C.d"]);
+        assert_eq!(found.unwrap().unwrap(), b"c");
+        assert_eq!(dotted.unwrap().unwrap(), b"c.d");
+        assert!(missing.unwrap().is_none());
+        assert!(outside.unwrap().is_none());
+    }
+}
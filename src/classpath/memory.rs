@@ -0,0 +1,28 @@
+//! Class paths held in memory, mapping the name of a class to its bytes.
+
+use std::collections::{BTreeMap, HashMap};
+
+use super::ClassPath;
+use result::*;
+
+impl ClassPath for HashMap<String, Vec<u8>> {
+    fn find_class(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.get(name).cloned())
+    }
+
+    fn class_names(&self) -> Result<Vec<String>> {
+        let mut names: Vec<String> = self.keys().cloned().collect();
+        names.sort();
+        Ok(names)
+    }
+}
+
+impl ClassPath for BTreeMap<String, Vec<u8>> {
+    fn find_class(&self, name: &str) -> Result<Option<Vec<u8>>> {
+        Ok(self.get(name).cloned())
+    }
+
+    fn class_names(&self) -> Result<Vec<String>> {
+        Ok(self.keys().cloned().collect())
+    }
+}
@@ -0,0 +1,35 @@
+//! The classpath module finds classes by their name,
+//! in directories, archives or memory.
+//!
+//! A `ClassRepository` searches multiple class paths in order
+//! and caches the classes parsed.
+
+mod archive;
+mod directory;
+mod memory;
+mod repository;
+
+pub use self::directory::*;
+pub use self::repository::*;
+
+use result::*;
+
+/// A source of classes, like a directory or a jar.
+/// All names are internal names (e.g. `java/lang/String`).
+pub trait ClassPath {
+    /// Returns the bytes of a class or `None` if the class is not part of this class path.
+    fn find_class(&self, name: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Returns the name of every class of this class path, in ascending order.
+    fn class_names(&self) -> Result<Vec<String>>;
+}
+
+/// Returns the name of a class, if a path (with `/` as separator) names a class file.
+fn class_name(path: &str) -> Option<&str> {
+    let name = path.strip_suffix(".class")?;
+    if name.is_empty() || name.ends_with('/') {
+        None
+    } else {
+        Some(name)
+    }
+}
@@ -0,0 +1,259 @@
+//! The repository module searches classes in multiple class paths
+//! and caches them once parsed.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::rc::Rc;
+
+use super::ClassPath;
+use basic::{self, Class, ClassAccessFlags, ClassHierarchy, ClassResolver, Pool};
+use result::*;
+
+/// A parsed class, which is shared by a repository and everyone requesting it
+pub type SharedClass = Rc<(Pool, Class)>;
+
+/// A class defined by more than one class path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Duplicate {
+    /// The name of the class in internal form
+    pub name: String,
+    /// The indices of the class paths defining the class, in ascending order.
+    /// Only the class of the first one is ever used.
+    pub class_paths: Vec<usize>,
+}
+
+/// Finds classes by their internal name in multiple class paths,
+/// the first class path containing a class wins.
+///
+/// Classes are parsed lazily, when they are requested for the first time,
+/// and are shared afterwards.
+/// Classes, which are not found, are remembered as well.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use jbcrs::archive::JarReader;
+/// use jbcrs::classpath::ClassRepository;
+///
+/// let mut memory = BTreeMap::new();
+/// memory.insert(
+///     "Fibonacci".to_owned(),
+///     include_bytes!("../../examples/resources/Fibonacci.class").to_vec(),
+/// );
+///
+/// let mut repository = ClassRepository::new();
+/// repository.push(memory);
+/// repository.push(
+///     JarReader::new(include_bytes!("../../examples/resources/Classes.jar").to_vec()).unwrap(),
+/// );
+///
+/// let class = repository.get("Sealed$Leaf").unwrap();
+/// let (ref pool, ref class) = *class;
+/// assert_eq!(pool.get_class_name(class.super_name).unwrap(), "Sealed");
+/// assert!(repository.find("java/lang/Object").unwrap().is_none());
+///
+/// // Fibonacci is part of both class paths
+/// let duplicates = repository.duplicates().unwrap();
+/// assert_eq!(duplicates[0].name, "Fibonacci");
+/// assert_eq!(duplicates[0].class_paths, vec![0, 1]);
+/// ```
+pub struct ClassRepository<'a> {
+    class_paths: Vec<Box<dyn ClassPath + 'a>>,
+    cache: RefCell<HashMap<String, Option<SharedClass>>>,
+}
+
+impl<'a> ClassRepository<'a> {
+    /// Creates a repository without any class path
+    pub fn new() -> ClassRepository<'a> {
+        ClassRepository {
+            class_paths: Vec::new(),
+            cache: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Appends a class path, which is searched after all class paths pushed before
+    pub fn push<C: ClassPath + 'a>(&mut self, class_path: C) {
+        // classes found before cannot be shadowed, but missing ones may be found now
+        self.cache.get_mut().retain(|_, class| class.is_some());
+        self.class_paths.push(Box::new(class_path));
+    }
+
+    /// Returns the number of class paths
+    pub fn len(&self) -> usize {
+        self.class_paths.len()
+    }
+
+    /// Returns true if there are no class paths
+    pub fn is_empty(&self) -> bool {
+        self.class_paths.is_empty()
+    }
+
+    /// Finds and parses a class given in internal form, like `java/lang/Object`.
+    /// `None` is returned, if no class path contains the class.
+    ///
+    /// `Error::WrongClassName` is returned, if the class found declares another name.
+    /// Errors are not cached, so the class is read again on the next request.
+    pub fn find(&self, name: &str) -> Result<Option<SharedClass>> {
+        if let Some(class) = self.cache.borrow().get(name) {
+            return Ok(class.clone());
+        }
+
+        let mut found = None;
+        for class_path in &self.class_paths {
+            if let Some(bytes) = class_path.find_class(name)? {
+                let (pool, class) = basic::parse(&bytes)?;
+                let declared = pool.get_class_name(class.name)?;
+                if declared != name {
+                    return Err(Error::WrongClassName {
+                        expected: name.to_owned(),
+                        found: declared,
+                    });
+                }
+                found = Some(Rc::new((pool, class)));
+                break;
+            }
+        }
+
+        self.cache
+            .borrow_mut()
+            .insert(name.to_owned(), found.clone());
+        Ok(found)
+    }
+
+    /// Finds and parses a class just like `find`,
+    /// but `Error::ClassNotFound` is returned, if no class path contains the class
+    pub fn get(&self, name: &str) -> Result<SharedClass> {
+        self.find(name)?
+            .ok_or_else(|| Error::ClassNotFound(name.to_owned()))
+    }
+
+    /// Returns the name of every class of all class paths, in ascending order
+    pub fn class_names(&self) -> Result<Vec<String>> {
+        let mut names = BTreeSet::new();
+        for class_path in &self.class_paths {
+            names.extend(class_path.class_names()?);
+        }
+        Ok(names.into_iter().collect())
+    }
+
+    /// Reads every class of all class paths and creates their hierarchy
+    pub fn hierarchy(&self) -> Result<ClassHierarchy> {
+        let mut hierarchy = ClassHierarchy::default();
+        for name in self.class_names()? {
+            let class = self.get(&name)?;
+            let (ref pool, ref class) = *class;
+            hierarchy.push(pool, class)?;
+        }
+        Ok(hierarchy)
+    }
+
+    /// Returns every class defined by more than one class path, ordered by name.
+    pub fn duplicates(&self) -> Result<Vec<Duplicate>> {
+        let mut class_paths: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (index, class_path) in self.class_paths.iter().enumerate() {
+            for name in class_path.class_names()? {
+                class_paths.entry(name).or_default().push(index);
+            }
+        }
+
+        Ok(class_paths
+            .into_iter()
+            .filter(|(_, class_paths)| class_paths.len() > 1)
+            .map(|(name, class_paths)| Duplicate { name, class_paths })
+            .collect())
+    }
+}
+
+impl<'a> Default for ClassRepository<'a> {
+    fn default() -> ClassRepository<'a> {
+        ClassRepository::new()
+    }
+}
+
+/// Resolves classes for `basic::compute_frames`,
+/// `Error::ClassNotFound` is returned for any class not found.
+impl<'a> ClassResolver for ClassRepository<'a> {
+    fn super_class(&self, name: &str) -> Result<Option<String>> {
+        let class = self.get(name)?;
+        let (ref pool, ref class) = *class;
+        if class.super_name == 0 {
+            Ok(None)
+        } else {
+            pool.get_class_name(class.super_name).map(Some)
+        }
+    }
+
+    fn is_interface(&self, name: &str) -> Result<bool> {
+        let class = self.get(name)?;
+        Ok(class.1.access_flags.contains(ClassAccessFlags::INTERFACE))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn class(name: &str) -> (String, Vec<u8>) {
+        let bytes: &[u8] = match name {
+            "Fibonacci" => include_bytes!("../../examples/resources/Fibonacci.class"),
+            "Sealed" => include_bytes!("../../examples/resources/Sealed.class"),
+            "Sealed$Leaf" => include_bytes!("../../examples/resources/Sealed$Leaf.class"),
+            _ => unreachable!(),
+        };
+        (name.to_owned(), bytes.to_vec())
+    }
+
+    #[test]
+    fn find() {
+        let first: HashMap<String, Vec<u8>> =
+            vec![class("Fibonacci"), class("Sealed$Leaf")].into_iter().collect();
+        let mut repository = ClassRepository::new();
+        repository.push(first);
+        assert_eq!(repository.len(), 1);
+
+        let leaf = repository.get("Sealed$Leaf").unwrap();
+        assert!(Rc::ptr_eq(&leaf, &repository.get("Sealed$Leaf").unwrap()));
+        assert_eq!(
+            repository.super_class("Sealed$Leaf").unwrap(),
+            Some("Sealed".to_owned())
+        );
+        assert!(!repository.is_interface("Sealed$Leaf").unwrap());
+
+        // not found is cached, until another class path is pushed
+        assert!(repository.find("Sealed").unwrap().is_none());
+        match repository.is_interface("Sealed") {
+            Err(Error::ClassNotFound(ref name)) if name == "Sealed" => {}
+            _ => panic!("Sealed should not be found"),
+        }
+
+        let mut second: BTreeMap<String, Vec<u8>> =
+            vec![class("Fibonacci"), class("Sealed")].into_iter().collect();
+        let (_, bytes) = class("Fibonacci");
+        second.insert("Renamed".to_owned(), bytes);
+        repository.push(second);
+
+        assert!(repository.find("Sealed").unwrap().is_some());
+        assert!(repository.hierarchy().is_err());
+        assert!(Rc::ptr_eq(&leaf, &repository.get("Sealed$Leaf").unwrap()));
+        match repository.find("Renamed") {
+            Err(Error::WrongClassName {
+                ref expected,
+                ref found,
+            }) if expected == "Renamed" && found == "Fibonacci" => {}
+            _ => panic!("Renamed should declare the wrong name"),
+        }
+
+        assert_eq!(
+            repository.class_names().unwrap(),
+            vec!["Fibonacci", "Renamed", "Sealed", "Sealed$Leaf"]
+        );
+        assert_eq!(
+            repository.duplicates().unwrap(),
+            vec![Duplicate {
+                name: "Fibonacci".to_owned(),
+                class_paths: vec![0, 1],
+            }]
+        );
+    }
+}
@@ -4,10 +4,15 @@ extern crate byteorder;
 #[macro_use]
 extern crate yade;
 
+pub mod advanced;
+pub mod archive;
 pub mod basic;
+pub mod classpath;
 
 mod result;
+mod source;
 mod types;
 
 pub use result::*;
+pub use source::*;
 pub use types::*;
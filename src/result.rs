@@ -1,5 +1,7 @@
 use std::{io, result};
 
+use basic::ResolutionError;
+
 #[derive(Debug, YadeError)]
 pub enum Error {
     /// Just an I/O error
@@ -23,6 +25,9 @@ pub enum Error {
     /// Not a valid descriptor
     InvalidDescriptor { desc: String, at: usize },
 
+    /// Not a valid generic signature
+    InvalidSignature { signature: String, at: usize },
+
     /// Invalid instruction, (e.g. unknown op code)
     InvalidInstruction { op_code: u8, at: u32 },
 
@@ -40,6 +45,62 @@ pub enum Error {
 
     /// Invalid type path element kind of annotation
     InvalidTypePath,
+
+    /// A table, the code or the constant pool is too large to be encoded
+    TooLarge,
+
+    /// A string takes up more than 65535 bytes encoded as modified utf-8,
+    /// the actual amount of bytes is given
+    StringTooLong(usize),
+
+    /// The offset of an instruction does not match its location in the encoded code,
+    /// or an offset does not point to the start of an instruction
+    InvalidOffset(u32),
+
+    /// The name of an attribute could not be found in the constant pool,
+    /// it has to be pushed first
+    MissingAttributeName(&'static str),
+
+    /// The attribute is not of the expected kind
+    UnexpectedAttribute,
+
+    /// A label has not been placed in the code or has been placed more than once
+    InvalidLabel,
+
+    /// An `advanced::Instruction::Simple` holds an instruction (given by its mnemonic),
+    /// which references the code or the constant pool and has its own variant
+    UnexpectedInstruction(&'static str),
+
+    /// The operand stack underflows at an offset,
+    /// or its height differs between two paths leading to it
+    InvalidStack(u32),
+
+    /// An instruction cannot be reached, so no frame can be computed for it
+    UnreachableCode(u32),
+
+    /// A frame is required in front of the instruction at an offset,
+    /// but cannot be computed without the method declaring the code
+    MissingFrame(u32),
+
+    /// A class could not be found
+    ClassNotFound(String),
+
+    /// A class has been found by a name, but declares another name
+    WrongClassName { expected: String, found: String },
+
+    /// A reference to a field or a method cannot be resolved,
+    /// or no method can be selected for a receiver
+    Resolution(ResolutionError),
+
+    /// The text given to the assembler is malformed at a line (starting at 1)
+    InvalidAssembly { line: usize, message: String },
+
+    /// A zip archive (like a jar) or one of its entries is malformed,
+    /// the reason is given
+    InvalidArchive(&'static str),
+
+    /// An entry of a zip archive is compressed with a method other than stored or deflated
+    UnsupportedCompression(u16),
 }
 
 pub type Result<T> = result::Result<T, Error>;
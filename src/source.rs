@@ -0,0 +1,413 @@
+//! Renders descriptors and generic signatures the way they are written in java source code.
+
+use types::*;
+
+/// How class names are rendered.
+///
+/// The default renders fully qualified names and keeps the `$` of nested classes,
+/// like `java.util.Map$Entry`.
+/// An inner class of a parameterized class is always separated by a `.`,
+/// like `java.util.Map<K, V>.Entry`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SourceStyle {
+    /// Omit the package of a class, `java.lang.String` becomes `String`.
+    pub simple_names: bool,
+    /// Separate nested classes with a `.` instead of a `$`,
+    /// `java.util.Map$Entry` becomes `java.util.Map.Entry`.
+    /// Only a `$` followed by the start of an identifier is replaced,
+    /// so local and anonymous classes like `Outer$1` keep their `$`.
+    pub nested_dots: bool,
+}
+
+impl SourceStyle {
+    /// Renders a class name given in internal form.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jbcrs::SourceStyle;
+    ///
+    /// let style = SourceStyle::default();
+    /// assert_eq!(style.class_name("java/util/Map$Entry"), "java.util.Map$Entry");
+    ///
+    /// let style = SourceStyle {
+    ///     simple_names: true,
+    ///     nested_dots: true,
+    /// };
+    /// assert_eq!(style.class_name("java/util/Map$Entry"), "Map.Entry");
+    /// ```
+    pub fn class_name(&self, name: &str) -> String {
+        let name = if self.simple_names {
+            match name.rfind('/') {
+                Some(i) => &name[i + 1..],
+                None => name,
+            }
+        } else {
+            name
+        };
+
+        let name = name.replace('/', ".");
+        if self.nested_dots {
+            replace_nested_separators(&name)
+        } else {
+            name
+        }
+    }
+
+    /// The separator between an outer and a nested class.
+    fn nested_separator(&self) -> char {
+        if self.nested_dots {
+            '.'
+        } else {
+            '$'
+        }
+    }
+}
+
+/// Replaces every `$` separating a nested class from its outer class by a `.`.
+/// A `$` is considered a separator, if it follows a character other than `$`
+/// and is followed by a letter or `_`, which may start the name of a member class.
+fn replace_nested_separators(name: &str) -> String {
+    let mut replaced = String::with_capacity(name.len());
+    let mut chars = name.chars().peekable();
+    let mut previous = None;
+    while let Some(c) = chars.next() {
+        let separator = c == '$'
+            && previous.is_some_and(|previous| previous != '$' && previous != '.')
+            && chars
+                .peek()
+                .is_some_and(|&next| next.is_alphabetic() || next == '_');
+        replaced.push(if separator { '.' } else { c });
+        previous = Some(c);
+    }
+    replaced
+}
+
+/// A type, which can be rendered as java source code.
+pub trait ToSource {
+    /// Appends the source representation to `out`.
+    fn write_source(&self, out: &mut String, style: &SourceStyle);
+
+    /// Returns the source representation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jbcrs::{SourceStyle, ToSource, TypeDescriptor};
+    ///
+    /// let desc: TypeDescriptor = "[[Ljava/lang/String;".parse().unwrap();
+    /// assert_eq!(desc.to_source(&SourceStyle::default()), "java.lang.String[][]");
+    /// ```
+    fn to_source(&self, style: &SourceStyle) -> String {
+        let mut out = String::new();
+        self.write_source(&mut out, style);
+        out
+    }
+}
+
+impl ToSource for Type {
+    fn write_source(&self, out: &mut String, style: &SourceStyle) {
+        match *self {
+            Type::Boolean => out.push_str("boolean"),
+            Type::Byte => out.push_str("byte"),
+            Type::Short => out.push_str("short"),
+            Type::Int => out.push_str("int"),
+            Type::Long => out.push_str("long"),
+            Type::Float => out.push_str("float"),
+            Type::Double => out.push_str("double"),
+            Type::Char => out.push_str("char"),
+            Type::Reference(ref name) => out.push_str(&style.class_name(name)),
+        }
+    }
+}
+
+impl ToSource for TypeDescriptor {
+    fn write_source(&self, out: &mut String, style: &SourceStyle) {
+        self.base_type.write_source(out, style);
+        for _ in 0..self.dimensions {
+            out.push_str("[]");
+        }
+    }
+}
+
+impl ToSource for TypeSignature {
+    fn write_source(&self, out: &mut String, style: &SourceStyle) {
+        match *self {
+            TypeSignature::Base(ref base_type) => base_type.write_source(out, style),
+            TypeSignature::Class(ref class) => class.write_source(out, style),
+            TypeSignature::TypeVariable(ref name) => out.push_str(name),
+            TypeSignature::Array(ref component) => {
+                component.write_source(out, style);
+                out.push_str("[]");
+            }
+        }
+    }
+}
+
+impl ToSource for ClassTypeSignature {
+    /// Renders the class and its inner classes,
+    /// `java.util.Map<K, V>$Entry` would be no valid java type.
+    fn write_source(&self, out: &mut String, style: &SourceStyle) {
+        out.push_str(&style.class_name(&self.name));
+        write_type_arguments(out, &self.type_arguments, style);
+        let mut parameterized = !self.type_arguments.is_empty();
+        for inner_class in &self.inner_classes {
+            out.push(if parameterized {
+                '.'
+            } else {
+                style.nested_separator()
+            });
+            out.push_str(&inner_class.name);
+            write_type_arguments(out, &inner_class.type_arguments, style);
+            parameterized = !inner_class.type_arguments.is_empty();
+        }
+    }
+}
+
+impl ToSource for TypeArgument {
+    fn write_source(&self, out: &mut String, style: &SourceStyle) {
+        match *self {
+            TypeArgument::Any => out.push('?'),
+            TypeArgument::Extends(ref bound) => {
+                out.push_str("? extends ");
+                bound.write_source(out, style);
+            }
+            TypeArgument::Super(ref bound) => {
+                out.push_str("? super ");
+                bound.write_source(out, style);
+            }
+            TypeArgument::Exact(ref argument) => argument.write_source(out, style),
+        }
+    }
+}
+
+impl ToSource for TypeParameter {
+    /// Renders the name and the bounds of a type parameter,
+    /// a sole bound of `java.lang.Object` is omitted.
+    fn write_source(&self, out: &mut String, style: &SourceStyle) {
+        out.push_str(&self.name);
+
+        let mut bounds: Vec<&TypeSignature> = self.class_bound.iter().collect();
+        bounds.extend(&self.interface_bounds);
+        if let [TypeSignature::Class(class)] = bounds[..] {
+            if *class == ClassTypeSignature::new("java/lang/Object".to_owned()) {
+                return;
+            }
+        }
+
+        for (i, bound) in bounds.into_iter().enumerate() {
+            out.push_str(if i == 0 { " extends " } else { " & " });
+            bound.write_source(out, style);
+        }
+    }
+}
+
+impl ToSource for FieldSignature {
+    fn write_source(&self, out: &mut String, style: &SourceStyle) {
+        self.0.write_source(out, style);
+    }
+}
+
+impl MethodDescriptor {
+    /// Renders the declaration of a method named `name`, without any modifiers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jbcrs::{MethodDescriptor, SourceStyle};
+    ///
+    /// let desc: MethodDescriptor = "(I[Ljava/lang/String;)V".parse().unwrap();
+    /// assert_eq!(
+    ///     desc.source_declaration("foo", &SourceStyle::default()),
+    ///     "void foo(int, java.lang.String[])"
+    /// );
+    /// ```
+    pub fn source_declaration(&self, name: &str, style: &SourceStyle) -> String {
+        let mut out = String::new();
+        match self.return_type {
+            Some(ref return_type) => return_type.write_source(&mut out, style),
+            None => out.push_str("void"),
+        }
+        out.push(' ');
+        out.push_str(name);
+        write_list(&mut out, "(", &self.params, ")", style);
+        out
+    }
+}
+
+impl MethodSignature {
+    /// Renders the declaration of a generic method named `name`, without any modifiers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jbcrs::{MethodSignature, SourceStyle};
+    ///
+    /// let signature: MethodSignature =
+    ///     "<T:Ljava/lang/Object;>(ILjava/util/List<+TT;>;)V^Ljava/io/IOException;"
+    ///         .parse()
+    ///         .unwrap();
+    /// let style = SourceStyle {
+    ///     simple_names: true,
+    ///     nested_dots: false,
+    /// };
+    /// assert_eq!(
+    ///     signature.source_declaration("foo", &style),
+    ///     "<T> void foo(int, List<? extends T>) throws IOException"
+    /// );
+    /// ```
+    pub fn source_declaration(&self, name: &str, style: &SourceStyle) -> String {
+        let mut out = String::new();
+        if !self.type_parameters.is_empty() {
+            write_list(&mut out, "<", &self.type_parameters, "> ", style);
+        }
+        match self.return_type {
+            Some(ref return_type) => return_type.write_source(&mut out, style),
+            None => out.push_str("void"),
+        }
+        out.push(' ');
+        out.push_str(name);
+        write_list(&mut out, "(", &self.params, ")", style);
+        if !self.throws.is_empty() {
+            write_list(&mut out, " throws ", &self.throws, "", style);
+        }
+        out
+    }
+}
+
+impl ClassSignature {
+    /// Renders the declaration of a generic class or interface named `name`
+    /// (given in internal form), without any modifiers.
+    /// A super class of `java.lang.Object` is omitted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jbcrs::{ClassSignature, SourceStyle};
+    ///
+    /// let signature: ClassSignature =
+    ///     "<E:Ljava/lang/Object;>Ljava/lang/Object;Ljava/lang/Iterable<TE;>;"
+    ///         .parse()
+    ///         .unwrap();
+    /// assert_eq!(
+    ///     signature.source_declaration("a/List", false, &SourceStyle::default()),
+    ///     "a.List<E> implements java.lang.Iterable<E>"
+    /// );
+    /// ```
+    pub fn source_declaration(&self, name: &str, interface: bool, style: &SourceStyle) -> String {
+        let mut out = style.class_name(name);
+        if !self.type_parameters.is_empty() {
+            write_list(&mut out, "<", &self.type_parameters, ">", style);
+        }
+
+        if !interface && self.super_class != ClassTypeSignature::new("java/lang/Object".to_owned()) {
+            out.push_str(" extends ");
+            self.super_class.write_source(&mut out, style);
+        }
+        if !self.interfaces.is_empty() {
+            let keyword = if interface { " extends " } else { " implements " };
+            write_list(&mut out, keyword, &self.interfaces, "", style);
+        }
+        out
+    }
+}
+
+/// Renders type arguments, if there are any.
+fn write_type_arguments(out: &mut String, type_arguments: &[TypeArgument], style: &SourceStyle) {
+    if !type_arguments.is_empty() {
+        write_list(out, "<", type_arguments, ">", style);
+    }
+}
+
+/// Renders a list of items separated by `, `.
+fn write_list<T: ToSource>(
+    out: &mut String,
+    start: &str,
+    items: &[T],
+    end: &str,
+    style: &SourceStyle,
+) {
+    out.push_str(start);
+    for (i, item) in items.iter().enumerate() {
+        if i != 0 {
+            out.push_str(", ");
+        }
+        item.write_source(out, style);
+    }
+    out.push_str(end);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn render() {
+        let qualified = SourceStyle::default();
+        let simple = SourceStyle {
+            simple_names: true,
+            nested_dots: true,
+        };
+
+        let desc: TypeDescriptor = "[[Ljava/util/Map$Entry;".parse().unwrap();
+        assert_eq!(desc.to_source(&qualified), "java.util.Map$Entry[][]");
+        assert_eq!(desc.to_source(&simple), "Map.Entry[][]");
+        assert_eq!(Type::Char.to_source(&simple), "char");
+        assert_eq!(
+            Type::Reference("Unnamed$1".to_owned()).to_source(&simple),
+            "Unnamed$1"
+        );
+        assert_eq!(
+            Type::Reference("Outer$1Local$Inner".to_owned()).to_source(&simple),
+            "Outer$1Local.Inner"
+        );
+        assert_eq!(
+            Type::Reference("a/$Proxy$$Generated".to_owned()).to_source(&simple),
+            "$Proxy$$Generated"
+        );
+
+        let field: FieldSignature = "Ljava/util/Map<TK;[I>.Entry<*-Ljava/lang/Number;>;"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            field.to_source(&qualified),
+            "java.util.Map<K, int[]>.Entry<?, ? super java.lang.Number>"
+        );
+        assert_eq!(
+            field.to_source(&simple),
+            "Map<K, int[]>.Entry<?, ? super Number>"
+        );
+        let field: FieldSignature = "Ljava/util/Map$Entry<TK;TV;>;".parse().unwrap();
+        assert_eq!(field.to_source(&qualified), "java.util.Map$Entry<K, V>");
+
+        let desc: MethodDescriptor = "()J".parse().unwrap();
+        assert_eq!(desc.source_declaration("size", &simple), "long size()");
+
+        let signature: MethodSignature =
+            "<T::Ljava/lang/Comparable<-TT;>;U:Ljava/lang/Number;:Ljava/io/Serializable;>\
+             ([TT;TU;)TT;^TX;"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            signature.source_declaration("max", &simple),
+            "<T extends Comparable<? super T>, U extends Number & Serializable> \
+             T max(T[], U) throws X"
+        );
+
+        let signature: ClassSignature =
+            "<K:Ljava/lang/Object;>La/Base<TK;>;Ljava/lang/Comparable<La/Key<TK;>;>;"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            signature.source_declaration("a/Key", false, &simple),
+            "Key<K> extends Base<K> implements Comparable<Key<K>>"
+        );
+        let signature: ClassSignature = "Ljava/lang/Object;Ljava/lang/Iterable<TE;>;Ljava/lang/Runnable;"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            signature.source_declaration("a/Tasks", true, &simple),
+            "Tasks extends Iterable<E>, Runnable"
+        );
+    }
+}
@@ -4,7 +4,7 @@ use std::str::FromStr;
 use std::fmt::{self, Write};
 
 /// All types present in a type descriptor.
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Eq, PartialEq, Debug, Clone)]
 pub enum Type {
     Boolean,
     Byte,
@@ -22,7 +22,7 @@ pub enum Type {
 /// or an element value of an annotation.
 /// In the JVM Specification `FieldDescriptor` is used as a name.
 /// Maybe using that one would be better, but I am too lazy to refactor now.
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Eq, PartialEq, Debug, Clone)]
 pub struct TypeDescriptor {
     /// The dimensions of the type
     ///
@@ -61,6 +61,25 @@ impl TypeDescriptor {
             base_type,
         }
     }
+
+    /// Returns the amount of slots a value of this type takes up
+    /// on the operand stack or in the local variables.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jbcrs::{Type, TypeDescriptor};
+    ///
+    /// assert_eq!(TypeDescriptor::new(0, Type::Int).size(), 1);
+    /// assert_eq!(TypeDescriptor::new(0, Type::Double).size(), 2);
+    /// assert_eq!(TypeDescriptor::new(1, Type::Long).size(), 1);
+    /// ```
+    pub fn size(&self) -> u16 {
+        match self.base_type {
+            Type::Long | Type::Double if self.dimensions == 0 => 2,
+            _ => 1,
+        }
+    }
 }
 
 impl FromStr for TypeDescriptor {
@@ -197,7 +216,7 @@ impl fmt::Display for TypeDescriptor {
     }
 }
 
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Eq, PartialEq, Debug, Clone)]
 pub struct MethodDescriptor {
     /// The parameter types of the method.
     ///
@@ -399,6 +418,514 @@ impl fmt::Display for MethodDescriptor {
     }
 }
 
+/// A java type used in a generic signature.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub enum TypeSignature {
+    /// A primitive type, never `Type::Reference(_)`.
+    Base(Type),
+    /// A class or an interface, possibly with type arguments.
+    Class(ClassTypeSignature),
+    /// A type variable given by its name, like `T`.
+    TypeVariable(String),
+    /// An array of the component type.
+    Array(Box<TypeSignature>),
+}
+
+impl TypeSignature {
+    /// Returns true if this is a class, a type variable or an array.
+    pub fn is_reference(&self) -> bool {
+        !matches!(*self, TypeSignature::Base(_))
+    }
+}
+
+/// A class or an interface used in a generic signature.
+///
+/// # Examples
+///
+/// ```
+/// use jbcrs::{ClassTypeSignature, TypeArgument, TypeSignature};
+///
+/// let signature: TypeSignature = "Ljava/util/Map<TK;TV;>.Entry<TK;TV;>;".parse().unwrap();
+/// match signature {
+///     TypeSignature::Class(ClassTypeSignature { name, inner_classes, .. }) => {
+///         assert_eq!(name, "java/util/Map");
+///         assert_eq!(inner_classes[0].name, "Entry");
+///         assert_eq!(
+///             inner_classes[0].type_arguments[1],
+///             TypeArgument::Exact(TypeSignature::TypeVariable("V".to_owned()))
+///         );
+///     }
+///     _ => unreachable!(),
+/// }
+/// ```
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct ClassTypeSignature {
+    /// The internal name of the outermost class, like `java/util/Map`.
+    pub name: String,
+    pub type_arguments: Vec<TypeArgument>,
+    /// The inner classes from the outermost to the innermost one,
+    /// like `Entry` of `java/util/Map$Entry`.
+    pub inner_classes: Vec<InnerClassTypeSignature>,
+}
+
+impl ClassTypeSignature {
+    /// Creates a signature of a class without type arguments.
+    pub fn new(name: String) -> ClassTypeSignature {
+        ClassTypeSignature {
+            name,
+            type_arguments: Vec::new(),
+            inner_classes: Vec::new(),
+        }
+    }
+}
+
+/// An inner class of a `ClassTypeSignature`.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct InnerClassTypeSignature {
+    /// The simple name of the inner class.
+    pub name: String,
+    pub type_arguments: Vec<TypeArgument>,
+}
+
+/// A type argument of a class.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub enum TypeArgument {
+    /// An unbounded wildcard (`?`).
+    Any,
+    /// A wildcard with an upper bound (`? extends T`).
+    Extends(TypeSignature),
+    /// A wildcard with a lower bound (`? super T`).
+    Super(TypeSignature),
+    /// A type without a wildcard.
+    Exact(TypeSignature),
+}
+
+/// A type parameter of a generic class or method.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct TypeParameter {
+    pub name: String,
+    /// The class bound, which is absent if the first bound is an interface.
+    pub class_bound: Option<TypeSignature>,
+    pub interface_bounds: Vec<TypeSignature>,
+}
+
+/// The generic signature of a class.
+///
+/// # Examples
+///
+/// ```
+/// use jbcrs::ClassSignature;
+///
+/// let signature = "<T:Ljava/lang/Object;>Ljava/lang/Object;Ljava/lang/Iterable<TT;>;";
+/// let parsed: ClassSignature = signature.parse().unwrap();
+/// assert_eq!(parsed.type_parameters[0].name, "T");
+/// assert_eq!(parsed.interfaces[0].name, "java/lang/Iterable");
+/// assert_eq!(parsed.to_string(), signature);
+/// ```
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct ClassSignature {
+    pub type_parameters: Vec<TypeParameter>,
+    pub super_class: ClassTypeSignature,
+    pub interfaces: Vec<ClassTypeSignature>,
+}
+
+/// The generic signature of a method.
+///
+/// # Examples
+///
+/// ```
+/// use jbcrs::{MethodSignature, TypeSignature};
+///
+/// let signature = "<X:Ljava/lang/Throwable;>(Ljava/util/List<*>;)V^TX;";
+/// let parsed: MethodSignature = signature.parse().unwrap();
+/// assert_eq!(parsed.return_type, None);
+/// assert_eq!(parsed.throws, vec![TypeSignature::TypeVariable("X".to_owned())]);
+/// assert_eq!(parsed.to_string(), signature);
+/// ```
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct MethodSignature {
+    pub type_parameters: Vec<TypeParameter>,
+    pub params: Vec<TypeSignature>,
+    /// The return type, `None` indicates `void`.
+    pub return_type: Option<TypeSignature>,
+    /// The exceptions thrown, which are classes or type variables.
+    pub throws: Vec<TypeSignature>,
+}
+
+/// The generic signature of a field, a record component or a local variable,
+/// which is a class, a type variable or an array.
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub struct FieldSignature(pub TypeSignature);
+
+/// Reads a generic signature char by char.
+struct SignatureReader<'a> {
+    signature: &'a str,
+    /// The current location in the signature in bytes
+    at: usize,
+}
+
+impl<'a> SignatureReader<'a> {
+    fn new(signature: &'a str) -> SignatureReader<'a> {
+        SignatureReader { signature, at: 0 }
+    }
+
+    fn err<T>(&self) -> Result<T> {
+        Err(Error::InvalidSignature {
+            signature: self.signature.to_owned(),
+            at: self.at,
+        })
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.signature[self.at..].chars().next()
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        if self.peek() == Some(expected) {
+            self.at += expected.len_utf8();
+            Ok(())
+        } else {
+            self.err()
+        }
+    }
+
+    /// Returns an error, if any chars are left.
+    fn end(&self) -> Result<()> {
+        if self.at == self.signature.len() {
+            Ok(())
+        } else {
+            self.err()
+        }
+    }
+
+    /// Reads a name until one of the chars given is found, it must not be empty.
+    fn name(&mut self, terminators: &[char]) -> Result<String> {
+        let start = self.at;
+        while let Some(ch) = self.peek() {
+            if terminators.contains(&ch) {
+                break;
+            }
+            self.at += ch.len_utf8();
+        }
+
+        if self.at == start {
+            return self.err();
+        }
+        Ok(self.signature[start..self.at].to_owned())
+    }
+
+    fn type_parameters(&mut self) -> Result<Vec<TypeParameter>> {
+        let mut type_parameters = Vec::new();
+        if self.peek() != Some('<') {
+            return Ok(type_parameters);
+        }
+        self.at += 1;
+
+        loop {
+            let name = self.name(&[':', '<', '>', ';', '.', '/', '['])?;
+            self.expect(':')?;
+            let class_bound = match self.peek() {
+                Some(':') | Some('>') => None,
+                _ => Some(self.reference_type()?),
+            };
+            let mut interface_bounds = Vec::new();
+            while self.peek() == Some(':') {
+                self.at += 1;
+                interface_bounds.push(self.reference_type()?);
+            }
+
+            type_parameters.push(TypeParameter {
+                name,
+                class_bound,
+                interface_bounds,
+            });
+            if self.peek() == Some('>') {
+                self.at += 1;
+                return Ok(type_parameters);
+            }
+        }
+    }
+
+    fn java_type(&mut self) -> Result<TypeSignature> {
+        let base_type = match self.peek() {
+            Some('Z') => Type::Boolean,
+            Some('B') => Type::Byte,
+            Some('S') => Type::Short,
+            Some('I') => Type::Int,
+            Some('J') => Type::Long,
+            Some('F') => Type::Float,
+            Some('D') => Type::Double,
+            Some('C') => Type::Char,
+            _ => return self.reference_type(),
+        };
+        self.at += 1;
+        Ok(TypeSignature::Base(base_type))
+    }
+
+    fn reference_type(&mut self) -> Result<TypeSignature> {
+        match self.peek() {
+            Some('L') => Ok(TypeSignature::Class(self.class_type()?)),
+            Some('T') => {
+                self.at += 1;
+                let name = self.name(&[';', '<', '>', ':', '.', '/', '['])?;
+                self.expect(';')?;
+                Ok(TypeSignature::TypeVariable(name))
+            }
+            Some('[') => {
+                self.at += 1;
+                Ok(TypeSignature::Array(Box::new(self.java_type()?)))
+            }
+            _ => self.err(),
+        }
+    }
+
+    fn class_type(&mut self) -> Result<ClassTypeSignature> {
+        self.expect('L')?;
+        let name = self.name(&['<', '.', ';', '>', ':', '['])?;
+        let type_arguments = self.type_arguments()?;
+
+        let mut inner_classes = Vec::new();
+        while self.peek() == Some('.') {
+            self.at += 1;
+            let name = self.name(&['<', '.', ';', '>', ':', '[', '/'])?;
+            let type_arguments = self.type_arguments()?;
+            inner_classes.push(InnerClassTypeSignature {
+                name,
+                type_arguments,
+            });
+        }
+        self.expect(';')?;
+
+        Ok(ClassTypeSignature {
+            name,
+            type_arguments,
+            inner_classes,
+        })
+    }
+
+    fn type_arguments(&mut self) -> Result<Vec<TypeArgument>> {
+        let mut type_arguments = Vec::new();
+        if self.peek() != Some('<') {
+            return Ok(type_arguments);
+        }
+        self.at += 1;
+
+        loop {
+            type_arguments.push(match self.peek() {
+                Some('*') => {
+                    self.at += 1;
+                    TypeArgument::Any
+                }
+                Some('+') => {
+                    self.at += 1;
+                    TypeArgument::Extends(self.reference_type()?)
+                }
+                Some('-') => {
+                    self.at += 1;
+                    TypeArgument::Super(self.reference_type()?)
+                }
+                _ => TypeArgument::Exact(self.reference_type()?),
+            });
+            if self.peek() == Some('>') {
+                self.at += 1;
+                return Ok(type_arguments);
+            }
+        }
+    }
+}
+
+impl FromStr for TypeSignature {
+    type Err = Error;
+
+    /// Parses a single type of a signature, which may be primitive.
+    fn from_str(signature: &str) -> Result<TypeSignature> {
+        let mut reader = SignatureReader::new(signature);
+        let java_type = reader.java_type()?;
+        reader.end()?;
+        Ok(java_type)
+    }
+}
+
+impl FromStr for ClassSignature {
+    type Err = Error;
+
+    fn from_str(signature: &str) -> Result<ClassSignature> {
+        let mut reader = SignatureReader::new(signature);
+        let type_parameters = reader.type_parameters()?;
+        let super_class = reader.class_type()?;
+        let mut interfaces = Vec::new();
+        while reader.peek().is_some() {
+            interfaces.push(reader.class_type()?);
+        }
+
+        Ok(ClassSignature {
+            type_parameters,
+            super_class,
+            interfaces,
+        })
+    }
+}
+
+impl FromStr for MethodSignature {
+    type Err = Error;
+
+    fn from_str(signature: &str) -> Result<MethodSignature> {
+        let mut reader = SignatureReader::new(signature);
+        let type_parameters = reader.type_parameters()?;
+
+        reader.expect('(')?;
+        let mut params = Vec::new();
+        while reader.peek() != Some(')') {
+            params.push(reader.java_type()?);
+        }
+        reader.at += 1;
+
+        let return_type = if reader.peek() == Some('V') {
+            reader.at += 1;
+            None
+        } else {
+            Some(reader.java_type()?)
+        };
+
+        let mut throws = Vec::new();
+        while reader.peek().is_some() {
+            reader.expect('^')?;
+            let exception = match reader.peek() {
+                Some('L') | Some('T') => reader.reference_type()?,
+                _ => return reader.err(),
+            };
+            throws.push(exception);
+        }
+
+        Ok(MethodSignature {
+            type_parameters,
+            params,
+            return_type,
+            throws,
+        })
+    }
+}
+
+impl FromStr for FieldSignature {
+    type Err = Error;
+
+    /// Parses the signature of a field, which must not be primitive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use jbcrs::{FieldSignature, TypeArgument, TypeSignature};
+    ///
+    /// let signature = "[Ljava/util/List<+Ljava/lang/Number;>;";
+    /// let parsed: FieldSignature = signature.parse().unwrap();
+    /// assert_eq!(parsed.to_string(), signature);
+    ///
+    /// assert!("I".parse::<FieldSignature>().is_err());
+    /// ```
+    fn from_str(signature: &str) -> Result<FieldSignature> {
+        let mut reader = SignatureReader::new(signature);
+        let field_type = reader.reference_type()?;
+        reader.end()?;
+        Ok(FieldSignature(field_type))
+    }
+}
+
+/// Formats all type parameters, if there are any.
+fn fmt_type_parameters(f: &mut fmt::Formatter, type_parameters: &[TypeParameter]) -> fmt::Result {
+    if type_parameters.is_empty() {
+        return Ok(());
+    }
+
+    f.write_char('<')?;
+    for type_parameter in type_parameters {
+        write!(f, "{}:", type_parameter.name)?;
+        if let Some(ref class_bound) = type_parameter.class_bound {
+            write!(f, "{}", class_bound)?;
+        }
+        for interface_bound in &type_parameter.interface_bounds {
+            write!(f, ":{}", interface_bound)?;
+        }
+    }
+    f.write_char('>')
+}
+
+/// Formats all type arguments, if there are any.
+fn fmt_type_arguments(f: &mut fmt::Formatter, type_arguments: &[TypeArgument]) -> fmt::Result {
+    if type_arguments.is_empty() {
+        return Ok(());
+    }
+
+    f.write_char('<')?;
+    for type_argument in type_arguments {
+        match *type_argument {
+            TypeArgument::Any => f.write_char('*')?,
+            TypeArgument::Extends(ref bound) => write!(f, "+{}", bound)?,
+            TypeArgument::Super(ref bound) => write!(f, "-{}", bound)?,
+            TypeArgument::Exact(ref argument) => write!(f, "{}", argument)?,
+        }
+    }
+    f.write_char('>')
+}
+
+impl fmt::Display for TypeSignature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TypeSignature::Base(ref base_type) => TypeDescriptor::new(0, base_type.clone()).fmt(f),
+            TypeSignature::Class(ref class) => class.fmt(f),
+            TypeSignature::TypeVariable(ref name) => write!(f, "T{};", name),
+            TypeSignature::Array(ref component) => write!(f, "[{}", component),
+        }
+    }
+}
+
+impl fmt::Display for ClassTypeSignature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "L{}", self.name)?;
+        fmt_type_arguments(f, &self.type_arguments)?;
+        for inner_class in &self.inner_classes {
+            write!(f, ".{}", inner_class.name)?;
+            fmt_type_arguments(f, &inner_class.type_arguments)?;
+        }
+        f.write_char(';')
+    }
+}
+
+impl fmt::Display for ClassSignature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_type_parameters(f, &self.type_parameters)?;
+        self.super_class.fmt(f)?;
+        for interface in &self.interfaces {
+            interface.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for MethodSignature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_type_parameters(f, &self.type_parameters)?;
+        f.write_char('(')?;
+        for param in &self.params {
+            param.fmt(f)?;
+        }
+        f.write_char(')')?;
+        match self.return_type {
+            Some(ref return_type) => return_type.fmt(f)?,
+            None => f.write_char('V')?,
+        }
+        for exception in &self.throws {
+            write!(f, "^{}", exception)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for FieldSignature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -478,4 +1005,137 @@ mod test {
         assert!(parse(format!("({})V", "I".repeat(256)).as_ref()).is_err()); // too many parameters
     }
 
+    #[test]
+    fn signature_parse() {
+        fn round_trip<T: FromStr<Err = Error> + fmt::Display>(s: &str) -> T {
+            let parsed: T = s.parse().unwrap();
+            assert_eq!(parsed.to_string(), s);
+            parsed
+        }
+
+        let object = || TypeSignature::Class(ClassTypeSignature::new("java/lang/Object".to_owned()));
+        let variable = |name: &str| TypeSignature::TypeVariable(name.to_owned());
+
+        let field: FieldSignature = round_trip("Ljava/util/Map<*+[TK;-Ljava/lang/Number;>.Entry;");
+        assert_eq!(
+            field,
+            FieldSignature(TypeSignature::Class(ClassTypeSignature {
+                name: "java/util/Map".to_owned(),
+                type_arguments: vec![
+                    TypeArgument::Any,
+                    TypeArgument::Extends(TypeSignature::Array(Box::new(variable("K")))),
+                    TypeArgument::Super(TypeSignature::Class(ClassTypeSignature::new(
+                        "java/lang/Number".to_owned()
+                    ))),
+                ],
+                inner_classes: vec![InnerClassTypeSignature {
+                    name: "Entry".to_owned(),
+                    type_arguments: Vec::new(),
+                }],
+            }))
+        );
+
+        let class: ClassSignature = round_trip(
+            "<T:Ljava/lang/Object;U::Ljava/lang/Comparable<TU;>;:Ljava/io/Serializable;>\
+             Ljava/lang/Object;",
+        );
+        assert_eq!(class.type_parameters[0].class_bound, Some(object()));
+        assert_eq!(class.type_parameters[1].class_bound, None);
+        assert_eq!(class.type_parameters[1].interface_bounds.len(), 2);
+        assert!(class.interfaces.is_empty());
+
+        let method: MethodSignature = round_trip("<E:Ljava/lang/Exception;>([[ITE;J)[TE;^TE;^Ljava/io/IOException;");
+        assert_eq!(
+            method.params,
+            vec![
+                TypeSignature::Array(Box::new(TypeSignature::Array(Box::new(
+                    TypeSignature::Base(Type::Int)
+                )))),
+                variable("E"),
+                TypeSignature::Base(Type::Long),
+            ]
+        );
+        assert_eq!(
+            method.return_type,
+            Some(TypeSignature::Array(Box::new(variable("E"))))
+        );
+        assert_eq!(method.throws.len(), 2);
+        round_trip::<MethodSignature>("()V");
+        round_trip::<TypeSignature>("Z");
+
+        // errors
+        fn at<T: FromStr<Err = Error> + fmt::Debug>(s: &str) -> usize {
+            match s.parse::<T>() {
+                Err(Error::InvalidSignature { ref signature, at }) if signature == s => at,
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+        assert_eq!(at::<FieldSignature>("I"), 0); // primitive field
+        assert_eq!(at::<FieldSignature>("Ljava/lang/Object"), 17); // no trailing semicolon
+        assert_eq!(at::<FieldSignature>("TT;I"), 3); // trailing chars
+        assert_eq!(at::<FieldSignature>("Ljava/util/List<>;"), 16); // no type arguments
+        assert_eq!(at::<FieldSignature>("LOuter.a/Inner;"), 8); // package in inner class
+        assert_eq!(at::<ClassSignature>("<>Ljava/lang/Object;"), 1); // no type parameters
+        assert_eq!(at::<ClassSignature>("<T>Ljava/lang/Object;"), 2); // no bound separator
+        assert_eq!(at::<ClassSignature>("I"), 0); // primitive super class
+        assert_eq!(at::<MethodSignature>("(V)V"), 1); // void parameter
+        assert_eq!(at::<MethodSignature>("()V^I"), 4); // primitive exception
+        assert_eq!(at::<MethodSignature>("()"), 2); // no return type
+    }
+
+    #[test]
+    fn signature_resources() {
+        use basic::{self, Attribute};
+
+        fn check(pool: &basic::Pool, attributes: &[Attribute], parse: &dyn Fn(&str) -> String) {
+            for attribute in attributes {
+                match *attribute {
+                    Attribute::Signature(index) => {
+                        let signature = pool.get_utf8(index).unwrap();
+                        assert_eq!(parse(&signature), signature);
+                    }
+                    Attribute::Code { ref attributes, .. } => check(pool, attributes, &field_signature),
+                    Attribute::LocalVariableTypeTable(ref variables) => {
+                        for variable in variables {
+                            let signature = pool.get_utf8(variable.signature).unwrap();
+                            assert_eq!(field_signature(&signature), signature);
+                        }
+                    }
+                    Attribute::Record(ref components) => {
+                        for component in components {
+                            check(pool, &component.attributes, &field_signature);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        fn field_signature(s: &str) -> String {
+            s.parse::<FieldSignature>().unwrap().to_string()
+        }
+
+        for bytes in &[
+            &include_bytes!("../examples/resources/AnyAnnotation.class")[..],
+            &include_bytes!("../examples/resources/CustomLinkedList.class")[..],
+            &include_bytes!("../examples/resources/CustomLinkedList$Element.class")[..],
+            &include_bytes!("../examples/resources/CustomLinkedList$Iter.class")[..],
+            &include_bytes!("../examples/resources/FizzBuzzStream.class")[..],
+            &include_bytes!("../examples/resources/Records.class")[..],
+            &include_bytes!("../examples/resources/Records$Component.class")[..],
+            &include_bytes!("../examples/resources/Sealed.class")[..],
+        ] {
+            let (pool, class) = basic::parse(bytes).unwrap();
+            check(&pool, &class.attributes, &|s| {
+                s.parse::<ClassSignature>().unwrap().to_string()
+            });
+            for field in &class.fields {
+                check(&pool, &field.attributes, &field_signature);
+            }
+            for method in &class.methods {
+                check(&pool, &method.attributes, &|s| {
+                    s.parse::<MethodSignature>().unwrap().to_string()
+                });
+            }
+        }
+    }
 }